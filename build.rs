@@ -0,0 +1,38 @@
+//! Captures the git commit and the build profile, so the program can report which build produced a benchmark.
+
+use std::{path::Path, process::Command};
+
+fn main() {
+    let git_hash = git(&["rev-parse", "--short", "HEAD"]);
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"])
+        .is_some_and(|status| !status.is_empty());
+    let git_hash = match git_hash {
+        Some(hash) if dirty => format!("{hash}-dirty"),
+        Some(hash) => hash,
+        None => "unknown".to_string(),
+    };
+    println!("cargo:rustc-env=HEAT_TRANSFER_GIT_HASH={git_hash}");
+    println!(
+        "cargo:rustc-env=HEAT_TRANSFER_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_else(|_| "unknown".to_string())
+    );
+
+    // A missing file would rerun the build script on every build.
+    for file in [".git/HEAD", ".git/index"] {
+        if Path::new(file).exists() {
+            println!("cargo:rerun-if-changed={file}");
+        }
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+}
+
+/// Runs git with the passed arguments and returns the trimmed output, if git succeeded.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .map(|s| s.trim().to_string())
+}
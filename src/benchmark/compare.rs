@@ -0,0 +1,120 @@
+//! A text table that compares the median simulation times of the different methods, the counterpart of the compare mode box plot.
+
+use std::path::PathBuf;
+
+use anyhow::*;
+
+use crate::{
+    heat_transfer::simulations::{
+        get_benchmark_format, BenchmarkPathPart, BenchmarkReader, SimulationType1D,
+    },
+    output::benchmark_root,
+};
+
+use super::{get_benchmark_elements, BenchmarkName};
+
+/// The methods that are compared, the same as in the compare mode box plot.
+const COMPARED_METHODS: [SimulationType1D; 4] = [
+    SimulationType1D::Cpu,
+    SimulationType1D::GpuM1,
+    SimulationType1D::GpuM2,
+    SimulationType1D::GpuM3,
+];
+
+/// Returns the path of the benchmark of the method with the passed number of wall elements.
+///
+/// # Errors
+///
+/// This function will return an error if the label is not a valid path.
+fn benchmark_path(
+    benchmark_name: BenchmarkName,
+    label: &str,
+    simulation_type: SimulationType1D,
+    size: usize,
+) -> Result<PathBuf> {
+    let benchmark_path_part =
+        BenchmarkPathPart::new(None, label.to_string(), simulation_type.into());
+    Ok(benchmark_root()
+        .join(benchmark_name.path_str())
+        .join(benchmark_path_part.path_str()?)
+        .join(get_benchmark_format().file_name(size)))
+}
+
+/// Returns the median simulation time of the benchmark at the passed path or [`None`] if it does not exist.
+/// A benchmark that can not be read is skipped with a warning.
+fn median(path: PathBuf) -> Option<f64> {
+    if !path.exists() {
+        return None;
+    }
+    match BenchmarkReader::try_new(&path).and_then(BenchmarkReader::stats) {
+        std::result::Result::Ok(stats) => Some(stats.median),
+        Err(err) => {
+            println!("Warning: Skipped benchmark at {path:?}: {err:#}");
+            None
+        }
+    }
+}
+
+/// Prints a table with the median simulation time of each compared method and the speedup of the fastest GPU method over the CPU method for every size of the benchmark.
+/// Sizes without any benchmark are left out and missing benchmarks are printed as `-`.
+///
+/// # Errors
+///
+/// This function will return an error if the label is not a valid path.
+fn print_table(benchmark_name: BenchmarkName, label: &str) -> Result<()> {
+    let mut rows = vec![];
+    for size in get_benchmark_elements() {
+        let medians = COMPARED_METHODS
+            .iter()
+            .map(|&s| benchmark_path(benchmark_name, label, s, size).map(median))
+            .collect::<Result<Vec<_>>>()?;
+        if medians.iter().any(Option::is_some) {
+            rows.push((size, medians));
+        }
+    }
+    if rows.is_empty() {
+        println!(
+            "\n{} ({label}): no benchmarks found",
+            benchmark_name.path_str()
+        );
+        return Ok(());
+    }
+
+    println!("\n{} ({label}): median time [s]", benchmark_name.path_str());
+    let header = COMPARED_METHODS
+        .iter()
+        .map(|s| format!("{:>12}", s.to_string()))
+        .collect::<String>();
+    println!("  {:>8}{header}{:>14}", "Size", "Best speedup");
+    for (size, medians) in rows {
+        let times = medians
+            .iter()
+            .map(|m| match m {
+                Some(m) => format!("{m:>12.6}"),
+                None => format!("{:>12}", "-"),
+            })
+            .collect::<String>();
+        let best_gpu = medians[1..].iter().flatten().copied().reduce(f64::min);
+        let speedup = match (medians[0], best_gpu) {
+            (Some(cpu), Some(gpu)) if gpu > 0.0 => format!("{:>13.2}x", cpu / gpu),
+            _ => format!("{:>14}", "-"),
+        };
+        println!("  {size:>8}{times}{speedup}");
+    }
+    Ok(())
+}
+
+/// Prints the median simulation times of the CPU, GPU M1, GPU M2 and GPU M3 method for all material benchmarks of the label, as the compare mode box plot shows them.
+/// Only the passed `benchmark_names` are printed, all if none are passed.
+///
+/// # Errors
+///
+/// This function will return an error if the label is not a valid path.
+pub fn print_methods_compare(label: &str, benchmark_names: Option<&[BenchmarkName]>) -> Result<()> {
+    for benchmark_name in BenchmarkName::MATERIAL {
+        if benchmark_name.is_benchmark(benchmark_names) {
+            print_table(benchmark_name, label)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,191 @@
+//! Export of all benchmark results into a single CSV file.
+
+use std::{
+    fs::File,
+    io::{LineWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::*;
+
+use crate::{
+    fds::is_cores_prefix,
+    heat_transfer::simulations::{
+        BenchmarkHardware, BenchmarkReader, BenchmarkStats, SETUP_PREFIX,
+    },
+    output::benchmark_root,
+};
+
+/// A single row of the CSV summary.
+struct SummaryRow {
+    simulation_path: String,
+    /// The prefix of the [`BenchmarkPathPart`](crate::heat_transfer::simulations::BenchmarkPathPart), empty if the benchmark has none.
+    prefix: String,
+    method: String,
+    label: String,
+    size: usize,
+    hardware: BenchmarkHardware,
+    stats: BenchmarkStats,
+}
+impl SummaryRow {
+    /// Tries to create a [`SummaryRow`] from a benchmark file inside the benchmark directory.
+    /// The path must have the layout `<simulation path>/[<prefix>/]<label>/<method>/<size>.<extension>` of [`BenchmarkPathPart::path_str`](crate::heat_transfer::simulations::BenchmarkPathPart::path_str).
+    /// The directory above the label is only taken as prefix if it is a known prefix, see [`is_prefix`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the path does not match the benchmark layout.
+    /// - the benchmark can not be read.
+    fn try_from_path(path: &Path) -> Result<Self> {
+        let size = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or(anyhow!("Failed to get the size from the file name."))?
+            .parse::<usize>()
+            .with_context(|| "Failed to parse the size from the file name.")?;
+        let method_directory = path
+            .parent()
+            .ok_or(anyhow!("The method directory is missing."))?;
+        let label_directory = method_directory
+            .parent()
+            .ok_or(anyhow!("The label directory is missing."))?;
+        let mut simulation_directory = label_directory
+            .parent()
+            .ok_or(anyhow!("The simulation directory is missing."))?;
+        let mut prefix = String::new();
+        if is_prefix(&file_name_to_string(simulation_directory)) {
+            prefix = file_name_to_string(simulation_directory);
+            simulation_directory = simulation_directory
+                .parent()
+                .ok_or(anyhow!("The simulation directory is missing."))?;
+        }
+
+        let benchmark_reader = BenchmarkReader::try_new(path)?;
+        let hardware = benchmark_reader.hardware().clone();
+        let stats = benchmark_reader.stats()?;
+
+        Ok(Self {
+            simulation_path: path_to_string(
+                simulation_directory
+                    .strip_prefix(benchmark_root())
+                    .unwrap_or(simulation_directory),
+            ),
+            prefix,
+            method: file_name_to_string(method_directory),
+            label: file_name_to_string(label_directory),
+            size,
+            hardware,
+            stats,
+        })
+    }
+}
+
+/// Returns whether the directory name is a prefix of a benchmark path, like [`SETUP_PREFIX`], a core count of the FDS speed test or the number of a compared variant.
+fn is_prefix(name: &str) -> bool {
+    name == SETUP_PREFIX || is_cores_prefix(name) || name.parse::<u8>().is_ok()
+}
+
+/// Returns the path with `/` as separator.
+fn path_to_string(path: &Path) -> String {
+    path.iter()
+        .map(|p| p.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Returns the value as CSV field, quoted if it contains a separator or a quote.
+fn csv_field(value: Option<&str>) -> String {
+    let value = value.unwrap_or_default();
+    if value.contains([',', '"']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Returns the last component of the path.
+fn file_name_to_string(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Collects all benchmark files inside the directory and all of its sub directories.
+///
+/// # Errors
+///
+/// This function will return an error if a directory can not be read.
+fn collect_benchmark_files(directory: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(directory)
+        .with_context(|| format!("Failed to read directory at {directory:?}."))?
+    {
+        let path = entry
+            .with_context(|| format!("Failed to read entry in directory {directory:?}."))?
+            .path();
+        if path.is_dir() {
+            collect_benchmark_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "bin" || ext == "json")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Walks through the benchmark directory and writes the statistics of all benchmarks into a single CSV file.
+/// Benchmarks that can not be read are skipped with a warning.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the benchmark directory can not be read.
+/// - the CSV file can not be written.
+pub fn export_csv<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    let mut files = vec![];
+    collect_benchmark_files(&benchmark_root(), &mut files)?;
+    files.sort();
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directories at {parent:?}."))?;
+    }
+    let file = File::create(path).with_context(|| format!("Failed to create file at {path:?}."))?;
+    let mut line_writer = LineWriter::new(file);
+    writeln!(
+        line_writer,
+        "simulation_path,prefix,method,label,size,device,backend,median,mean,std_dev"
+    )
+    .with_context(|| format!("Failed to write to {path:?}."))?;
+
+    let mut count = 0;
+    for file in files {
+        match SummaryRow::try_from_path(&file) {
+            std::result::Result::Ok(row) => {
+                writeln!(
+                    line_writer,
+                    "{},{},{},{},{},{},{},{},{},{}",
+                    row.simulation_path,
+                    row.prefix,
+                    row.method,
+                    row.label,
+                    row.size,
+                    csv_field(row.hardware.device.as_deref()),
+                    csv_field(row.hardware.backend.as_deref()),
+                    row.stats.median,
+                    row.stats.mean,
+                    row.stats.std_dev
+                )
+                .with_context(|| format!("Failed to write to {path:?}."))?;
+                count += 1;
+            }
+            Err(err) => println!("Warning: Skipped benchmark at {file:?}: {err:#}"),
+        }
+    }
+    println!("Exported {count} benchmarks to {path:?}.");
+
+    Ok(())
+}
@@ -1,19 +1,101 @@
 //! The benchmarks are defined and executed in this module.
 
 use crate::{
+    dry_run::get_dry_run,
     fds::{self},
-    heat_transfer::simulations::{
-        one_dimensional_by_simulation_type, SimulationKind, SimulationMethod, SimulationType1D,
+    heat_transfer::{
+        one_dimensional::set_max_element_per_chunk,
+        simulations::{
+            duplication, load_fds_simulation_one_dimensional, load_fds_simulation_two_dimensional,
+            one_dimensional_by_simulation_type, one_dimensional_setup_by_simulation_type,
+            two_dimensional_by_simulation_type, SimulationKind, SimulationMethod, SimulationType1D,
+            SimulationType2D,
+        },
     },
 };
 use anyhow::*;
 use clap::ValueEnum;
+use indicatif::MultiProgress;
+use rayon::{prelude::*, ThreadPoolBuilder};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    PoisonError, RwLock,
+};
+
+mod compare;
+mod export;
+pub use compare::print_methods_compare;
+pub use export::export_csv;
 
-/// The different quantities of wall elements that are tested.
+/// The different quantities of wall elements that are tested, unless others are set with [`set_benchmark_elements`].
 pub const BENCHMARK_ELEMENTS: [usize; 8] = [256, 512, 1024, 2048, 4096, 8192, 16384, 32768];
 /// The different quantities of wall elements that are additionally tested in order to check the adjustment using chunks.
 pub const BENCHMARK_CHUNK: [usize; 3] = [32768 * 2, 32768 * 4, 32768 * 8];
 
+/// The default amount of CPU benchmarks that run in parallel. This value is replaced at the start of the program.
+static BENCHMARK_THREADS: AtomicUsize = AtomicUsize::new(1);
+
+/// Set the amount of CPU benchmarks that run in parallel.
+/// Parallel CPU benchmarks share the cores, so their times are only comparable with each other. The GPU benchmarks always run serially after the CPU benchmarks.
+pub fn set_benchmark_threads(benchmark_threads: usize) {
+    BENCHMARK_THREADS.store(benchmark_threads, Ordering::Relaxed);
+    println!("Set benchmark threads to {benchmark_threads}")
+}
+
+/// Get the amount of CPU benchmarks that run in parallel.
+#[inline]
+pub fn get_benchmark_threads() -> usize {
+    BENCHMARK_THREADS.load(Ordering::Relaxed)
+}
+
+/// The wall thicknesses in cm the thickness benchmarks are limited to. If it is not set, all thicknesses are selected. This value is replaced at the start of the program.
+static THICKNESSES: RwLock<Option<Vec<u32>>> = RwLock::new(None);
+
+/// Set the wall thicknesses in cm the thickness benchmarks and plots are limited to, e.g. `[50, 500]`.
+pub fn set_thicknesses(thicknesses: Vec<u32>) {
+    println!("Set thicknesses to {thicknesses:?} cm");
+    *THICKNESSES.write().unwrap_or_else(PoisonError::into_inner) = Some(thicknesses);
+}
+
+/// Get the wall thicknesses in cm the thickness benchmarks and plots are limited to, if any are set.
+#[inline]
+pub fn get_thicknesses() -> Option<Vec<u32>> {
+    THICKNESSES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// The quantities of wall elements that are tested instead of [`BENCHMARK_ELEMENTS`]. If it is not set, [`BENCHMARK_ELEMENTS`] are tested. This value is replaced at the start of the program.
+static ELEMENTS: RwLock<Option<Vec<usize>>> = RwLock::new(None);
+
+/// Set the quantities of wall elements that are tested and plotted instead of [`BENCHMARK_ELEMENTS`], e.g. `[10000]`. They are sorted and duplicates are removed.
+pub fn set_benchmark_elements(mut elements: Vec<usize>) {
+    elements.sort_unstable();
+    elements.dedup();
+    println!("Set benchmark elements to {elements:?}");
+    *ELEMENTS.write().unwrap_or_else(PoisonError::into_inner) = Some(elements);
+}
+
+/// Get the quantities of wall elements that are tested and plotted, [`BENCHMARK_ELEMENTS`] if none are set with [`set_benchmark_elements`].
+#[inline]
+pub fn get_benchmark_elements() -> Vec<usize> {
+    ELEMENTS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| BENCHMARK_ELEMENTS.to_vec())
+}
+
+/// Get the quantities of wall elements set with [`set_benchmark_elements`], if any are set.
+#[inline]
+pub fn get_set_benchmark_elements() -> Option<Vec<usize>> {
+    ELEMENTS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
 /// All possible benchmarks that can be performed.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum BenchmarkName {
@@ -42,9 +124,12 @@ pub enum BenchmarkName {
     /// Diabatic simulation with steel material.
     #[clap(name = "steel")]
     DiabaticSteelKC = 1 << 6,
-    /// Both diabatic simulations with concrete and steel.
+    /// Diabatic simulation with gypsum material.
+    #[clap(name = "gypsum")]
+    DiabaticGypsumKC = 1 << 15,
+    /// All diabatic simulations with concrete, steel and gypsum.
     #[clap(name = "compare")]
-    DiabaticCompareKC = (1 << 5) + (1 << 6),
+    DiabaticCompareKC = (1 << 5) + (1 << 6) + (1 << 15),
 
     /// Diabatic simulation with steel material and 5cm wall thickness.
     #[clap(name = "thickness_005")]
@@ -71,11 +156,19 @@ pub enum BenchmarkName {
     /// Adiabatic FDS simulation.
     #[clap(name = "fds_speed_test")]
     SpeedTestFDS = 1 << 13,
+
+    /// Diabatic two dimensional simulation with steel material.
+    #[clap(name = "2d_steel")]
+    Diabatic2DSteelKC = 1 << 14,
+
+    /// Only the setup of the diabatic simulation with steel material, without any time step.
+    #[clap(name = "setup")]
+    SetupSteelKC = 1 << 16,
 }
 
 impl BenchmarkName {
     /// All diabatic simulations that test the influence of multiple materials.
-    pub const MATERIAL: [BenchmarkName; 7] = [
+    pub const MATERIAL: [BenchmarkName; 8] = [
         BenchmarkName::Diabatic1MaterialKC,
         BenchmarkName::Diabatic2MaterialsKC,
         BenchmarkName::Diabatic4MaterialsKC,
@@ -83,6 +176,7 @@ impl BenchmarkName {
         BenchmarkName::Diabatic16MaterialsKC,
         BenchmarkName::DiabaticConcreteKC,
         BenchmarkName::DiabaticSteelKC,
+        BenchmarkName::DiabaticGypsumKC,
     ];
     /// All diabatic simulations with steel material and different thicknesses.
     pub const THICKNESS: [BenchmarkName; 5] = [
@@ -93,6 +187,26 @@ impl BenchmarkName {
         BenchmarkName::DiabaticThickness500,
     ];
 
+    /// Returns the wall thickness in cm of a single thickness benchmark.
+    pub fn thickness(&self) -> Option<u32> {
+        match self {
+            BenchmarkName::DiabaticThickness005 => Some(5),
+            BenchmarkName::DiabaticThickness010 => Some(10),
+            BenchmarkName::DiabaticThickness050 => Some(50),
+            BenchmarkName::DiabaticThickness100 => Some(100),
+            BenchmarkName::DiabaticThickness500 => Some(500),
+            _ => None,
+        }
+    }
+
+    /// Returns `false` if this is a thickness benchmark whose thickness is not selected with [`set_thicknesses`].
+    pub fn is_selected_thickness(&self) -> bool {
+        match (self.thickness(), get_thicknesses()) {
+            (Some(thickness), Some(thicknesses)) => thicknesses.contains(&thickness),
+            _ => true,
+        }
+    }
+
     pub fn is_benchmark(self, benchmark_names: Option<&[BenchmarkName]>) -> bool {
         match benchmark_names {
             Some(benchmark_names) => benchmark_names
@@ -115,7 +229,10 @@ impl BenchmarkName {
             BenchmarkName::Diabatic16MaterialsKC => "fds/1D/Diabatic/multiple/16",
 
             BenchmarkName::DiabaticConcreteKC => "fds/1D/Diabatic/concrete_k_c",
-            BenchmarkName::DiabaticSteelKC => "fds/1D/Diabatic/steel_k_c",
+            BenchmarkName::DiabaticSteelKC | BenchmarkName::SetupSteelKC => {
+                "fds/1D/Diabatic/steel_k_c"
+            }
+            BenchmarkName::DiabaticGypsumKC => "fds/1D/Diabatic/gypsum_k_c",
 
             BenchmarkName::DiabaticThickness005 => "fds/1D/Diabatic/thickness_steel_k_c/005cm",
             BenchmarkName::DiabaticThickness010 => "fds/1D/Diabatic/thickness_steel_k_c/010cm",
@@ -125,6 +242,8 @@ impl BenchmarkName {
 
             BenchmarkName::Adiabatic => "fds/1D/Adiabatic/concrete_k_c",
 
+            BenchmarkName::Diabatic2DSteelKC => "fds/2D/Diabatic/steel_k_c",
+
             BenchmarkName::DiabaticAllMaterialsKC
             | BenchmarkName::DiabaticThickness
             | BenchmarkName::SpeedTestFDS
@@ -135,127 +254,386 @@ impl BenchmarkName {
     }
 }
 
-/// This function executes all benchmarks that are defined via `simulation_methods`, `sumulation_kinds` and `benchmark_names`.
-/// - If `sumulation_methods` == [`None`], all simulation methods are checked.
-/// - If `sumulation_kinds` == [`None`], all simulation types are checked.
-/// - If `benchmark_names` == [`None`], all benchmarks are performed.
-/// # Errors
-///
-/// This function will return an error if the simulations can not be started.
-pub fn run_benchmark(
-    name: &str,
-    simulation_methods: Option<&[SimulationMethod]>,
-    simulation_kinds: Option<&[SimulationKind]>,
-    benchmark_names: Option<&[BenchmarkName]>,
-) -> Result<()> {
-    println!("Benchmarks");
-    for benchmark_name in BenchmarkName::MATERIAL {
-        if benchmark_name.is_benchmark(benchmark_names) {
-            for simulation_type in [
-                SimulationType1D::Cpu,
-                SimulationType1D::GpuM1,
-                SimulationType1D::GpuM2,
-                SimulationType1D::GpuM3,
-            ] {
-                if simulation_type.is_simulation_type(simulation_methods)
-                    && SimulationKind::Diabatic.is_simulation_kind(simulation_kinds)
-                {
-                    println!(" {:?} with {:?}.", benchmark_name, simulation_type);
-                    one_dimensional_by_simulation_type(
-                        benchmark_name.path_str(),
-                        name.to_string(),
-                        &BENCHMARK_ELEMENTS,
-                        SimulationKind::Diabatic,
-                        simulation_type,
-                    )?;
-                }
+/// The simulation method of a [`BenchmarkJob`].
+#[derive(Debug, Clone, Copy)]
+enum BenchmarkJobType {
+    OneDimensional(SimulationType1D),
+    TwoDimensional(SimulationType2D),
+    /// Only times the setup of the one dimensional simulation method.
+    Setup(SimulationType1D),
+}
+
+/// A single benchmark that is executed for all of its sizes.
+#[derive(Debug, Clone)]
+struct BenchmarkJob {
+    benchmark_name: BenchmarkName,
+    elements: Vec<usize>,
+    simulation_kind: SimulationKind,
+    simulation_type: BenchmarkJobType,
+}
+impl BenchmarkJob {
+    /// Returns `true` if this [`BenchmarkJob`] uses the GPU and therefore has to be run serially.
+    fn is_gpu(&self) -> bool {
+        !matches!(
+            self.simulation_type,
+            BenchmarkJobType::OneDimensional(SimulationType1D::Cpu)
+                | BenchmarkJobType::TwoDimensional(SimulationType2D::Cpu)
+                | BenchmarkJobType::Setup(SimulationType1D::Cpu)
+        )
+    }
+
+    /// Returns `true` if this [`BenchmarkJob`] splits the wall elements into chunks of the size set with [`set_max_element_per_chunk`].
+    fn uses_chunks(&self) -> bool {
+        matches!(
+            self.simulation_type,
+            BenchmarkJobType::OneDimensional(SimulationType1D::GpuM1 | SimulationType1D::GpuM3)
+        )
+    }
+
+    /// Returns the prefix of the progress bars of this [`BenchmarkJob`].
+    fn prefix(&self) -> String {
+        match self.simulation_type {
+            BenchmarkJobType::OneDimensional(simulation_type) => {
+                format!("{:?} with {}", self.benchmark_name, simulation_type)
+            }
+            BenchmarkJobType::TwoDimensional(simulation_type) => {
+                format!("{:?} with {}", self.benchmark_name, simulation_type)
+            }
+            BenchmarkJobType::Setup(simulation_type) => {
+                format!("{:?} with {}", self.benchmark_name, simulation_type)
             }
         }
     }
-    for benchmark_name in BenchmarkName::MATERIAL {
-        if benchmark_name.is_benchmark(benchmark_names) {
-            let simulation_type = SimulationType1D::GpuM3;
-            if simulation_type.is_simulation_type(simulation_methods)
-                && SimulationKind::Diabatic.is_simulation_kind(simulation_kinds)
-            {
-                println!(" {:?} with {:?}.", benchmark_name, simulation_type);
+
+    /// Runs this [`BenchmarkJob`] or only prints it in a dry run.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the simulations can not be started.
+    fn run(&self, label: &str, multi_progress: &MultiProgress) -> Result<()> {
+        let path = self.benchmark_name.path_str();
+        if get_dry_run() {
+            println!(" {}.", self.prefix());
+            println!(
+                "  Would run {:?} benchmark at {path:?} for the sizes {:?}.",
+                self.simulation_kind, self.elements
+            );
+            return Ok(());
+        }
+        match self.simulation_type {
+            BenchmarkJobType::OneDimensional(simulation_type) => {
                 one_dimensional_by_simulation_type(
-                    benchmark_name.path_str(),
-                    name.to_string(),
-                    &BENCHMARK_CHUNK,
-                    SimulationKind::Diabatic,
+                    path,
+                    label.to_string(),
+                    &self.elements,
+                    self.simulation_kind,
                     simulation_type,
-                )?;
+                    multi_progress,
+                    &self.prefix(),
+                )
             }
+            BenchmarkJobType::TwoDimensional(simulation_type) => {
+                two_dimensional_by_simulation_type(
+                    path,
+                    label.to_string(),
+                    &self.elements,
+                    self.simulation_kind,
+                    simulation_type,
+                    multi_progress,
+                    &self.prefix(),
+                )
+            }
+            BenchmarkJobType::Setup(simulation_type) => one_dimensional_setup_by_simulation_type(
+                path,
+                label.to_string(),
+                &self.elements,
+                simulation_type,
+                multi_progress,
+                &self.prefix(),
+            ),
         }
     }
+}
 
-    for benchmark_name in BenchmarkName::THICKNESS {
-        if benchmark_name.is_benchmark(benchmark_names) {
-            for simulation_type in [SimulationType1D::Cpu, SimulationType1D::GpuM3] {
-                if simulation_type.is_simulation_type(simulation_methods)
-                    && SimulationKind::Diabatic.is_simulation_kind(simulation_kinds)
-                {
-                    println!(" {:?} with {:?}.", benchmark_name, simulation_type);
-                    one_dimensional_by_simulation_type(
-                        benchmark_name.path_str(),
-                        name.to_string(),
-                        &BENCHMARK_ELEMENTS,
-                        SimulationKind::Diabatic,
-                        simulation_type,
-                    )?;
+/// Collects all benchmarks that are defined via `simulation_methods`, `sumulation_kinds` and `benchmark_names` in the order they are run serially.
+fn benchmark_jobs(
+    simulation_methods: Option<&[SimulationMethod]>,
+    simulation_kinds: Option<&[SimulationKind]>,
+    benchmark_names: Option<&[BenchmarkName]>,
+) -> Vec<BenchmarkJob> {
+    let mut jobs = vec![];
+    let benchmark_elements = get_benchmark_elements();
+    let mut push_1d = |benchmark_name: BenchmarkName,
+                       simulation_types: &[SimulationType1D],
+                       elements: &[usize],
+                       simulation_kind: SimulationKind| {
+        if benchmark_name.is_benchmark(benchmark_names)
+            && benchmark_name.is_selected_thickness()
+            && simulation_kind.is_simulation_kind(simulation_kinds)
+        {
+            for &simulation_type in simulation_types {
+                if simulation_type.is_simulation_type(simulation_methods) {
+                    jobs.push(BenchmarkJob {
+                        benchmark_name,
+                        elements: elements.to_vec(),
+                        simulation_kind,
+                        simulation_type: BenchmarkJobType::OneDimensional(simulation_type),
+                    });
                 }
             }
         }
+    };
+
+    for benchmark_name in BenchmarkName::MATERIAL {
+        push_1d(
+            benchmark_name,
+            &SimulationType1D::ALL_1D,
+            &benchmark_elements,
+            SimulationKind::Diabatic,
+        );
     }
+    for benchmark_name in BenchmarkName::MATERIAL {
+        push_1d(
+            benchmark_name,
+            &[SimulationType1D::GpuM3, SimulationType1D::GpuM4],
+            &BENCHMARK_CHUNK,
+            SimulationKind::Diabatic,
+        );
+    }
+
     for benchmark_name in BenchmarkName::THICKNESS {
-        if benchmark_name.is_benchmark(benchmark_names) {
-            let simulation_type = SimulationType1D::GpuM3;
-            if simulation_type.is_simulation_type(simulation_methods)
-                && SimulationKind::Diabatic.is_simulation_kind(simulation_kinds)
-            {
-                println!(" {:?} with {:?}.", benchmark_name, simulation_type);
-                one_dimensional_by_simulation_type(
-                    benchmark_name.path_str(),
-                    name.to_string(),
-                    &BENCHMARK_CHUNK,
-                    SimulationKind::Diabatic,
-                    simulation_type,
-                )?;
+        push_1d(
+            benchmark_name,
+            &[SimulationType1D::Cpu, SimulationType1D::GpuM3],
+            &benchmark_elements,
+            SimulationKind::Diabatic,
+        );
+    }
+    for benchmark_name in BenchmarkName::THICKNESS {
+        push_1d(
+            benchmark_name,
+            &[SimulationType1D::GpuM3, SimulationType1D::GpuM4],
+            &BENCHMARK_CHUNK,
+            SimulationKind::Diabatic,
+        );
+    }
+
+    push_1d(
+        BenchmarkName::Adiabatic,
+        &SimulationType1D::ALL_1D,
+        &benchmark_elements,
+        SimulationKind::Adiabatic,
+    );
+
+    if BenchmarkName::SetupSteelKC.is_benchmark(benchmark_names)
+        && SimulationKind::Diabatic.is_simulation_kind(simulation_kinds)
+    {
+        for simulation_type in SimulationType1D::ALL_1D {
+            if simulation_type.is_simulation_type(simulation_methods) {
+                jobs.push(BenchmarkJob {
+                    benchmark_name: BenchmarkName::SetupSteelKC,
+                    elements: benchmark_elements.clone(),
+                    simulation_kind: SimulationKind::Diabatic,
+                    simulation_type: BenchmarkJobType::Setup(simulation_type),
+                });
             }
         }
     }
 
-    if BenchmarkName::Adiabatic.is_benchmark(benchmark_names) {
-        for simulation_type in [
-            SimulationType1D::Cpu,
-            SimulationType1D::GpuM1,
-            SimulationType1D::GpuM2,
-            SimulationType1D::GpuM3,
-        ] {
-            if simulation_type.is_simulation_type(simulation_methods)
-                && SimulationKind::Adiabatic.is_simulation_kind(simulation_kinds)
-            {
-                println!(
-                    " {:?} with {:?}.",
-                    BenchmarkName::Adiabatic,
-                    simulation_type
-                );
-                one_dimensional_by_simulation_type(
-                    BenchmarkName::Adiabatic.path_str(),
-                    name.to_string(),
-                    &BENCHMARK_ELEMENTS,
-                    SimulationKind::Adiabatic,
-                    simulation_type,
-                )?;
+    if BenchmarkName::Diabatic2DSteelKC.is_benchmark(benchmark_names)
+        && SimulationKind::Diabatic.is_simulation_kind(simulation_kinds)
+    {
+        for simulation_type in SimulationType2D::ALL_2D {
+            if simulation_type.is_simulation_type(simulation_methods) {
+                jobs.push(BenchmarkJob {
+                    benchmark_name: BenchmarkName::Diabatic2DSteelKC,
+                    elements: benchmark_elements.clone(),
+                    simulation_kind: SimulationKind::Diabatic,
+                    simulation_type: BenchmarkJobType::TwoDimensional(simulation_type),
+                });
+            }
+        }
+    }
+    jobs
+}
+
+/// Checks that all element counts of the benchmarks are divisible by the wall element count of their simulation, before any benchmark runs.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - a simulation of the benchmarks can not be loaded.
+/// - element counts are not compatible, listing every simulation and element count.
+fn validate_element_counts(jobs: &[BenchmarkJob]) -> Result<()> {
+    let mut wall_element_counts: Vec<(&str, bool, usize)> = vec![];
+    let mut incompatible = vec![];
+    for job in jobs {
+        let path = job.benchmark_name.path_str();
+        let two_dimensional = matches!(job.simulation_type, BenchmarkJobType::TwoDimensional(_));
+        let wall_element_count = match wall_element_counts
+            .iter()
+            .find(|(p, t, _)| *p == path && *t == two_dimensional)
+        {
+            Some((_, _, wall_element_count)) => *wall_element_count,
+            None => {
+                let wall_element_count = if two_dimensional {
+                    load_fds_simulation_two_dimensional(path)
+                        .map(|(_, wall_section)| wall_section.columns.len())
+                } else {
+                    load_fds_simulation_one_dimensional(path)
+                        .map(|(_, wall_elements)| wall_elements.len())
+                }
+                .with_context(|| {
+                    format!(
+                        "Failed to load the simulation at {path:?} of the benchmark {:?}.",
+                        job.benchmark_name
+                    )
+                })?;
+                wall_element_counts.push((path, two_dimensional, wall_element_count));
+                wall_element_count
+            }
+        };
+        for &elements in &job.elements {
+            if let Err(err) = duplication(elements, wall_element_count) {
+                let message = format!("  {path:?}: {err}");
+                if !incompatible.contains(&message) {
+                    incompatible.push(message);
+                }
             }
         }
     }
+    if !incompatible.is_empty() {
+        bail!(
+            "{} element counts of the benchmarks are not divisible by the wall element count of their simulation:\n{}",
+            incompatible.len(),
+            incompatible.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// This function executes all benchmarks that are defined via `simulation_methods`, `sumulation_kinds` and `benchmark_names`.
+/// - If `sumulation_methods` == [`None`], all simulation methods are checked.
+/// - If `sumulation_kinds` == [`None`], all simulation types are checked.
+/// - If `benchmark_names` == [`None`], all benchmarks are performed.
+/// - The thickness benchmarks are limited to the thicknesses set with [`set_thicknesses`].
+///
+/// If more than one benchmark thread is set, the CPU benchmarks run in parallel on a thread pool and the GPU benchmarks run serially afterwards, see [`run_jobs`].
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - a simulation of the benchmarks can not be loaded.
+/// - an element count is not divisible by the wall element count of its simulation.
+/// - the simulations can not be started.
+/// - the thread pool can not be created.
+pub fn run_benchmark(
+    name: &str,
+    simulation_methods: Option<&[SimulationMethod]>,
+    simulation_kinds: Option<&[SimulationKind]>,
+    benchmark_names: Option<&[BenchmarkName]>,
+) -> Result<()> {
+    println!("Benchmarks");
+    let jobs = benchmark_jobs(simulation_methods, simulation_kinds, benchmark_names);
+    validate_element_counts(&jobs)?;
+    let multi_progress = MultiProgress::new();
+    run_jobs(name, jobs, &multi_progress)?;
 
     if BenchmarkName::SpeedTestFDS.is_benchmark(benchmark_names) {
         println!("Speed Test");
-        fds::benchmark(name)?
+        if get_dry_run() {
+            println!(
+                "  Would run fds speed test for the sizes {:?} with {:?} cores.",
+                get_benchmark_elements(),
+                fds::get_speed_test_cores()
+            );
+        } else {
+            fds::benchmark(name, &multi_progress)?
+        }
+    }
+
+    Ok(())
+}
+
+/// Executes the benchmarks like [`run_benchmark`] once for each of the passed chunk sizes.
+/// The first chunk size writes to the benchmark `name`. Every other chunk size only reruns the methods that use chunks and writes to a directory with the chunk size as suffix, e.g. `desktop_l_4096`.
+/// A chunk size that is not smaller than the largest benchmark size disables the chunks and uses the suffix `n`, e.g. `desktop_l_n`, as expected by the chunk comparison plots.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - no chunk size is passed.
+/// - a benchmark of any chunk size fails, see [`run_benchmark`].
+pub fn run_benchmark_with_chunk_sizes(
+    name: &str,
+    chunk_sizes: &[usize],
+    simulation_methods: Option<&[SimulationMethod]>,
+    simulation_kinds: Option<&[SimulationKind]>,
+    benchmark_names: Option<&[BenchmarkName]>,
+) -> Result<()> {
+    let Some((&chunk_size, other_chunk_sizes)) = chunk_sizes.split_first() else {
+        bail!("The benchmark \"{name}\" has no chunk size.");
+    };
+    set_max_element_per_chunk(chunk_size);
+    run_benchmark(name, simulation_methods, simulation_kinds, benchmark_names)?;
+
+    for &chunk_size in other_chunk_sizes {
+        set_max_element_per_chunk(chunk_size);
+        let label = chunk_size_label(name, chunk_size);
+        println!("Benchmarks with chunk size {chunk_size} as {label:?}");
+        let jobs = benchmark_jobs(simulation_methods, simulation_kinds, benchmark_names)
+            .into_iter()
+            .filter(BenchmarkJob::uses_chunks)
+            .collect::<Vec<_>>();
+        run_jobs(&label, jobs, &MultiProgress::new())?;
     }
+    Ok(())
+}
 
+/// Returns the benchmark name of an additional chunk size, see [`run_benchmark_with_chunk_sizes`].
+fn chunk_size_label(name: &str, chunk_size: usize) -> String {
+    let largest_size = get_benchmark_elements()
+        .iter()
+        .chain(BENCHMARK_CHUNK.iter())
+        .max()
+        .copied()
+        .unwrap_or_default();
+    if chunk_size >= largest_size {
+        format!("{name}_n")
+    } else {
+        format!("{name}_{chunk_size}")
+    }
+}
+
+/// Runs the passed benchmark jobs. If more than one benchmark thread is set, the CPU benchmarks run in parallel on a thread pool.
+/// The GPU benchmarks run serially after the thread pool finished, so their times are not influenced by the load of the CPU benchmarks.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the simulations can not be started.
+/// - the thread pool can not be created.
+fn run_jobs(name: &str, jobs: Vec<BenchmarkJob>, multi_progress: &MultiProgress) -> Result<()> {
+    let benchmark_threads = get_benchmark_threads();
+    if benchmark_threads > 1 && !get_dry_run() {
+        let (gpu_jobs, cpu_jobs): (Vec<_>, Vec<_>) = jobs.into_iter().partition(|j| j.is_gpu());
+        let thread_pool = ThreadPoolBuilder::new()
+            .num_threads(benchmark_threads)
+            .build()
+            .with_context(|| "Failed to create the thread pool for the CPU benchmarks.")?;
+        thread_pool.install(|| {
+            cpu_jobs
+                .par_iter()
+                .map(|job| job.run(name, multi_progress))
+                .collect::<Result<()>>()
+        })?;
+        for job in gpu_jobs {
+            job.run(name, multi_progress)?;
+        }
+    } else {
+        for job in jobs {
+            job.run(name, multi_progress)?;
+        }
+    }
     Ok(())
 }
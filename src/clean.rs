@@ -0,0 +1,74 @@
+//! This module contains the removal of the generated benchmarks, plots and FDS results.
+
+use std::fs;
+
+use anyhow::*;
+use clap::ValueEnum;
+
+use crate::{
+    dry_run::get_dry_run,
+    fds::find_result_dirs,
+    output::{benchmark_root, plot_root},
+};
+
+/// The generated files that can be removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CleanTarget {
+    /// The `plot` directory.
+    Plots,
+    /// The `benchmark` directory.
+    Benchmarks,
+    /// The `result` directories of all FDS simulations.
+    Results,
+    /// The plots, benchmarks and results.
+    All,
+}
+
+/// Removes the generated files of the passed [`CleanTarget`]. The templates and simulation files are never removed.
+/// The benchmark directory also contains the recorded benchmarks of the project, so it is only removed if `force_clean` is set.
+/// A symbolic link is skipped, so no results outside of the project are removed. In a dry run the paths are only printed.
+///
+/// # Errors
+///
+/// This function will return an error if a directory can not be removed.
+pub fn clean(clean_target: CleanTarget, force_clean: bool) -> Result<()> {
+    let mut paths = vec![];
+    if matches!(clean_target, CleanTarget::Plots | CleanTarget::All) {
+        paths.push(plot_root());
+    }
+    if matches!(clean_target, CleanTarget::Benchmarks | CleanTarget::All) {
+        if force_clean {
+            paths.push(benchmark_root());
+        } else {
+            println!(
+                "  Skipped the benchmark directory {:?}, since it contains the recorded benchmarks. Set --force-clean to delete it.",
+                benchmark_root()
+            );
+        }
+    }
+    if matches!(clean_target, CleanTarget::Results | CleanTarget::All) {
+        paths.extend(find_result_dirs());
+    }
+
+    for path in paths {
+        let std::result::Result::Ok(metadata) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if metadata.is_symlink() {
+            println!("  Skipped symbolic link at {path:?}.");
+            continue;
+        }
+        if get_dry_run() {
+            println!("  Would delete {path:?}.");
+            continue;
+        }
+        if metadata.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        }
+        .with_context(|| format!("Failed to delete {path:?}."))?;
+        println!("  Deleted {path:?}.");
+    }
+    Ok(())
+}
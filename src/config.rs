@@ -0,0 +1,168 @@
+//! This module contains the config file that can replace the most common command line arguments.
+
+use std::{collections::BTreeMap, fs::File, io::BufReader, path::Path};
+
+use anyhow::*;
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer};
+
+#[cfg(feature = "plot")]
+use crate::plot::PlotType;
+use crate::{
+    benchmark::BenchmarkName,
+    heat_transfer::simulations::{SimulationKind, SimulationMethod},
+};
+
+/// The file the chunk sizes of the benchmark labels are read from, if they are not set inside the config file.
+pub const BENCHMARKS_FILE: &str = "benchmarks.txt";
+
+/// The values of a config file, e.g.
+/// `{ "benchmark": "desktop_l", "method": ["1d_cpu", "1d_gpu_m3"], "chunk_sizes": { "desktop_l": [18432, 10000000] } }`.
+/// The values of the enums are written like the values of the command line arguments.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Whether the fds simulations are started.
+    pub simulations: bool,
+    /// The name the benchmark is assigned to.
+    pub benchmark: Option<String>,
+    /// The benchmarks that should be run.
+    #[serde(deserialize_with = "value_enums")]
+    pub benchmark_name: Option<Vec<BenchmarkName>>,
+    /// The simulation methods that should be used.
+    #[serde(deserialize_with = "value_enums")]
+    pub method: Option<Vec<SimulationMethod>>,
+    /// The simulation kinds that should be used.
+    #[serde(deserialize_with = "value_enums")]
+    pub kind: Option<Vec<SimulationKind>>,
+    /// The plots that should be created.
+    #[cfg(feature = "plot")]
+    #[serde(deserialize_with = "value_enum")]
+    pub plots: Option<PlotType>,
+    /// The plots that should be created. They are ignored without the `plot` feature.
+    #[cfg(not(feature = "plot"))]
+    pub plots: Option<serde::de::IgnoredAny>,
+    /// The maximum numbers of wall elements per chunk of each benchmark label, either a single size or a list of sizes. A label that is not set here is read from [`BENCHMARKS_FILE`].
+    #[serde(deserialize_with = "chunk_sizes")]
+    pub chunk_sizes: BTreeMap<String, Vec<usize>>,
+}
+impl Config {
+    /// Loads the [`Config`] from a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file can not be read or contains unknown or invalid values.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("Failed to open config file at {path:?}."))?;
+        let config = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to parse config file at {path:?}."))?;
+        println!("Loaded config file at {path:?}");
+        Ok(config)
+    }
+
+    /// Returns the maximum numbers of wall elements per chunk of the benchmark label.
+    /// The chunk sizes of the config file are preferred, otherwise the label is read from [`BENCHMARKS_FILE`], where each line has the form `label = size` or `label = size1,size2,...`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the label is not set inside the config file and [`BENCHMARKS_FILE`] can not be read.
+    /// - a size of the label can not be parsed or is 0.
+    /// - no chunk size is assigned to the label.
+    pub fn chunk_sizes(&self, label: &str) -> Result<Vec<usize>> {
+        let chunk_sizes = match self.chunk_sizes.get(label) {
+            Some(chunk_sizes) => chunk_sizes.clone(),
+            None => chunk_sizes_from_file(label)?,
+        };
+        if chunk_sizes.is_empty() {
+            bail!("The benchmark label \"{label}\" has no chunk size assigned.");
+        }
+        if chunk_sizes.contains(&0) {
+            bail!("The chunk sizes {chunk_sizes:?} of the benchmark label \"{label}\" must be positive.");
+        }
+        Ok(chunk_sizes)
+    }
+}
+
+/// Reads the chunk sizes of the benchmark label from [`BENCHMARKS_FILE`].
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - [`BENCHMARKS_FILE`] can not be read.
+/// - a size of the label can not be parsed.
+/// - no chunk size is assigned to the label.
+fn chunk_sizes_from_file(label: &str) -> Result<Vec<usize>> {
+    for line in std::fs::read_to_string(BENCHMARKS_FILE)
+        .with_context(|| format!("Failed to read file at {BENCHMARKS_FILE:?}"))?
+        .lines()
+    {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let mut splits = line.split('=');
+        if let (Some(name), Some(numbers)) = (splits.next(), splits.next()) {
+            if name.trim() == label {
+                return numbers
+                    .split(',')
+                    .map(|number| {
+                        number
+                            .trim()
+                            .parse::<usize>()
+                            .with_context(|| format!("Failed to parse {number}"))
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    bail!("No chunk size assigned to the current benchmark label \"{label}\". Insert \"{label} = [size]\" inside {BENCHMARKS_FILE:?} as a new line or add it to \"chunk_sizes\" of the config file.")
+}
+
+/// Deserializes the chunk sizes of each benchmark label, which are either a single size or a list of sizes.
+fn chunk_sizes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<BTreeMap<String, Vec<usize>>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum ChunkSizes {
+        Single(usize),
+        List(Vec<usize>),
+    }
+    std::result::Result::Ok(
+        BTreeMap::<String, ChunkSizes>::deserialize(deserializer)?
+            .into_iter()
+            .map(|(label, chunk_sizes)| match chunk_sizes {
+                ChunkSizes::Single(chunk_size) => (label, vec![chunk_size]),
+                ChunkSizes::List(chunk_sizes) => (label, chunk_sizes),
+            })
+            .collect(),
+    )
+}
+
+/// Deserializes a list of [`ValueEnum`]s by the names of their command line values.
+fn value_enums<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<T>>, D::Error> {
+    Option::<Vec<String>>::deserialize(deserializer)?
+        .map(|values| values.iter().map(|v| parse_value_enum(v)).collect())
+        .transpose()
+}
+
+/// Deserializes a single [`ValueEnum`] by the name of its command line value.
+#[cfg(feature = "plot")]
+fn value_enum<'de, D: Deserializer<'de>, T: ValueEnum>(
+    deserializer: D,
+) -> std::result::Result<Option<T>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| parse_value_enum(&value))
+        .transpose()
+}
+
+/// Parses a [`ValueEnum`] by the name of its command line value.
+fn parse_value_enum<E: serde::de::Error, T: ValueEnum>(value: &str) -> std::result::Result<T, E> {
+    T::from_str(value, false).map_err(E::custom)
+}
@@ -0,0 +1,19 @@
+//! This module contains the switch to only print what would be simulated, benchmarked or plotted.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the actions are only printed instead of executed. This value is replaced at the start of the program.
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Set whether the actions are only printed instead of executed.
+/// In a dry run no simulation, GPU work or file write is done.
+pub fn set_dry_run(dry_run: bool) {
+    DRY_RUN.store(dry_run, Ordering::Relaxed);
+    println!("Set dry run to {dry_run}")
+}
+
+/// Get whether the actions are only printed instead of executed.
+#[inline]
+pub fn get_dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
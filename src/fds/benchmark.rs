@@ -1,19 +1,128 @@
-use std::{io::Write, num::ParseFloatError, path::PathBuf};
+use std::{
+    num::{NonZeroUsize, ParseFloatError},
+    path::Path,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        PoisonError, RwLock,
+    },
+};
 
 use anyhow::Context;
+use clap::ValueEnum;
+use indicatif::MultiProgress;
 
-use crate::heat_transfer::simulations::{
-    BenchmarkPathPart, BenchmarkWriter, SimulationMethod, SIMULATION_RERUNS,
+use crate::{
+    heat_transfer::simulations::{
+        finish_progress_bar, get_simulation_reruns, rerun_progress_bar, skip_complete_benchmark,
+        BenchmarkPathPart, BenchmarkWriter, SimulationMethod,
+    },
+    output::benchmark_root,
 };
 
 use super::{
     sampler::create_simulation_for_speed_test,
-    simulations::{run_simulation_unchecked, Status},
+    simulations::{remove_fds_results, run_simulation_unchecked, Status},
 };
 
 pub const PATH: &str = "fds/1D/AdiabaticSpeedTest";
 
-/// Executes the adiabatic FDS simulations that serve as a benchmark. Each simulation is repeated [`SIMULATION_RERUNS`] times. The time to calculate the walls is selected from the created `heat_transfer_cpu.csv` file. If several threads are executed, the time required is saved for each individual thread. The median is therefore selected from the values and written to the benchmark file.
+/// The core counts the speed test is run with. If it is not set, only the available parallelism is used. This value is replaced at the start of the program.
+static SPEED_TEST_CORES: RwLock<Option<Vec<usize>>> = RwLock::new(None);
+
+/// Set the core counts the FDS speed test is run with, e.g. `[1, 2, 4, 8]` for a strong scaling test.
+pub fn set_speed_test_cores(speed_test_cores: Vec<usize>) {
+    println!("Set speed test cores to {speed_test_cores:?}");
+    *SPEED_TEST_CORES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(speed_test_cores);
+}
+
+/// Get the core counts the FDS speed test is run with.
+#[inline]
+pub fn get_speed_test_cores() -> Vec<usize> {
+    SPEED_TEST_CORES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| vec![available_cores()])
+}
+
+/// Which FDS results of the speed test reruns are kept inside the `result` directories.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum KeepResults {
+    /// Keep the results of every rerun. Each rerun replaces the files of the previous one.
+    #[default]
+    All,
+    /// Keep only the results of the last rerun of each simulation.
+    Last,
+    /// Remove the results after the time of each rerun was read.
+    None,
+}
+
+/// Which FDS results of the speed test reruns are kept, stored as [`KeepResults`] discriminant. This value is replaced at the start of the program.
+static KEEP_RESULTS: AtomicU8 = AtomicU8::new(KeepResults::All as u8);
+
+/// Set which FDS results of the speed test reruns are kept.
+pub fn set_keep_results(keep_results: KeepResults) {
+    KEEP_RESULTS.store(keep_results as u8, Ordering::Relaxed);
+    println!("Set keep results to {keep_results:?}")
+}
+
+/// Get which FDS results of the speed test reruns are kept.
+#[inline]
+pub fn get_keep_results() -> KeepResults {
+    match KEEP_RESULTS.load(Ordering::Relaxed) {
+        x if x == KeepResults::Last as u8 => KeepResults::Last,
+        x if x == KeepResults::None as u8 => KeepResults::None,
+        _ => KeepResults::All,
+    }
+}
+
+/// Returns the available parallelism of the machine.
+fn available_cores() -> usize {
+    std::thread::available_parallelism()
+        .map(NonZeroUsize::get)
+        .unwrap_or(1)
+}
+
+/// The prefix older benchmarks used for the speed test with all available cores of the machine they ran on.
+const LEGACY_MAX_CORES_PREFIX: &str = "multi_core";
+
+/// Returns the prefix of the benchmark path for the speed test with the passed core count.
+/// One core keeps the prefix `single_core`, any other core count is written to `<cores>_cores`, so the path does not depend on the machine.
+fn cores_prefix(cores: usize) -> String {
+    if cores == 1 {
+        "single_core".to_string()
+    } else {
+        format!("{cores}_cores")
+    }
+}
+
+/// Returns whether the directory name is a prefix of the speed test benchmark path, see [`cores_prefix`] and [`LEGACY_MAX_CORES_PREFIX`].
+pub fn is_cores_prefix(name: &str) -> bool {
+    name == "single_core"
+        || name == LEGACY_MAX_CORES_PREFIX
+        || name
+            .strip_suffix("_cores")
+            .is_some_and(|cores| cores.parse::<usize>().is_ok())
+}
+
+/// Returns the prefix of the benchmark path for the speed test with the most cores of [`get_speed_test_cores`] that has results for the label inside `fds_directory`.
+/// If none has results, [`LEGACY_MAX_CORES_PREFIX`] is returned.
+pub fn max_cores_prefix<P: AsRef<Path>>(fds_directory: P, label: &str) -> String {
+    let fds_directory = benchmark_root().join(fds_directory);
+    let mut cores = get_speed_test_cores();
+    cores.sort_unstable();
+    cores
+        .into_iter()
+        .rev()
+        .map(cores_prefix)
+        .find(|prefix| fds_directory.join(prefix).join(label).is_dir())
+        .unwrap_or_else(|| LEGACY_MAX_CORES_PREFIX.to_string())
+}
+
+/// Executes the adiabatic FDS simulations that serve as a benchmark for each core count of [`get_speed_test_cores`]. Each simulation is repeated [`get_simulation_reruns`] times. The time to calculate the walls is selected from the created `heat_transfer_cpu.csv` file. If several threads are executed, the time required is saved for each individual thread. The median is therefore selected from the values and written to the benchmark file.
+/// After the time of a rerun was read, the FDS results are removed as set with [`set_keep_results`]. The results of a failed rerun are always kept.
 ///
 /// # Panics
 ///
@@ -22,29 +131,37 @@ pub const PATH: &str = "fds/1D/AdiabaticSpeedTest";
 /// # Errors
 ///
 /// This function will return an error if
+/// - the simulations can not be created for a core count.
 /// - an error occurs during the simulation.
 /// - This function will return an error if `heat_transfer_cpu.csv` cannot be read or the values cannot be determined from the file.
-pub fn benchmark(label: &str) -> Result<(), anyhow::Error> {
-    for (simulation_path, size, cores) in create_simulation_for_speed_test()? {
-        println!("\n Run FDS simulation at {simulation_path:?}");
-        print!("  0/{SIMULATION_RERUNS}");
-        std::io::stdout().flush().unwrap();
+/// - the FDS results can not be removed.
+pub fn benchmark(label: &str, multi_progress: &MultiProgress) -> Result<(), anyhow::Error> {
+    let reruns = get_simulation_reruns();
+    let keep_results = get_keep_results();
+    for (simulation_path, size, cores) in create_simulation_for_speed_test(&get_speed_test_cores())?
+    {
+        let prefix = format!("FDS simulation at {simulation_path:?}");
         let parent = simulation_path.parent().unwrap();
-        let mut benchmark_writer = BenchmarkWriter::try_new(
-            PathBuf::from("benchmark").join(parent.parent().unwrap()),
-            &BenchmarkPathPart::new(
-                if cores == 1 {
-                    Some("single_core".to_string())
-                } else {
-                    Some("multi_core".to_string())
-                },
-                label.to_string(),
-                SimulationMethod::SpeedTestFDS,
-            ),
+        let benchmark_path = benchmark_root().join(parent.parent().unwrap());
+        let benchmark_path_part = BenchmarkPathPart::new(
+            Some(cores_prefix(cores)),
+            label.to_string(),
+            SimulationMethod::SpeedTestFDS,
+        );
+        if skip_complete_benchmark(
+            &benchmark_path,
+            &benchmark_path_part,
             size,
-        )?;
+            multi_progress,
+            &prefix,
+        )? {
+            continue;
+        }
+        let progress_bar = rerun_progress_bar(multi_progress, prefix, reruns)?;
+        let mut benchmark_writer =
+            BenchmarkWriter::try_new(benchmark_path, &benchmark_path_part, size)?;
         let read_file = parent.join("result/heat_transfer_cpu.csv");
-        for i in 0..SIMULATION_RERUNS {
+        for rerun in 0..reruns {
             match run_simulation_unchecked(simulation_path.clone(), cores)? {
                 Status::Passed(_) => unreachable!(),
                 Status::Succeeded(_) => {
@@ -64,14 +181,21 @@ pub fn benchmark(label: &str) -> Result<(), anyhow::Error> {
                         time[time.len() / 2]
                     };
                     benchmark_writer.write(mean_time)?;
-                    print!("\r  {}/{SIMULATION_RERUNS}", i + 1);
-                    std::io::stdout().flush().unwrap();
+                    match keep_results {
+                        KeepResults::All => {}
+                        KeepResults::Last if rerun + 1 == reruns => {}
+                        KeepResults::Last | KeepResults::None => {
+                            remove_fds_results(&simulation_path)?
+                        }
+                    }
+                    progress_bar.inc(1);
                 }
                 Status::Failed(path) => {
                     anyhow::bail!("Failed to run simulation at path {path:?}")
                 }
             }
         }
+        finish_progress_bar(&progress_bar);
     }
 
     Ok(())
@@ -2,54 +2,192 @@ use anyhow::*;
 use std::{
     fs::File,
     io::{BufRead, BufReader, Lines},
-    path::Path,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
 };
 
+/// Whether device names have to match the column names exactly instead of ignoring surrounding whitespace and case. This value is replaced at the start of the program.
+static STRICT_DEVICE_NAMES: AtomicBool = AtomicBool::new(false);
+
+/// Set whether device names have to match the column names exactly instead of ignoring surrounding whitespace and case.
+pub fn set_strict_device_names(strict_device_names: bool) {
+    STRICT_DEVICE_NAMES.store(strict_device_names, Ordering::Relaxed);
+    println!("Set strict device names to {strict_device_names}")
+}
+
+/// Get whether device names have to match the column names exactly instead of ignoring surrounding whitespace and case.
+#[inline]
+pub fn get_strict_device_names() -> bool {
+    STRICT_DEVICE_NAMES.load(Ordering::Relaxed)
+}
+
+/// Returns the device name without surrounding whitespace and in lowercase.
+fn normalize_device_name(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// The magic bytes every gzip-compressed file starts with.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Opens the device file at the path. A gzip-compressed file is detected by its magic header and decompressed while reading.
+/// If no file exists at the path, the path with an additional `.gz` extension is tried.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the file could not be read.
+/// - the file is gzip-compressed, but the `gzip` feature is not enabled.
+fn open_device_file(path: &Path) -> Result<Box<dyn BufRead>> {
+    let gz_path = {
+        let mut gz_path = path.as_os_str().to_owned();
+        gz_path.push(".gz");
+        PathBuf::from(gz_path)
+    };
+    let path = if !path.exists() && gz_path.exists() {
+        gz_path.as_path()
+    } else {
+        path
+    };
+
+    let file = File::open(path).with_context(|| format!("Failed to open file at {path:?}."))?;
+    let mut reader = BufReader::new(file);
+    let is_gzip = reader
+        .fill_buf()
+        .with_context(|| format!("Failed to read file at {path:?}."))?
+        .starts_with(&GZIP_MAGIC);
+    if is_gzip {
+        gzip_reader(reader, path)
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Wraps the reader of a gzip-compressed file into a decoder.
+#[cfg(feature = "gzip")]
+fn gzip_reader(reader: BufReader<File>, _path: &Path) -> Result<Box<dyn BufRead>> {
+    Ok(Box::new(BufReader::new(flate2::bufread::GzDecoder::new(
+        reader,
+    ))))
+}
+
+/// Wraps the reader of a gzip-compressed file into a decoder.
+#[cfg(not(feature = "gzip"))]
+fn gzip_reader(_reader: BufReader<File>, path: &Path) -> Result<Box<dyn BufRead>> {
+    bail!("The file at {path:?} is gzip-compressed, which requires the \"gzip\" feature.")
+}
+
+/// Returns the number of data rows of the device file at the path without the two header lines.
+/// The device file may be gzip-compressed, see [`open_device_file`].
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read.
+pub fn count_device_rows<P: AsRef<Path>>(path: P) -> Result<usize> {
+    let path = path.as_ref();
+    let mut rows = 0usize;
+    for line in open_device_file(path)?.lines() {
+        line.with_context(|| format!("Failed to read line in file at {path:?}."))?;
+        rows += 1;
+    }
+    Ok(rows.saturating_sub(2))
+}
+
 /// Structure that has a reader for the `heat_transfer_devc.csv` file and can read line by line. When reading, the selected entries are returned as [`Vec<f32>`].
 pub struct Devices {
-    /// Indexes of the selected entries
-    indexes: Vec<usize>,
+    /// Indexes of the selected entries sorted by their column, each with the position it is returned at.
+    sorted_indexes: Vec<(usize, usize)>,
+    /// The number of selected entries including the missing optional entries.
+    device_count: usize,
     /// reader to the device file
-    lines: Lines<BufReader<File>>,
+    lines: Lines<Box<dyn BufRead>>,
 }
 
 impl Devices {
     /// Attempts to create a [`Devices`] from the path to the device file and the selected entries.
+    /// The device file may be gzip-compressed, see [`open_device_file`].
+    /// A column matching an entry exactly is preferred. Otherwise surrounding whitespace and case are ignored, unless strict device names are set.
     ///
     /// # Errors
     ///
     /// This function will return an error if
     /// - the file could not be read
-    /// - one passed entry could not be found inside the file.
+    /// - the file is gzip-compressed, but the `gzip` feature is not enabled.
+    /// - one passed entry could not be found inside the file. The error lists all available columns.
     pub fn try_new<P: AsRef<Path>, S: AsRef<str>>(path: P, devices: &[S]) -> Result<Self> {
+        Self::try_new_with_optional(path, devices, |_| false).map(|(devices, _)| devices)
+    }
+
+    /// Attempts to create a [`Devices`] like [`Devices::try_new`], but entries for which `is_optional` returns `true` may be missing inside the file.
+    /// A missing optional entry is always returned as `0.0`. The number of missing optional entries is returned alongside the [`Devices`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the file could not be read
+    /// - the file is gzip-compressed, but the `gzip` feature is not enabled.
+    /// - one passed entry that is not optional could not be found inside the file. The error lists all available columns.
+    pub fn try_new_with_optional<P: AsRef<Path>, S: AsRef<str>, F: Fn(&str) -> bool>(
+        path: P,
+        devices: &[S],
+        is_optional: F,
+    ) -> Result<(Self, usize)> {
         let path = path.as_ref();
-        let file = File::open(path).with_context(|| format!("Failed to open file at {path:?}."))?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-        let mut indexes = vec![None; devices.len()];
+        let mut lines = open_device_file(path)?.lines();
+        let mut columns = vec![];
 
         lines.next();
 
         if let Some(line) = lines.next() {
             let line = line.with_context(|| format!("Failed to read line in file at {path:?}."))?;
-            for (i, s) in line.split(',').map(|s| s.replace('\"', "")).enumerate() {
-                for (j, device) in devices.iter().enumerate() {
-                    if device.as_ref() == s {
-                        indexes[j] = Some(i);
-                    }
-                }
-            }
+            columns = line
+                .split(',')
+                .map(|s| s.replace('\"', ""))
+                .collect::<Vec<_>>();
         }
 
-        if let Some(indexes) = indexes.into_iter().collect::<Option<Vec<_>>>() {
-            if indexes.len() == devices.len() {
-                return Ok(Self { indexes, lines });
-            }
+        let strict_device_names = get_strict_device_names();
+        let indexes = devices
+            .iter()
+            .map(|device| {
+                let device = device.as_ref();
+                columns.iter().rposition(|c| c == device).or_else(|| {
+                    if strict_device_names {
+                        return None;
+                    }
+                    let device = normalize_device_name(device);
+                    columns
+                        .iter()
+                        .rposition(|c| normalize_device_name(c) == device)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let missing = devices
+            .iter()
+            .zip(indexes.iter())
+            .filter(|(device, index)| index.is_none() && !is_optional(device.as_ref()))
+            .map(|(device, _)| device.as_ref())
+            .collect::<Vec<_>>();
+        if missing.is_empty() {
+            let missing_optional = indexes.iter().filter(|index| index.is_none()).count();
+            let mut sorted_indexes = indexes
+                .into_iter()
+                .enumerate()
+                .filter_map(|(position, column)| Some((column?, position)))
+                .collect::<Vec<_>>();
+            sorted_indexes.sort_unstable();
+            return Ok((
+                Self {
+                    sorted_indexes,
+                    device_count: devices.len(),
+                    lines,
+                },
+                missing_optional,
+            ));
         };
 
         Err(anyhow!(
-            "On or multiple devices are missing. {:?}",
-            devices.iter().map(|s| s.as_ref()).collect::<Vec<_>>()
+            "One or multiple devices are missing in the file at {path:?}. Missing: {missing:?}. Available: {columns:?}."
         ))
     }
 }
@@ -64,20 +202,111 @@ impl Iterator for Devices {
                 return Some(Err(err).with_context(|| "Failed to read line."));
             }
         };
-        let splits = line.split(',').map(|s| s.trim()).collect::<Vec<_>>();
-        let len = self.indexes.len();
-        let mut devices = Vec::with_capacity(len);
+        let mut devices = vec![0.0; self.device_count];
+        let mut sorted_indexes = self.sorted_indexes.iter().peekable();
 
-        for i in 0..len {
-            let device = match splits[self.indexes[i]].parse::<f32>() {
+        // The line is only read up to the last selected column and only the selected columns are parsed.
+        for (column, split) in line.split(',').enumerate() {
+            let Some(&&(next_column, _)) = sorted_indexes.peek() else {
+                break;
+            };
+            if column < next_column {
+                continue;
+            }
+            let device = match split.trim().parse::<f32>() {
                 std::result::Result::Ok(ok) => ok,
                 Err(err) => {
                     return Some(Err(err.into()));
                 }
             };
-            devices.push(device);
+            while let Some((_, position)) = sorted_indexes.next_if(|(c, _)| *c == column) {
+                devices[*position] = device;
+            }
+        }
+
+        if sorted_indexes.peek().is_some() {
+            return Some(Err(anyhow!(
+                "The line has fewer entries than the selected devices require."
+            )));
         }
 
         Some(Ok(devices))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Write, time::Instant};
+
+    const COLUMNS: usize = 5000;
+    const ROWS: usize = 200;
+
+    /// Writes a device file with a time column and `COLUMNS - 1` devices, the value of each entry is `row * 10000 + column`.
+    fn write_wide_device_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{name}_{}.csv", std::process::id()));
+        let mut file = std::io::BufWriter::new(File::create(&path).unwrap());
+        let units = (0..COLUMNS)
+            .map(|c| if c == 0 { "s" } else { "C" })
+            .collect::<Vec<_>>();
+        writeln!(file, "{}", units.join(",")).unwrap();
+        let names = (0..COLUMNS)
+            .map(|c| match c {
+                0 => "\"Time\"".to_string(),
+                c => format!("\"D{c}\""),
+            })
+            .collect::<Vec<_>>();
+        writeln!(file, "{}", names.join(",")).unwrap();
+        for row in 0..ROWS {
+            let values = (0..COLUMNS)
+                .map(|c| format!(" {:E}", (row * 10000 + c) as f32))
+                .collect::<Vec<_>>();
+            writeln!(file, "{}", values.join(",")).unwrap();
+        }
+        path
+    }
+
+    #[test]
+    fn wide_device_file_returns_requested_order() {
+        let path = write_wide_device_file("heat_transfer_wide_devc");
+        let selected = ["D4999", "Time", "D17", "D2500", "D17"];
+        let columns = [4999, 0, 17, 2500, 17];
+
+        let start = Instant::now();
+        let rows = Devices::try_new(&path, &selected)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let streamed = start.elapsed();
+
+        // Splitting and parsing every column of a line, like before the lines were streamed.
+        let start = Instant::now();
+        let split_rows = open_device_file(&path)
+            .unwrap()
+            .lines()
+            .skip(2)
+            .map(|line| {
+                let values = line
+                    .unwrap()
+                    .split(',')
+                    .map(|s| s.trim().parse::<f32>().unwrap())
+                    .collect::<Vec<_>>();
+                columns.iter().map(|&c| values[c]).collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let split = start.elapsed();
+        println!("streamed: {streamed:?}, split: {split:?}");
+        assert!(streamed < split, "{streamed:?} >= {split:?}");
+
+        assert_eq!(rows.len(), ROWS);
+        for (row, values) in rows.iter().enumerate() {
+            let expected = columns
+                .iter()
+                .map(|&c| (row * 10000 + c) as f32)
+                .collect::<Vec<_>>();
+            assert_eq!(values, &expected);
+        }
+        assert_eq!(rows, split_rows);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,192 @@
+//! Inspection of the cell grid that is created for a one dimensional simulation.
+
+use std::path::Path;
+
+use anyhow::*;
+
+use crate::heat_transfer::{
+    one_dimensional::{WallCell, WallElement},
+    simulations::load_fds_simulation_one_dimensional_named,
+};
+
+use super::Material;
+
+/// A single [`WallCell`] of a wall element together with its position inside the wall.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedCell {
+    pub cell: WallCell,
+    /// The `ID` of the material of the cell, e.g. `MATL_WALL`.
+    pub material_name: String,
+    /// The distance in m from the front of the wall to the end of the cell.
+    pub depth: f32,
+    /// Whether the cell is one of the duplicated boundary cells at the front or the back of the wall.
+    pub boundary: bool,
+}
+
+/// Loads the one dimensional simulation inside the passed directory and returns the cells of each wall element in the order of the surfaces.
+/// The duplicated boundary cells are included, but do not add to the depth.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be loaded as a one dimensional simulation.
+pub fn inspect_cells<P: AsRef<Path>>(path: P) -> Result<Vec<Vec<InspectedCell>>> {
+    let path = path.as_ref();
+    let (materials, wall_elements) = load_fds_simulation_one_dimensional_named(path)
+        .with_context(|| format!("Failed to load simulation at {path:?}."))?;
+
+    Ok(wall_elements
+        .iter()
+        .map(|wall_element| {
+            let last = wall_element.len().saturating_sub(1);
+            let mut depth = 0.0;
+            wall_element
+                .iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    let boundary = i == 0 || i == last;
+                    if !boundary {
+                        depth += cell.size;
+                    }
+                    InspectedCell {
+                        cell: *cell,
+                        material_name: materials
+                            .get(cell.material as usize)
+                            .map(|(name, _)| name.clone())
+                            .unwrap_or_else(|| format!("material {}", cell.material)),
+                        depth,
+                        boundary,
+                    }
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Prints the cells of each wall element of the one dimensional simulation inside the passed directory as a table.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be loaded as a one dimensional simulation.
+pub fn print_cells<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    for (i, cells) in inspect_cells(path)?.iter().enumerate() {
+        let inner_cells = cells.iter().filter(|c| !c.boundary).count();
+        let thickness = cells.last().map(|c| c.depth).unwrap_or_default();
+        println!(
+            "\n Wall element {i} with {inner_cells} cells and {} boundary cells, thickness {thickness} m",
+            cells.len() - inner_cells
+        );
+        println!(
+            "  {:>5} {:>16} {:>14} {:>14} {:>12}",
+            "Cell", "Material", "Size [m]", "Depth [m]", "Temp. [°C]"
+        );
+        for (
+            j,
+            InspectedCell {
+                cell,
+                material_name,
+                depth,
+                boundary,
+            },
+        ) in cells.iter().enumerate()
+        {
+            println!(
+                "  {j:>5} {material_name:>16} {:>14.8} {depth:>14.8} {:>12.2}{}",
+                cell.size,
+                cell.temperature,
+                if *boundary { "  boundary" } else { "" }
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The smallest time step limit of the explicit stability criterion and the cell it belongs to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StableTimeStep {
+    /// The largest time step in s an explicit scheme is stable with, see [`Material::max_explicit_time_step`].
+    pub time_step: f32,
+    /// The index of the wall element of the cell.
+    pub wall_element: usize,
+    /// The index of the cell inside the wall element, including the boundary cell at the front.
+    pub cell: usize,
+}
+
+/// Returns the smallest explicit stability limit `ρ⋅c⋅Δx² / (2⋅k)` of all cells at their initial temperature, or [`None`] if there are no cells.
+/// The duplicated boundary cells have the same limit as their neighbors and are skipped.
+/// The solver is implicit and stable with larger time steps, so the limit only hints at cells that are too thin to resolve the time step accurately.
+///
+/// # Panics
+///
+/// Panics if the material of a cell does not exist inside the passed materials.
+pub fn max_stable_time_step(
+    materials: &[Material],
+    wall_elements: &[WallElement],
+) -> Option<StableTimeStep> {
+    wall_elements
+        .iter()
+        .enumerate()
+        .flat_map(|(i, wall_element)| {
+            let last = wall_element.len().saturating_sub(1);
+            wall_element
+                .iter()
+                .enumerate()
+                .filter(move |(j, _)| *j != 0 && *j != last)
+                .map(move |(j, cell)| StableTimeStep {
+                    time_step: materials[cell.material as usize]
+                        .max_explicit_time_step(cell.size, cell.temperature),
+                    wall_element: i,
+                    cell: j,
+                })
+        })
+        .min_by(|a, b| a.time_step.total_cmp(&b.time_step))
+}
+
+/// Prints the explicit stability limit of each wall element of the one dimensional simulation inside the passed directory and the smallest one of the simulation, see [`max_stable_time_step`].
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be loaded as a one dimensional simulation.
+pub fn print_stability<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    let (named_materials, wall_elements) = load_fds_simulation_one_dimensional_named(path)
+        .with_context(|| format!("Failed to load simulation at {path:?}."))?;
+    let materials = named_materials
+        .iter()
+        .map(|(_, material)| material.clone())
+        .collect::<Vec<_>>();
+
+    let material_name = |wall_element: usize, cell: usize| {
+        let material = wall_elements[wall_element][cell].material as usize;
+        named_materials[material].0.as_str()
+    };
+    println!(
+        "  {:>12} {:>16} {:>5} {:>14}",
+        "Wall element", "Material", "Cell", "Time step [s]"
+    );
+    for (i, wall_element) in wall_elements.iter().enumerate() {
+        if let Some(StableTimeStep {
+            time_step, cell, ..
+        }) = max_stable_time_step(&materials, std::slice::from_ref(wall_element))
+        {
+            println!(
+                "  {i:>12} {:>16} {cell:>5} {time_step:>14.6}",
+                material_name(i, cell)
+            );
+        }
+    }
+    let Some(StableTimeStep {
+        time_step,
+        wall_element,
+        cell,
+    }) = max_stable_time_step(&materials, &wall_elements)
+    else {
+        bail!("The simulation at {path:?} has no cells inside its wall elements.");
+    };
+    println!(
+        "\n Largest stable explicit time step of {path:?} is {time_step} s at cell {cell} of wall element {wall_element} with the material \"{}\".",
+        material_name(wall_element, cell)
+    );
+    println!(" The solver is implicit and stable with larger time steps, but cells this thin may resolve them less accurately.");
+    Ok(())
+}
@@ -1,12 +1,33 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use super::{
-    parser::Property,
+    parser::{parse_fds_float, Property},
     ramp::{Ramp, RampList},
 };
 
 use anyhow::*;
 
+/// The emissivity FDS uses if a material does not set `EMISSIVITY`.
+pub const DEFAULT_EMISSIVITY: f32 = 0.9;
+
+/// Whether a missing `EMISSIVITY` of a material is an error instead of using [`DEFAULT_EMISSIVITY`]. This value is replaced at the start of the program.
+static STRICT_EMISSIVITY: AtomicBool = AtomicBool::new(false);
+
+/// Set whether a missing `EMISSIVITY` of a material is an error instead of using [`DEFAULT_EMISSIVITY`].
+pub fn set_strict_emissivity(strict_emissivity: bool) {
+    STRICT_EMISSIVITY.store(strict_emissivity, Ordering::Relaxed);
+    println!("Set strict emissivity to {strict_emissivity}")
+}
+
+/// Get whether a missing `EMISSIVITY` of a material is an error instead of using [`DEFAULT_EMISSIVITY`].
+#[inline]
+pub fn get_strict_emissivity() -> bool {
+    STRICT_EMISSIVITY.load(Ordering::Relaxed)
+}
+
 /// All relevant and supported data of a material.
 #[derive(Debug, Clone)]
 pub struct Material {
@@ -15,12 +36,70 @@ pub struct Material {
     pub density: f32,
     pub emissivity: f32,
 }
+impl Material {
+    /// Returns the thermal diffusivity `k / (ρ⋅c)` in m²/s at the passed temperature in °C.
+    pub fn thermal_diffusivity(&self, temperature: f32) -> f32 {
+        self.conductivity.calc(temperature) / (self.density * self.specific_heat.calc(temperature))
+    }
+
+    /// Returns the cell size `sqrt(α⋅Δt)` in m at the passed temperature in °C and time step in s, where the Fourier number `α⋅Δt / Δx²` is 1.
+    /// The geometric discretization keeps the smallest cell of a layer below this size.
+    pub fn stable_cell_size(&self, temperature: f32, delta_time: f32) -> f32 {
+        f32::sqrt(self.thermal_diffusivity(temperature) * delta_time)
+    }
+
+    /// Returns the largest time step `ρ⋅c⋅Δx² / (2⋅k)` in s an explicit scheme is stable with for a cell of the passed size in m at the passed temperature in °C.
+    pub fn max_explicit_time_step(&self, size: f32, temperature: f32) -> f32 {
+        size * size / (2.0 * self.thermal_diffusivity(temperature))
+    }
+
+    /// Blends the passed [`Material`]s with their mass fractions, which must sum up to 1, into a single [`Material`] like FDS does for the components of a layer.
+    /// - The specific heat is weighted by the mass fractions.
+    /// - The density is the inverse of the sum of the mass fractions divided by the densities, so the volumes of the components add up.
+    /// - The conductivity and the emissivity are weighted by the volume fractions.
+    pub fn blend(components: &[(f32, &Material)]) -> Self {
+        let specific_heat = Ramp::weighted_sum(
+            &components
+                .iter()
+                .map(|(mass_fraction, m)| (*mass_fraction, &m.specific_heat))
+                .collect::<Vec<_>>(),
+        );
+        let density = 1.0
+            / components
+                .iter()
+                .map(|(mass_fraction, m)| mass_fraction / m.density)
+                .sum::<f32>();
+        let volume_fractions = components
+            .iter()
+            .map(|(mass_fraction, m)| mass_fraction / m.density * density)
+            .collect::<Vec<_>>();
+        let conductivity = Ramp::weighted_sum(
+            &components
+                .iter()
+                .zip(&volume_fractions)
+                .map(|((_, m), volume_fraction)| (*volume_fraction, &m.conductivity))
+                .collect::<Vec<_>>(),
+        );
+        let emissivity = components
+            .iter()
+            .zip(&volume_fractions)
+            .map(|((_, m), volume_fraction)| volume_fraction * m.emissivity)
+            .sum();
+        Material {
+            specific_heat,
+            conductivity,
+            density,
+            emissivity,
+        }
+    }
+}
 
 /// List of all [`Material`]s inside a Simulation wich could be parsed correctly and the corresponding name.
 #[derive(Debug, Default)]
 pub struct MaterialList(Vec<(String, Material)>);
 impl MaterialList {
     /// Attempts to add a [`Material`] to the list from the [`Property`]s and the existing [`Ramp`]s inside the [`RampList`].
+    /// If `strict_emissivity` is not set, a missing `EMISSIVITY` defaults to [`DEFAULT_EMISSIVITY`].
     ///
     /// # Errors
     ///
@@ -29,9 +108,10 @@ impl MaterialList {
         &mut self,
         properties: Vec<Property>,
         ramp_list: &RampList,
+        strict_emissivity: bool,
     ) -> Result<()> {
         self.0.push(
-            try_material_from_properties(properties, ramp_list)
+            try_material_from_properties(properties, ramp_list, strict_emissivity)
                 .with_context(|| "Failed to add properties as material.")?,
         );
         Ok(())
@@ -46,6 +126,12 @@ impl MaterialList {
     pub fn into_materials(self) -> Vec<Material> {
         self.0.into_iter().map(|(_, m)| m).collect()
     }
+
+    /// Converts this [`MaterialList`] to a [`Vec`] of the [`Material`]s together with their `ID`.
+    /// The index of a material is the same as in [`into_materials`](MaterialList::into_materials), so the `material` of a cell can be mapped back to its name.
+    pub fn into_named_materials(self) -> Vec<(String, Material)> {
+        self.0
+    }
 }
 impl Deref for MaterialList {
     type Target = Vec<(String, Material)>;
@@ -61,6 +147,7 @@ impl DerefMut for MaterialList {
 }
 
 /// Attempts to create a [`Material`] from the [`Property`]s and the existing [`Ramp`]s inside the [`RampList`].
+/// If `strict_emissivity` is not set, a missing `EMISSIVITY` defaults to [`DEFAULT_EMISSIVITY`] like in FDS.
 ///
 /// # Errors
 ///
@@ -71,6 +158,7 @@ impl DerefMut for MaterialList {
 fn try_material_from_properties(
     properties: Vec<Property>,
     ramp_list: &RampList,
+    strict_emissivity: bool,
 ) -> Result<(String, Material)> {
     let mut id = None;
     let mut specific_heat = None;
@@ -89,15 +177,7 @@ fn try_material_from_properties(
                     }
                 }
             }
-            "SPECIFIC_HEAT" => {
-                specific_heat = Some(
-                    (value
-                        .parse::<f32>()
-                        .with_context(|| format!("Failed to parse \"{value}\" to float."))?
-                        * 1000.0)
-                        .into(),
-                )
-            }
+            "SPECIFIC_HEAT" => specific_heat = Some((parse_fds_float(&value)? * 1000.0).into()),
             "CONDUCTIVITY_RAMP" => {
                 conductivity = match ramp_list.find(&value) {
                     Some(some) => Some(some.clone()),
@@ -106,48 +186,118 @@ fn try_material_from_properties(
                     }
                 }
             }
-            "CONDUCTIVITY" => {
-                conductivity = Some(
-                    value
-                        .parse::<f32>()
-                        .with_context(|| format!("Failed to parse \"{value}\" to float."))?
-                        .into(),
-                )
-            }
-            "DENSITY" => {
-                density = Some(
-                    value
-                        .parse::<f32>()
-                        .with_context(|| format!("Failed to parse \"{value}\" to float."))?,
-                )
-            }
-            "EMISSIVITY" => {
-                emissivity = Some(
-                    value
-                        .parse::<f32>()
-                        .with_context(|| format!("Failed to parse \"{value}\" to float."))?,
-                )
-            }
+            "CONDUCTIVITY" => conductivity = Some(parse_fds_float(&value)?.into()),
+            "DENSITY" => density = Some(parse_fds_float(&value)?),
+            "EMISSIVITY" => emissivity = Some(parse_fds_float(&value)?),
             _ => {}
         }
     }
 
-    match id.is_none()
-            || specific_heat.is_none()
-            || conductivity.is_none()
-            || density.is_none()
-            || emissivity.is_none() {
-        true => bail!("On or more properties are missing. Found ID: {}, SPECIFIC_HEAT(_RAMP): {}, CONDUCTIVITY(_RAMP): {}, DENSITY: {}, EMISSIVITY: {}.", id.is_some(), specific_heat.is_some(), conductivity.is_some(), density.is_some(), emissivity.is_some()),
-        false => {
+    if !strict_emissivity {
+        emissivity = emissivity.or(Some(DEFAULT_EMISSIVITY));
+    }
+
+    let missing = [
+        ("SPECIFIC_HEAT(_RAMP)", specific_heat.is_none()),
+        ("CONDUCTIVITY(_RAMP)", conductivity.is_none()),
+        ("DENSITY", density.is_none()),
+        ("EMISSIVITY", emissivity.is_none()),
+    ]
+    .into_iter()
+    .filter_map(|(key, is_missing)| is_missing.then_some(key))
+    .collect::<Vec<_>>();
+
+    match (id, specific_heat, conductivity, density, emissivity) {
+        (Some(id), Some(specific_heat), Some(conductivity), Some(density), Some(emissivity)) => {
             std::result::Result::Ok((
-                id.unwrap(),
+                id,
                 Material {
-                    specific_heat: specific_heat.unwrap(),
-                    conductivity: conductivity.unwrap(),
-                    density: density.unwrap(),
-                    emissivity: emissivity.unwrap(),
+                    specific_heat,
+                    conductivity,
+                    density,
+                    emissivity,
                 },
             ))
         }
+        (None, ..) => bail!("The property ID is missing."),
+        (Some(id), ..) => bail!(
+            "The material \"{id}\" is missing the properties {}.",
+            missing.join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the [`Property`]s of a `&MATL` without `EMISSIVITY`.
+    fn properties_without_emissivity() -> Vec<Property> {
+        [
+            ("ID", "STEEL"),
+            ("SPECIFIC_HEAT", "0.46"),
+            ("CONDUCTIVITY", "45.8"),
+            ("DENSITY", "7850.0"),
+        ]
+        .into_iter()
+        .map(|(key, value)| Property {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn missing_emissivity_defaults_to_0_9() {
+        let (id, material) = try_material_from_properties(
+            properties_without_emissivity(),
+            &RampList::default(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(id, "STEEL");
+        assert_eq!(material.emissivity, DEFAULT_EMISSIVITY);
+        assert_eq!(material.emissivity, 0.9);
+    }
+
+    #[test]
+    fn missing_emissivity_fails_if_strict() {
+        let err = try_material_from_properties(
+            properties_without_emissivity(),
+            &RampList::default(),
+            true,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("EMISSIVITY"), "{err}");
+    }
+
+    #[test]
+    fn diffusivity_helpers_of_steel() {
+        let (_, steel) = try_material_from_properties(
+            properties_without_emissivity(),
+            &RampList::default(),
+            false,
+        )
+        .unwrap();
+        let relative_error = |value: f32, expected: f32| ((value - expected) / expected).abs();
+
+        let diffusivity = steel.thermal_diffusivity(20.0);
+        assert!(
+            relative_error(diffusivity, 1.268347e-5) < 1e-5,
+            "{diffusivity}"
+        );
+
+        let size = steel.stable_cell_size(20.0, 10.0);
+        assert!(relative_error(size, 1.126209e-2) < 1e-5, "{size}");
+        // The Fourier number of the stable cell size is 1.
+        assert!(relative_error(diffusivity * 10.0 / (size * size), 1.0) < 1e-5);
+
+        let delta_time = steel.max_explicit_time_step(0.001, 20.0);
+        assert!(
+            relative_error(delta_time, 3.94214e-2) < 1e-5,
+            "{delta_time}"
+        );
+        // The explicit scheme is stable up to a Fourier number of 0.5.
+        assert!(relative_error(diffusivity * delta_time / (0.001 * 0.001), 0.5) < 1e-5);
     }
 }
@@ -0,0 +1,167 @@
+//! Overrides of the built-in material properties of the simulations that are created from the templates.
+
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::{PoisonError, RwLock},
+};
+
+use anyhow::*;
+use serde::Deserialize;
+
+use crate::heat_transfer::simulations::SimulationKind;
+
+use super::sampler::Material;
+
+/// The loaded material overrides. If it is not set, the built-in material properties are used. This value is replaced at the start of the program.
+static MATERIAL_OVERRIDES: RwLock<Option<MaterialOverrides>> = RwLock::new(None);
+
+/// The material overrides together with the file they are loaded from.
+#[derive(Debug, Clone)]
+struct MaterialOverrides {
+    path: PathBuf,
+    overrides: Vec<MaterialOverride>,
+}
+
+/// Replaced properties of a material. Properties that are not set keep their built-in value.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(super) struct MaterialOverride {
+    /// The material the override applies to.
+    pub material: Material,
+    /// The simulation kind the override applies to. If it is not set, the override applies to all simulation kinds.
+    #[serde(default)]
+    pub kind: Option<SimulationKind>,
+    pub density: Option<f64>,
+    pub emissivity: Option<f64>,
+    /// The constant conductivity of the simulations without a conductivity ramp.
+    pub conductivity: Option<f64>,
+    /// The conductivity ramp of the simulations with a conductivity ramp as `[temperature, value]` pairs.
+    pub conductivity_ramp: Option<Vec<(f64, f64)>>,
+    /// The constant specific heat of the simulations without a specific heat ramp.
+    pub specific_heat: Option<f64>,
+    /// The specific heat ramp of the simulations with a specific heat ramp as `[temperature, value]` pairs.
+    pub specific_heat_ramp: Option<Vec<(f64, f64)>>,
+}
+impl MaterialOverride {
+    /// Checks if the set values can be written to a FDS simulation.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - a value is not finite or negative.
+    /// - the emissivity is larger than 1.
+    /// - a ramp is empty or its temperatures are not increasing.
+    fn validate(&self) -> Result<()> {
+        let values = [
+            ("density", self.density),
+            ("emissivity", self.emissivity),
+            ("conductivity", self.conductivity),
+            ("specific_heat", self.specific_heat),
+        ];
+        for (name, value) in values {
+            if let Some(value) = value {
+                if !value.is_finite() || value < 0.0 {
+                    bail!("The {name} {value} must be a finite, non-negative number.");
+                }
+            }
+        }
+        if self.emissivity.is_some_and(|e| e > 1.0) {
+            bail!(
+                "The emissivity {} must not be larger than 1.",
+                self.emissivity.unwrap_or_default()
+            );
+        }
+        let ramps = [
+            ("conductivity_ramp", &self.conductivity_ramp),
+            ("specific_heat_ramp", &self.specific_heat_ramp),
+        ];
+        for (name, ramp) in ramps {
+            let Some(ramp) = ramp else {
+                continue;
+            };
+            if ramp.is_empty() {
+                bail!("The {name} must contain at least one point.");
+            }
+            if ramp
+                .iter()
+                .any(|(t, f)| !t.is_finite() || !f.is_finite() || *f < 0.0)
+            {
+                bail!("The {name} must only contain finite values that are not negative.");
+            }
+            if ramp.windows(2).any(|w| w[0].0 >= w[1].0) {
+                bail!("The temperatures of the {name} must be strictly increasing.");
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks if this override applies to the passed material and simulation kind.
+    fn applies_to(&self, material: Material, kind: SimulationKind) -> bool {
+        self.material == material && self.kind.is_none_or(|k| k == kind)
+    }
+}
+
+/// Loads the material overrides from a JSON file that contains a list of [`MaterialOverride`]s, e.g.
+/// `[{ "material": "steel", "kind": "diabatic", "conductivity": 45.0 }]`.
+/// The overrides are applied in the order of the file to all simulations created afterwards.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the file can not be read or is not a valid list of overrides.
+/// - an override contains an invalid value.
+pub fn load_material_overrides<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open material override file at {path:?}."))?;
+    let overrides: Vec<MaterialOverride> = serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("Failed to parse material override file at {path:?}."))?;
+    for (i, material_override) in overrides.iter().enumerate() {
+        material_override
+            .validate()
+            .with_context(|| format!("Invalid material override {i} in file at {path:?}."))?;
+    }
+    println!(
+        "Set material overrides to {} entries from {path:?}",
+        overrides.len()
+    );
+    *MATERIAL_OVERRIDES
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = Some(MaterialOverrides {
+        path: path.to_path_buf(),
+        overrides,
+    });
+    Ok(())
+}
+
+/// Get the path of the loaded material override file.
+#[inline]
+pub(super) fn get_material_override_path() -> Option<PathBuf> {
+    MATERIAL_OVERRIDES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|m| m.path.clone())
+}
+
+/// Get all material overrides that apply to the passed material and simulation kind in the order of the file.
+#[inline]
+pub(super) fn get_material_overrides(
+    material: Material,
+    kind: SimulationKind,
+) -> Vec<MaterialOverride> {
+    MATERIAL_OVERRIDES
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .map(|m| {
+            m.overrides
+                .iter()
+                .filter(|o| o.applies_to(material, kind))
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
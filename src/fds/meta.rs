@@ -1,15 +1,25 @@
 use super::SurfaceList;
 use anyhow::*;
 
-/// Meta data for a simulation, wether it is 1D or 3D. The fist line of a simulation must be set with a meta definition.
+/// Meta data for a simulation, wether it is 1D, 2D or 3D. The fist line of a simulation must be set with a meta definition.
 /// 1D supports multiple materials. A definition could look like the following.
 /// `//META SURF_STEEL; SURF_STEEL;`
+/// 2D only supports one material and must be defined with the number of columns and the width of a single column in m like the following.
+/// `//META 16,0.01, SURF_STEEL;`
 /// 3D only supports one material and must be defined like the following.
 /// `//META 8,8,8, SURF_STEEL;`
 pub enum Meta {
     OneDimensional {
         surface_ids: Vec<usize>,
     },
+    /// A cross-section through the wall. The surface defines the cells across the thickness.
+    TwoDimensional {
+        /// The number of cell columns along the width of the cross-section.
+        x: usize,
+        /// The width of a single cell column in m.
+        column_width: f32,
+        surface_id: usize,
+    },
     ThreeDimensional {
         x: usize,
         y: usize,
@@ -24,37 +34,36 @@ impl Meta {
     ///
     /// This function will return an error if
     /// - the dimension values cannot be converted to numbers.
+    /// - the column width of a 2D simulation is not finite and positive.
     /// - the specified surfaces do not exist.
-    /// - when there is 3D data initialized, but multiple surface are defined.
+    /// - when there is 2D or 3D data initialized, but multiple surface are defined.
     pub fn try_new(
-        dimensions: Option<(String, String, String)>,
+        dimensions: Option<Vec<String>>,
         meta: Vec<String>,
         surface_list: &SurfaceList,
     ) -> Result<Self> {
         let meta = match dimensions {
-            Some((x, y, z)) => {
+            Some(dimensions) => {
                 if meta.len() > 1 {
-                    bail!("Multiple surfaces in 3D are not supported.")
-                } else {
-                    let name = &meta[0];
-                    let x: usize = x
-                        .parse()
-                        .with_context(|| format!("Failed to parse x value \"{x}\"."))?;
-                    let y: usize = y
-                        .parse()
-                        .with_context(|| format!("Failed to parse y value \"{y}\"."))?;
-                    let z: usize = z
-                        .parse()
-                        .with_context(|| format!("Failed to parse z value \"{z}\"."))?;
-                    let surface_id = surface_list
-                        .find_index(name)
-                        .ok_or(anyhow!("Could not find SURF wit ID = \"{name}\"."))?;
-                    Meta::ThreeDimensional {
-                        x,
-                        y,
-                        z,
+                    bail!("Multiple surfaces in 2D and 3D are not supported.")
+                }
+                let name = &meta[0];
+                let surface_id = surface_list
+                    .find_index(name)
+                    .ok_or(anyhow!("Could not find SURF wit ID = \"{name}\"."))?;
+                match &dimensions[..] {
+                    [x, column_width] => Meta::TwoDimensional {
+                        x: parse_dimension("x", x)?,
+                        column_width: parse_column_width(column_width)?,
+                        surface_id,
+                    },
+                    [x, y, z] => Meta::ThreeDimensional {
+                        x: parse_dimension("x", x)?,
+                        y: parse_dimension("y", y)?,
+                        z: parse_dimension("z", z)?,
                         surface_id,
-                    }
+                    },
+                    _ => bail!("Only 2 or 3 dimensions are supported."),
                 }
             }
             None => {
@@ -73,3 +82,29 @@ impl Meta {
         Ok(meta)
     }
 }
+
+/// Parses the number of cells along an axis.
+///
+/// # Errors
+///
+/// This function will return an error if the value is not a whole number.
+fn parse_dimension(axis: &str, value: &str) -> Result<usize> {
+    value
+        .parse::<usize>()
+        .with_context(|| format!("Failed to parse {axis} value \"{value}\"."))
+}
+
+/// Parses the width of a single column of a 2D simulation in m.
+///
+/// # Errors
+///
+/// This function will return an error if the value is not a finite and positive number.
+fn parse_column_width(value: &str) -> Result<f32> {
+    let column_width = value
+        .parse::<f32>()
+        .with_context(|| format!("Failed to parse column width \"{value}\"."))?;
+    if !column_width.is_finite() || column_width <= 0.0 {
+        bail!("The column width should be finite and positive, but is {value}.");
+    }
+    Ok(column_width)
+}
@@ -1,6 +1,8 @@
 mod benchmark;
 mod device;
+mod inspect;
 mod material;
+mod material_override;
 mod meta;
 mod parser;
 mod ramp;
@@ -8,12 +10,26 @@ mod sampler;
 mod simulations;
 mod surface;
 
-pub use benchmark::{benchmark, PATH};
-pub use device::Devices;
-pub use material::{Material, MaterialList};
+pub use benchmark::{
+    benchmark, get_keep_results, get_speed_test_cores, is_cores_prefix, max_cores_prefix,
+    set_keep_results, set_speed_test_cores, KeepResults, PATH,
+};
+pub use device::{count_device_rows, set_strict_device_names, Devices};
+pub use inspect::{
+    inspect_cells, max_stable_time_step, print_cells, print_stability, InspectedCell,
+    StableTimeStep,
+};
+pub use material::{set_strict_emissivity, Material, MaterialList, DEFAULT_EMISSIVITY};
+pub use material_override::load_material_overrides;
 pub use meta::Meta;
-pub use parser::parse_script_from_file;
+pub use parser::{parse_script_from_file, parse_wall_increment_from_file};
 pub use ramp::Ramp;
 pub use sampler::create_simulations;
-pub use simulations::run_simulations;
-pub use surface::{cells_from_materials_and_thickness, Surface, SurfaceCell, SurfaceList};
+pub use simulations::{
+    find_result_dirs, run_simulations, run_simulations_with_status, set_fds_bin, set_fds_timeout,
+    set_mpi_launcher, Status, DEFAULT_FDS_BIN, DEFAULT_MPI_LAUNCHER,
+};
+pub use surface::{
+    cells_from_materials_and_thickness, set_grid_stability_warning, Discretization, Surface,
+    SurfaceCell, SurfaceList, DEFAULT_INITIAL_TEMPERATURE, DELTA_TIME,
+};
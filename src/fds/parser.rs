@@ -8,7 +8,12 @@ use chumsky::{
     text::{newline, whitespace},
 };
 
-use super::{material::MaterialList, meta::Meta, ramp::RampList, surface::SurfaceList};
+use super::{
+    material::{get_strict_emissivity, MaterialList},
+    meta::Meta,
+    ramp::RampList,
+    surface::SurfaceList,
+};
 
 use anyhow::*;
 
@@ -19,12 +24,19 @@ pub struct Property {
     pub value: String,
 }
 
+/// The parsed meta data consisting of the optional dimensions and the surface names.
+type MetaData = (Option<Vec<String>>, Vec<String>);
+
+/// The parsed script consisting of the [`MetaData`] and the properties of all supported namespaces.
+type Script = (MetaData, Vec<(NameSpace, Vec<Property>)>);
+
 /// The different supported namespaces, witch the parser search and parse for.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum NameSpace {
     Ramp,
     Material,
     Surface,
+    Time,
 }
 
 /// Reads an FDS simulation and determines the relevant data [`Meta`], [`MaterialList`] and [`SurfaceList`].
@@ -37,19 +49,14 @@ pub(super) enum NameSpace {
 /// - no [`RampList`] can be created because the file is structured incorrectly
 /// - no [`MaterialList`] can be created because the file is structured incorrectly
 /// - no [`SurfaceList`] can be created because the file is structured incorrectly
+/// - a surface referenced by the metadata cannot be parsed.
+///
+/// Surfaces that cannot be parsed but are not referenced by the metadata are skipped. A warning is printed if such a surface has a `MATL_ID`.
 pub fn parse_script_from_file<P: AsRef<Path>>(
     path: P,
 ) -> Result<(Meta, MaterialList, SurfaceList)> {
     let path = path.as_ref();
-
-    let script_parser = script_parser();
-
-    let script = std::fs::read_to_string(path)
-        .with_context(|| format!("Failed to read file at {path:?}"))?;
-    let ((dimensions, meta), namespaces) = match script_parser.parse(script) {
-        std::result::Result::Ok(ok) => ok,
-        Err(_) => bail!("Meta data is missing or incorrect formatted."),
-    };
+    let ((dimensions, meta), namespaces) = parse_namespaces_from_file(path)?;
 
     let (ramps, other): (Vec<_>, Vec<_>) = namespaces
         .into_iter()
@@ -60,50 +67,149 @@ pub fn parse_script_from_file<P: AsRef<Path>>(
         ramp_list.try_add_from_properties(properties)?;
     }
 
-    let (materials, surfaces): (Vec<_>, Vec<_>) = other
+    let (materials, other): (Vec<_>, Vec<_>) = other
         .into_iter()
         .partition(|(n, _)| *n == NameSpace::Material);
+    let surfaces = other.into_iter().filter(|(n, _)| *n == NameSpace::Surface);
 
+    let strict_emissivity = get_strict_emissivity();
     let mut material_list = MaterialList::default();
     for properties in materials.into_iter().map(|(_, m)| m) {
-        material_list.try_add_from_properties(properties, &ramp_list)?;
+        material_list.try_add_from_properties(properties, &ramp_list, strict_emissivity)?;
     }
 
+    // Surfaces without a material (e.g. adiabatic or hot boundaries) do not need all properties and are skipped silently.
+    // Only the surfaces referenced by the meta data must be valid.
     let mut surface_list = SurfaceList::default();
     for properties in surfaces.into_iter().map(|(_, s)| s) {
-        // HACK Some surface do not need all properties.
-        _ = surface_list.try_add_from_properties(properties, &material_list);
+        let id = properties
+            .iter()
+            .find(|p| p.key == "ID")
+            .map(|p| p.value.clone());
+        let has_material = properties.iter().any(|p| p.key == "MATL_ID");
+        if let Err(err) = surface_list.try_add_from_properties(properties, &mut material_list) {
+            match id {
+                Some(id) if meta.contains(&id) => {
+                    return Err(err).with_context(|| {
+                        format!("Failed to parse SURF with ID = \"{id}\" referenced by the meta data in file at {path:?}.")
+                    });
+                }
+                _ if !has_material => {}
+                Some(id) => println!(
+                    "Warning: Skipped SURF with ID = \"{id}\" in file at {path:?}: {err:#}"
+                ),
+                None => println!("Warning: Skipped SURF without ID in file at {path:?}: {err:#}"),
+            }
+        }
     }
     let meta = Meta::try_new(dimensions, meta, &surface_list)?;
 
     Ok((meta, material_list, surface_list))
 }
 
-/// Parses the dimension information in the meta data. 3 numbers must be specified that are separated by `,`. The last element must also end with `,`
+/// Reads the `WALL_INCREMENT` of the `&TIME` namespace of an FDS simulation, which is the number of gas time steps per solid time step.
+/// Returns [`None`] if it is not set.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the file cannot be read.
+/// - the metadata at the beginning of the file has been forgotten or cannot be converted correctly.
+/// - `WALL_INCREMENT` is not a whole number between 1 and 255.
+pub fn parse_wall_increment_from_file<P: AsRef<Path>>(path: P) -> Result<Option<u8>> {
+    let path = path.as_ref();
+    let (_, namespaces) = parse_namespaces_from_file(path)?;
+    let Some(value) = namespaces
+        .into_iter()
+        .filter(|(n, _)| *n == NameSpace::Time)
+        .flat_map(|(_, properties)| properties)
+        .filter(|p| p.key == "WALL_INCREMENT")
+        .map(|p| p.value)
+        .next_back()
+    else {
+        return Ok(None);
+    };
+    let wall_increment = parse_fds_float(&value)?;
+    if wall_increment.fract() != 0.0 || !(1.0..=u8::MAX as f32).contains(&wall_increment) {
+        bail!("WALL_INCREMENT in file at {path:?} should be a whole number between 1 and {}, but is {value}.", u8::MAX);
+    }
+    Ok(Some(wall_increment as u8))
+}
+
+/// Reads an FDS simulation and splits it into the meta data and all supported namespaces.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the file cannot be read.
+/// - the metadata at the beginning of the file has been forgotten or cannot be converted correctly.
+fn parse_namespaces_from_file(path: &Path) -> Result<Script> {
+    let script = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read file at {path:?}"))?;
+    let parsed = match script_parser().parse(script.as_str()) {
+        std::result::Result::Ok(ok) => ok,
+        Err(errors) => {
+            let locations = errors
+                .iter()
+                .map(|error| {
+                    let (line, column, text) = line_and_column(&script, error.span().start);
+                    format!("line {line}, column {column}: \"{}\"", text.trim_end())
+                })
+                .collect::<Vec<_>>();
+            bail!(
+                "Meta data is missing or incorrect formatted in file at {path:?} at {}.",
+                locations.join("; ")
+            )
+        }
+    };
+    Ok(parsed)
+}
+
+/// Converts a char offset inside the script to a 1-based line and column and returns them together with the text of the line.
+fn line_and_column(script: &str, offset: usize) -> (usize, usize, &str) {
+    let mut start = 0;
+    for (i, line) in script.split('\n').enumerate() {
+        let length = line.chars().count() + 1;
+        if offset < start + length {
+            return (i + 1, offset - start + 1, line);
+        }
+        start += length;
+    }
+    let line_count = script.split('\n').count();
+    let last_line = script.split('\n').next_back().unwrap_or_default();
+    (line_count, last_line.chars().count() + 1, last_line)
+}
+
+/// Parses a numeric FDS property value to a float.
+/// Surrounding whitespace, quotes and trailing `,` are trimmed and a Fortran style `D` exponent (e.g. `1.2D-3`) is rewritten to `E`.
 ///
-/// # Panics
+/// # Errors
 ///
-/// Panics if there are not 3 elements.
-fn meta_dimension_parser() -> impl Parser<char, (String, String, String), Error = Cheap<char>> {
-    filter(char::is_ascii_digit)
+/// This function will return an error if the value is not a valid float after the normalization.
+pub(super) fn parse_fds_float(value: &str) -> Result<f32> {
+    let trimmed =
+        value.trim_matches(|c: char| c.is_whitespace() || c == ',' || c == '\'' || c == '"');
+    let normalized = trimmed.replace(['D', 'd'], "E");
+    normalized
+        .parse::<f32>()
+        .with_context(|| format!("Failed to parse \"{value}\" to float."))
+}
+
+/// Parses the dimension information in the meta data. 2 or 3 numbers must be specified that are separated by `,`. The last element must also end with `,`
+/// The numbers may contain a `.`, so the column width of a 2D simulation can be given in m.
+fn meta_dimension_parser() -> impl Parser<char, Vec<String>, Error = Cheap<char>> {
+    filter(|c: &char| c.is_ascii_digit() || *c == '.')
         .repeated()
         .at_least(1)
         .then_ignore(just(',').padded())
         .repeated()
-        .exactly(3)
+        .at_least(2)
+        .at_most(3)
         .map(|dimensions| {
-            let mut dimensions = dimensions.into_iter();
-            if let (Some(x), Some(y), Some(z)) =
-                (dimensions.next(), dimensions.next(), dimensions.next())
-            {
-                (
-                    x.into_iter().collect::<String>(),
-                    y.into_iter().collect::<String>(),
-                    z.into_iter().collect::<String>(),
-                )
-            } else {
-                panic!("No 3 elements in vector wich should not occur.")
-            }
+            dimensions
+                .into_iter()
+                .map(|dimension| dimension.into_iter().collect::<String>())
+                .collect::<Vec<_>>()
         })
 }
 
@@ -123,9 +229,8 @@ fn meta_surface_parser() -> impl Parser<char, Vec<String>, Error = Cheap<char>>
         })
 }
 
-/// Determines the metha data. This must be at the very beginning of the file and start with `//META`. If it is a 3D simulation, the dimension must be defined and then only one surface. In the 1D case, several surfaces can be defined.
-fn meta_parser(
-) -> impl Parser<char, (Option<(String, String, String)>, Vec<String>), Error = Cheap<char>> {
+/// Determines the metha data. This must be at the very beginning of the file and start with `//META`. If it is a 2D or 3D simulation, the dimensions must be defined and then only one surface. In the 1D case, several surfaces can be defined.
+fn meta_parser() -> impl Parser<char, MetaData, Error = Cheap<char>> {
     just("//")
         .then(just("META").padded())
         .ignore_then(meta_dimension_parser().or_not())
@@ -133,11 +238,20 @@ fn meta_parser(
 }
 
 /// A property assignment can end with a `,` so that the name of the next property is not mistakenly recognized as an assignment, this function is executed.
+/// The name of a property can end with an index in parentheses, e.g. `MATL_ID(1,2)`, whose `,` does not end the name.
+/// Line breaks are treated like spaces, so a namespace can span multiple lines until the closing `/`.
 fn ignore_parser() -> impl Parser<char, (), Error = Cheap<char>> {
-    take_until(none_of(",=/ ").repeated().at_least(1))
-        .then(whitespace())
-        .then(just('='))
-        .ignored()
+    take_until(
+        none_of(",=/() \t\r\n").repeated().at_least(1).then(
+            just('(')
+                .then(none_of("()=/").repeated())
+                .then(just(')'))
+                .or_not(),
+        ),
+    )
+    .then(whitespace())
+    .then(just('='))
+    .ignored()
 }
 /// Attempts to determine a property with name and assignment. If a string is assigned to the property, this string must not contain `=`, `/` and space.
 pub(super) fn property_parser() -> impl Parser<char, Property, Error = Cheap<char>> {
@@ -159,25 +273,20 @@ pub(super) fn property_parser() -> impl Parser<char, Property, Error = Cheap<cha
 }
 
 /// Determines the namespace. This must begin with `&`. Only the namespaces defined in [`NameSpace`] are supported.
+/// The properties of a namespace can be split over multiple lines, the namespace only ends with `/`.
 pub(super) fn namespace_parser(
 ) -> impl Parser<char, (NameSpace, Vec<Property>), Error = Cheap<char>> {
     just("&RAMP")
         .to(NameSpace::Ramp)
         .or(just("&MATL").to(NameSpace::Material))
         .or(just("&SURF").to(NameSpace::Surface))
+        .or(just("&TIME").to(NameSpace::Time))
         .padded()
         .then(property_parser().repeated())
 }
 
 /// Parses the whole text to relevant data.
-pub(super) fn script_parser() -> impl Parser<
-    char,
-    (
-        (Option<(String, String, String)>, Vec<String>),
-        Vec<(NameSpace, Vec<Property>)>,
-    ),
-    Error = Cheap<char>,
-> {
+pub(super) fn script_parser() -> impl Parser<char, Script, Error = Cheap<char>> {
     meta_parser().then(
         namespace_parser()
             .map(Some)
@@ -186,3 +295,173 @@ pub(super) fn script_parser() -> impl Parser<
             .flatten(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fds_float_with_exponents_and_signs() {
+        assert_eq!(parse_fds_float("1.2D-3").unwrap(), 1.2e-3);
+        assert_eq!(parse_fds_float("5.67E-8").unwrap(), 5.67e-8);
+        assert_eq!(parse_fds_float("-0.5").unwrap(), -0.5);
+        assert_eq!(parse_fds_float(" 2.0d2, ").unwrap(), 200.0);
+        assert!(parse_fds_float("1.2X-3").is_err());
+    }
+
+    #[test]
+    fn namespace_spanning_multiple_lines() {
+        let script = "//META SURF_STEEL;\n\
+            &HEAD CHID='multi_line' /\n\
+            &MATL ID = 'STEEL'\n\
+                  SPECIFIC_HEAT = 0.46\n\
+                  CONDUCTIVITY = 45.8,\n\
+                  DENSITY = 7850.0\n\
+                  EMISSIVITY = 0.9 /\n\
+            &SURF ID = 'SURF_STEEL', MATL_ID = 'STEEL', THICKNESS = 0.01 /\n";
+        let ((dimensions, surfaces), namespaces) = script_parser().parse(script).unwrap();
+        assert_eq!(dimensions, None);
+        assert_eq!(surfaces, ["SURF_STEEL"]);
+        assert_eq!(namespaces.len(), 2);
+
+        let (namespace, properties) = &namespaces[0];
+        assert_eq!(*namespace, NameSpace::Material);
+        let properties = properties
+            .iter()
+            .map(|p| (p.key.as_str(), p.value.trim()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            properties,
+            [
+                ("ID", "STEEL"),
+                ("SPECIFIC_HEAT", "0.46"),
+                ("CONDUCTIVITY", "45.8"),
+                ("DENSITY", "7850.0"),
+                ("EMISSIVITY", "0.9"),
+            ]
+        );
+        assert_eq!(namespaces[1].0, NameSpace::Surface);
+    }
+
+    #[test]
+    fn golden_one_dimensional_steel_deck() {
+        let (meta, material_list, surface_list) =
+            parse_script_from_file("fds/1D/Diabatic/thickness_steel_k_c/010cm/heat_transfer.fds")
+                .unwrap();
+
+        let Meta::OneDimensional { surface_ids } = meta else {
+            panic!("Expected a 1D meta definition.");
+        };
+        assert_eq!(surface_ids, [0]);
+
+        assert_eq!(material_list.len(), 1);
+        let (id, material) = &material_list[0];
+        assert_eq!(id, "MATL_WALL");
+        assert_eq!(material.density, 7800.0);
+        assert_eq!(material.emissivity, 0.79);
+        assert_eq!(material.conductivity.calc(20.0), 53.3);
+        assert_eq!(material.conductivity.calc(1200.0), 27.3);
+        assert_eq!(material.specific_heat.calc(20.0), 439.8);
+        assert_eq!(material.specific_heat.calc(735.0), 5000.0);
+
+        // The adiabatic and the hot surface have no material and are skipped.
+        assert_eq!(surface_list.len(), 1);
+        let (id, surface) = &surface_list[0];
+        assert_eq!(id, "SURF_WALL");
+        // The geometric discretization adds a boundary cell on each side.
+        assert_eq!(surface.len(), 8 + 2);
+        assert!(surface.iter().all(|c| c.material_id == 0));
+        let thickness = surface[1..surface.len() - 1]
+            .iter()
+            .map(|c| c.size)
+            .sum::<f32>();
+        assert!((thickness - 0.1).abs() < 1e-6, "{thickness}");
+        assert!(surface.contact_resistances.is_empty());
+    }
+
+    #[test]
+    fn golden_two_dimensional_steel_deck() {
+        let (meta, material_list, surface_list) =
+            parse_script_from_file("fds/2D/Diabatic/steel_k_c/heat_transfer.fds").unwrap();
+
+        let Meta::TwoDimensional {
+            x,
+            column_width,
+            surface_id,
+        } = meta
+        else {
+            panic!("Expected a 2D meta definition.");
+        };
+        assert_eq!(x, 16);
+        assert_eq!(column_width, 0.01);
+        assert_eq!(surface_id, 0);
+
+        assert_eq!(material_list.len(), 1);
+        assert_eq!(material_list[0].0, "MATL_WALL");
+
+        assert_eq!(surface_list.len(), 1);
+        let (id, surface) = &surface_list[0];
+        assert_eq!(id, "SURF_WALL");
+        assert_eq!(surface.len(), 6 + 2);
+        let thickness = surface[1..surface.len() - 1]
+            .iter()
+            .map(|c| c.size)
+            .sum::<f32>();
+        assert!((thickness - 0.05).abs() < 1e-6, "{thickness}");
+    }
+
+    #[test]
+    fn golden_multiple_materials_deck() {
+        let (meta, material_list, surface_list) =
+            parse_script_from_file("fds/1D/Diabatic/multiple/4/heat_transfer.fds").unwrap();
+
+        let Meta::OneDimensional { surface_ids } = meta else {
+            panic!("Expected a 1D meta definition.");
+        };
+        assert_eq!(surface_ids, [0, 1, 2, 3]);
+        assert_eq!(material_list.len(), 16);
+        assert_eq!(surface_list.len(), 16);
+        let cell_counts = surface_list[..4]
+            .iter()
+            .map(|(_, s)| s.len())
+            .collect::<Vec<_>>();
+        assert_eq!(cell_counts, [3 + 2; 4]);
+        for (i, (id, surface)) in surface_list.iter().enumerate() {
+            assert_eq!(id, &format!("SURF_WALL_{}", i + 1));
+            assert!(surface.iter().all(|c| c.material_id == i as u32));
+            let thickness = surface[1..surface.len() - 1]
+                .iter()
+                .map(|c| c.size)
+                .sum::<f32>();
+            let expected = 0.1 + 0.01 * i as f32;
+            assert!((thickness - expected).abs() < 1e-6, "{id}: {thickness}");
+        }
+    }
+
+    #[test]
+    fn referenced_surface_with_unknown_material_fails() {
+        let path = std::env::temp_dir().join(format!(
+            "heat_transfer_unknown_material_{}.fds",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            "//META SURF_WALL;\n\
+            &MATL ID='STEEL', SPECIFIC_HEAT=0.46, CONDUCTIVITY=45.8, DENSITY=7850.0, EMISSIVITY=0.9 /\n\
+            &SURF ID='SURF_WALL', MATL_ID='STEEL_TYPO', THICKNESS=0.01 /\n\
+            &SURF ID='SURF_ADIABATIC', ADIABATIC=.TRUE. /\n",
+        )
+        .unwrap();
+        let result = parse_script_from_file(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let err = format!("{:#}", result.err().unwrap());
+        assert!(
+            err.starts_with(
+                "Failed to parse SURF with ID = \"SURF_WALL\" referenced by the meta data"
+            ),
+            "{err}"
+        );
+        assert!(err.contains("STEEL_TYPO"), "{err}");
+    }
+}
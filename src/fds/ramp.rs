@@ -1,25 +1,33 @@
 use std::ops::{Deref, DerefMut};
 
-use super::parser::Property;
+use super::parser::{parse_fds_float, Property};
 use anyhow::*;
+use num_traits::Float;
 
 /// All interpolation values of a ramp as a list of tuples with temperature and value.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Ramp(Vec<(f32, f32)>);
 impl Ramp {
     /// Calculate the value for a given temperature.
+    /// The interpolation tuples are searched with a binary search, so the temperatures must be sorted ascending.
     pub fn calc(&self, temperature: f32) -> f32 {
-        if temperature <= self.0[0].0 {
-            return self.0[0].1;
+        self.calc_as(temperature)
+    }
+
+    /// Calculate the value for a given temperature like [`Ramp::calc`], but with the precision of the passed temperature.
+    pub fn calc_as<F: Float>(&self, temperature: F) -> F {
+        let cast = |value: f32| F::from(value).unwrap_or_else(F::nan);
+        if temperature <= cast(self.0[0].0) {
+            return cast(self.0[0].1);
         }
-        for i in 1..self.0.len() {
-            let (t1, f1) = self.0[i];
-            if temperature < t1 {
-                let (t0, f0) = self.0[i - 1];
-                return f0 + (f1 - f0) / (t1 - t0) * (temperature - t0);
-            }
+        // Index of the first tuple with a temperature greater than the given one. It is only 0 if the temperature is NaN.
+        let i = self.0.partition_point(|&(t, _)| cast(t) <= temperature);
+        if i == 0 || i == self.0.len() {
+            return cast(self.0[self.0.len() - 1].1);
         }
-        self.0[self.0.len() - 1].1
+        let (t0, f0) = (cast(self.0[i - 1].0), cast(self.0[i - 1].1));
+        let (t1, f1) = (cast(self.0[i].0), cast(self.0[i].1));
+        f0 + (f1 - f0) / (t1 - t0) * (temperature - t0)
     }
 
     /// Multiply all values and return a new [`Ramp`].
@@ -29,12 +37,41 @@ impl Ramp {
         }
         self
     }
+
+    /// Returns the sum of the passed [`Ramp`]s multiplied with their weight.
+    /// The result has a point at every temperature of the passed ramps, so it interpolates exactly like the weighted sum of their values.
+    pub fn weighted_sum(ramps: &[(f32, &Ramp)]) -> Self {
+        let mut temperatures = ramps
+            .iter()
+            .flat_map(|(_, ramp)| ramp.iter().map(|(t, _)| *t))
+            .collect::<Vec<_>>();
+        temperatures.sort_by(f32::total_cmp);
+        temperatures.dedup();
+        Ramp(
+            temperatures
+                .into_iter()
+                .map(|t| {
+                    let value = ramps
+                        .iter()
+                        .map(|(weight, ramp)| weight * ramp.calc(t))
+                        .sum();
+                    (t, value)
+                })
+                .collect(),
+        )
+    }
 }
 impl From<f32> for Ramp {
     fn from(value: f32) -> Self {
         Ramp(vec![(20.0, value)])
     }
 }
+impl From<Vec<(f32, f32)>> for Ramp {
+    /// Creates a [`Ramp`] from interpolation tuples with strictly increasing temperatures.
+    fn from(values: Vec<(f32, f32)>) -> Self {
+        Ramp(values)
+    }
+}
 impl Deref for Ramp {
     type Target = Vec<(f32, f32)>;
 
@@ -55,12 +92,20 @@ impl RampList {
     ///
     /// # Errors
     ///
-    /// This function will return an error if no interpolation tuple could be created from the [`Property`]s.
+    /// This function will return an error if
+    /// - no interpolation tuple could be created from the [`Property`]s.
+    /// - the temperature of the interpolation tuple is not greater than the temperature of the previous tuple of the same [`Ramp`].
     pub fn try_add_from_properties(&mut self, properties: Vec<Property>) -> Result<()> {
         let (id, t, f) = try_ramp_line_from_properties(properties)
             .with_context(|| "Failed to add properties as ramp.")?;
         if let Some(last_entry) = self.0.last_mut() {
             if last_entry.0 == id {
+                // The interpolation in [`Ramp::calc`] requires strictly increasing temperatures.
+                if let Some(&(last_t, _)) = last_entry.1.last() {
+                    if t.partial_cmp(&last_t) != Some(std::cmp::Ordering::Greater) {
+                        bail!("The temperatures of ramp \"{id}\" are not strictly increasing. Point {} with T: {t}, F: {f} follows T: {last_t}.", last_entry.1.len() + 1);
+                    }
+                }
                 last_entry.1 .0.push((t, f));
                 return Ok(());
             }
@@ -100,20 +145,8 @@ fn try_ramp_line_from_properties(properties: Vec<Property>) -> Result<(String, f
 
     for Property { key, value } in properties {
         match key.as_str() {
-            "T" => {
-                t = Some(
-                    value
-                        .parse::<f32>()
-                        .with_context(|| format!("Failed to parse \"{value}\" to float."))?,
-                )
-            }
-            "F" => {
-                f = Some(
-                    value
-                        .parse::<f32>()
-                        .with_context(|| format!("Failed to parse \"{value}\" to float."))?,
-                )
-            }
+            "T" => t = Some(parse_fds_float(&value)?),
+            "F" => f = Some(parse_fds_float(&value)?),
             "ID" => id = Some(value),
             _ => {}
         }
@@ -131,3 +164,50 @@ fn try_ramp_line_from_properties(properties: Vec<Property>) -> Result<(String, f
         false => Ok((id.unwrap(), t.unwrap(), f.unwrap())),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The linear scan [`Ramp::calc`] was implemented with before the binary search.
+    fn calc_linear(ramp: &Ramp, temperature: f32) -> f32 {
+        if temperature <= ramp[0].0 {
+            return ramp[0].1;
+        }
+        for i in 1..ramp.len() {
+            let (t1, f1) = ramp[i];
+            if temperature < t1 {
+                let (t0, f0) = ramp[i - 1];
+                return f0 + (f1 - f0) / (t1 - t0) * (temperature - t0);
+            }
+        }
+        ramp[ramp.len() - 1].1
+    }
+
+    #[test]
+    fn calc_matches_linear_scan() {
+        let ramp = Ramp::from(vec![
+            (20.0, 439.8),
+            (400.0, 605.9),
+            (630.0, 786.4),
+            (690.0, 936.9),
+            (720.0, 1388.3),
+            (735.0, 5000.0),
+            (750.0, 1482.9),
+            (780.0, 908.7),
+            (830.0, 725.0),
+            (900.0, 650.0),
+            (1200.0, 650.0),
+        ]);
+        let temperatures = (-1000..=14000)
+            .map(|t| t as f32 / 10.0)
+            .chain(ramp.iter().map(|(t, _)| *t));
+        for temperature in temperatures {
+            assert_eq!(
+                ramp.calc(temperature).to_bits(),
+                calc_linear(&ramp, temperature).to_bits(),
+                "T = {temperature}"
+            );
+        }
+    }
+}
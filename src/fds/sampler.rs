@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fmt::Write as FmtWrite,
     fs::File,
     io::{BufRead, BufReader, Write},
@@ -6,13 +7,18 @@ use std::{
 };
 
 use crate::{
-    benchmark::BENCHMARK_ELEMENTS,
+    benchmark::get_benchmark_elements,
+    dry_run::get_dry_run,
     heat_transfer::simulations::{SimulationKind, SimulationMethod},
-    modification::was_modified,
+    modification::{is_modified, store_source_hashes},
+    summary::{record_created_simulation, RecordStatus},
 };
 
 use anyhow::*;
 use rayon::prelude::*;
+use serde::Deserialize;
+
+use super::material_override::{get_material_override_path, get_material_overrides};
 
 /// The name of a template simulation, witch should not be started.
 const TEMPLATE_NAME: &str = "template_heat_transfer.fds";
@@ -20,17 +26,39 @@ const TEMPLATE_NAME: &str = "template_heat_transfer.fds";
 const SIMULATION_NAME: &str = "heat_transfer.fds";
 
 /// The directories of the one dimensional templates.
-pub(super) const TEMPLATE_ONE_DIMENSIONAL_DIRECTORY_PATHS: [&str; 3] = [
+pub(super) const TEMPLATE_ONE_DIMENSIONAL_DIRECTORY_PATHS: [&str; 4] = [
     "fds/1D/Diabatic",
     "fds/1D/DiabaticOneSide",
     "fds/1D/Adiabatic",
+    "fds/1D/Convective",
 ];
 
 /// The directory of the fds speed test template.
 pub(super) const SPEED_TEST_DIRECTORY_PATHS: [&str; 1] = ["fds/1D/AdiabaticSpeedTest"];
 
+/// The replacements of a template sorted by their name, so they are always applied in the same order.
 #[derive(Debug, Clone)]
-struct Replace(Vec<(String, String)>);
+struct Replace(BTreeMap<String, String>);
+impl Replace {
+    /// Sets the value of a replacement, or adds the replacement if it does not exist.
+    fn set(&mut self, name: &str, value: String) {
+        self.0.insert(name.to_string(), value);
+    }
+}
+
+/// Formats a floating point value with the shortest representation that parses back to the same value, so the same value always results in the same simulation file and small values are not rounded to 0.
+fn fds_float(value: f64) -> String {
+    // Adding 0.0 turns -0.0 into 0.0.
+    format!("{}", value + 0.0)
+}
+
+/// Creates the `&RAMP` lines of a ramp with the passed id.
+fn ramp_lines(id: &str, ramp: &[(f64, f64)]) -> String {
+    ramp.iter()
+        .map(|(t, f)| format!("&RAMP ID = \"{id}\" T = {t:.3} F = {} /", fds_float(*f)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ReplaceType {
@@ -38,10 +66,12 @@ enum ReplaceType {
     SpeedTest { size: usize, threads: usize },
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Material {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(super) enum Material {
     Concrete,
     Steel,
+    Gypsum,
 }
 
 struct SimulationSettings {
@@ -59,7 +89,7 @@ impl SimulationSettings {
 
     /// Returns a reference to the template dir of this [`SimulationSettings`].
     /// # Panics
-    /// Panics if the path for a FDS speed test simulation with [`SimulationKind::Diabatic`] or [`SimulationKind::DiabaticOneSide`] are requested since they are not supported.
+    /// Panics if the path for a FDS speed test simulation with [`SimulationKind::Diabatic`], [`SimulationKind::DiabaticOneSide`] or [`SimulationKind::Convective`] are requested since they are not supported.
     fn template_dir(&self) -> &'static str {
         match (self.replace_type, self.simulation_kind) {
             (ReplaceType::OneDimensional, SimulationKind::Diabatic) => {
@@ -71,13 +101,17 @@ impl SimulationSettings {
             (ReplaceType::OneDimensional, SimulationKind::Adiabatic) => {
                 TEMPLATE_ONE_DIMENSIONAL_DIRECTORY_PATHS[2]
             }
+            (ReplaceType::OneDimensional, SimulationKind::Convective) => {
+                TEMPLATE_ONE_DIMENSIONAL_DIRECTORY_PATHS[3]
+            }
 
             (ReplaceType::SpeedTest { .. }, SimulationKind::Adiabatic) => {
                 SPEED_TEST_DIRECTORY_PATHS[0]
             }
 
             (ReplaceType::SpeedTest { .. }, SimulationKind::Diabatic)
-            | (ReplaceType::SpeedTest { .. }, SimulationKind::DiabaticOneSide) => {
+            | (ReplaceType::SpeedTest { .. }, SimulationKind::DiabaticOneSide)
+            | (ReplaceType::SpeedTest { .. }, SimulationKind::Convective) => {
                 unimplemented!()
             }
         }
@@ -90,6 +124,7 @@ impl SimulationSettings {
         path_string += match self.material {
             Material::Concrete => "/concrete",
             Material::Steel => "/steel",
+            Material::Gypsum => "/gypsum",
         };
         path_string += match (self.k_ramp, self.c_ramp) {
             (true, true) => "_k_c",
@@ -111,6 +146,7 @@ impl SimulationSettings {
     }
 
     /// Generates the build replace of this [`SimulationSettings`].
+    /// The loaded material overrides replace the built-in values. The constant values only apply to simulations without the corresponding ramp and the ramps only to simulations with it.
     ///
     /// # Panics
     ///
@@ -132,7 +168,7 @@ impl SimulationSettings {
                     SimulationKind::Diabatic | SimulationKind::DiabaticOneSide => {
                         replace.push((EMISSIVITY.to_string(), String::from("EMISSIVITY=0.70")));
                     }
-                    SimulationKind::Adiabatic => {
+                    SimulationKind::Adiabatic | SimulationKind::Convective => {
                         replace.push((EMISSIVITY.to_string(), String::from("EMISSIVITY=0.0")));
                     }
                 }
@@ -190,7 +226,7 @@ impl SimulationSettings {
                     SimulationKind::Diabatic | SimulationKind::DiabaticOneSide => {
                         replace.push((EMISSIVITY.to_string(), String::from("EMISSIVITY=0.79")));
                     }
-                    SimulationKind::Adiabatic => {
+                    SimulationKind::Adiabatic | SimulationKind::Convective => {
                         replace.push((EMISSIVITY.to_string(), String::from("EMISSIVITY=0.0")));
                     }
                 }
@@ -238,6 +274,60 @@ impl SimulationSettings {
                     ));
                 }
             }
+            Material::Gypsum => {
+                replace.push((DENSITY.to_string(), String::from("DENSITY=810")));
+                match self.simulation_kind {
+                    SimulationKind::Diabatic | SimulationKind::DiabaticOneSide => {
+                        replace.push((EMISSIVITY.to_string(), String::from("EMISSIVITY=0.90")));
+                    }
+                    SimulationKind::Adiabatic | SimulationKind::Convective => {
+                        replace.push((EMISSIVITY.to_string(), String::from("EMISSIVITY=0.0")));
+                    }
+                }
+                if self.k_ramp {
+                    replace.push((
+                        CONDUCTIVITY.to_string(),
+                        String::from("CONDUCTIVITY_RAMP=\"ramp_k\""),
+                    ));
+                    replace.push((
+                        CONDUCTIVITY_RAMP.to_string(),
+                        String::from(
+                            r#"&RAMP ID = "ramp_k" T = 20.000 F = 0.25 /
+&RAMP ID = "ramp_k" T = 100.000 F = 0.25 /
+&RAMP ID = "ramp_k" T = 200.000 F = 0.12 /
+&RAMP ID = "ramp_k" T = 600.000 F = 0.12 /
+&RAMP ID = "ramp_k" T = 1000.000 F = 0.30 /
+&RAMP ID = "ramp_k" T = 1200.000 F = 0.45 /"#,
+                        ),
+                    ));
+                } else {
+                    replace.push((CONDUCTIVITY.to_string(), String::from("CONDUCTIVITY=0.25")));
+                }
+                if self.c_ramp {
+                    replace.push((
+                        SPECIFIC_HEAT.to_string(),
+                        String::from("SPECIFIC_HEAT_RAMP=\"ramp_c\""),
+                    ));
+                    replace.push((
+                        SPECIFIC_HEAT_RAMP.to_string(),
+                        String::from(
+                            r#"&RAMP ID = "ramp_c" T = 20.000 F = 0.9500 /
+&RAMP ID = "ramp_c" T = 90.000 F = 0.9500 /
+&RAMP ID = "ramp_c" T = 120.000 F = 5.5000 /
+&RAMP ID = "ramp_c" T = 150.000 F = 0.9500 /
+&RAMP ID = "ramp_c" T = 700.000 F = 0.9500 /
+&RAMP ID = "ramp_c" T = 720.000 F = 1.8000 /
+&RAMP ID = "ramp_c" T = 740.000 F = 0.9500 /
+&RAMP ID = "ramp_c" T = 1200.000 F = 0.9500 /"#,
+                        ),
+                    ));
+                } else {
+                    replace.push((
+                        SPECIFIC_HEAT.to_string(),
+                        String::from("SPECIFIC_HEAT=0.9500"),
+                    ));
+                }
+            }
         }
 
         match self.replace_type {
@@ -263,17 +353,79 @@ impl SimulationSettings {
                     _ = writeln!(
                         mesh,
                         "&MESH IJK=3,{cells},4, XB=0.0,0.3,{},{},0.0,0.4 MPI_PROCESS={i} /",
-                        start / 10.0,
-                        end / 10.0
+                        fds_float(start / 10.0),
+                        fds_float(end / 10.0)
                     )
                 }
-                let y = (row as f64 / 10.0).to_string();
+                let y = fds_float(row as f64 / 10.0);
                 replace.push((String::from("#MESH#"), mesh));
                 replace.push((String::from("#Y#"), y));
             }
         }
 
-        Replace(replace)
+        let mut replace = Replace(replace.into_iter().collect());
+        for material_override in get_material_overrides(self.material, self.simulation_kind) {
+            if let Some(density) = material_override.density {
+                replace.set(DENSITY, format!("DENSITY={}", fds_float(density)));
+            }
+            if let Some(emissivity) = material_override.emissivity {
+                replace.set(EMISSIVITY, format!("EMISSIVITY={}", fds_float(emissivity)));
+            }
+            match (
+                self.k_ramp,
+                material_override.conductivity,
+                &material_override.conductivity_ramp,
+            ) {
+                (false, Some(conductivity), _) => replace.set(
+                    CONDUCTIVITY,
+                    format!("CONDUCTIVITY={}", fds_float(conductivity)),
+                ),
+                (true, _, Some(ramp)) => replace.set(CONDUCTIVITY_RAMP, ramp_lines("ramp_k", ramp)),
+                _ => {}
+            }
+            match (
+                self.c_ramp,
+                material_override.specific_heat,
+                &material_override.specific_heat_ramp,
+            ) {
+                (false, Some(specific_heat), _) => replace.set(
+                    SPECIFIC_HEAT,
+                    format!("SPECIFIC_HEAT={}", fds_float(specific_heat)),
+                ),
+                (true, _, Some(ramp)) => {
+                    replace.set(SPECIFIC_HEAT_RAMP, ramp_lines("ramp_c", ramp))
+                }
+                _ => {}
+            }
+        }
+        replace
+    }
+
+    /// Returns the content of the simulation file of this [`SimulationSettings`], which is the template with all replacements of [`SimulationSettings::build_replace`] applied.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the template file can not be read.
+    fn content(&self) -> Result<String> {
+        let template_path = self.template_path();
+        let replace = self.build_replace();
+
+        let source_reader = File::open(&template_path)
+            .with_context(|| format!("Failed to open file at {:?}", template_path))?;
+        let source_reader = BufReader::new(source_reader);
+        let mut content = String::new();
+        for line in source_reader.lines() {
+            let mut line = line
+                .with_context(|| format!("Failed to read line of file at {:?}.", template_path))?;
+
+            for (name, value) in replace.0.iter() {
+                line = line.replace(name, value);
+            }
+
+            content += &line;
+            content.push('\n');
+        }
+        Ok(content)
     }
 
     /// Create a simulation from this [`SimulationSettings`].
@@ -283,44 +435,49 @@ impl SimulationSettings {
     /// This function will return an error if
     /// - the simulation folder can not be created.
     /// - the template file can not be read.
+    /// - the simulation file can not be written.
     fn create(self) -> Result<PathBuf> {
         let template_path = self.template_path();
         let simulation_dir = self.simulation_dir();
+        if get_dry_run() {
+            let simulation_path = simulation_dir.join(SIMULATION_NAME);
+            println!(
+                "  Would create simulation at {:?} from template {:?}.",
+                simulation_path, template_path
+            );
+            record_created_simulation(simulation_path.clone(), RecordStatus::DryRun);
+            return Ok(simulation_path);
+        }
         std::fs::create_dir_all(&simulation_dir)
             .with_context(|| format!("Failed to create directories {:?}.", simulation_dir))?;
         let simulation_path = simulation_dir.join(SIMULATION_NAME);
-        if !was_modified(&[&template_path], &[&simulation_path])
+        let mut sources = vec![template_path.clone()];
+        sources.extend(get_material_override_path());
+
+        let content = self.content()?;
+
+        // A changed content is written even if the sources are unchanged, so removing the material overrides restores the built-in values.
+        if !is_modified(&sources, &[&simulation_path])
             .with_context(|| "Failed to get modification date.")?
+            && std::fs::read_to_string(&simulation_path).is_ok_and(|c| c == content)
         {
             println!(
                 "  Simulation at {:?} is newer than the template.",
                 simulation_path
             );
+            record_created_simulation(simulation_path.clone(), RecordStatus::Passed);
             return Ok(simulation_path);
         }
 
-        let replace = self.build_replace();
-
         let mut file_writer = File::create(&simulation_path)
             .with_context(|| format!("Failed to create file at {:?}", simulation_path))?;
+        file_writer
+            .write_all(content.as_bytes())
+            .with_context(|| format!("Failed to write file at {:?}", simulation_path))?;
 
-        let source_reader = File::open(&template_path)
-            .with_context(|| format!("Failed to open file at {:?}", template_path))?;
-        let source_reader = BufReader::new(source_reader);
-        for line in source_reader.lines() {
-            let mut line = line
-                .with_context(|| format!("Failed to read line of file at {:?}.", template_path))?;
-
-            for (name, value) in replace.0.iter() {
-                line = line.replace(name, value);
-            }
-
-            writeln!(file_writer, "{line}").with_context(|| {
-                format!("Failed to write line to file at {:?}", simulation_path)
-            })?;
-        }
-
+        store_source_hashes(&sources, &[&simulation_path])?;
         println!("  Created simulation at {:?}.", simulation_path);
+        record_created_simulation(simulation_path.clone(), RecordStatus::Created);
         Ok(simulation_path)
     }
 }
@@ -338,20 +495,25 @@ pub fn create_simulations(
         SimulationKind::Adiabatic,
         SimulationKind::Diabatic,
         SimulationKind::DiabaticOneSide,
+        SimulationKind::Convective,
     ]
     .iter()
     .filter_map(|s| {
         if s.is_simulation_kind(kind) && SimulationMethod::OneDimensional.is_simulation_type(method)
         {
-            Some([Material::Concrete, Material::Steel].iter().flat_map(|m| {
-                [true, false].iter().map(|b| SimulationSettings {
-                    simulation_kind: *s,
-                    material: *m,
-                    k_ramp: *b,
-                    c_ramp: *b,
-                    replace_type: ReplaceType::OneDimensional,
-                })
-            }))
+            Some(
+                [Material::Concrete, Material::Steel, Material::Gypsum]
+                    .iter()
+                    .flat_map(|m| {
+                        [true, false].iter().map(|b| SimulationSettings {
+                            simulation_kind: *s,
+                            material: *m,
+                            k_ramp: *b,
+                            c_ramp: *b,
+                            replace_type: ReplaceType::OneDimensional,
+                        })
+                    }),
+            )
         } else {
             None
         }
@@ -362,34 +524,41 @@ pub fn create_simulations(
     create_simulations_from_settings(simulations)
 }
 
-/// Creates all FDS speed test simulations from the template.
-///
-/// # Panics
-///
-/// Panics if the available thread can not be determent.
+/// Creates the FDS speed test simulations from the template, one for each combination of size of [`get_benchmark_elements`] and core count.
+/// The mesh of each simulation is split into one mesh per core. Returns the path, the size and the core count of each simulation.
 ///
 /// # Errors
 ///
-/// This function will return an error if the simulations can not be created.
-pub fn create_simulation_for_speed_test() -> std::result::Result<Vec<(PathBuf, usize, usize)>, Error>
-{
-    BENCHMARK_ELEMENTS
-        .iter()
-        .map(|size| {
-            let threads: usize = std::thread::available_parallelism().unwrap().into();
+/// This function will return an error if
+/// - a size is not a multiple of 4, since the mesh has 4 cells per row.
+/// - a core count is 0 or larger than the number of mesh rows of a size.
+/// - the simulations can not be created.
+pub fn create_simulation_for_speed_test(
+    cores: &[usize],
+) -> std::result::Result<Vec<(PathBuf, usize, usize)>, Error> {
+    get_benchmark_elements()
+        .into_iter()
+        .flat_map(|size| cores.iter().map(move |cores| (size, *cores)))
+        .map(|(size, threads)| {
+            if size % 4 != 0 {
+                bail!("The speed test needs a multiple of 4 cells, but {size} cells are requested.");
+            }
+            let rows = size / 4;
+            if threads == 0 || threads > rows {
+                bail!(
+                    "The speed test with {size} cells can only be run with 1 to {rows} cores, but {threads} cores are requested."
+                );
+            }
 
             let simulation_settings = SimulationSettings {
                 simulation_kind: SimulationKind::Adiabatic,
                 material: Material::Concrete,
                 k_ramp: true,
                 c_ramp: true,
-                replace_type: ReplaceType::SpeedTest {
-                    size: *size,
-                    threads,
-                },
+                replace_type: ReplaceType::SpeedTest { size, threads },
             };
             let path = simulation_settings.create()?;
-            Ok((path, *size, threads))
+            Ok((path, size, threads))
         })
         .collect()
 }
@@ -405,10 +574,7 @@ fn create_simulations_from_settings(
     let errors = simulations
         .par_iter_mut()
         .filter_map(|s: &mut Option<SimulationSettings>| s.take().map(|s| s.create()))
-        .filter_map(|r| match r {
-            std::result::Result::Ok(_) => None,
-            Err(err) => Some(err),
-        })
+        .filter_map(|r| r.err())
         .collect::<Vec<_>>();
     if errors.is_empty() {
         std::result::Result::Ok(())
@@ -416,3 +582,61 @@ fn create_simulations_from_settings(
         Err(errors)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fds_float_is_normalized() {
+        assert_eq!(fds_float(-0.0), fds_float(0.0));
+        assert_eq!(fds_float(0.3), "0.3");
+        for value in [1.0 / 3.0, 4e-5, 1e-12, 7850.0, -273.15] {
+            assert_eq!(fds_float(value).parse::<f64>().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn same_settings_generate_identical_content() {
+        let settings = [
+            SimulationKind::Adiabatic,
+            SimulationKind::Diabatic,
+            SimulationKind::DiabaticOneSide,
+            SimulationKind::Convective,
+        ]
+        .into_iter()
+        .flat_map(|simulation_kind| {
+            [Material::Concrete, Material::Steel, Material::Gypsum]
+                .into_iter()
+                .flat_map(move |material| {
+                    [true, false].map(|ramp| SimulationSettings {
+                        material,
+                        simulation_kind,
+                        k_ramp: ramp,
+                        c_ramp: ramp,
+                        replace_type: ReplaceType::OneDimensional,
+                    })
+                })
+        })
+        .chain([SimulationSettings {
+            material: Material::Concrete,
+            simulation_kind: SimulationKind::Adiabatic,
+            k_ramp: true,
+            c_ramp: true,
+            replace_type: ReplaceType::SpeedTest {
+                size: 256,
+                threads: 4,
+            },
+        }]);
+        for settings in settings {
+            let content = settings.content().unwrap();
+            assert!(content.contains("&MATL"), "{:?}", settings.simulation_dir());
+            assert_eq!(
+                content.as_bytes(),
+                settings.content().unwrap().as_bytes(),
+                "{:?}",
+                settings.simulation_dir()
+            );
+        }
+    }
+}
@@ -1,20 +1,27 @@
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
 use std::{
-    fs,
+    env, fs,
+    io::{ErrorKind, Read},
     path::{Path, PathBuf},
-    process::Command,
-    sync::{Arc, Mutex},
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError, RwLock,
+    },
     thread,
+    time::{Duration, Instant},
 };
 
 use ::anyhow::*;
 
 use crate::{
+    dry_run::get_dry_run,
     heat_transfer::simulations::{SimulationKind, SimulationMethod},
-    modification::was_modified,
+    modification::{is_modified, store_source_hashes},
+    summary::{record_run_simulation, RecordStatus},
 };
 
-/// How many simulations can be run in parallel.
-const MAX_PARALLEL: usize = 10;
 /// Witch paths should be ignored. If the path starts wit `**` there can be an arbitrary amount of directory before.
 const IGNORE_PATHS: [&str; 7] = [
     "**/template_heat_transfer.fds",
@@ -28,11 +35,89 @@ const IGNORE_PATHS: [&str; 7] = [
 /// The root path to search fo fds simulations
 const ROOT_PATH: &str = "fds";
 
+/// The default FDS executable.
+pub const DEFAULT_FDS_BIN: &str = "fds";
+/// The default MPI launcher that starts FDS.
+pub const DEFAULT_MPI_LAUNCHER: &str = "mpiexec";
+
+/// The FDS executable. If it is not set, [`DEFAULT_FDS_BIN`] is used. This value is replaced at the start of the program.
+static FDS_BIN: RwLock<Option<PathBuf>> = RwLock::new(None);
+/// The MPI launcher that starts FDS. If it is not set, [`DEFAULT_MPI_LAUNCHER`] is used. This value is replaced at the start of the program.
+static MPI_LAUNCHER: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Set the FDS executable. It can be a name that is searched in `PATH` or a path.
+pub fn set_fds_bin(fds_bin: PathBuf) {
+    println!("Set fds executable to {fds_bin:?}");
+    *FDS_BIN.write().unwrap_or_else(PoisonError::into_inner) = Some(fds_bin);
+}
+
+/// Get the FDS executable.
+#[inline]
+pub fn get_fds_bin() -> PathBuf {
+    FDS_BIN
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_FDS_BIN))
+}
+
+/// Set the MPI launcher that starts FDS, e.g. `mpiexec` or `srun`. It can be a name that is searched in `PATH` or a path.
+pub fn set_mpi_launcher(mpi_launcher: PathBuf) {
+    println!("Set mpi launcher to {mpi_launcher:?}");
+    *MPI_LAUNCHER.write().unwrap_or_else(PoisonError::into_inner) = Some(mpi_launcher);
+}
+
+/// Get the MPI launcher that starts FDS.
+#[inline]
+pub fn get_mpi_launcher() -> PathBuf {
+    MPI_LAUNCHER
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_MPI_LAUNCHER))
+}
+
+/// The default timeout of a FDS simulation in seconds. A value of 0 disables the timeout. This value is replaced at the start of the program.
+static FDS_TIMEOUT: AtomicU64 = AtomicU64::new(0);
+/// The interval in which a running FDS simulation is checked for the timeout.
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Set the time after which a FDS simulation is killed and counted as failed. With [`None`] the simulations can run forever.
+pub fn set_fds_timeout(fds_timeout: Option<Duration>) {
+    FDS_TIMEOUT.store(
+        fds_timeout.map(|t| t.as_secs().max(1)).unwrap_or_default(),
+        Ordering::Relaxed,
+    );
+    println!("Set fds timeout to {fds_timeout:?}")
+}
+
+/// Get the time after which a FDS simulation is killed and counted as failed.
+#[inline]
+pub fn get_fds_timeout() -> Option<Duration> {
+    match FDS_TIMEOUT.load(Ordering::Relaxed) {
+        0 => None,
+        seconds => Some(Duration::from_secs(seconds)),
+    }
+}
+
+/// Checks if an executable exists. A bare name is searched in the directories of `PATH`.
+fn executable_exists(executable: &Path) -> bool {
+    if executable.components().count() > 1 {
+        return executable.is_file();
+    }
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|directory| directory.join(executable).is_file())
+    })
+}
+
 /// The status of a simulation that had run.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub(super) enum Status {
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// The result of the simulation is newer than the input file, so it was not run again.
     Passed(PathBuf),
+    /// The simulation finished successfully.
     Succeeded(PathBuf),
+    /// FDS reported an error while running the simulation.
     Failed(PathBuf),
 }
 
@@ -74,13 +159,40 @@ fn find_fds_files<P1: AsRef<Path>, P2: AsRef<Path>>(
             }
             if path.is_dir() {
                 find_fds_files(path, ignore_paths, file_paths);
-            } else if path.extension().map_or(false, |ext| ext == "fds") {
+            } else if path.extension().is_some_and(|ext| ext == "fds") {
                 file_paths.push(path)
             }
         }
     }
 }
 
+/// Find all `result` directories of the fds simulations. Symbolic links named `result` are returned as well, but not followed.
+pub fn find_result_dirs() -> Vec<PathBuf> {
+    let mut result_dirs = vec![];
+    find_result_dirs_in(ROOT_PATH, &mut result_dirs);
+    result_dirs
+}
+
+/// Find all `result` directories inside the passed directory.
+fn find_result_dirs_in<P: AsRef<Path>>(start_path: P, result_dirs: &mut Vec<PathBuf>) {
+    if let std::result::Result::Ok(read_dir) = fs::read_dir(start_path) {
+        for path in read_dir {
+            let std::result::Result::Ok(dir_entry) = path else {
+                continue;
+            };
+            let path = dir_entry.path();
+            let std::result::Result::Ok(file_type) = dir_entry.file_type() else {
+                continue;
+            };
+            if path.file_name().is_some_and(|name| name == "result") {
+                result_dirs.push(path);
+            } else if file_type.is_dir() {
+                find_result_dirs_in(path, result_dirs);
+            }
+        }
+    }
+}
+
 /// Returns the file name, the absolut result directory, and the run file path.
 ///
 /// # Errors
@@ -119,7 +231,42 @@ fn get_run_simulation_path_data(file_path: &Path) -> Result<(String, PathBuf, Pa
     Ok((file_name.to_string(), abs_result_dir, run_file))
 }
 
+/// The files inside a `result` directory that are not generated by FDS: the run file of [`run_simulation_checked`] and its stored source hashes.
+const RUN_FILES: [&str; 2] = ["run", "run.hash"];
+
+/// Removes the files FDS generated inside the `result` directory of the simulation. The run file and its source hashes are kept, so the modification check of the simulation is not changed.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the `result` directory can not be read.
+/// - a generated file or directory can not be removed.
+pub(super) fn remove_fds_results(file_path: &Path) -> Result<()> {
+    let (_, abs_result_dir, _) = get_run_simulation_path_data(file_path)?;
+    let read_dir = fs::read_dir(&abs_result_dir)
+        .with_context(|| format!("Failed to read directory at {abs_result_dir:?}."))?;
+    for dir_entry in read_dir {
+        let path = dir_entry
+            .with_context(|| format!("Failed to read directory at {abs_result_dir:?}."))?
+            .path();
+        if path
+            .file_name()
+            .is_some_and(|name| RUN_FILES.iter().any(|run_file| name == *run_file))
+        {
+            continue;
+        }
+        if path.is_dir() && !path.is_symlink() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        }
+        .with_context(|| format!("Failed to delete {path:?}."))?;
+    }
+    Ok(())
+}
+
 /// Checks if the results are newer then the simulation file, else the simulation is run.
+/// The run file is only created if the simulation succeeded, so a failed simulation is run again.
 ///
 /// # Errors
 ///
@@ -129,20 +276,23 @@ fn get_run_simulation_path_data(file_path: &Path) -> Result<(String, PathBuf, Pa
 /// - the run file can not be created.
 fn run_simulation_checked(file_path: PathBuf) -> Result<Status> {
     let (file_name, abs_result_dir, run_file) = get_run_simulation_path_data(&file_path)?;
-    if !was_modified(&[&file_path], &[&run_file])
+    if !is_modified(&[&file_path], &[&run_file])
         .with_context(|| format!("Failed to run fds simulation at {:?}.", file_path))?
     {
         return Ok(Status::Passed(file_path));
     }
     println!("  Start fds simulation at {:?}.", file_path);
 
-    let result = run_simulation(file_path, file_name, abs_result_dir, 1)?;
-    fs::File::create(&run_file).with_context(|| {
-        format!(
-            "Failed to create a run file for fds simulation at {:?}",
-            run_file
-        )
-    })?;
+    let result = run_simulation(file_path.clone(), file_name, abs_result_dir, 1)?;
+    if let Status::Succeeded(_) = result {
+        fs::File::create(&run_file).with_context(|| {
+            format!(
+                "Failed to create a run file for fds simulation at {:?}",
+                run_file
+            )
+        })?;
+        store_source_hashes(&[&file_path], &[&run_file])?;
+    }
     Ok(result)
 }
 
@@ -152,57 +302,165 @@ pub(super) fn run_simulation_unchecked(file_path: PathBuf, cores: usize) -> Resu
     run_simulation(file_path, file_name, abs_result_dir, cores)
 }
 
+/// Checks if a line of the FDS output starts with the `ERROR:` or `ERROR(<code>):` prefix FDS uses for errors.
+fn is_error_line(line: &str) -> bool {
+    line.starts_with("ERROR:") || line.starts_with("ERROR(")
+}
+
+/// Waits for the child to exit. If the timeout expires first, the child and all processes it started are killed and [`None`] is returned.
+///
+/// # Errors
+///
+/// This function will return an error if waiting for or killing the child fails.
+fn wait_with_timeout(child: &mut Child, timeout: Option<Duration>) -> Result<Option<ExitStatus>> {
+    let Some(timeout) = timeout else {
+        return Ok(Some(child.wait()?));
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(exit_status) = child.try_wait()? {
+            return Ok(Some(exit_status));
+        }
+        if Instant::now() >= deadline {
+            kill_process_group(child)?;
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep(TIMEOUT_POLL_INTERVAL);
+    }
+}
+
+/// Kills the child together with all processes of its process group.
+///
+/// # Errors
+///
+/// This function will return an error if the signal can not be sent.
+#[cfg(unix)]
+fn kill_process_group(child: &mut Child) -> Result<()> {
+    // The child is the leader of its own process group, so the negative id addresses the whole group.
+    if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Failed to kill process group {}.", child.id()));
+    }
+    Ok(())
+}
+
+/// Kills the child. Processes started by the child are not killed on this platform.
+///
+/// # Errors
+///
+/// This function will return an error if the child can not be killed.
+#[cfg(not(unix))]
+fn kill_process_group(child: &mut Child) -> Result<()> {
+    child
+        .kill()
+        .with_context(|| format!("Failed to kill process {}.", child.id()))
+}
+
 /// Starts a FDS simulation.
+/// The simulation failed if any line of stderr starts with an FDS error prefix, FDS exited with a non-zero exit code, or it ran longer than the timeout. The reason is printed.
 ///
 /// # Errors
 ///
-/// This function will return an error if running the simulation fails.
+/// This function will return an error if
+/// - the FDS executable or the MPI launcher can not be found.
+/// - running the simulation fails.
 fn run_simulation(
     file_path: PathBuf,
     file_name: String,
     abs_result_dir: PathBuf,
     cores: usize,
 ) -> Result<Status> {
-    let output = Command::new("mpiexec")
+    let fds_bin = get_fds_bin();
+    let mpi_launcher = get_mpi_launcher();
+    if !executable_exists(&fds_bin) {
+        bail!(
+            "Failed to find the fds executable {fds_bin:?} for {file_path:?}. Set it with --fds-bin or the FDS_BIN environment variable."
+        )
+    }
+    let mut command = Command::new(&mpi_launcher);
+    command
         .arg("-n")
         .arg(cores.to_string())
-        .arg("fds")
+        .arg(&fds_bin)
         .arg(format!("../{}", file_name))
         .current_dir(abs_result_dir)
-        .output()
-        .with_context(|| format!("Failed to execute fds for {:?}.", file_path))?;
-    let stderr = output.stderr;
-    if stderr.len() > 150 {
-        let slice = &stderr[(stderr.len() - 150)..stderr.len()];
-        let end = core::str::from_utf8(slice).with_context(|| {
-            format!(
-                "Failed convert output to string for fds simulation at {:?}.",
-                file_path
-            )
-        })?;
-        if end.contains("ERROR") || end.contains("error") {
-            return Ok(Status::Failed(file_path));
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    // A own process group allows to kill the processes started by the launcher together with it on a timeout.
+    // Without a timeout the launcher stays in the process group of the terminal, so Ctrl-C reaches it and FDS.
+    let timeout = get_fds_timeout();
+    #[cfg(unix)]
+    if timeout.is_some() {
+        command.process_group(0);
+    }
+    let mut child = match command.spawn() {
+        std::result::Result::Ok(child) => child,
+        Err(err) if err.kind() == ErrorKind::NotFound => bail!(
+            "Failed to find the mpi launcher {mpi_launcher:?} for {file_path:?}. Set it with --mpi-launcher or the MPI_LAUNCHER environment variable."
+        ),
+        Err(err) => {
+            return Err(err).with_context(|| {
+                format!("Failed to execute {fds_bin:?} with {mpi_launcher:?} for {file_path:?}.")
+            })
         }
+    };
+    // The pipe is read on a separate thread, so a full pipe does not block the simulation while waiting for it.
+    let stderr_reader = child.stderr.take().map(|mut stderr| {
+        thread::spawn(move || {
+            let mut buffer = vec![];
+            _ = stderr.read_to_end(&mut buffer);
+            buffer
+        })
+    });
+    let Some(exit_status) = wait_with_timeout(&mut child, timeout)
+        .with_context(|| format!("Failed to wait for fds simulation at {file_path:?}."))?
+    else {
+        println!(
+            "  FDS timed out after {:?} for simulation at {file_path:?}.",
+            timeout.unwrap_or_default()
+        );
+        return Ok(Status::Failed(file_path));
+    };
+    let stderr = stderr_reader
+        .and_then(|handle| handle.join().ok())
+        .unwrap_or_default();
+
+    let stderr = String::from_utf8_lossy(&stderr);
+    if let Some(error_line) = stderr.lines().map(str::trim).find(|l| is_error_line(l)) {
+        println!("  FDS reported an error for simulation at {file_path:?}: {error_line}");
+        return Ok(Status::Failed(file_path));
+    }
+    if !exit_status.success() {
+        println!("  FDS exited with {exit_status} for simulation at {file_path:?}.");
+        return Ok(Status::Failed(file_path));
     }
 
     Ok(Status::Succeeded(file_path))
 }
 
-/// Run all Simulations that can be found.
+/// Run all Simulations that can be found with `jobs` simulations in parallel and return the sorted [`Status`] of each simulation without printing it.
+/// In a dry run the simulations are only printed and no [`Status`] is returned.
 ///
 /// # Errors
 ///
 /// This function will return an error if running the simulation fails.
-pub fn run_simulations(
+pub fn run_simulations_with_status(
     method: Option<&[SimulationMethod]>,
     kind: Option<&[SimulationKind]>,
-) -> Result<(), Vec<anyhow::Error>> {
+    jobs: usize,
+) -> Result<Vec<Status>, Vec<anyhow::Error>> {
     let ignore = [
         if !SimulationMethod::OneDimensional.is_simulation_type(method) {
             Some("fds/1D")
         } else {
             None
         },
+        if !SimulationMethod::TwoDimensionalCpu.is_simulation_type(method) {
+            Some("fds/2D")
+        } else {
+            None
+        },
         if !SimulationKind::Adiabatic.is_simulation_kind(kind) {
             Some("**/Adiabatic")
         } else {
@@ -218,6 +476,11 @@ pub fn run_simulations(
         } else {
             None
         },
+        if !SimulationKind::Convective.is_simulation_kind(kind) {
+            Some("**/Convective")
+        } else {
+            None
+        },
     ];
     let ignore = ignore
         .iter()
@@ -228,10 +491,20 @@ pub fn run_simulations(
     let mut file_paths = vec![];
     find_fds_files(ROOT_PATH, &ignore, &mut file_paths);
 
+    if get_dry_run() {
+        file_paths.sort();
+        for file_path in file_paths {
+            println!("  Would run fds simulation at {:?}.", file_path);
+            record_run_simulation(file_path, RecordStatus::DryRun);
+        }
+        return std::result::Result::Ok(vec![]);
+    }
+
     let file_paths = Arc::new(Mutex::new(file_paths));
 
-    let mut handles = Vec::with_capacity(MAX_PARALLEL);
-    for _ in 0..MAX_PARALLEL {
+    let jobs = jobs.max(1);
+    let mut handles = Vec::with_capacity(jobs);
+    for _ in 0..jobs {
         let file_paths = file_paths.clone();
         let handle = thread::spawn(move || -> Vec<Result<Status, anyhow::Error>> {
             let mut results = vec![];
@@ -267,8 +540,36 @@ pub fn run_simulations(
             Err(e) => errors.push(e),
         });
 
+    for status in run.iter() {
+        let (path, status) = match status {
+            Status::Passed(path) => (path, RecordStatus::Passed),
+            Status::Succeeded(path) => (path, RecordStatus::Succeeded),
+            Status::Failed(path) => (path, RecordStatus::Failed),
+        };
+        record_run_simulation(path.clone(), status);
+    }
+
+    if errors.is_empty() {
+        run.sort();
+        std::result::Result::Ok(run)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Run all Simulations that can be found with `jobs` simulations in parallel and print the [`Status`] of each simulation.
+///
+/// # Errors
+///
+/// This function will return an error if running the simulation fails or any simulation failed.
+pub fn run_simulations(
+    method: Option<&[SimulationMethod]>,
+    kind: Option<&[SimulationKind]>,
+    jobs: usize,
+) -> Result<(), Vec<anyhow::Error>> {
+    let run = run_simulations_with_status(method, kind, jobs)?;
+
     let mut any_failed = false;
-    run.sort();
     for r in run {
         match r {
             Status::Passed(p) => {
@@ -287,12 +588,73 @@ pub fn run_simulations(
         }
     }
     if any_failed {
-        errors.push(anyhow!("Some fds simulations failed to run."));
+        Err(vec![anyhow!("Some fds simulations failed to run.")])
+    } else {
+        std::result::Result::Ok(())
     }
+}
 
-    if errors.is_empty() {
-        std::result::Result::Ok(())
-    } else {
-        Err(errors)
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Writes an executable shell script.
+    fn write_script(path: &Path, script: &str) {
+        fs::write(path, script).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn run_simulation_returns_status() {
+        let dir = env::temp_dir().join(format!("heat_transfer_status_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // The launcher drops `-n <cores>` and starts FDS, which reports an error for decks containing FAIL.
+        write_script(&dir.join("mpiexec"), "#!/bin/sh\nshift 2\nexec \"$@\"\n");
+        write_script(
+            &dir.join("fds"),
+            "#!/bin/sh\nif grep -q SLEEP \"$1\"; then sleep 10; fi\nif grep -q FAIL \"$1\"; then echo 'ERROR: FAIL' >&2; fi\n",
+        );
+        set_mpi_launcher(dir.join("mpiexec"));
+        set_fds_bin(dir.join("fds"));
+
+        let succeeding = dir.join("succeeding.fds");
+        fs::write(&succeeding, "&HEAD CHID='succeeding' /\n").unwrap();
+        let failing = dir.join("failing.fds");
+        fs::write(&failing, "&HEAD CHID='FAIL' /\n").unwrap();
+
+        assert_eq!(
+            run_simulation_unchecked(succeeding.clone(), 1).unwrap(),
+            Status::Succeeded(succeeding)
+        );
+        assert_eq!(
+            run_simulation_unchecked(failing.clone(), 1).unwrap(),
+            Status::Failed(failing)
+        );
+
+        // Only a successful simulation is marked as run, so a failed one is started again.
+        for (name, chid, expected_run_file) in [("ok", "succeeding", true), ("err", "FAIL", false)]
+        {
+            let deck = dir.join(name).join("deck.fds");
+            fs::create_dir_all(deck.parent().unwrap()).unwrap();
+            fs::write(&deck, format!("&HEAD CHID='{chid}' /\n")).unwrap();
+            run_simulation_checked(deck).unwrap();
+            assert_eq!(
+                dir.join(name).join("result/run").exists(),
+                expected_run_file,
+                "{name}"
+            );
+        }
+
+        // A simulation killed by the timeout failed and is not marked as run either.
+        let sleeping = dir.join("sleep").join("deck.fds");
+        fs::create_dir_all(sleeping.parent().unwrap()).unwrap();
+        fs::write(&sleeping, "&HEAD CHID='SLEEP' /\n").unwrap();
+        set_fds_timeout(Some(Duration::from_secs(1)));
+        let status = run_simulation_checked(sleeping.clone());
+        set_fds_timeout(None);
+        assert_eq!(status.unwrap(), Status::Failed(sleeping));
+        assert!(!dir.join("sleep/result/run").exists());
+        fs::remove_dir_all(dir).unwrap();
     }
 }
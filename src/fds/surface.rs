@@ -1,9 +1,50 @@
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, Ordering},
+};
 
-use super::{material::MaterialList, parser::Property};
+use super::{
+    material::{Material, MaterialList},
+    parser::{parse_fds_float, Property},
+};
 
 use anyhow::*;
 
+/// The initial temperature of all cells of a [`Surface`] if `TMP_INNER` is not set.
+pub const DEFAULT_INITIAL_TEMPERATURE: f32 = 20.0;
+
+/// The time step in s the cell sizes of the [`Discretization::Geometric`] are determined with.
+pub const DELTA_TIME: f32 = 1.0;
+
+/// The largest Fourier number `α⋅Δt / Δx²` an explicit scheme is stable with.
+const MAX_EXPLICIT_FOURIER_NUMBER: f32 = 0.5;
+
+/// Whether a warning is printed for surfaces whose smallest cell is below the stability limit of an explicit scheme. This value is replaced at the start of the program.
+static GRID_STABILITY_WARNING: AtomicBool = AtomicBool::new(false);
+
+/// Set whether a warning is printed for surfaces whose smallest cell is below the stability limit of an explicit scheme at [`DELTA_TIME`].
+/// The solver is implicit and therefore stable with these cells, but large Fourier numbers can cause oscillations of thin layers, e.g. of steel.
+pub fn set_grid_stability_warning(grid_stability_warning: bool) {
+    GRID_STABILITY_WARNING.store(grid_stability_warning, Ordering::Relaxed);
+    println!("Set grid stability warning to {grid_stability_warning}")
+}
+
+/// Get whether a warning is printed for surfaces whose smallest cell is below the stability limit of an explicit scheme.
+#[inline]
+pub fn get_grid_stability_warning() -> bool {
+    GRID_STABILITY_WARNING.load(Ordering::Relaxed)
+}
+
+/// The way the layers of a [`Surface`] are divided into cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Discretization {
+    /// Small cells at the boarder and bigger cells in the middle of each layer. The cell sizes are determined with the material properties.
+    #[default]
+    Geometric,
+    /// `n` cells of equal size in each layer, set with `CELL_COUNT`.
+    Uniform { n: usize },
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SurfaceCell {
     pub material_id: u32,
@@ -12,17 +53,23 @@ pub struct SurfaceCell {
 
 /// Structure of the surface from several [`SurfaceCell`]s.
 #[derive(Debug)]
-pub struct Surface(Vec<SurfaceCell>);
+pub struct Surface {
+    cells: Vec<SurfaceCell>,
+    /// The initial temperature of all cells, set with `TMP_INNER`. Defaults to [`DEFAULT_INITIAL_TEMPERATURE`].
+    pub initial_temperature: f32,
+    /// The thermal contact resistance in m²K/W between a cell and the following cell, set per layer interface with `CONTACT_RESISTANCE`. Empty if all layers are in perfect contact.
+    pub contact_resistances: Vec<f32>,
+}
 impl Deref for Surface {
     type Target = Vec<SurfaceCell>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.cells
     }
 }
 impl DerefMut for Surface {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.cells
     }
 }
 
@@ -31,6 +78,7 @@ impl DerefMut for Surface {
 pub struct SurfaceList(pub Vec<(String, Surface)>);
 impl SurfaceList {
     /// Attempts to add a [`Surface`] to the list from the [`Property`]s and the [`MaterialList`].
+    /// The blended materials of layers with multiple components are added to the [`MaterialList`].
     ///
     /// # Errors
     ///
@@ -38,7 +86,7 @@ impl SurfaceList {
     pub fn try_add_from_properties(
         &mut self,
         properties: Vec<Property>,
-        material_list: &MaterialList,
+        material_list: &mut MaterialList,
     ) -> Result<()> {
         self.0.push(
             try_surface_from_properties(properties, material_list)
@@ -73,6 +121,7 @@ impl DerefMut for SurfaceList {
 }
 
 /// Attempts to create a [`Surface`] from the [`Property`]s and the [`MaterialList`].
+/// The layers are either set with `MATL_ID='A','B'` with a single material per layer, or with `MATL_ID(layer,component)` and `MATL_MASS_FRACTION(layer,component)`, see [`layer_materials`].
 ///
 /// # Errors
 ///
@@ -80,13 +129,21 @@ impl DerefMut for SurfaceList {
 /// - the [`Property`] values could not be parsed.
 /// - a [`Property`] is missing
 /// - a requested [`Material`] could not be found.
+/// - the components of a layer are invalid.
+/// - the number of `MATL_ID` and `THICKNESS` values differs or a HT3D surface has multiple materials.
 fn try_surface_from_properties(
     properties: Vec<Property>,
-    material_list: &MaterialList,
+    material_list: &mut MaterialList,
 ) -> Result<(String, Surface)> {
     let mut id = None;
     let mut material_ids = None;
+    let mut component_ids = vec![];
+    let mut component_mass_fractions = vec![];
     let mut thicknesses = None;
+    let mut initial_temperature = DEFAULT_INITIAL_TEMPERATURE;
+    let mut layer_contact_resistances = None;
+    let mut discretization = Discretization::default();
+    let mut ht3d = false;
 
     for Property { key, value } in properties {
         match key.as_str() {
@@ -104,27 +161,75 @@ fn try_surface_from_properties(
                         .collect::<Result<Vec<usize>>>()?,
                 );
             }
+            key if key.starts_with("MATL_ID(") => {
+                let (layer, components) = parse_layer_components(key, "MATL_ID")?;
+                let names = value.split(',').map(|s| s.trim().to_string());
+                component_ids.extend(zip_components(key, layer, components, names)?);
+            }
+            key if key.starts_with("MATL_MASS_FRACTION(") => {
+                let (layer, components) = parse_layer_components(key, "MATL_MASS_FRACTION")?;
+                let mass_fractions = value
+                    .split(',')
+                    .map(|s| s.trim())
+                    .map(parse_fds_float)
+                    .collect::<Result<Vec<f32>>>()?;
+                component_mass_fractions.extend(zip_components(
+                    key,
+                    layer,
+                    components,
+                    mass_fractions,
+                )?);
+            }
             "THICKNESS" => {
                 thicknesses = Some(
                     value
                         .split(',')
                         .map(|s| s.trim())
-                        .map(|s| {
-                            s.parse::<f32>()
-                                .with_context(|| format!("Failed to parse \"{s}\" to float."))
-                        })
+                        .map(parse_fds_float)
                         .collect::<Result<Vec<f32>>>()?,
                 );
             }
-            "HT3D" => {
-                if value == ".TRUE." || value == "T" {
-                    thicknesses = Some(vec![0.0])
+            "TMP_INNER" => initial_temperature = parse_fds_float(&value)?,
+            "CONTACT_RESISTANCE" => {
+                layer_contact_resistances = Some(
+                    value
+                        .split(',')
+                        .map(|s| s.trim())
+                        .map(parse_fds_float)
+                        .collect::<Result<Vec<f32>>>()?,
+                );
+            }
+            "CELL_COUNT" => {
+                let n = value
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("Failed to parse CELL_COUNT = \"{value}\"."))?;
+                if n == 0 {
+                    bail!("CELL_COUNT should be at least 1.");
                 }
+                discretization = Discretization::Uniform { n };
+            }
+            "HT3D" if value == ".TRUE." || value == "T" => {
+                thicknesses = Some(vec![0.0]);
+                ht3d = true;
             }
             _ => {}
         }
     }
 
+    if !component_ids.is_empty() {
+        if material_ids.is_some() {
+            bail!("MATL_ID can not be set with and without the index of the layer and the component in the same surface.");
+        }
+        material_ids = Some(layer_materials(
+            material_list,
+            component_ids,
+            component_mass_fractions,
+        )?);
+    } else if !component_mass_fractions.is_empty() {
+        bail!("MATL_MASS_FRACTION is set, but no MATL_ID with the index of the layer and the component.");
+    }
+
     match id.is_none() || material_ids.is_none() || thicknesses.is_none() {
         true => {
             bail!(
@@ -135,56 +240,412 @@ fn try_surface_from_properties(
             )
         }
         false => {
+            let id = id.unwrap();
+            let material_ids = material_ids.unwrap();
+            let thicknesses = thicknesses.unwrap();
+            if !ht3d {
+                validate_layers(&id, material_list, &material_ids, &thicknesses)?;
+            } else if material_ids.len() != 1 {
+                bail!(
+                    "The HT3D surface \"{id}\" has {} MATL_ID values, but only a single material is supported.",
+                    material_ids.len()
+                );
+            }
             let surface_cells = cells_from_materials_and_thickness(
                 material_list,
-                &material_ids.unwrap(),
-                &thicknesses.unwrap(),
+                &material_ids,
+                &thicknesses,
+                initial_temperature,
+                discretization,
             );
+            if get_grid_stability_warning() {
+                warn_explicit_stability(&id, material_list, &surface_cells, initial_temperature);
+            }
+            let contact_resistances = match layer_contact_resistances {
+                Some(layer_contact_resistances) => {
+                    if layer_contact_resistances.len() + 1 != material_ids.len() {
+                        bail!(
+                            "The surface \"{id}\" has {} layers and therefore needs {} CONTACT_RESISTANCE values, but found {}.",
+                            material_ids.len(),
+                            material_ids.len().saturating_sub(1),
+                            layer_contact_resistances.len()
+                        );
+                    }
+                    contact_resistances_from_layers(
+                        material_list,
+                        &material_ids,
+                        &thicknesses,
+                        initial_temperature,
+                        discretization,
+                        &layer_contact_resistances,
+                    )
+                }
+                None => vec![],
+            };
 
-            Ok((id.unwrap(), Surface(surface_cells)))
+            Ok((
+                id,
+                Surface {
+                    cells: surface_cells,
+                    initial_temperature,
+                    contact_resistances,
+                },
+            ))
         }
     }
 }
 
-/// Creates all cells for a Surface with smaller cells at the boarder and bigger cells in the middle for all layers
+/// Parses the 1-based layer and component indices of a key like `MATL_ID(1,2)` or `MATL_ID(1,1:3)` and returns them 0-based.
+///
+/// # Errors
+///
+/// This function will return an error if the key does not end with a valid index.
+fn parse_layer_components(key: &str, name: &str) -> Result<(usize, Vec<usize>)> {
+    let index = key
+        .strip_prefix(name)
+        .and_then(|k| k.strip_prefix('('))
+        .and_then(|k| k.strip_suffix(')'))
+        .ok_or(anyhow!("Failed to parse the index of {key}."))?;
+    let parse = |s: &str| match s.trim().parse::<usize>() {
+        std::result::Result::Ok(i) if i > 0 => Ok(i - 1),
+        _ => bail!("The index of {key} should contain integers starting at 1."),
+    };
+    let (layer, components) = index.split_once(',').ok_or(anyhow!(
+        "The index of {key} should contain a layer and a component."
+    ))?;
+    let components = match components.split_once(':') {
+        Some((first, last)) => (parse(first)?..=parse(last)?).collect::<Vec<_>>(),
+        None => vec![parse(components)?],
+    };
+    if components.is_empty() {
+        bail!("The component range of {key} is empty.");
+    }
+    Ok((parse(layer)?, components))
+}
+
+/// Assigns the values of a key like `MATL_ID(1,1:2)` to the layer and each component.
+///
+/// # Errors
+///
+/// This function will return an error if the number of values differs from the number of components.
+fn zip_components<T>(
+    key: &str,
+    layer: usize,
+    components: Vec<usize>,
+    values: impl IntoIterator<Item = T>,
+) -> Result<Vec<((usize, usize), T)>> {
+    let values = values.into_iter().collect::<Vec<_>>();
+    if values.len() != components.len() {
+        bail!(
+            "{key} has {} components, but {} values.",
+            components.len(),
+            values.len()
+        );
+    }
+    Ok(components
+        .into_iter()
+        .map(|component| (layer, component))
+        .zip(values)
+        .collect())
+}
+
+/// Returns the index of the material of each layer for the components set with `MATL_ID(layer,component)` and `MATL_MASS_FRACTION(layer,component)`.
+/// A layer with a single component and without mass fraction uses the material directly.
+/// The components of a layer with multiple components are blended with [`Material::blend`] into a new material, which is added to the [`MaterialList`] with an ID like `A*0.7+B*0.3`.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - a layer or a component is missing or set twice.
+/// - a material could not be found.
+/// - a layer with multiple components is missing a mass fraction.
+/// - the mass fractions of a layer are negative or do not sum up to 1.
+fn layer_materials(
+    material_list: &mut MaterialList,
+    mut component_ids: Vec<((usize, usize), String)>,
+    mut component_mass_fractions: Vec<((usize, usize), f32)>,
+) -> Result<Vec<usize>> {
+    const MASS_FRACTION_TOLERANCE: f32 = 0.01;
+
+    component_ids.sort_by_key(|(index, _)| *index);
+    component_mass_fractions.sort_by_key(|(index, _)| *index);
+    for (indices, key) in [
+        (
+            component_ids.iter().map(|(i, _)| *i).collect::<Vec<_>>(),
+            "MATL_ID",
+        ),
+        (
+            component_mass_fractions
+                .iter()
+                .map(|(i, _)| *i)
+                .collect::<Vec<_>>(),
+            "MATL_MASS_FRACTION",
+        ),
+    ] {
+        if let Some(w) = indices.windows(2).find(|w| w[0] == w[1]) {
+            bail!("{key}({},{}) is set twice.", w[0].0 + 1, w[0].1 + 1);
+        }
+    }
+
+    let layer_count = component_ids.last().map(|((l, _), _)| l + 1).unwrap_or(0);
+    let mut material_ids = Vec::with_capacity(layer_count);
+    for layer in 0..layer_count {
+        let names = component_ids
+            .iter()
+            .filter(|((l, _), _)| *l == layer)
+            .map(|((_, c), name)| (*c, name.as_str()))
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            bail!("The layer {} has no MATL_ID.", layer + 1);
+        }
+        if let Some((c, _)) = names.iter().enumerate().find(|(i, (c, _))| i != c) {
+            bail!("MATL_ID({},{}) is missing.", layer + 1, c + 1);
+        }
+        let mass_fractions = component_mass_fractions
+            .iter()
+            .filter(|((l, _), _)| *l == layer)
+            .map(|((_, c), f)| (*c, *f))
+            .collect::<Vec<_>>();
+        if let Some((c, _)) = mass_fractions.iter().find(|(c, _)| *c >= names.len()) {
+            bail!(
+                "MATL_MASS_FRACTION({},{}) is set, but MATL_ID({},{}) is missing.",
+                layer + 1,
+                c + 1,
+                layer + 1,
+                c + 1
+            );
+        }
+
+        let material_index = |name: &str| {
+            material_list
+                .find_index(name)
+                .ok_or(anyhow!("Could not find MATL wit ID = \"{name}\""))
+        };
+        if names.len() == 1 && mass_fractions.is_empty() {
+            material_ids.push(material_index(names[0].1)?);
+            continue;
+        }
+        if mass_fractions.len() != names.len() {
+            bail!(
+                "The layer {} has {} components, but {} MATL_MASS_FRACTION values.",
+                layer + 1,
+                names.len(),
+                mass_fractions.len()
+            );
+        }
+        let sum = mass_fractions.iter().map(|(_, f)| f).sum::<f32>();
+        if mass_fractions
+            .iter()
+            .any(|(_, f)| !f.is_finite() || *f < 0.0)
+            || (sum - 1.0).abs() > MASS_FRACTION_TOLERANCE
+        {
+            bail!(
+                "The MATL_MASS_FRACTION values of layer {} should not be negative and sum up to 1, but sum up to {sum}.",
+                layer + 1
+            );
+        }
+        let components = names
+            .iter()
+            .zip(&mass_fractions)
+            .map(|((_, name), (_, f))| std::result::Result::Ok((f / sum, material_index(name)?)))
+            .collect::<Result<Vec<_>>>()?;
+        if let [(_, material_id)] = components.as_slice() {
+            material_ids.push(*material_id);
+            continue;
+        }
+
+        let id = components
+            .iter()
+            .map(|(f, m)| format!("{}*{f}", material_list[*m].0))
+            .collect::<Vec<_>>()
+            .join("+");
+        let material_id = match material_list.find_index(&id) {
+            Some(material_id) => material_id,
+            None => {
+                let material = Material::blend(
+                    &components
+                        .iter()
+                        .map(|(f, m)| (*f, &material_list[*m].1))
+                        .collect::<Vec<_>>(),
+                );
+                material_list.push((id, material));
+                material_list.len() - 1
+            }
+        };
+        material_ids.push(material_id);
+    }
+    Ok(material_ids)
+}
+
+/// Validates that every layer of the surface has a material that resolves inside the [`MaterialList`] and a finite and positive thickness.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the surface has no layers.
+/// - the number of materials and thicknesses differ.
+/// - a material index does not resolve inside the [`MaterialList`].
+/// - a thickness is zero, negative or not finite.
+fn validate_layers(
+    id: &str,
+    material_list: &MaterialList,
+    material_ids: &[usize],
+    thicknesses: &[f32],
+) -> Result<()> {
+    if material_ids.is_empty() {
+        bail!("The surface \"{id}\" has no layers.");
+    }
+    if material_ids.len() != thicknesses.len() {
+        bail!(
+            "The surface \"{id}\" has {} MATL_ID values, but {} THICKNESS values.",
+            material_ids.len(),
+            thicknesses.len()
+        );
+    }
+    for (layer, (&material_id, &thickness)) in material_ids.iter().zip(thicknesses).enumerate() {
+        if material_id >= material_list.len() {
+            bail!(
+                "The material of layer {} of the surface \"{id}\" does not exist.",
+                layer + 1
+            );
+        }
+        if !thickness.is_finite() || thickness <= 0.0 {
+            bail!(
+                "The THICKNESS of layer {} of the surface \"{id}\" is {thickness}, but should be finite and positive.",
+                layer + 1
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Creates all cells for a Surface for all layers with the passed [`Discretization`]. The first and the last cell are duplicated as boundary cells.
+/// For [`Discretization::Geometric`] the cell sizes are determined with the material properties at the initial temperature.
+/// Every layer has at least one cell, therefore the surface has at least three cells.
+///
+/// # Panics
+///
+/// Panics if no layer is passed.
 pub fn cells_from_materials_and_thickness(
     material_list: &MaterialList,
     material_ids: &[usize],
     thicknesses: &[f32],
+    initial_temperature: f32,
+    discretization: Discretization,
 ) -> Vec<SurfaceCell> {
     let mut surface_cells = material_ids
         .iter()
         .zip(thicknesses)
-        .flat_map(|(m, t)| cells_from_material_and_thickness(material_list, *m, *t))
+        .flat_map(|(m, t)| {
+            cells_from_material_and_thickness(
+                material_list,
+                *m,
+                *t,
+                initial_temperature,
+                discretization,
+            )
+        })
         .collect::<Vec<_>>();
     surface_cells.insert(0, surface_cells[0]);
     surface_cells.push(surface_cells[surface_cells.len() - 1]);
     surface_cells
 }
 
-/// Creates all cells for a Surface with smaller cells at the boarder and bigger cells in the middle for a single layer.
+/// Maps the contact resistances between the layers to the interfaces between the cells created by [`cells_from_materials_and_thickness`].
+/// Interfaces inside a layer have no contact resistance.
+fn contact_resistances_from_layers(
+    material_list: &MaterialList,
+    material_ids: &[usize],
+    thicknesses: &[f32],
+    initial_temperature: f32,
+    discretization: Discretization,
+    layer_contact_resistances: &[f32],
+) -> Vec<f32> {
+    let layer_cell_counts = material_ids
+        .iter()
+        .zip(thicknesses)
+        .map(|(m, t)| {
+            cells_from_material_and_thickness(
+                material_list,
+                *m,
+                *t,
+                initial_temperature,
+                discretization,
+            )
+            .len()
+        })
+        .collect::<Vec<_>>();
+
+    // The first cell is the duplicated boundary cell, therefore the interface after the last cell of a layer has the index of the summed cell count.
+    let mut contact_resistances = vec![0.0; layer_cell_counts.iter().sum::<usize>() + 1];
+    let mut index = 0;
+    for (count, contact_resistance) in layer_cell_counts.iter().zip(layer_contact_resistances) {
+        index += count;
+        contact_resistances[index] = *contact_resistance;
+    }
+    contact_resistances
+}
+
+/// Prints a warning if a cell of the surface is smaller than the stability limit `sqrt(α⋅Δt / 0.5)` of an explicit scheme at [`DELTA_TIME`].
+/// Only the cell with the smallest ratio of its size to the limit is reported.
+fn warn_explicit_stability(
+    id: &str,
+    material_list: &MaterialList,
+    cells: &[SurfaceCell],
+    initial_temperature: f32,
+) {
+    let worst = cells
+        .iter()
+        .map(|cell| {
+            let (name, material) = &material_list[cell.material_id as usize];
+            let limit = material.stable_cell_size(initial_temperature, DELTA_TIME)
+                / MAX_EXPLICIT_FOURIER_NUMBER.sqrt();
+            (name, cell.size, limit)
+        })
+        .min_by(|(_, size_a, limit_a), (_, size_b, limit_b)| {
+            (size_a / limit_a).total_cmp(&(size_b / limit_b))
+        });
+    if let Some((name, size, limit)) = worst {
+        if size < limit {
+            println!(
+                "Warning: The smallest cell of \"{name}\" in the surface \"{id}\" is {size} m, but an explicit scheme needs at least {limit} m for a time step of {DELTA_TIME} s."
+            );
+        }
+    }
+}
+
+/// Creates all cells for a Surface with the passed [`Discretization`] for a single layer.
 fn cells_from_material_and_thickness(
     material_list: &MaterialList,
     material_id: usize,
     thickness: f32,
-) -> impl Iterator<Item = SurfaceCell> {
-    const DELTA_TIME: f32 = 1.0;
-    const TEMPERATURE: f32 = 20.0;
-
-    let material = &material_list[material_id].1;
-    let specific_heat = material.specific_heat.calc(TEMPERATURE);
-    let conductivity = material.conductivity.calc(TEMPERATURE);
-    let density = material.density;
+    initial_temperature: f32,
+    discretization: Discretization,
+) -> Vec<SurfaceCell> {
+    if let Discretization::Uniform { n } = discretization {
+        // Every layer has at least one cell.
+        let n = n.max(1);
+        return vec![
+            SurfaceCell {
+                material_id: material_id as u32,
+                size: thickness / n as f32,
+            };
+            n
+        ];
+    }
 
-    let size = f32::sqrt((conductivity * DELTA_TIME) / (density * specific_heat));
+    let size = material_list[material_id]
+        .1
+        .stable_cell_size(initial_temperature, DELTA_TIME);
 
     let (cell_count, start_size) = get_cell_count_and_start_size(size, thickness);
     (0..cell_count)
-        .map(move |i| start_size * 2.0f32.powi(usize::min(i, cell_count - i - 1) as i32))
-        .map(move |size| SurfaceCell {
+        .map(|i| start_size * 2.0f32.powi(usize::min(i, cell_count - i - 1) as i32))
+        .map(|size| SurfaceCell {
             material_id: material_id as u32,
             size,
         })
+        .collect()
 }
 
 /// Calculate the amount of cells in a single layer of a surface.
@@ -203,3 +664,132 @@ fn get_cell_count_and_start_size(size: f32, thickness: f32) -> (usize, f32) {
     }
     (MAX_CELLS, thickness / s)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fds::ramp::RampList;
+
+    /// Returns the [`Property`]s of the passed key value pairs.
+    fn properties(pairs: &[(&str, &str)]) -> Vec<Property> {
+        pairs
+            .iter()
+            .map(|(key, value)| Property {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+            .collect()
+    }
+
+    /// Returns a [`MaterialList`] with `STEEL` at index 0 and `CONCRETE` at index 1.
+    fn material_list() -> MaterialList {
+        let mut material_list = MaterialList::default();
+        for material in [
+            [
+                ("ID", "STEEL"),
+                ("SPECIFIC_HEAT", "0.46"),
+                ("CONDUCTIVITY", "45.8"),
+                ("DENSITY", "7850.0"),
+                ("EMISSIVITY", "0.9"),
+            ],
+            [
+                ("ID", "CONCRETE"),
+                ("SPECIFIC_HEAT", "1.04"),
+                ("CONDUCTIVITY", "1.8"),
+                ("DENSITY", "2280.0"),
+                ("EMISSIVITY", "0.9"),
+            ],
+        ] {
+            material_list
+                .try_add_from_properties(properties(&material), &RampList::default(), true)
+                .unwrap();
+        }
+        material_list
+    }
+
+    #[test]
+    fn uniform_surface_has_equal_cells_and_boundary_cells() {
+        let (id, surface) = try_surface_from_properties(
+            properties(&[
+                ("ID", "WALL"),
+                ("MATL_ID", "STEEL"),
+                ("THICKNESS", "0.01"),
+                ("CELL_COUNT", "10"),
+            ]),
+            &mut material_list(),
+        )
+        .unwrap();
+        assert_eq!(id, "WALL");
+        assert_eq!(surface.len(), 10 + 2);
+        assert!(surface
+            .iter()
+            .all(|c| c.size == 0.01 / 10.0 && c.material_id == 0));
+    }
+
+    #[test]
+    fn concrete_over_steel_spans_both_layers() {
+        let (_, surface) = try_surface_from_properties(
+            properties(&[
+                ("ID", "WALL"),
+                ("MATL_ID", "CONCRETE,STEEL"),
+                ("THICKNESS", "0.05,0.01"),
+            ]),
+            &mut material_list(),
+        )
+        .unwrap();
+        let material_ids = surface.iter().map(|c| c.material_id).collect::<Vec<_>>();
+        let mut expected = vec![1; 10 + 1];
+        expected.extend([0; 3 + 1]);
+        assert_eq!(material_ids, expected);
+
+        let len = surface.len();
+        assert_eq!(surface[0].size, surface[1].size);
+        assert_eq!(surface[len - 1].size, surface[len - 2].size);
+        let concrete = surface[1..11].iter().map(|c| c.size).sum::<f32>();
+        let steel = surface[11..len - 1].iter().map(|c| c.size).sum::<f32>();
+        assert!((concrete - 0.05).abs() < 1e-6, "{concrete}");
+        assert!((steel - 0.01).abs() < 1e-6, "{steel}");
+    }
+
+    #[test]
+    fn ht3d_surface_with_multiple_materials_fails() {
+        let err = try_surface_from_properties(
+            properties(&[
+                ("ID", "WALL"),
+                ("MATL_ID", "CONCRETE,STEEL"),
+                ("THICKNESS", "0.05,0.01"),
+                ("HT3D", ".TRUE."),
+            ]),
+            &mut material_list(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The HT3D surface \"WALL\" has 2 MATL_ID values, but only a single material is supported."
+        );
+    }
+
+    #[test]
+    fn zero_thickness_fails() {
+        let err = try_surface_from_properties(
+            properties(&[("ID", "WALL"), ("MATL_ID", "STEEL"), ("THICKNESS", "0.0")]),
+            &mut material_list(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The THICKNESS of layer 1 of the surface \"WALL\" is 0, but should be finite and positive."
+        );
+    }
+
+    #[test]
+    fn thin_surface_has_one_cell_and_boundary_cells() {
+        let (_, surface) = try_surface_from_properties(
+            properties(&[("ID", "WALL"), ("MATL_ID", "STEEL"), ("THICKNESS", "1E-6")]),
+            &mut material_list(),
+        )
+        .unwrap();
+        assert_eq!(surface.len(), 3);
+        assert!(surface.iter().all(|c| c.size == 1e-6));
+    }
+}
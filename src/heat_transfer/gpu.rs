@@ -1,32 +1,139 @@
-use std::{borrow::Cow, path::Path};
+use std::{
+    borrow::Cow,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use anyhow::*;
+use clap::ValueEnum;
+use futures::executor::block_on;
 use wgpu::{
-    Backends, Device, DeviceDescriptor, Features, InstanceDescriptor, Limits, Queue,
-    RequestAdapterOptions, ShaderModule,
+    AdapterInfo, Backends, Device, DeviceDescriptor, Features, InstanceDescriptor, Limits,
+    PowerPreference, Queue, RequestAdapterOptions, ShaderModule,
 };
 
-/// Get the [`Device`] and [`Queue`] of the GPU.
+/// The default backends used to find a GPU. This value is replaced at the start of the program.
+static BACKENDS: AtomicU32 = AtomicU32::new(Backends::VULKAN.bits() | Backends::DX12.bits());
+
+/// All GPU backends that can be selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GpuBackend {
+    Vulkan,
+    Dx12,
+    Metal,
+    Gl,
+    All,
+}
+impl GpuBackend {
+    /// Returns the [`Backends`] of this [`GpuBackend`].
+    pub fn backends(&self) -> Backends {
+        match self {
+            GpuBackend::Vulkan => Backends::VULKAN,
+            GpuBackend::Dx12 => Backends::DX12,
+            GpuBackend::Metal => Backends::METAL,
+            GpuBackend::Gl => Backends::GL,
+            GpuBackend::All => Backends::all(),
+        }
+    }
+}
+
+/// Set the backends used to find a GPU.
+pub fn set_gpu_backends(backends: Backends) {
+    BACKENDS.store(backends.bits(), Ordering::Relaxed);
+    println!("Set GPU backends to {backends:?}")
+}
+
+/// Get the backends used to find a GPU.
+#[inline]
+pub fn get_gpu_backends() -> Backends {
+    Backends::from_bits_truncate(BACKENDS.load(Ordering::Relaxed))
+}
+
+/// The default power preference used to find a GPU is high performance. This value is replaced at the start of the program.
+static GPU_POWER_LOW: AtomicBool = AtomicBool::new(false);
+
+/// All GPU power preferences that can be selected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GpuPower {
+    High,
+    Low,
+}
+impl GpuPower {
+    /// Returns the [`PowerPreference`] of this [`GpuPower`].
+    pub fn power_preference(&self) -> PowerPreference {
+        match self {
+            GpuPower::High => PowerPreference::HighPerformance,
+            GpuPower::Low => PowerPreference::LowPower,
+        }
+    }
+}
+
+/// Set the power preference used to find a GPU.
+/// The low power preference allows to benchmark an integrated GPU on machines that also have a discrete one.
+pub fn set_gpu_power(gpu_power: GpuPower) {
+    GPU_POWER_LOW.store(gpu_power == GpuPower::Low, Ordering::Relaxed);
+    println!("Set GPU power preference to {gpu_power:?}")
+}
+
+/// Get the power preference used to find a GPU.
+#[inline]
+pub fn get_gpu_power() -> GpuPower {
+    if GPU_POWER_LOW.load(Ordering::Relaxed) {
+        GpuPower::Low
+    } else {
+        GpuPower::High
+    }
+}
+
+/// A [`Device`] and [`Queue`] of the GPU together with the information about the adapter they belong to.
+pub struct GpuDevice {
+    pub device: Device,
+    pub queue: Queue,
+    /// The information about the adapter, so it can be recorded which GPU was used.
+    pub adapter_info: AdapterInfo,
+}
+
+/// Get the [`Device`] and [`Queue`] of the GPU and print the name, backend and driver of the selected adapter.
+/// If no adapter matches the selected power preference, the default power preference is used instead.
 ///
 /// # Errors
 ///
-/// This function will return an error if either the [`Device`] ore the [`Queue`] can not be obtained.
-pub async fn get_gpu_device_and_queue() -> Result<(Device, Queue)> {
+/// This function will return an error if
+/// - no adapter is found for the selected backends.
+/// - either the [`Device`] ore the [`Queue`] can not be obtained.
+pub async fn get_gpu_device_and_queue() -> Result<GpuDevice> {
+    let backends = get_gpu_backends();
     // Instantiates instance of WebGPU
     let instance = wgpu::Instance::new(InstanceDescriptor {
-        backends: Backends::VULKAN | Backends::DX12,
+        backends,
         ..Default::default()
     });
 
-    // `request_adapter` instantiates the general connection to the GPU in high Performance mode
-    let Some(adapter) = instance
+    // `request_adapter` instantiates the general connection to the GPU with the selected power preference
+    let gpu_power = get_gpu_power();
+    let adapter = match instance
         .request_adapter(&RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
+            power_preference: gpu_power.power_preference(),
             ..Default::default()
         })
         .await
-    else {
-        bail!("No adapter found.");
+    {
+        Some(adapter) => adapter,
+        None => {
+            println!(
+                "Warning: No adapter found for the power preference {gpu_power:?}, falling back to the default power preference."
+            );
+            let Some(adapter) = instance
+                .request_adapter(&RequestAdapterOptions::default())
+                .await
+            else {
+                bail!("No adapter found for the backends {backends:?}.");
+            };
+            adapter
+        }
     };
 
     let (
@@ -46,9 +153,78 @@ pub async fn get_gpu_device_and_queue() -> Result<(Device, Queue)> {
         .await
         .with_context(|| "No device found.")?;
 
+    let gpu_device = GpuDevice {
+        device,
+        queue,
+        adapter_info: adapter.get_info(),
+    };
+    let AdapterInfo {
+        name,
+        backend,
+        driver,
+        driver_info,
+        ..
+    } = &gpu_device.adapter_info;
+    println!("Use GPU {name} with backend {backend:?} and driver {driver} {driver_info}");
+
+    Ok(gpu_device)
+}
+
+/// A [`Device`] and [`Queue`] together with the backends and power preference they were requested for and the information about their adapter.
+type SharedDeviceAndQueue = (Backends, GpuPower, Arc<Device>, Arc<Queue>, AdapterInfo);
+
+/// The [`Device`] and [`Queue`] shared by all GPU methods.
+static SHARED_DEVICE_AND_QUEUE: Mutex<Option<SharedDeviceAndQueue>> = Mutex::new(None);
+
+/// Get the [`Device`] and [`Queue`] of the GPU that are shared by all GPU methods.
+/// They are requested on the first call and reused afterwards. If the selected backends or power preference changed since, they are requested again.
+/// Only the device and the queue are shared, buffers and pipelines are still created by every setup.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the shared device is poisoned.
+/// - no adapter is found for the selected backends.
+/// - either the [`Device`] ore the [`Queue`] can not be obtained.
+pub fn get_shared_gpu_device_and_queue() -> Result<(Arc<Device>, Arc<Queue>)> {
+    let backends = get_gpu_backends();
+    let gpu_power = get_gpu_power();
+    let mut shared = SHARED_DEVICE_AND_QUEUE
+        .lock()
+        .map_err(|_| anyhow!("The shared device and queue are poisoned."))?;
+
+    if let Some((shared_backends, shared_gpu_power, device, queue, _)) = shared.as_ref() {
+        if *shared_backends == backends && *shared_gpu_power == gpu_power {
+            return Ok((device.clone(), queue.clone()));
+        }
+    }
+
+    let GpuDevice {
+        device,
+        queue,
+        adapter_info,
+    } = block_on(get_gpu_device_and_queue())?;
+    let (device, queue) = (Arc::new(device), Arc::new(queue));
+    *shared = Some((
+        backends,
+        gpu_power,
+        device.clone(),
+        queue.clone(),
+        adapter_info,
+    ));
     Ok((device, queue))
 }
 
+/// Get the [`AdapterInfo`] of the shared [`Device`].
+/// Returns [`None`] if no shared device was requested so far or the shared device is poisoned.
+pub fn get_shared_gpu_adapter_info() -> Option<AdapterInfo> {
+    SHARED_DEVICE_AND_QUEUE
+        .lock()
+        .ok()?
+        .as_ref()
+        .map(|(_, _, _, _, adapter_info)| adapter_info.clone())
+}
+
 /// Loads a shader from a file.
 ///
 /// # Errors
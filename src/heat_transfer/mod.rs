@@ -2,3 +2,4 @@ pub mod gpu;
 pub mod one_dimensional;
 pub mod shader;
 pub mod simulations;
+pub mod two_dimensional;
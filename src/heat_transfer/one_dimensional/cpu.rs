@@ -1,11 +1,17 @@
+use std::fmt::Debug;
+
+use num_traits::Float;
 use rayon::prelude::*;
 
-use super::{HeatTransfer1D, WallElement};
+use super::{
+    check_buffer_lengths, check_cell_counts, get_solver_config, HeatTransfer1D, SolverConfig,
+    SubdivisionCounter, SubdivisionDiagnostics, WallElement, MIN_CELL_COUNT,
+};
 use crate::fds::Material;
 
-/// The maximum temperature difference that may be between neighboring cells before the time step is reduced.
+/// The default maximum temperature difference that may be between neighboring cells before the time step is reduced.
 pub const MAX_DELTA_TEMPERATURE: f32 = 10.0;
-/// The maximum number of times the time step may be reduced.
+/// The default maximum number of times the time step may be reduced.
 pub const MAX_TIME_SUBDIVISIONS: usize = 4;
 ///  Stefan Boltzmann constant
 pub const SIGMA: f32 = 0.0000000567;
@@ -15,20 +21,67 @@ pub const ADIABATIC_H: f32 = -100000.0;
 /// Value for `h_f` and / or `h_b` to indicate that the wall side has a constant temperature. Note `q_f` and / or `q_b` has to be set to the constant temperature.
 pub const CONST_TEMP_H: f32 = -100001.0;
 
-/// All relevant data for the heat transfer algorithm on the CPU.
-pub struct CPUSetupData {
+/// The floating point type the CPU solver calculates with, see [`Precision`](super::Precision).
+pub trait SolverFloat: Float + Debug + Send + Sync {
+    /// Converts a single precision value, like the boundary conditions and the material properties.
+    fn from_single(value: f32) -> Self;
+    /// Converts the value to single precision, like the surface temperatures written to the output buffer.
+    fn to_single(self) -> f32;
+}
+impl SolverFloat for f32 {
+    #[inline]
+    fn from_single(value: f32) -> Self {
+        value
+    }
+    #[inline]
+    fn to_single(self) -> f32 {
+        self
+    }
+}
+impl SolverFloat for f64 {
+    #[inline]
+    fn from_single(value: f32) -> Self {
+        value as f64
+    }
+    #[inline]
+    fn to_single(self) -> f32 {
+        self as f32
+    }
+}
+
+/// All relevant data for the heat transfer algorithm on the CPU, calculated with the precision of `F`.
+/// The values are only converted to single precision when the surface temperatures are written to the output buffer.
+pub struct CPUSolver<F: SolverFloat> {
     materials: Vec<Material>,
-    wall_elements: Vec<WallElement>,
+    wall_elements: Vec<WallElement<F>>,
+    solver_config: SolverConfig,
+    subdivision_counter: Option<SubdivisionCounter>,
 }
+/// The heat transfer algorithm on the CPU with single precision.
+pub type CPUSetupData = CPUSolver<f32>;
+/// The heat transfer algorithm on the CPU with double precision.
+pub type CPUF64SetupData = CPUSolver<f64>;
 
-impl HeatTransfer1D for CPUSetupData {
+impl<F: SolverFloat> HeatTransfer1D for CPUSolver<F> {
     fn setup(materials: Vec<Material>, wall_elements: Vec<WallElement>) -> anyhow::Result<Self> {
+        check_cell_counts(&wall_elements)?;
         Ok(Self {
             materials,
-            wall_elements,
+            wall_elements: wall_elements.into_iter().map(WallElement::cast).collect(),
+            solver_config: get_solver_config(),
+            subdivision_counter: SubdivisionCounter::new_if_enabled(),
         })
     }
 
+    fn reset(&mut self, wall_elements: Vec<WallElement>) -> anyhow::Result<()> {
+        check_cell_counts(&wall_elements)?;
+        self.wall_elements = wall_elements.into_iter().map(WallElement::cast).collect();
+        if let Some(subdivision_counter) = &self.subdivision_counter {
+            subdivision_counter.reset();
+        }
+        Ok(())
+    }
+
     fn update(
         &mut self,
         delta_time: f32,
@@ -36,7 +89,15 @@ impl HeatTransfer1D for CPUSetupData {
         wall_q_in: &[[f32; 2]],
         wall_temperature: &mut [[f32; 2]],
     ) -> anyhow::Result<()> {
+        check_buffer_lengths(
+            self.wall_elements.len(),
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
         let materials = &self.materials;
+        let solver_config = &self.solver_config;
+        let subdivision_counter = self.subdivision_counter.as_ref();
 
         let mut_iter = self
             .wall_elements
@@ -46,50 +107,330 @@ impl HeatTransfer1D for CPUSetupData {
             .par_iter()
             .zip_eq(wall_q_in.par_iter());
 
-        mut_iter.zip_eq(iter).for_each(
+        mut_iter.zip_eq(iter).try_for_each(
             |((wall_element, wall_temperature), (wall_heat_transfer_coefficient, wall_q_in))| {
-                heat_transfer(
-                    wall_element,
-                    materials,
-                    *wall_heat_transfer_coefficient,
-                    *wall_q_in,
-                    delta_time,
-                );
-                let len = wall_element.len();
-                wall_temperature[0] =
-                    (wall_element[0].temperature + wall_element[1].temperature) / 2.0;
-                wall_temperature[1] =
-                    (wall_element[len - 1].temperature + wall_element[len - 2].temperature) / 2.0;
+                let step_config = StepConfig {
+                    wall_heat_transfer_coefficient: *wall_heat_transfer_coefficient,
+                    wall_q_in: *wall_q_in,
+                    heat_generation: &[],
+                    solver_config,
+                    subdivision_counter,
+                };
+                *wall_temperature =
+                    step(wall_element, materials, &step_config, delta_time)?.map(F::to_single);
+                Ok(())
+            },
+        )
+    }
+
+    fn update_with_heat_generation(
+        &mut self,
+        delta_time: f32,
+        wall_heat_transfer_coefficients: &[[f32; 2]],
+        wall_q_in: &[[f32; 2]],
+        heat_generation: &[f32],
+        wall_temperature: &mut [[f32; 2]],
+    ) -> anyhow::Result<()> {
+        check_buffer_lengths(
+            self.wall_elements.len(),
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
+        let cell_count = self.wall_elements.iter().map(|w| w.len()).sum::<usize>();
+        if heat_generation.len() != cell_count {
+            anyhow::bail!(
+                "The heat generation has {} values, but there are {cell_count} cells.",
+                heat_generation.len()
+            );
+        }
+        let mut heat_generation = heat_generation;
+        let wall_heat_generation = self
+            .wall_elements
+            .iter()
+            .map(|w| {
+                let (head, tail) = heat_generation.split_at(w.len());
+                heat_generation = tail;
+                head
+            })
+            .collect::<Vec<_>>();
+
+        let materials = &self.materials;
+        let solver_config = &self.solver_config;
+        let subdivision_counter = self.subdivision_counter.as_ref();
+
+        let mut_iter = self
+            .wall_elements
+            .par_iter_mut()
+            .zip_eq(wall_temperature.par_iter_mut());
+        let iter = wall_heat_transfer_coefficients
+            .par_iter()
+            .zip_eq(wall_q_in.par_iter())
+            .zip_eq(wall_heat_generation.par_iter());
+
+        mut_iter.zip_eq(iter).try_for_each(
+            |(
+                (wall_element, wall_temperature),
+                ((wall_heat_transfer_coefficient, wall_q_in), heat_generation),
+            )| {
+                let step_config = StepConfig {
+                    wall_heat_transfer_coefficient: *wall_heat_transfer_coefficient,
+                    wall_q_in: *wall_q_in,
+                    heat_generation,
+                    solver_config,
+                    subdivision_counter,
+                };
+                *wall_temperature =
+                    step(wall_element, materials, &step_config, delta_time)?.map(F::to_single);
+                Ok(())
             },
+        )
+    }
+
+    fn subdivision_diagnostics(&self) -> Option<SubdivisionDiagnostics> {
+        self.subdivision_counter
+            .as_ref()
+            .map(SubdivisionCounter::diagnostics)
+    }
+
+    fn profile(&self) -> anyhow::Result<Vec<Vec<f32>>> {
+        Ok(self
+            .wall_elements
+            .iter()
+            .map(|w| w.iter().map(|c| c.temperature.to_single()).collect())
+            .collect())
+    }
+}
+
+impl CPUSetupData {
+    /// Simulates a single time step like [`HeatTransfer1D::update`] and returns the [`EnergyBalance`] of every wall element for this time step.
+    /// The energy entering through the surfaces is integrated over each subdivision of the time step with the mean of the heat fluxes before and after it.
+    /// The wall elements are calculated one after another, therefore this is only intended for debugging.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the buffers do not contain a value for each wall element, see [`check_buffer_lengths`].
+    pub fn update_with_energy_balance(
+        &mut self,
+        delta_time: f32,
+        wall_heat_transfer_coefficients: &[[f32; 2]],
+        wall_q_in: &[[f32; 2]],
+        wall_temperature: &mut [[f32; 2]],
+    ) -> anyhow::Result<Vec<EnergyBalance>> {
+        check_buffer_lengths(
+            self.wall_elements.len(),
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
+        let materials = &self.materials;
+        let solver_config = &self.solver_config;
+        let subdivision_counter = self.subdivision_counter.as_ref();
+        // The lengths are checked, so zipping the buffers does not drop a wall element.
+        self.wall_elements
+            .iter_mut()
+            .zip(wall_temperature.iter_mut())
+            .zip(wall_heat_transfer_coefficients.iter().zip(wall_q_in))
+            .map(
+                |(
+                    (wall_element, wall_temperature),
+                    (wall_heat_transfer_coefficient, wall_q_in),
+                )| {
+                    let stored_energy_before = stored_energy(wall_element, materials);
+                    let step_config = StepConfig {
+                        wall_heat_transfer_coefficient: *wall_heat_transfer_coefficient,
+                        wall_q_in: *wall_q_in,
+                        heat_generation: &[],
+                        solver_config,
+                        subdivision_counter,
+                    };
+                    let mut flux_before = boundary_heat_flux(wall_element, materials);
+                    let mut boundary_energy = 0.0;
+                    *wall_temperature = step_inspected(
+                        wall_element,
+                        materials,
+                        &step_config,
+                        delta_time,
+                        |wall_element, delta_time| {
+                            let flux_after = boundary_heat_flux(wall_element, materials);
+                            boundary_energy += delta_time as f64
+                                * (flux_before.iter().sum::<f64>()
+                                    + flux_after.iter().sum::<f64>())
+                                / 2.0;
+                            flux_before = flux_after;
+                        },
+                    )?;
+                    let stored_energy = stored_energy(wall_element, materials);
+                    Ok(EnergyBalance {
+                        stored_energy,
+                        delta_stored_energy: stored_energy - stored_energy_before,
+                        boundary_energy,
+                    })
+                },
+            )
+            .collect()
+    }
+}
+
+/// The energy balance of a single [`WallElement`] over a time step. All energies are per wall area in J/m² and summed up with double precision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyBalance {
+    /// The energy `Σ rho * c * T * dx` stored inside the wall element after the time step.
+    pub stored_energy: f64,
+    /// The change of the stored energy during the time step.
+    pub delta_stored_energy: f64,
+    /// The energy that entered the wall element through the front and the back surface during the time step.
+    pub boundary_energy: f64,
+}
+impl EnergyBalance {
+    /// The part of the change of the stored energy that is not explained by the energy entering through the surfaces.
+    /// It should be close to 0 for materials with a constant specific heat. With a specific heat ramp `Σ rho * c * T * dx` is not the enthalpy, therefore an imbalance remains.
+    pub fn imbalance(&self) -> f64 {
+        self.delta_stored_energy - self.boundary_energy
+    }
+}
+
+/// Calculation of the energy `Σ rho * c * T * dx` in J/m² stored inside the inner cells of a [`WallElement`].
+#[inline]
+pub fn stored_energy(wall_element: &WallElement, materials: &[Material]) -> f64 {
+    let len = wall_element.len();
+    if len < MIN_CELL_COUNT {
+        return 0.0;
+    }
+    wall_element[1..(len - 1)]
+        .iter()
+        .map(|cell| {
+            let material = &materials[cell.material as usize];
+            material.density as f64
+                * material.specific_heat.calc(cell.temperature) as f64
+                * cell.temperature as f64
+                * cell.size as f64
+        })
+        .sum()
+}
+
+/// Calculation of the heat flux in W/m² that enters a [`WallElement`] through the front and the back surface, derived from the boundary cells.
+#[inline]
+pub fn boundary_heat_flux(wall_element: &WallElement, materials: &[Material]) -> [f64; 2] {
+    let len = wall_element.len();
+    if len < MIN_CELL_COUNT {
+        return [0.0, 0.0];
+    }
+    let flux = |boundary: usize, inner: usize, interface: usize| {
+        let boundary_cell = &wall_element[boundary];
+        let inner_cell = &wall_element[inner];
+        let k = with_contact_resistance(
+            (materials[boundary_cell.material as usize]
+                .conductivity
+                .calc(boundary_cell.temperature)
+                + materials[inner_cell.material as usize]
+                    .conductivity
+                    .calc(inner_cell.temperature))
+                / 2.0,
+            boundary_cell.size,
+            inner_cell.size,
+            wall_element.contact_resistance(interface),
         );
-        Ok(())
+        k as f64 * (boundary_cell.temperature - inner_cell.temperature) as f64
+            / ((boundary_cell.size + inner_cell.size) as f64 / 2.0)
+    };
+    [flux(0, 1, 0), flux(len - 1, len - 2, len - 2)]
+}
+
+/// The boundary conditions and the configuration of a single time step of a [`WallElement`], see [`step`].
+#[derive(Debug, Clone, Copy)]
+pub struct StepConfig<'a> {
+    /// The heat transfer coefficients of the front and the back surface, or [`ADIABATIC_H`] and [`CONST_TEMP_H`].
+    pub wall_heat_transfer_coefficient: [f32; 2],
+    /// The energy insertions of the front and the back surface.
+    pub wall_q_in: [f32; 2],
+    /// The volumetric heat generation in W/m³ for every cell. If it is empty, no heat is generated.
+    pub heat_generation: &'a [f32],
+    pub solver_config: &'a SolverConfig,
+    /// Counts the time step, if set.
+    pub subdivision_counter: Option<&'a SubdivisionCounter>,
+}
+
+/// Simulates a single time step of a single [`WallElement`] and returns the temperatures of the front and the back surface.
+///
+/// # Errors
+///
+/// This function will return an error if the [`WallElement`] has less than [`MIN_CELL_COUNT`] cells.
+#[inline]
+pub fn step<F: SolverFloat>(
+    wall_element: &mut WallElement<F>,
+    materials: &[Material],
+    step_config: &StepConfig,
+    delta_time: f32,
+) -> anyhow::Result<[F; 2]> {
+    step_inspected(wall_element, materials, step_config, delta_time, |_, _| {})
+}
+
+/// Simulates a single time step like [`step`] and calls `inspect` with the [`WallElement`] and the time of each subdivision after it was solved.
+#[inline]
+fn step_inspected<F: SolverFloat>(
+    wall_element: &mut WallElement<F>,
+    materials: &[Material],
+    step_config: &StepConfig,
+    delta_time: f32,
+    inspect: impl FnMut(&WallElement<F>, F),
+) -> anyhow::Result<[F; 2]> {
+    let len = wall_element.len();
+    if len < MIN_CELL_COUNT {
+        anyhow::bail!(
+            "The wall element has {len} cells, but at least {MIN_CELL_COUNT} are needed."
+        );
+    }
+    let capped = heat_transfer_inspected(
+        wall_element,
+        materials,
+        step_config.wall_heat_transfer_coefficient,
+        step_config.wall_q_in,
+        step_config.heat_generation,
+        step_config.solver_config,
+        delta_time,
+        inspect,
+    );
+    if let Some(subdivision_counter) = step_config.subdivision_counter {
+        subdivision_counter.record(capped);
     }
+    let two = F::from_single(2.0);
+    Ok([
+        (wall_element[0].temperature + wall_element[1].temperature) / two,
+        (wall_element[len - 1].temperature + wall_element[len - 2].temperature) / two,
+    ])
 }
 
 /// Calculation of the highest temperature between two neighboring cells.
 #[inline]
-pub fn max_delta_temperature(
-    wall_element: &WallElement,
+pub fn max_delta_temperature<F: SolverFloat>(
+    wall_element: &WallElement<F>,
     materials: &[Material],
-    delta_time: f32,
-) -> f32 {
+    delta_time: F,
+) -> F {
+    let two = F::from_single(2.0);
     let len = wall_element.len();
-    let mut delta_temperature = 0.0;
+    let mut delta_temperature = F::zero();
 
     let material_b = &materials[wall_element[0].material as usize];
     let t_b = wall_element[0].temperature;
     let x_b = wall_element[0].size;
-    let k_b = material_b.conductivity.calc(t_b);
+    let k_b = material_b.conductivity.calc_as(t_b);
 
     let material_c = &materials[wall_element[1].material as usize];
     let t_c = wall_element[1].temperature;
     let mut x_c = wall_element[1].size;
-    let k_c = material_c.conductivity.calc(t_c);
-    let c_c = material_c.specific_heat.calc(t_c);
-    let rho_c = material_c.density;
+    let k_c = material_c.conductivity.calc_as(t_c);
+    let c_c = material_c.specific_heat.calc_as(t_c);
+    let rho_c = F::from_single(material_c.density);
 
-    let k_m_b = (k_c + k_b) / 2.0;
-    let mut before = k_m_b * (t_c - t_b) / ((x_c + x_b) / 2.0);
+    let k_m_b = with_contact_resistance(
+        (k_c + k_b) / two,
+        x_c,
+        x_b,
+        wall_element.contact_resistance(0),
+    );
+    let mut before = k_m_b * (t_c - t_b) / ((x_c + x_b) / two);
 
     let mut f1 = delta_time * (rho_c * c_c);
 
@@ -97,13 +438,18 @@ pub fn max_delta_temperature(
         let material_a = &materials[wall_element[i + 1].material as usize];
         let t_a = wall_element[i + 1].temperature;
         let x_a = wall_element[i + 1].size;
-        let k_a = material_a.conductivity.calc(t_a);
-        let c_a = material_a.specific_heat.calc(t_a);
-        let rho_a = material_a.density;
+        let k_a = material_a.conductivity.calc_as(t_a);
+        let c_a = material_a.specific_heat.calc_as(t_a);
+        let rho_a = F::from_single(material_a.density);
 
-        let k_m_a = (k_c + k_a) / 2.0;
+        let k_m_a = with_contact_resistance(
+            (k_c + k_a) / two,
+            x_c,
+            x_a,
+            wall_element.contact_resistance(i),
+        );
 
-        let after = k_m_a * (t_a - t_c) / ((x_a + x_c) / 2.0);
+        let after = k_m_a * (t_a - t_c) / ((x_a + x_c) / two);
 
         delta_temperature = ((f1 * (after - before) / x_c).abs()).max(delta_temperature);
 
@@ -115,44 +461,81 @@ pub fn max_delta_temperature(
     delta_temperature
 }
 
+/// Reduces the mean conductivity between two neighboring cells by the thermal contact resistance of their interface.
+/// Without a contact resistance the conductivity is returned unchanged.
+#[inline]
+pub fn with_contact_resistance<F: SolverFloat>(
+    conductivity: F,
+    dx_a: F,
+    dx_b: F,
+    contact_resistance: F,
+) -> F {
+    if contact_resistance.is_zero() {
+        return conductivity;
+    }
+    conductivity
+        / (F::one() + contact_resistance * conductivity / ((dx_a + dx_b) / F::from_single(2.0)))
+}
+
 /// Calculations of repetitions / divisions due to large temperature difference between two cells.
 #[inline]
-pub fn repeats(max_delta_temperature: f32) -> usize {
-    if max_delta_temperature < MAX_DELTA_TEMPERATURE {
+pub fn repeats<F: SolverFloat>(max_delta_temperature: F, solver_config: &SolverConfig) -> usize {
+    required_repeats(max_delta_temperature, solver_config)
+        .clamp(1, solver_config.max_time_subdivisions)
+}
+
+/// Calculations of repetitions / divisions due to large temperature difference between two cells without the limit of [`SolverConfig::max_time_subdivisions`].
+#[inline]
+pub fn required_repeats<F: SolverFloat>(
+    max_delta_temperature: F,
+    solver_config: &SolverConfig,
+) -> usize {
+    let limit = F::from_single(solver_config.max_delta_temperature);
+    if max_delta_temperature < limit {
         return 1;
     }
-    let eta = max_delta_temperature / MAX_DELTA_TEMPERATURE;
-    2_usize
-        .pow((eta.ln() / 2.0f32.ln()).ceil() as u32)
-        .clamp(1, MAX_TIME_SUBDIVISIONS)
+    let eta = max_delta_temperature / limit;
+    2_usize.saturating_pow((eta.ln() / F::from_single(2.0).ln()).ceil().to_single() as u32)
 }
 
 /// Calculation of the gas interaction variables.
 #[inline]
-pub fn calc_rfac2_and_qdxk_no_radiation(
-    wall_element: &WallElement,
+pub fn calc_rfac2_and_qdxk_no_radiation<F: SolverFloat>(
+    wall_element: &WallElement<F>,
     materials: &[Material],
     wall_heat_transfer_coefficient: [f32; 2],
     wall_q_in: [f32; 2],
-) -> [f32; 4] {
+    sigma: f32,
+    radiation: bool,
+) -> [F; 4] {
+    let (two, three) = (F::from_single(2.0), F::from_single(3.0));
+    let sigma = F::from_single(sigma);
+    let convective = wall_element.convective();
     let h_f = wall_heat_transfer_coefficient[0];
     let (rfac2_f, qdxk_f) = if h_f == ADIABATIC_H {
-        (1.0, 0.0)
+        (F::one(), F::zero())
     } else if h_f == CONST_TEMP_H {
-        let q2_f = wall_q_in[0];
-        (-1.0, 2.0 * q2_f)
+        let q2_f = F::from_single(wall_q_in[0]);
+        (-F::one(), two * q2_f)
     } else {
-        let q2_f = wall_q_in[0];
+        let q2_f = F::from_single(wall_q_in[0]);
 
-        let temperature_f = (wall_element[0].temperature + wall_element[1].temperature) / 2.0;
+        let temperature_f = (wall_element[0].temperature + wall_element[1].temperature) / two;
         let material_f = &materials[wall_element[0].material as usize];
+        let emissivity_f = F::from_single(material_f.emissivity);
         let dx_f = wall_element[0].size;
 
-        let emission_rfac_f = 2.0 * material_f.emissivity * SIGMA * temperature_f.powf(3.0);
-        let emission_qdxk_f = 3.0 * material_f.emissivity * SIGMA * temperature_f.powf(4.0);
+        let (emission_rfac_f, emission_qdxk_f) = if !radiation || convective[0] {
+            (F::zero(), F::zero())
+        } else {
+            (
+                two * emissivity_f * sigma * temperature_f.powf(three),
+                three * emissivity_f * sigma * temperature_f.powf(F::from_single(4.0)),
+            )
+        };
 
-        let rfac_f = 0.5 * h_f + emission_rfac_f;
-        let k_f = material_f.conductivity.calc(temperature_f);
+        let rfac_f = F::from_single(0.5 * h_f) + emission_rfac_f;
+        let k_f = material_f.conductivity.calc_as(temperature_f);
         let rfac2_f = (k_f / dx_f - rfac_f) / (k_f / dx_f + rfac_f);
         let qdxk_f = (q2_f + emission_qdxk_f) / (k_f / dx_f + rfac_f);
         (rfac2_f, qdxk_f)
@@ -160,24 +543,31 @@ pub fn calc_rfac2_and_qdxk_no_radiation(
 
     let h_b = wall_heat_transfer_coefficient[1];
     let (rfac2_b, qdxk_b) = if h_b == ADIABATIC_H {
-        (1.0, 0.0)
+        (F::one(), F::zero())
     } else if h_b == CONST_TEMP_H {
-        let q2_b = wall_q_in[1];
-        (-1.0, 2.0 * q2_b)
+        let q2_b = F::from_single(wall_q_in[1]);
+        (-F::one(), two * q2_b)
     } else {
-        let q2_b = wall_q_in[1];
+        let q2_b = F::from_single(wall_q_in[1]);
 
         let len = wall_element.len();
         let temperature_b =
-            (wall_element[len - 1].temperature + wall_element[len - 2].temperature) / 2.0;
+            (wall_element[len - 1].temperature + wall_element[len - 2].temperature) / two;
         let material_b = &materials[wall_element[len - 1].material as usize];
+        let emissivity_b = F::from_single(material_b.emissivity);
         let dx_b = wall_element[len - 1].size;
 
-        let emission_rfac_b = 2.0 * material_b.emissivity * SIGMA * temperature_b.powf(3.0);
-        let emission_qdxk_b = 3.0 * material_b.emissivity * SIGMA * temperature_b.powf(4.0);
+        let (emission_rfac_b, emission_qdxk_b) = if !radiation || convective[1] {
+            (F::zero(), F::zero())
+        } else {
+            (
+                two * emissivity_b * sigma * temperature_b.powf(three),
+                three * emissivity_b * sigma * temperature_b.powf(F::from_single(4.0)),
+            )
+        };
 
-        let rfac_b = 0.5 * h_b + emission_rfac_b;
-        let k_b = material_b.conductivity.calc(temperature_b);
+        let rfac_b = F::from_single(0.5 * h_b) + emission_rfac_b;
+        let k_b = material_b.conductivity.calc_as(temperature_b);
         let rfac2_b = (k_b / dx_b - rfac_b) / (k_b / dx_b + rfac_b);
         let qdxk_b = (q2_b + emission_qdxk_b) / (k_b / dx_b + rfac_b);
 
@@ -187,30 +577,38 @@ pub fn calc_rfac2_and_qdxk_no_radiation(
     [rfac2_f, qdxk_f, rfac2_b, qdxk_b]
 }
 
-/// Filling the solution matrix.
+/// Filling the solution matrix. The volumetric heat generation is added to the right-hand side. If it is empty, no heat is generated.
 #[inline]
-pub fn populate_solve_matrix(
-    wall_element: &WallElement,
+pub fn populate_solve_matrix<F: SolverFloat>(
+    wall_element: &WallElement<F>,
     materials: &[Material],
-    delta_time: f32,
-) -> Vec<[f32; 4]> {
+    heat_generation: &[f32],
+    delta_time: F,
+) -> Vec<[F; 4]> {
+    let two = F::from_single(2.0);
     let mut matrix = Vec::with_capacity(wall_element.len() - 2);
 
     let mut temperature_d = wall_element[1].temperature;
     let mut material_d = &materials[wall_element[1].material as usize];
     let mut dx_d = wall_element[1].size;
 
-    let mut f1 = 2.0 * material_d.density * material_d.specific_heat.calc(temperature_d);
+    let mut f1 =
+        two * F::from_single(material_d.density) * material_d.specific_heat.calc_as(temperature_d);
 
     // B
     let temperature_b = wall_element[0].temperature;
     let material_b = &materials[wall_element[0].material as usize];
     let dx_b = wall_element[0].size;
 
-    let k_b = (material_d.conductivity.calc(temperature_d)
-        + material_b.conductivity.calc(temperature_b))
-        / 2.0;
-    let mut b = -delta_time * k_b / (f1 * dx_d * (dx_d + dx_b) / 2.0);
+    let k_b = with_contact_resistance(
+        (material_d.conductivity.calc_as(temperature_d)
+            + material_b.conductivity.calc_as(temperature_b))
+            / two,
+        dx_d,
+        dx_b,
+        wall_element.contact_resistance(0),
+    );
+    let mut b = -delta_time * k_b / (f1 * dx_d * (dx_d + dx_b) / two);
     let mut c_b = b * (temperature_d - temperature_b);
 
     for i in 1..(wall_element.len() - 1) {
@@ -219,24 +617,40 @@ pub fn populate_solve_matrix(
         let material_a = &materials[wall_element[i + 1].material as usize];
         let dx_a = wall_element[i + 1].size;
 
-        let k_a = (material_d.conductivity.calc(temperature_d)
-            + material_a.conductivity.calc(temperature_a))
-            / 2.0;
-        let a = -delta_time * k_a / (f1 * dx_d * (dx_d + dx_a) / 2.0);
+        let contact_resistance = wall_element.contact_resistance(i);
+        let k_a = with_contact_resistance(
+            (material_d.conductivity.calc_as(temperature_d)
+                + material_a.conductivity.calc_as(temperature_a))
+                / two,
+            dx_d,
+            dx_a,
+            contact_resistance,
+        );
+        let a = -delta_time * k_a / (f1 * dx_d * (dx_d + dx_a) / two);
         let c_a = a * (temperature_a - temperature_d);
 
         // D
-        let d = 1.0 - a - b;
+        let d = F::one() - a - b;
 
         // C
-        let c = temperature_d - c_a + c_b;
+        let mut c = temperature_d - c_a + c_b;
+        if let Some(&q_gen) = heat_generation.get(i) {
+            c = c + two * delta_time * F::from_single(q_gen) / f1;
+        }
         matrix.push([b, d, a, c]);
 
-        f1 = 2.0 * material_a.density * material_a.specific_heat.calc(temperature_a);
-        let k_b = (material_a.conductivity.calc(temperature_a)
-            + material_a.conductivity.calc(temperature_d))
-            / 2.0;
-        b = -delta_time * k_b / (f1 * dx_a * (dx_a + dx_d) / 2.0);
+        f1 = two
+            * F::from_single(material_a.density)
+            * material_a.specific_heat.calc_as(temperature_a);
+        let k_b = with_contact_resistance(
+            (material_a.conductivity.calc_as(temperature_a)
+                + material_a.conductivity.calc_as(temperature_d))
+                / two,
+            dx_a,
+            dx_d,
+            contact_resistance,
+        );
+        b = -delta_time * k_b / (f1 * dx_a * (dx_a + dx_d) / two);
         c_b = b * (temperature_a - temperature_d);
 
         temperature_d = temperature_a;
@@ -249,32 +663,33 @@ pub fn populate_solve_matrix(
 
 /// Solving the solution matrix with the Thomas algorithm.
 #[inline]
-pub fn solve_heat_transfer(
-    wall_element: &mut WallElement,
+pub fn solve_heat_transfer<F: SolverFloat>(
+    wall_element: &mut WallElement<F>,
     materials: &[Material],
-    rfac2_qdxk: [f32; 4],
-    delta_time: f32,
+    rfac2_qdxk: [F; 4],
+    heat_generation: &[f32],
+    delta_time: F,
 ) {
     let len = wall_element.len();
     let n = len - 2;
-    let mut matrix = populate_solve_matrix(wall_element, materials, delta_time);
+    let mut matrix = populate_solve_matrix(wall_element, materials, heat_generation, delta_time);
 
     let [rfac2_f, qdxk_f, rfac2_b, qdxk_b] = rfac2_qdxk;
 
     // 0: b, 1: d, 2: a, 3: c
-    matrix[0][3] -= matrix[0][0] * qdxk_f;
-    matrix[n - 1][3] -= matrix[n - 1][2] * qdxk_b;
+    matrix[0][3] = matrix[0][3] - matrix[0][0] * qdxk_f;
+    matrix[n - 1][3] = matrix[n - 1][3] - matrix[n - 1][2] * qdxk_b;
 
-    matrix[0][1] += matrix[0][0] * rfac2_f;
-    matrix[n - 1][1] += matrix[n - 1][2] * rfac2_b;
+    matrix[0][1] = matrix[0][1] + matrix[0][0] * rfac2_f;
+    matrix[n - 1][1] = matrix[n - 1][1] + matrix[n - 1][2] * rfac2_b;
 
     for i in 1..n {
         let r = matrix[i][0] / matrix[i - 1][1];
-        matrix[i][1] -= r * matrix[i - 1][2];
-        matrix[i][3] -= r * matrix[i - 1][3];
+        matrix[i][1] = matrix[i][1] - r * matrix[i - 1][2];
+        matrix[i][3] = matrix[i][3] - r * matrix[i - 1][3];
     }
 
-    matrix[n - 1][3] /= matrix[n - 1][1];
+    matrix[n - 1][3] = matrix[n - 1][3] / matrix[n - 1][1];
     for i in (0..(n - 1)).rev() {
         matrix[i][3] = (matrix[i][3] - matrix[i][2] * matrix[i + 1][3]) / matrix[i][1]
     }
@@ -287,25 +702,441 @@ pub fn solve_heat_transfer(
 }
 
 //Calculation of the total heat transfer with reduction of the time step if necessary.
+// Wall elements with less than `MIN_CELL_COUNT` cells are left unchanged, since the solver indexes the boundary and the first inner cell on both sides.
+// Returns whether the time step needed more subdivisions than `max_time_subdivisions` allows.
 #[inline]
-pub fn heat_transfer(
-    wall_element: &mut WallElement,
+pub fn heat_transfer<F: SolverFloat>(
+    wall_element: &mut WallElement<F>,
     materials: &[Material],
     wall_heat_transfer_coefficient: [f32; 2],
     wall_q_in: [f32; 2],
+    heat_generation: &[f32],
+    solver_config: &SolverConfig,
     delta_time: f32,
-) {
+) -> bool {
+    heat_transfer_inspected(
+        wall_element,
+        materials,
+        wall_heat_transfer_coefficient,
+        wall_q_in,
+        heat_generation,
+        solver_config,
+        delta_time,
+        |_, _| {},
+    )
+}
+
+// Calculation of the total heat transfer like `heat_transfer`, `inspect` is called with the wall element and the time of each subdivision after it was solved.
+#[allow(clippy::too_many_arguments)]
+#[inline]
+fn heat_transfer_inspected<F: SolverFloat>(
+    wall_element: &mut WallElement<F>,
+    materials: &[Material],
+    wall_heat_transfer_coefficient: [f32; 2],
+    wall_q_in: [f32; 2],
+    heat_generation: &[f32],
+    solver_config: &SolverConfig,
+    delta_time: f32,
+    mut inspect: impl FnMut(&WallElement<F>, F),
+) -> bool {
+    if wall_element.len() < MIN_CELL_COUNT {
+        return false;
+    }
+    let delta_time = F::from_single(delta_time);
     let max_delta_temperature = max_delta_temperature(wall_element, materials, delta_time);
-    let repeats = repeats(max_delta_temperature);
+    let repeats = repeats(max_delta_temperature, solver_config);
 
-    let new_delta_time = delta_time / (repeats as f32);
+    let new_delta_time = delta_time / F::from_single(repeats as f32);
     for _ in 0..repeats {
         let rfac2_qdxk = calc_rfac2_and_qdxk_no_radiation(
             wall_element,
             materials,
             wall_heat_transfer_coefficient,
             wall_q_in,
+            solver_config.sigma,
+            solver_config.radiation,
         );
-        solve_heat_transfer(wall_element, materials, rfac2_qdxk, new_delta_time);
+        solve_heat_transfer(
+            wall_element,
+            materials,
+            rfac2_qdxk,
+            heat_generation,
+            new_delta_time,
+        );
+        inspect(wall_element, new_delta_time);
+    }
+    required_repeats(max_delta_temperature, solver_config) > solver_config.max_time_subdivisions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fds::Ramp, heat_transfer::one_dimensional::WallCell};
+
+    /// Returns steel with constant properties.
+    fn steel() -> Material {
+        Material {
+            specific_heat: Ramp::from(460.0),
+            conductivity: Ramp::from(45.8),
+            density: 7850.0,
+            emissivity: 0.9,
+        }
+    }
+
+    /// Returns a [`WallElement`] of material 0 with `cell_count` inner cells of the passed size and a boundary cell on both sides.
+    fn uniform_wall_element(cell_count: usize, size: f32, temperature: f32) -> WallElement {
+        WallElement::new(vec![
+            WallCell {
+                size,
+                material: 0,
+                temperature,
+            };
+            cell_count + 2
+        ])
+    }
+
+    /// Returns a [`WallElement`] of two steel layers with `cell_count` inner cells each and the passed contact resistance between the layers.
+    fn two_layer_wall_element(
+        cell_count: usize,
+        size: f32,
+        contact_resistance: f32,
+    ) -> WallElement {
+        let cells = uniform_wall_element(2 * cell_count, size, 20.0)
+            .into_iter()
+            .collect();
+        let mut contact_resistances = vec![0.0; 2 * cell_count + 1];
+        contact_resistances[cell_count] = contact_resistance;
+        WallElement::with_contact_resistances(cells, contact_resistances)
+    }
+
+    /// Returns the temperature profile of a steel [`WallElement`] between a front at 100 °C and a back at 0 °C after it reached the steady state.
+    fn steady_state_profile<H: HeatTransfer1D>(wall_element: WallElement) -> Vec<f32> {
+        let mut setup = H::setup(vec![steel()], vec![wall_element]).unwrap();
+        let mut wall_temperature = [[0.0; 2]];
+        for _ in 0..2000 {
+            setup
+                .update(
+                    10.0,
+                    &[[CONST_TEMP_H, CONST_TEMP_H]],
+                    &[[100.0, 0.0]],
+                    &mut wall_temperature,
+                )
+                .unwrap();
+        }
+        setup.profile().unwrap().remove(0)
+    }
+
+    #[test]
+    fn contact_resistance_adds_temperature_jump_between_layers() {
+        let (cell_count, size, contact_resistance) = (10, 0.001, 1e-3);
+        let conductivity = 45.8;
+        let resistance = 2.0 * cell_count as f32 * size / conductivity;
+
+        for (contact_resistance, profile) in [
+            (
+                0.0,
+                steady_state_profile::<CPUSetupData>(two_layer_wall_element(cell_count, size, 0.0)),
+            ),
+            (
+                contact_resistance,
+                steady_state_profile::<CPUSetupData>(two_layer_wall_element(
+                    cell_count,
+                    size,
+                    contact_resistance,
+                )),
+            ),
+            (
+                contact_resistance,
+                steady_state_profile::<CPUF64SetupData>(two_layer_wall_element(
+                    cell_count,
+                    size,
+                    contact_resistance,
+                )),
+            ),
+        ] {
+            let heat_flux = 100.0 / (resistance + contact_resistance);
+            let jump = profile[cell_count] - profile[cell_count + 1];
+            let expected = heat_flux * (size / conductivity + contact_resistance);
+            assert!(
+                (jump - expected).abs() < 1e-2,
+                "R = {contact_resistance}: {jump} != {expected}"
+            );
+            // The profile within each layer stays linear.
+            let gradient = heat_flux * size / conductivity;
+            for i in (1..cell_count).chain(cell_count + 1..2 * cell_count) {
+                let delta = profile[i] - profile[i + 1];
+                assert!(
+                    (delta - gradient).abs() < 1e-2,
+                    "{i}: {delta} != {gradient}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn thomas_solution_satisfies_tridiagonal_system() {
+        let materials = [steel()];
+        let mut wall_element = uniform_wall_element(20, 0.001, 0.0);
+        for (i, cell) in wall_element.iter_mut().enumerate() {
+            cell.temperature = 20.0 + 50.0 * (i as f32).sin();
+        }
+        let delta_time = 10.0;
+        let rfac2_qdxk = calc_rfac2_and_qdxk_no_radiation(
+            &wall_element,
+            &materials,
+            [CONST_TEMP_H, CONST_TEMP_H],
+            [100.0, 0.0],
+            SIGMA,
+            true,
+        );
+        // The coefficients of constant material properties do not depend on the temperatures, only the right-hand side does.
+        let matrix = populate_solve_matrix(&wall_element, &materials, &[], delta_time);
+        solve_heat_transfer(&mut wall_element, &materials, rfac2_qdxk, &[], delta_time);
+
+        for (i, [b, d, a, c]) in matrix.into_iter().enumerate() {
+            let residual = b * wall_element[i].temperature
+                + d * wall_element[i + 1].temperature
+                + a * wall_element[i + 2].temperature
+                - c;
+            assert!(residual.abs() < 1e-5 * c.abs().max(1.0), "{i}: {residual}");
+        }
+        let len = wall_element.len();
+        assert!(
+            ((wall_element[0].temperature + wall_element[1].temperature) / 2.0 - 100.0).abs()
+                < 1e-3
+        );
+        assert!(
+            (wall_element[len - 1].temperature + wall_element[len - 2].temperature).abs() < 1e-3
+        );
+    }
+
+    #[test]
+    fn constant_temperatures_converge_to_linear_profile() {
+        let (cell_count, size) = (20, 0.001);
+        let profile =
+            steady_state_profile::<CPUSetupData>(uniform_wall_element(cell_count, size, 20.0));
+        for (i, temperature) in profile[1..=cell_count].iter().enumerate() {
+            let expected = 100.0 * (1.0 - (i as f32 + 0.5) / cell_count as f32);
+            assert!(
+                (temperature - expected).abs() < 1e-2,
+                "{i}: {temperature} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn adiabatic_wall_conserves_energy() {
+        let materials = vec![steel()];
+        let mut wall_element = uniform_wall_element(20, 0.001, 0.0);
+        for (i, cell) in wall_element.iter_mut().enumerate() {
+            cell.temperature = 20.0 + 10.0 * i as f32;
+        }
+        let energy = stored_energy(&wall_element, &materials);
+
+        let mut setup = CPUSetupData::setup(materials.clone(), vec![wall_element]).unwrap();
+        let mut wall_temperature = [[0.0; 2]];
+        for _ in 0..1000 {
+            setup
+                .update(
+                    1.0,
+                    &[[ADIABATIC_H, ADIABATIC_H]],
+                    &[[0.0, 0.0]],
+                    &mut wall_temperature,
+                )
+                .unwrap();
+        }
+        let profile = setup.profile().unwrap().remove(0);
+        let wall_element = WallElement::new(
+            profile
+                .iter()
+                .map(|&temperature| WallCell {
+                    size: 0.001,
+                    material: 0,
+                    temperature,
+                })
+                .collect(),
+        );
+        let relative_error = (stored_energy(&wall_element, &materials) - energy).abs() / energy;
+        assert!(relative_error < 1e-5, "{relative_error}");
+        // Without losses the wall reaches the mean of the initial temperatures.
+        assert!(
+            profile.iter().all(|t| (t - 125.0).abs() < 1e-2),
+            "{profile:?}"
+        );
+    }
+
+    /// Returns the temperature profile of a steel [`WallElement`] with a volumetric heat generation of `heat_generation` between two surfaces at 0 °C after it reached the steady state.
+    fn heat_generation_profile<H: HeatTransfer1D>(
+        cell_count: usize,
+        size: f32,
+        heat_generation: f32,
+    ) -> Vec<f32> {
+        let mut setup = H::setup(
+            vec![steel()],
+            vec![uniform_wall_element(cell_count, size, 0.0)],
+        )
+        .unwrap();
+        let mut wall_temperature = [[0.0; 2]];
+        for _ in 0..2000 {
+            setup
+                .update_with_heat_generation(
+                    10.0,
+                    &[[CONST_TEMP_H, CONST_TEMP_H]],
+                    &[[0.0, 0.0]],
+                    &vec![heat_generation; cell_count + 2],
+                    &mut wall_temperature,
+                )
+                .unwrap();
+        }
+        setup.profile().unwrap().remove(0)
+    }
+
+    #[test]
+    fn heat_generation_reaches_parabolic_steady_state() {
+        let (cell_count, size, heat_generation) = (20, 0.001, 1e7);
+        let conductivity = 45.8;
+        let thickness = cell_count as f32 * size;
+
+        for profile in [
+            heat_generation_profile::<CPUSetupData>(cell_count, size, heat_generation),
+            heat_generation_profile::<CPUF64SetupData>(cell_count, size, heat_generation),
+        ] {
+            // T(x) = q x (L - x) / 2k for a slab with both surfaces at 0 °C.
+            for (i, temperature) in profile[1..=cell_count].iter().enumerate() {
+                let x = (i as f32 + 0.5) * size;
+                let expected = heat_generation * x * (thickness - x) / (2.0 * conductivity);
+                assert!(
+                    (temperature - expected).abs() < 0.1,
+                    "{i}: {temperature} != {expected}"
+                );
+            }
+        }
+    }
+
+    /// Returns the temperature profile of a steel [`WallElement`] heated by a gas of 500 °C on the front side with `h = 10 W/m²K` for 100 s.
+    fn gas_heated_profile<H: HeatTransfer1D>(emissivity: f32, convective: [bool; 2]) -> Vec<f32> {
+        let materials = vec![Material {
+            emissivity,
+            ..steel()
+        }];
+        let wall_element = uniform_wall_element(10, 0.001, 20.0).with_convective(convective);
+        let mut setup = H::setup(materials, vec![wall_element]).unwrap();
+        let mut wall_temperature = [[0.0; 2]];
+        for _ in 0..100 {
+            setup
+                .update(
+                    1.0,
+                    &[[10.0, ADIABATIC_H]],
+                    &[[10.0 * 500.0, 0.0]],
+                    &mut wall_temperature,
+                )
+                .unwrap();
+        }
+        setup.profile().unwrap().remove(0)
+    }
+
+    #[test]
+    fn convective_side_ignores_emission() {
+        let without_emission = gas_heated_profile::<CPUSetupData>(0.0, [false, false]);
+        let with_emission = gas_heated_profile::<CPUSetupData>(0.9, [false, false]);
+        assert_ne!(without_emission, with_emission);
+
+        assert_eq!(
+            gas_heated_profile::<CPUSetupData>(0.9, [true, false]),
+            without_emission
+        );
+        let f64_profile = gas_heated_profile::<CPUF64SetupData>(0.9, [true, false]);
+        for (a, b) in f64_profile.iter().zip(&without_emission) {
+            assert!((a - b).abs() < 1e-3, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn energy_balance_closes_for_constant_properties() {
+        let mut setup =
+            CPUSetupData::setup(vec![steel()], vec![uniform_wall_element(20, 0.001, 20.0)])
+                .unwrap();
+        let mut wall_temperature = [[0.0; 2]];
+        for _ in 0..100 {
+            let energy_balance = setup
+                .update_with_energy_balance(
+                    1.0,
+                    &[[CONST_TEMP_H, ADIABATIC_H]],
+                    &[[500.0, 0.0]],
+                    &mut wall_temperature,
+                )
+                .unwrap();
+            let [energy_balance] = energy_balance[..] else {
+                panic!("{energy_balance:?}")
+            };
+            assert!(energy_balance.boundary_energy > 0.0);
+            let relative_imbalance =
+                energy_balance.imbalance().abs() / energy_balance.delta_stored_energy.abs();
+            assert!(relative_imbalance < 1e-2, "{energy_balance:?}");
+        }
+
+        let err = setup
+            .update_with_energy_balance(
+                1.0,
+                &[[CONST_TEMP_H, ADIABATIC_H]],
+                &[[500.0, 0.0], [500.0, 0.0]],
+                &mut wall_temperature,
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The wall q in buffer should have a value for every wall element, expected 1, got 2."
+        );
+    }
+
+    #[test]
+    fn step_rejects_wall_element_with_too_few_cells() {
+        let materials = [steel()];
+        let solver_config = SolverConfig::default();
+        let step_config = StepConfig {
+            wall_heat_transfer_coefficient: [CONST_TEMP_H, ADIABATIC_H],
+            wall_q_in: [200.0, 0.0],
+            heat_generation: &[],
+            solver_config: &solver_config,
+            subdivision_counter: None,
+        };
+        for cell_count in 0..MIN_CELL_COUNT {
+            let mut wall_element = WallElement::new(vec![WallCell::default(); cell_count]);
+            let err = step(&mut wall_element, &materials, &step_config, 0.1).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("The wall element has {cell_count} cells, but at least 3 are needed.")
+            );
+        }
+
+        // A thin wall with a single inner cell is the smallest wall element that can be simulated.
+        let mut wall_element = uniform_wall_element(1, 1e-6, 20.0);
+        let [front, back] = step(&mut wall_element, &materials, &step_config, 0.1).unwrap();
+        assert!((front - 200.0).abs() < 1e-3);
+        assert!(back.is_finite() && back > 20.0);
+    }
+
+    #[test]
+    fn step_heats_adiabatic_steel_wall_monotonically() {
+        let materials = [steel()];
+        let mut wall_element = uniform_wall_element(10, 0.001, 20.0);
+        let solver_config = SolverConfig::default();
+        let step_config = StepConfig {
+            wall_heat_transfer_coefficient: [CONST_TEMP_H, ADIABATIC_H],
+            wall_q_in: [200.0, 0.0],
+            heat_generation: &[],
+            solver_config: &solver_config,
+            subdivision_counter: None,
+        };
+
+        let mut last_back = 20.0;
+        for _ in 0..100 {
+            let [front, back] = step(&mut wall_element, &materials, &step_config, 0.1).unwrap();
+            assert!((front - 200.0).abs() < 1e-3);
+            assert!(back >= last_back, "{back} < {last_back}");
+            assert!(back <= 200.0);
+            last_back = back;
+        }
+        assert!(last_back > 20.0);
     }
 }
@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc, time::Instant};
 
 use futures::{
     executor::block_on,
@@ -12,11 +12,18 @@ use wgpu::{
     Device, Queue,
 };
 
-use crate::{fds::Material, heat_transfer::shader::insert_material_data};
+use crate::{
+    fds::Material,
+    heat_transfer::shader::{
+        insert_convective_sides, insert_material_data, insert_solver_config, insert_workgroup_size,
+    },
+};
 
 use super::{
-    super::gpu::get_gpu_device_and_queue, get_max_element_per_chunk, update_bind_group,
-    DeviceFuture, HeatTransfer1D, WallCell, WallElement,
+    super::gpu::get_shared_gpu_device_and_queue, check_buffer_lengths,
+    check_no_contact_resistances, get_max_element_per_chunk, get_solver_config, get_workgroup_size,
+    read_storage_buffer, read_wall_temperatures, record_gpu_timing, shared_convective_sides,
+    update_bind_group, DeviceFuture, HeatTransfer1D, WallCell, WallElement,
 };
 use anyhow::*;
 
@@ -25,18 +32,29 @@ pub const SHADER: &str = include_str!("gpu_m1.wgsl");
 
 /// All relevant data for the heat transfer algorithm on the GPU with method 1.
 pub struct GPUSetupData {
-    device: Device,
-    queue: Queue,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
     compute_pipeline: ComputePipeline,
+    max_elements_per_chunk: usize,
+    workgroup_size: u32,
+    wall_element_count: usize,
     chunks: Vec<Chunk>,
+    /// The purely convective sides of the wall elements the shader is compiled for.
+    convective: [bool; 2],
 }
 
 impl HeatTransfer1D for GPUSetupData {
     fn setup(materials: Vec<Material>, wall_elements: Vec<WallElement>) -> Result<Self> {
-        let (device, queue) = block_on(get_gpu_device_and_queue())
-            .with_context(|| "Failed to get device and queue.")?;
+        check_no_contact_resistances(&wall_elements)?;
+        let convective = shared_convective_sides(&wall_elements)?;
+        let (device, queue) =
+            get_shared_gpu_device_and_queue().with_context(|| "Failed to get device and queue.")?;
 
+        let workgroup_size = get_workgroup_size();
         let shader = insert_material_data(SHADER, &materials);
+        let shader = insert_solver_config(&shader, &get_solver_config());
+        let shader = insert_convective_sides(&shader, convective);
+        let shader = insert_workgroup_size(&shader, workgroup_size);
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader Module"),
             source: wgpu::ShaderSource::Wgsl(Cow::from(shader)),
@@ -49,17 +67,45 @@ impl HeatTransfer1D for GPUSetupData {
             entry_point: "compute",
         });
 
-        let chunks = Chunk::build(&device, &compute_pipeline, wall_elements);
+        let max_elements_per_chunk = get_max_element_per_chunk();
+        let wall_element_count = wall_elements.len();
+        let chunks = Chunk::build(
+            &device,
+            &compute_pipeline,
+            wall_elements,
+            max_elements_per_chunk,
+            workgroup_size,
+        );
         let gpu_setup_data = GPUSetupData {
             device,
             queue,
             compute_pipeline,
+            max_elements_per_chunk,
+            workgroup_size,
+            wall_element_count,
             chunks,
+            convective,
         };
 
         Ok(gpu_setup_data)
     }
 
+    fn reset(&mut self, wall_elements: Vec<WallElement>) -> Result<()> {
+        check_no_contact_resistances(&wall_elements)?;
+        if shared_convective_sides(&wall_elements)? != self.convective {
+            bail!("The convective sides of the wall elements differ from the sides the shader is compiled for.");
+        }
+        self.wall_element_count = wall_elements.len();
+        self.chunks = Chunk::build(
+            &self.device,
+            &self.compute_pipeline,
+            wall_elements,
+            self.max_elements_per_chunk,
+            self.workgroup_size,
+        );
+        Ok(())
+    }
+
     fn update(
         &mut self,
         delta_time: f32,
@@ -67,14 +113,21 @@ impl HeatTransfer1D for GPUSetupData {
         wall_q_in: &[[f32; 2]],
         wall_temperature: &mut [[f32; 2]],
     ) -> Result<()> {
+        check_buffer_lengths(
+            self.wall_element_count,
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
+        let start = Instant::now();
         let receivers = self
             .chunks
             .iter()
-            .zip(wall_temperature.chunks_mut(get_max_element_per_chunk()))
+            .zip(wall_temperature.chunks_mut(self.max_elements_per_chunk))
             .zip(
                 wall_heat_transfer_coefficients
-                    .chunks(get_max_element_per_chunk())
-                    .zip(wall_q_in.chunks(get_max_element_per_chunk())),
+                    .chunks(self.max_elements_per_chunk)
+                    .zip(wall_q_in.chunks(self.max_elements_per_chunk)),
             )
             .map(
                 |(
@@ -93,14 +146,32 @@ impl HeatTransfer1D for GPUSetupData {
                 },
             )
             .collect::<Vec<_>>();
+        let submitted = Instant::now();
         _ = block_on(join(join_all(receivers), DeviceFuture(&self.device)));
+        record_gpu_timing(submitted - start, submitted.elapsed());
         Ok(())
     }
+
+    fn profile(&self) -> Result<Vec<Vec<f32>>> {
+        let mut profile = Vec::with_capacity(self.wall_element_count);
+        for chunk in self.chunks.iter() {
+            let data = read_storage_buffer(&self.device, &self.queue, &chunk.wall_cells_buffer)?;
+            let mut temperatures = data
+                .chunks_exact(std::mem::size_of::<WallCell>())
+                .map(|wall_cell| bytemuck::pod_read_unaligned::<WallCell>(wall_cell).temperature);
+            for &cell_count in chunk.cell_counts.iter() {
+                profile.push(temperatures.by_ref().take(cell_count).collect());
+            }
+        }
+        Ok(profile)
+    }
 }
 
 /// All data for a single chunk.
 struct Chunk {
     setup_bind_group: BindGroup,
+    wall_cells_buffer: Buffer,
+    cell_counts: Vec<usize>,
     matrix_bind_group: BindGroup,
     update_bind_group: BindGroup,
     wall_heat_transfer_coefficients_buffer: Buffer,
@@ -114,15 +185,18 @@ impl Chunk {
         device: &Device,
         compute_pipeline: &ComputePipeline,
         wall_elements: Vec<WallElement>,
+        max_elements_per_chunk: usize,
+        workgroup_size: u32,
     ) -> Vec<Self> {
         let mut chunks = vec![];
         let mut cell_indices = vec![];
+        let mut cell_counts = vec![];
         let mut wall_cells = vec![];
         for wall_elements_chunk in wall_elements
             .into_iter()
             .map(Option::Some)
             .collect::<Vec<Option<WallElement>>>()
-            .chunks_mut(get_max_element_per_chunk())
+            .chunks_mut(max_elements_per_chunk)
         {
             let mut last_size = 0;
             for wall_element in wall_elements_chunk {
@@ -130,7 +204,8 @@ impl Chunk {
                     Some(mut wall_element) => {
                         last_size += wall_element.len() as u32;
                         cell_indices.push(last_size);
-                        wall_cells.append(&mut wall_element.0);
+                        cell_counts.push(wall_element.len());
+                        wall_cells.append(&mut wall_element);
                     }
                     None => unreachable!(),
                 }
@@ -139,7 +214,7 @@ impl Chunk {
             let wall_element_count = cell_indices.len();
             let cell_count = cell_indices[cell_indices.len() - 1] as usize;
 
-            let setup_bind_group =
+            let (setup_bind_group, wall_cells_buffer) =
                 setup_bind_group(device, compute_pipeline, &cell_indices, &wall_cells);
             cell_indices.clear();
             wall_cells.clear();
@@ -152,10 +227,12 @@ impl Chunk {
                 delta_time_buffer,
             ) = update_bind_group(device, compute_pipeline, wall_element_count, 2);
 
-            let groups = (wall_element_count as f32 / 256.0).ceil() as u32;
+            let groups = (wall_element_count as f32 / workgroup_size as f32).ceil() as u32;
 
             let chunk = Chunk {
                 setup_bind_group,
+                wall_cells_buffer,
+                cell_counts: std::mem::take(&mut cell_counts),
                 matrix_bind_group,
                 update_bind_group,
                 wall_heat_transfer_coefficients_buffer,
@@ -227,20 +304,21 @@ impl Chunk {
         _ = receiver.await;
         {
             let data = self.wall_q_in_buffer.slice(..).get_mapped_range();
-            wall_temperature_buffer.copy_from_slice(bytemuck::cast_slice(&data));
+            read_wall_temperatures(&data, wall_temperature_buffer);
         }
         self.wall_q_in_buffer.unmap();
     }
 }
 
 /// Create the setup [`BindGroup`] with all the [`Buffer`]s.
+/// The wall cells [`Buffer`] is returned as well, so the temperatures can be read back for [`HeatTransfer1D::profile`].
 #[inline]
 fn setup_bind_group(
     device: &Device,
     compute_pipeline: &ComputePipeline,
     cell_indices: &[u32],
     wall_cells: &[WallCell],
-) -> BindGroup {
+) -> (BindGroup, Buffer) {
     let cell_indices_buffer = device.create_buffer_init(&BufferInitDescriptor {
         label: Some("Cell Indices Buffer"),
         contents: bytemuck::cast_slice(cell_indices),
@@ -249,7 +327,7 @@ fn setup_bind_group(
     let wall_cells_buffer = device.create_buffer_init(&BufferInitDescriptor {
         label: Some("Cell Sizes Buffer"),
         contents: bytemuck::cast_slice(wall_cells),
-        usage: BufferUsages::STORAGE,
+        usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
     });
 
     let setup_bind_group_layout = compute_pipeline.get_bind_group_layout(0);
@@ -267,7 +345,7 @@ fn setup_bind_group(
             },
         ],
     });
-    setup_bind_group
+    (setup_bind_group, wall_cells_buffer)
 }
 
 /// Create the matrix [`BindGroup`] with all the [`Buffer`]s.
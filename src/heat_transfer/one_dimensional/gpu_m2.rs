@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap, sync::Arc, time::Instant};
 
 use futures::{
     executor::block_on,
@@ -13,12 +13,17 @@ use wgpu::{
 
 use crate::{
     fds::Material,
-    heat_transfer::shader::{insert_gpu_m2_data, insert_material_data},
+    heat_transfer::shader::{
+        insert_convective_sides, insert_gpu_m2_data, insert_material_data, insert_solver_config,
+        insert_workgroup_size,
+    },
 };
 
 use super::{
-    super::gpu::get_gpu_device_and_queue, update_bind_group, DeviceFuture, HeatTransfer1D,
-    WallElement,
+    super::gpu::get_shared_gpu_device_and_queue, check_buffer_lengths,
+    check_no_contact_resistances, get_solver_config, get_workgroup_size, read_storage_buffer,
+    read_wall_temperatures, record_gpu_timing, shared_convective_sides, update_bind_group,
+    DeviceFuture, HeatTransfer1D, WallElement,
 };
 
 use anyhow::*;
@@ -26,29 +31,74 @@ use anyhow::*;
 /// The whole base shader for method 2.
 pub const SHADER: &str = include_str!("gpu_m2.wgsl");
 
+/// The compiled [`ComputePipeline`]s of method 2 keyed by the bits of the cell sizes and the cell materials they are compiled for.
+type PipelineCache = HashMap<(Vec<u32>, Vec<u32>), Arc<ComputePipeline>>;
+
 /// All relevant data for the heat transfer algorithm on the GPU with method 2.
 pub struct GPUSetupData {
-    device: Device,
-    queue: Queue,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    shader: String,
+    /// The pipelines are kept across [`HeatTransfer1D::reset`], so chunks with an already compiled cell layout reuse its pipeline.
+    pipelines: PipelineCache,
+    workgroup_size: u32,
+    wall_element_count: usize,
     shader_chunks: Vec<ShaderChunk>,
+    /// The purely convective sides of the wall elements the shader is compiled for.
+    convective: [bool; 2],
 }
 
 impl HeatTransfer1D for GPUSetupData {
     fn setup(materials: Vec<Material>, wall_elements: Vec<WallElement>) -> Result<Self> {
-        let (device, queue) = block_on(get_gpu_device_and_queue())
-            .with_context(|| "Failed to get device and queue.")?;
+        check_no_contact_resistances(&wall_elements)?;
+        let convective = shared_convective_sides(&wall_elements)?;
+        let (device, queue) =
+            get_shared_gpu_device_and_queue().with_context(|| "Failed to get device and queue.")?;
+        let workgroup_size = get_workgroup_size();
         let shader = insert_material_data(SHADER, &materials);
-        let shader_chunks = ShaderChunk::build(&device, shader, wall_elements);
+        let shader = insert_solver_config(&shader, &get_solver_config());
+        let shader = insert_convective_sides(&shader, convective);
+        let shader = insert_workgroup_size(&shader, workgroup_size);
+        let wall_element_count = wall_elements.len();
+        let mut pipelines = PipelineCache::new();
+        let shader_chunks = ShaderChunk::build(
+            &device,
+            &shader,
+            &mut pipelines,
+            wall_elements,
+            workgroup_size,
+        );
 
         let gpu_setup_data = GPUSetupData {
             device,
             queue,
+            shader,
+            pipelines,
+            workgroup_size,
+            wall_element_count,
             shader_chunks,
+            convective,
         };
 
         Ok(gpu_setup_data)
     }
 
+    fn reset(&mut self, wall_elements: Vec<WallElement>) -> Result<()> {
+        check_no_contact_resistances(&wall_elements)?;
+        if shared_convective_sides(&wall_elements)? != self.convective {
+            bail!("The convective sides of the wall elements differ from the sides the shader is compiled for.");
+        }
+        self.wall_element_count = wall_elements.len();
+        self.shader_chunks = ShaderChunk::build(
+            &self.device,
+            &self.shader,
+            &mut self.pipelines,
+            wall_elements,
+            self.workgroup_size,
+        );
+        Ok(())
+    }
+
     fn update(
         &mut self,
         delta_time: f32,
@@ -56,6 +106,13 @@ impl HeatTransfer1D for GPUSetupData {
         wall_q_in: &[[f32; 2]],
         wall_temperature: &mut [[f32; 2]],
     ) -> Result<()> {
+        check_buffer_lengths(
+            self.wall_element_count,
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
+        let start = Instant::now();
         let mut receivers = Vec::with_capacity(self.shader_chunks.len());
         let mut wall_temperature_buffer_chunk = wall_temperature;
         for (s, receiver) in self.shader_chunks.iter().map(|s| {
@@ -74,17 +131,34 @@ impl HeatTransfer1D for GPUSetupData {
             receivers.push(receiver)
         }
 
+        let submitted = Instant::now();
         let _ = block_on(join(join_all(receivers), DeviceFuture(&self.device)));
+        record_gpu_timing(submitted - start, submitted.elapsed());
         Ok(())
     }
+
+    fn profile(&self) -> Result<Vec<Vec<f32>>> {
+        let mut profile = Vec::with_capacity(self.wall_element_count);
+        for s in self.shader_chunks.iter() {
+            let data = read_storage_buffer(&self.device, &self.queue, &s.cell_temperatures_buffer)?;
+            let cell_temperatures = data
+                .chunks_exact(std::mem::size_of::<f32>())
+                .map(bytemuck::pod_read_unaligned::<f32>)
+                .collect::<Vec<_>>();
+            profile.extend(cell_temperatures.chunks(s.cell_count).map(<[f32]>::to_vec));
+        }
+        Ok(profile)
+    }
 }
 
 /// All data fo a single shader.
 struct ShaderChunk {
     start: usize,
     end: usize,
-    compute_pipeline: ComputePipeline,
+    compute_pipeline: Arc<ComputePipeline>,
     setup_bind_group: BindGroup,
+    cell_temperatures_buffer: Buffer,
+    cell_count: usize,
     update_bind_group: BindGroup,
     wall_heat_transfer_coefficients_buffer: Buffer,
     wall_q_in_buffer: Buffer,
@@ -93,24 +167,25 @@ struct ShaderChunk {
 }
 impl ShaderChunk {
     /// Creates a new [`ShaderChunk`].
+    /// The [`ComputePipeline`] is only compiled if the cache does not contain one for the cell sizes and materials.
+    #[allow(clippy::too_many_arguments)]
     fn new(
         end: usize,
         start: usize,
         device: &Device,
         shader: &str,
+        pipelines: &mut PipelineCache,
         cell_sizes: &[f32],
         cell_materials: &[u32],
         cell_temperatures: &[f32],
+        workgroup_size: u32,
     ) -> ShaderChunk {
         let wall_element_count = end - start;
-        let groups = (wall_element_count as f32 / 256.0).ceil() as u32;
-        let (compute_pipeline, setup_bind_group) = setup_bind_group(
-            device,
-            shader,
-            cell_sizes,
-            cell_materials,
-            cell_temperatures,
-        );
+        let groups = (wall_element_count as f32 / workgroup_size as f32).ceil() as u32;
+        let compute_pipeline =
+            compute_pipeline(device, shader, pipelines, cell_sizes, cell_materials);
+        let (setup_bind_group, cell_temperatures_buffer) =
+            setup_bind_group(device, &compute_pipeline, cell_temperatures);
         let (
             update_bind_group,
             wall_heat_transfer_coefficients_buffer,
@@ -123,6 +198,8 @@ impl ShaderChunk {
             end,
             compute_pipeline,
             setup_bind_group,
+            cell_temperatures_buffer,
+            cell_count: cell_sizes.len(),
             update_bind_group,
             wall_heat_transfer_coefficients_buffer,
             wall_q_in_buffer,
@@ -132,7 +209,13 @@ impl ShaderChunk {
     }
 
     /// Build all [`ShaderChunk`]s for all [`WallElement`]s.
-    fn build(device: &Device, shader: String, wall_elements: Vec<WallElement>) -> Vec<Self> {
+    fn build(
+        device: &Device,
+        shader: &str,
+        pipelines: &mut PipelineCache,
+        wall_elements: Vec<WallElement>,
+        workgroup_size: u32,
+    ) -> Vec<Self> {
         let mut shader_chunks = vec![];
         let (mut cell_sizes, mut cell_materials): (Vec<f32>, Vec<u32>) = wall_elements[0]
             .iter()
@@ -152,10 +235,12 @@ impl ShaderChunk {
                     end,
                     start,
                     device,
-                    &shader,
+                    shader,
+                    pipelines,
                     &cell_sizes,
                     &cell_materials,
                     &cell_temperatures,
+                    workgroup_size,
                 );
                 shader_chunks.push(shader_chunk);
 
@@ -177,10 +262,12 @@ impl ShaderChunk {
             end,
             start,
             device,
-            &shader,
+            shader,
+            pipelines,
             &cell_sizes,
             &cell_materials,
             &cell_temperatures,
+            workgroup_size,
         );
         shader_chunks.push(shader_chunk);
         shader_chunks
@@ -246,38 +333,55 @@ impl ShaderChunk {
         _ = receiver.await;
         {
             let data = self.wall_q_in_buffer.slice(..).get_mapped_range();
-            wall_temperature_buffer.copy_from_slice(bytemuck::cast_slice(&data));
+            read_wall_temperatures(&data, wall_temperature_buffer);
         }
         self.wall_q_in_buffer.unmap();
     }
 }
 
-/// Create the setup [`BindGroup`] with all the [`Buffer`]s.
-#[inline]
-fn setup_bind_group(
+/// Returns the [`ComputePipeline`] for the cell sizes and materials from the cache or compiles and caches it.
+fn compute_pipeline(
     device: &Device,
     shader: &str,
+    pipelines: &mut PipelineCache,
     cell_sizes: &[f32],
     cell_materials: &[u32],
-    cell_temperatures: &[f32],
-) -> (ComputePipeline, BindGroup) {
-    let shader = insert_gpu_m2_data(shader, cell_sizes, cell_materials);
-    let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-        label: Some("Shader Module"),
-        source: wgpu::ShaderSource::Wgsl(Cow::from(shader)),
-    });
+) -> Arc<ComputePipeline> {
+    let key = (
+        cell_sizes.iter().map(|s| s.to_bits()).collect(),
+        cell_materials.to_vec(),
+    );
+    pipelines
+        .entry(key)
+        .or_insert_with(|| {
+            let shader = insert_gpu_m2_data(shader, cell_sizes, cell_materials);
+            let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shader Module"),
+                source: wgpu::ShaderSource::Wgsl(Cow::from(shader)),
+            });
 
-    let compute_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
-        label: Some("Compute Pipeline"),
-        layout: None,
-        module: &shader_module,
-        entry_point: "compute",
-    });
+            Arc::new(device.create_compute_pipeline(&ComputePipelineDescriptor {
+                label: Some("Compute Pipeline"),
+                layout: None,
+                module: &shader_module,
+                entry_point: "compute",
+            }))
+        })
+        .clone()
+}
 
+/// Create the setup [`BindGroup`] with all the [`Buffer`]s.
+/// The cell temperatures [`Buffer`] is returned as well, so the temperatures can be read back for [`HeatTransfer1D::profile`].
+#[inline]
+fn setup_bind_group(
+    device: &Device,
+    compute_pipeline: &ComputePipeline,
+    cell_temperatures: &[f32],
+) -> (BindGroup, Buffer) {
     let cell_temperatures_buffer = device.create_buffer_init(&BufferInitDescriptor {
         label: Some("Cell Temperatures Buffer"),
         contents: bytemuck::cast_slice(cell_temperatures),
-        usage: BufferUsages::STORAGE,
+        usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
     });
 
     let setup_bind_group_layout = compute_pipeline.get_bind_group_layout(0);
@@ -289,5 +393,5 @@ fn setup_bind_group(
             resource: cell_temperatures_buffer.as_entire_binding(),
         }],
     });
-    (compute_pipeline, setup_bind_group)
+    (setup_bind_group, cell_temperatures_buffer)
 }
@@ -1,7 +1,16 @@
+//! max_delta_temperature
 const MAX_DELTA_TEMPERATURE: f32 = 10.0;
+//! max_time_subdivisions
 const MAX_TIME_SUBDIVISIONS: u32 = 4u;
 const DELTA_TIME_GAS: f32 = 1.0;
+//! sigma
 const SIGMA: f32 = 0.0000000567; // 5.67*10e-8; 
+//! radiation
+const RADIATION: bool = true;
+//! convective_front
+const CONVECTIVE_FRONT: bool = false;
+//! convective_back
+const CONVECTIVE_BACK: bool = false;
 
 const ADIABATIC_H: f32 = -100000.0;
 const CONST_TEMP_H: f32 = -100001.0;
@@ -137,7 +146,7 @@ fn calc_rfac2_and_qdxk_no_radiation(
         let material_id_f = cell_materials[0];
         let dx_f = cell_sizes[0];
 
-        let emissivity_f = emissivity(material_id_f);
+        let emissivity_f = select(emissivity(material_id_f), 0.0, CONVECTIVE_FRONT || !RADIATION);
         let emission_rfac_f = 2.0 * emissivity_f * SIGMA * pow(temperature_f, 3.0);
         let emission_qdxk_f = 3.0 * emissivity_f * SIGMA * pow(temperature_f, 4.0);
 
@@ -161,7 +170,7 @@ fn calc_rfac2_and_qdxk_no_radiation(
         let material_id_b = cell_materials[CELL_LENGTH - u32(1)];
         let dx_b = cell_sizes[CELL_LENGTH - u32(1)];
 
-        let emissivity_b = emissivity(material_id_b);
+        let emissivity_b = select(emissivity(material_id_b), 0.0, CONVECTIVE_BACK || !RADIATION);
         let emission_rfac_b = 2.0 * emissivity_b * SIGMA * pow(temperature_b, 3.0);
         let emission_qdxk_b = 3.0 * emissivity_b * SIGMA * pow(temperature_b, 4.0);
 
@@ -272,6 +281,7 @@ fn heat_transfer(
 
 
 @compute
+//! workgroup_size
 @workgroup_size(256)
 fn compute(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let index = global_id.x;
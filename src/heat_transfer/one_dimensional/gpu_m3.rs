@@ -1,4 +1,4 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, sync::Arc, time::Instant};
 
 use anyhow::*;
 use futures::{
@@ -13,13 +13,18 @@ use wgpu::{
 };
 
 use crate::heat_transfer::{
-    gpu::get_gpu_device_and_queue,
-    shader::{insert_gpu_m3_data, insert_material_data},
+    gpu::get_shared_gpu_device_and_queue,
+    shader::{
+        insert_convective_sides, insert_gpu_m3_data, insert_material_data, insert_solver_config,
+        insert_workgroup_size,
+    },
 };
 
 use super::{
-    get_max_element_per_chunk, update_bind_group, DeviceFuture, HeatTransfer1D, WallCell,
-    WallElement,
+    check_buffer_lengths, check_no_contact_resistances, get_max_element_per_chunk,
+    get_solver_config, get_workgroup_size, read_padded_wall_elements, read_storage_buffer,
+    read_wall_temperatures, record_gpu_timing, shared_convective_sides, update_bind_group,
+    DeviceFuture, HeatTransfer1D, WallCell, WallElement,
 };
 
 /// The whole base shader for method 3.
@@ -27,27 +32,39 @@ pub const SHADER: &str = include_str!("gpu_m3.wgsl");
 
 /// All relevant data for the heat transfer algorithm on the GPU with method 3.
 pub struct GPUSetupData {
-    device: Device,
-    queue: Queue,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
     compute_pipeline: ComputePipeline,
+    max_elements_per_chunk: usize,
+    workgroup_size: u32,
+    max_cell_count: usize,
+    wall_element_count: usize,
     chunks: Vec<Chunk>,
+    /// The purely convective sides of the wall elements the shader is compiled for.
+    convective: [bool; 2],
 }
 impl HeatTransfer1D for GPUSetupData {
     fn setup(
         materials: Vec<crate::fds::Material>,
         wall_elements: Vec<WallElement>,
     ) -> anyhow::Result<Self> {
+        check_no_contact_resistances(&wall_elements)?;
+        let convective = shared_convective_sides(&wall_elements)?;
         let max_cell_count = wall_elements
             .iter()
             .map(|w| w.len())
             .max()
             .unwrap_or_default();
 
-        let (device, queue) = block_on(get_gpu_device_and_queue())
-            .with_context(|| "Failed to get device and queue.")?;
+        let (device, queue) =
+            get_shared_gpu_device_and_queue().with_context(|| "Failed to get device and queue.")?;
 
         let shader = insert_material_data(SHADER, &materials);
+        let shader = insert_solver_config(&shader, &get_solver_config());
+        let shader = insert_convective_sides(&shader, convective);
         let shader = insert_gpu_m3_data(&shader, max_cell_count);
+        let workgroup_size = get_workgroup_size();
+        let shader = insert_workgroup_size(&shader, workgroup_size);
         let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader Module"),
             source: wgpu::ShaderSource::Wgsl(Cow::from(shader)),
@@ -60,15 +77,57 @@ impl HeatTransfer1D for GPUSetupData {
             entry_point: "compute",
         });
 
-        let chunks = Chunk::build(&device, &compute_pipeline, wall_elements, max_cell_count);
+        let max_elements_per_chunk = get_max_element_per_chunk();
+        let wall_element_count = wall_elements.len();
+        let chunks = Chunk::build(
+            &device,
+            &compute_pipeline,
+            wall_elements,
+            max_elements_per_chunk,
+            workgroup_size,
+            max_cell_count,
+        );
         Ok(Self {
             device,
             queue,
             compute_pipeline,
+            max_elements_per_chunk,
+            workgroup_size,
+            max_cell_count,
+            wall_element_count,
             chunks,
+            convective,
         })
     }
 
+    fn reset(&mut self, wall_elements: Vec<WallElement>) -> anyhow::Result<()> {
+        check_no_contact_resistances(&wall_elements)?;
+        if shared_convective_sides(&wall_elements)? != self.convective {
+            bail!("The convective sides of the wall elements differ from the sides the shader is compiled for.");
+        }
+        let max_cell_count = wall_elements
+            .iter()
+            .map(|w| w.len())
+            .max()
+            .unwrap_or_default();
+        if max_cell_count != self.max_cell_count {
+            bail!(
+                "The maximal cell count ({max_cell_count}) differs from the one the shader was built with ({}).",
+                self.max_cell_count
+            );
+        }
+        self.wall_element_count = wall_elements.len();
+        self.chunks = Chunk::build(
+            &self.device,
+            &self.compute_pipeline,
+            wall_elements,
+            self.max_elements_per_chunk,
+            self.workgroup_size,
+            self.max_cell_count,
+        );
+        Ok(())
+    }
+
     fn update(
         &mut self,
         delta_time: f32,
@@ -76,14 +135,21 @@ impl HeatTransfer1D for GPUSetupData {
         wall_q_in: &[[f32; 2]],
         wall_temperature: &mut [[f32; 2]],
     ) -> anyhow::Result<()> {
+        check_buffer_lengths(
+            self.wall_element_count,
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
+        let start = Instant::now();
         let receivers = self
             .chunks
             .iter()
-            .zip(wall_temperature.chunks_mut(get_max_element_per_chunk()))
+            .zip(wall_temperature.chunks_mut(self.max_elements_per_chunk))
             .zip(
                 wall_heat_transfer_coefficients
-                    .chunks(get_max_element_per_chunk())
-                    .zip(wall_q_in.chunks(get_max_element_per_chunk())),
+                    .chunks(self.max_elements_per_chunk)
+                    .zip(wall_q_in.chunks(self.max_elements_per_chunk)),
             )
             .map(
                 |(
@@ -102,14 +168,26 @@ impl HeatTransfer1D for GPUSetupData {
                 },
             )
             .collect::<Vec<_>>();
+        let submitted = Instant::now();
         _ = block_on(join(join_all(receivers), DeviceFuture(&self.device)));
+        record_gpu_timing(submitted - start, submitted.elapsed());
         Ok(())
     }
+
+    fn profile(&self) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut profile = Vec::with_capacity(self.wall_element_count);
+        for chunk in self.chunks.iter() {
+            let data = read_storage_buffer(&self.device, &self.queue, &chunk.wall_elements_buffer)?;
+            profile.append(&mut read_padded_wall_elements(&data, self.max_cell_count));
+        }
+        Ok(profile)
+    }
 }
 
 /// All data for a single chunk
 struct Chunk {
     setup_bind_group: BindGroup,
+    wall_elements_buffer: Buffer,
     update_bind_group: BindGroup,
     wall_heat_transfer_coefficients_buffer: Buffer,
     wall_q_in_buffer: Buffer,
@@ -122,6 +200,8 @@ impl Chunk {
         device: &Device,
         compute_pipeline: &ComputePipeline,
         wall_elements: Vec<WallElement>,
+        max_elements_per_chunk: usize,
+        workgroup_size: u32,
         max_cell_count: usize,
     ) -> Vec<Self> {
         let mut chunks = vec![];
@@ -130,7 +210,7 @@ impl Chunk {
             .into_iter()
             .map(Option::Some)
             .collect::<Vec<_>>()
-            .chunks_mut(get_max_element_per_chunk())
+            .chunks_mut(max_elements_per_chunk)
         {
             let wall_element_count = wall_elements_chunk.len();
             let mut flattened_wall_elements: Vec<u8> = vec![];
@@ -148,7 +228,7 @@ impl Chunk {
                     flattened_wall_elements.append(&mut bytes);
                 }
             }
-            let setup_bind_group =
+            let (setup_bind_group, wall_elements_buffer) =
                 setup_bind_group(device, compute_pipeline, &flattened_wall_elements);
             let (
                 update_bind_group,
@@ -156,9 +236,10 @@ impl Chunk {
                 wall_q_in_buffer,
                 delta_time_buffer,
             ) = update_bind_group(device, compute_pipeline, wall_element_count, 1);
-            let groups = (wall_element_count as f32 / 256.0).ceil() as u32;
+            let groups = (wall_element_count as f32 / workgroup_size as f32).ceil() as u32;
             let chunk = Chunk {
                 setup_bind_group,
+                wall_elements_buffer,
                 update_bind_group,
                 wall_heat_transfer_coefficients_buffer,
                 wall_q_in_buffer,
@@ -227,23 +308,24 @@ impl Chunk {
         _ = receiver.await;
         {
             let data = self.wall_q_in_buffer.slice(..).get_mapped_range();
-            wall_temperature_buffer.copy_from_slice(bytemuck::cast_slice(&data));
+            read_wall_temperatures(&data, wall_temperature_buffer);
         }
         self.wall_q_in_buffer.unmap();
     }
 }
 
 /// Create the setup [`BindGroup`] with all the [`Buffer`]s.
+/// The wall elements [`Buffer`] is returned as well, so the temperatures can be read back for [`HeatTransfer1D::profile`].
 #[inline]
 fn setup_bind_group(
     device: &Device,
     compute_pipeline: &ComputePipeline,
     wall_elements: &[u8],
-) -> BindGroup {
+) -> (BindGroup, Buffer) {
     let wall_elements_buffer = device.create_buffer_init(&BufferInitDescriptor {
         label: Some("Wall Elements Buffer"),
         contents: wall_elements,
-        usage: BufferUsages::STORAGE,
+        usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
     });
 
     let setup_bind_group_layout = compute_pipeline.get_bind_group_layout(0);
@@ -255,5 +337,5 @@ fn setup_bind_group(
             resource: wall_elements_buffer.as_entire_binding(),
         }],
     });
-    setup_bind_group
+    (setup_bind_group, wall_elements_buffer)
 }
@@ -0,0 +1,332 @@
+use std::{borrow::Cow, sync::Arc, time::Instant};
+
+use anyhow::*;
+use futures::{executor::block_on, future::join};
+use futures_channel::oneshot::{channel, Receiver};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupDescriptor, BindGroupEntry, Buffer, BufferUsages, CommandEncoderDescriptor,
+    ComputePassDescriptor, ComputePipeline, ComputePipelineDescriptor, Device, Queue,
+};
+
+use crate::heat_transfer::{
+    gpu::get_shared_gpu_device_and_queue,
+    shader::{
+        insert_convective_sides, insert_gpu_m3_data, insert_material_data, insert_solver_config,
+        insert_workgroup_size,
+    },
+};
+
+use super::{
+    check_buffer_lengths, check_no_contact_resistances, get_solver_config, get_workgroup_size,
+    read_padded_wall_elements, read_storage_buffer, read_wall_temperatures, record_gpu_timing,
+    shared_convective_sides, update_bind_group, DeviceFuture, HeatTransfer1D, WallCell,
+    WallElement,
+};
+
+/// The whole base shader for method 4.
+pub const SHADER: &str = include_str!("gpu_m4.wgsl");
+
+/// All relevant data for the heat transfer algorithm on the GPU with method 4.
+/// In contrast to method 3 all wall elements are stored inside a single buffer and calculated with a single dispatch.
+pub struct GPUSetupData {
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    compute_pipeline: ComputePipeline,
+    workgroup_size: u32,
+    max_cell_count: usize,
+    wall_element_count: usize,
+    batch: Batch,
+    /// The purely convective sides of the wall elements the shader is compiled for.
+    convective: [bool; 2],
+}
+impl HeatTransfer1D for GPUSetupData {
+    fn setup(
+        materials: Vec<crate::fds::Material>,
+        wall_elements: Vec<WallElement>,
+    ) -> anyhow::Result<Self> {
+        check_no_contact_resistances(&wall_elements)?;
+        let convective = shared_convective_sides(&wall_elements)?;
+        let max_cell_count = wall_elements
+            .iter()
+            .map(|w| w.len())
+            .max()
+            .unwrap_or_default();
+
+        let (device, queue) =
+            get_shared_gpu_device_and_queue().with_context(|| "Failed to get device and queue.")?;
+
+        let shader = insert_material_data(SHADER, &materials);
+        let shader = insert_solver_config(&shader, &get_solver_config());
+        let shader = insert_convective_sides(&shader, convective);
+        let shader = insert_gpu_m3_data(&shader, max_cell_count);
+        let workgroup_size = get_workgroup_size();
+        let shader = insert_workgroup_size(&shader, workgroup_size);
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader Module"),
+            source: wgpu::ShaderSource::Wgsl(Cow::from(shader)),
+        });
+
+        let compute_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("Compute Pipeline"),
+            layout: None,
+            module: &shader_module,
+            entry_point: "compute",
+        });
+
+        let wall_element_count = wall_elements.len();
+        let batch = Batch::build(
+            &device,
+            &compute_pipeline,
+            wall_elements,
+            workgroup_size,
+            max_cell_count,
+        )?;
+        Ok(Self {
+            device,
+            queue,
+            compute_pipeline,
+            workgroup_size,
+            max_cell_count,
+            wall_element_count,
+            batch,
+            convective,
+        })
+    }
+
+    fn reset(&mut self, wall_elements: Vec<WallElement>) -> anyhow::Result<()> {
+        check_no_contact_resistances(&wall_elements)?;
+        if shared_convective_sides(&wall_elements)? != self.convective {
+            bail!("The convective sides of the wall elements differ from the sides the shader is compiled for.");
+        }
+        let max_cell_count = wall_elements
+            .iter()
+            .map(|w| w.len())
+            .max()
+            .unwrap_or_default();
+        if max_cell_count != self.max_cell_count {
+            bail!(
+                "The maximal cell count ({max_cell_count}) differs from the one the shader was built with ({}).",
+                self.max_cell_count
+            );
+        }
+        let wall_element_count = wall_elements.len();
+        self.batch = Batch::build(
+            &self.device,
+            &self.compute_pipeline,
+            wall_elements,
+            self.workgroup_size,
+            self.max_cell_count,
+        )?;
+        self.wall_element_count = wall_element_count;
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        delta_time: f32,
+        wall_heat_transfer_coefficients: &[[f32; 2]],
+        wall_q_in: &[[f32; 2]],
+        wall_temperature: &mut [[f32; 2]],
+    ) -> anyhow::Result<()> {
+        check_buffer_lengths(
+            self.wall_element_count,
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
+        let start = Instant::now();
+        let receiver = self.batch.submit_update_to_queue(
+            &self.device,
+            &self.queue,
+            delta_time,
+            &self.compute_pipeline,
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+        );
+        let submitted = Instant::now();
+        _ = block_on(join(
+            self.batch.receive_update(receiver, wall_temperature),
+            DeviceFuture(&self.device),
+        ));
+        record_gpu_timing(submitted - start, submitted.elapsed());
+        Ok(())
+    }
+
+    fn profile(&self) -> anyhow::Result<Vec<Vec<f32>>> {
+        let data =
+            read_storage_buffer(&self.device, &self.queue, &self.batch.wall_elements_buffer)?;
+        Ok(read_padded_wall_elements(&data, self.max_cell_count))
+    }
+}
+
+/// All data for the batch of all wall elements.
+struct Batch {
+    setup_bind_group: BindGroup,
+    wall_elements_buffer: Buffer,
+    update_bind_group: BindGroup,
+    wall_heat_transfer_coefficients_buffer: Buffer,
+    wall_q_in_buffer: Buffer,
+    delta_time_buffer: Buffer,
+    groups: (u32, u32),
+}
+impl Batch {
+    /// Create the [`Batch`] for all passed [`WallElement`]s.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the wall elements do not fit into a single storage buffer of the device.
+    /// - the wall elements need more workgroups than the device can dispatch at once.
+    fn build(
+        device: &Device,
+        compute_pipeline: &ComputePipeline,
+        wall_elements: Vec<WallElement>,
+        workgroup_size: u32,
+        max_cell_count: usize,
+    ) -> Result<Self> {
+        let limits = device.limits();
+        let wall_element_count = wall_elements.len();
+
+        let wall_element_size =
+            std::mem::size_of::<u32>() + std::mem::size_of::<WallCell>() * max_cell_count;
+        let buffer_size = wall_element_size * wall_element_count;
+        if buffer_size > limits.max_storage_buffer_binding_size as usize {
+            bail!(
+                "The wall elements need {buffer_size} bytes, but the device only supports storage buffers up to {} bytes. Use a chunked method instead.",
+                limits.max_storage_buffer_binding_size
+            );
+        }
+
+        let groups = (wall_element_count as f32 / workgroup_size as f32).ceil() as u32;
+        let max_groups = limits.max_compute_workgroups_per_dimension;
+        let groups = if groups <= max_groups {
+            (groups, 1)
+        } else {
+            let groups_y = groups.div_ceil(max_groups);
+            if groups_y > max_groups {
+                bail!(
+                    "The wall elements need {groups} workgroups, but the device only supports {max_groups} workgroups per dimension."
+                );
+            }
+            (groups.div_ceil(groups_y), groups_y)
+        };
+
+        let mut flattened_wall_elements: Vec<u8> = Vec::with_capacity(buffer_size);
+        for mut wall_element in wall_elements {
+            let cell_count = wall_element.len() as u32;
+            for _ in 0..(max_cell_count - wall_element.len()) {
+                wall_element.push(WallCell::default());
+            }
+            flattened_wall_elements.extend_from_slice(bytemuck::cast_slice(&[cell_count]));
+            flattened_wall_elements
+                .extend_from_slice(bytemuck::cast_slice(wall_element.as_slice()));
+        }
+
+        let (setup_bind_group, wall_elements_buffer) =
+            setup_bind_group(device, compute_pipeline, &flattened_wall_elements);
+        let (
+            update_bind_group,
+            wall_heat_transfer_coefficients_buffer,
+            wall_q_in_buffer,
+            delta_time_buffer,
+        ) = update_bind_group(device, compute_pipeline, wall_element_count, 1);
+        Ok(Batch {
+            setup_bind_group,
+            wall_elements_buffer,
+            update_bind_group,
+            wall_heat_transfer_coefficients_buffer,
+            wall_q_in_buffer,
+            delta_time_buffer,
+            groups,
+        })
+    }
+
+    /// Start the calculation for all wall elements.
+    #[inline]
+    fn submit_update_to_queue(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        delta_time: f32,
+        compute_pipeline: &ComputePipeline,
+        wall_heat_transfer_coefficients: &[[f32; 2]],
+        wall_q_in: &[[f32; 2]],
+    ) -> Receiver<std::result::Result<(), wgpu::BufferAsyncError>> {
+        queue.write_buffer(
+            &self.wall_heat_transfer_coefficients_buffer,
+            0,
+            bytemuck::cast_slice(wall_heat_transfer_coefficients),
+        );
+        queue.write_buffer(&self.wall_q_in_buffer, 0, bytemuck::cast_slice(wall_q_in));
+        queue.write_buffer(
+            &self.delta_time_buffer,
+            0,
+            bytemuck::cast_slice(&[delta_time]),
+        );
+
+        let mut update_command_encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Update Command Encode"),
+        });
+        {
+            let mut update_compute_pass =
+                update_command_encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("Setup Compute Pass"),
+                });
+            update_compute_pass.set_pipeline(compute_pipeline);
+            update_compute_pass.set_bind_group(0, &self.setup_bind_group, &[]);
+            update_compute_pass.set_bind_group(1, &self.update_bind_group, &[]);
+            update_compute_pass.dispatch_workgroups(self.groups.0, self.groups.1, 1);
+        }
+        queue.submit(Some(update_command_encoder.finish()));
+
+        let (sender, receiver) = channel();
+        self.wall_q_in_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, |result| {
+                let _ = sender.send(result);
+            });
+        receiver
+    }
+
+    /// Receive the calculated data for all wall elements.
+    #[inline]
+    async fn receive_update(
+        &self,
+        receiver: Receiver<std::result::Result<(), wgpu::BufferAsyncError>>,
+        wall_temperature_buffer: &mut [[f32; 2]],
+    ) {
+        _ = receiver.await;
+        {
+            let data = self.wall_q_in_buffer.slice(..).get_mapped_range();
+            read_wall_temperatures(&data, wall_temperature_buffer);
+        }
+        self.wall_q_in_buffer.unmap();
+    }
+}
+
+/// Create the setup [`BindGroup`] with all the [`Buffer`]s.
+/// The wall elements [`Buffer`] is returned as well, so the temperatures can be read back for [`HeatTransfer1D::profile`].
+#[inline]
+fn setup_bind_group(
+    device: &Device,
+    compute_pipeline: &ComputePipeline,
+    wall_elements: &[u8],
+) -> (BindGroup, Buffer) {
+    let wall_elements_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Wall Elements Buffer"),
+        contents: wall_elements,
+        usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+    });
+
+    let setup_bind_group_layout = compute_pipeline.get_bind_group_layout(0);
+    let setup_bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("Setup Bind Group"),
+        layout: &setup_bind_group_layout,
+        entries: &[BindGroupEntry {
+            binding: 0,
+            resource: wall_elements_buffer.as_entire_binding(),
+        }],
+    });
+    (setup_bind_group, wall_elements_buffer)
+}
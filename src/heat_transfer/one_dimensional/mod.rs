@@ -1,82 +1,502 @@
-use crate::fds::Material;
+use crate::fds::{
+    cells_from_materials_and_thickness, Discretization, Material, MaterialList, SurfaceCell,
+    DEFAULT_INITIAL_TEMPERATURE,
+};
 use anyhow::*;
 use bytemuck::{Pod, Zeroable};
-use futures::Future;
+use clap::ValueEnum;
+use futures::{executor::block_on, future::join, Future};
+use futures_channel::oneshot::channel;
+use num_traits::Float;
 use std::{
+    cell::Cell,
     ops::{Deref, DerefMut},
+    sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
     task::Poll,
+    time::Duration,
 };
 use wgpu::{
     BindGroup, BindGroupDescriptor, BindGroupEntry, Buffer, BufferDescriptor, BufferUsages,
-    ComputePipeline, Device, MaintainBase,
+    CommandEncoderDescriptor, ComputePipeline, Device, MaintainBase, MapMode, Queue,
 };
 
 pub mod cpu;
 pub mod gpu_m1;
 pub mod gpu_m2;
 pub mod gpu_m3;
+pub mod gpu_m4;
 
 /// The default maximal wall elements fo one chunk. This value is replaced at the start of the program.
-static mut MAX_ELEMENTS_PER_CHUNK: usize = 16384;
+static MAX_ELEMENTS_PER_CHUNK: AtomicUsize = AtomicUsize::new(16384);
 
-/// Set the maximal wall element count per chunk. This should only run once!
-/// If this is run other then the start of the program, the wohle simulation might not work.
-///
-/// # Unsafe
-///
-/// It sets the static value of [`MAX_ELEMENTS_PER_CHUNK`] without checking if the value is read at the same time.
+/// Set the maximal wall element count per chunk.
+/// The value can be set multiple times, the last written value wins.
+/// Already built chunks keep the size they were built with, so the value should be set before [`HeatTransfer1D::setup`] is called.
 pub fn set_max_element_per_chunk(max_elements_per_chunk: usize) {
-    unsafe {
-        MAX_ELEMENTS_PER_CHUNK = max_elements_per_chunk;
-    }
+    MAX_ELEMENTS_PER_CHUNK.store(max_elements_per_chunk, Ordering::Relaxed);
     println!("Set max element per chunk to {max_elements_per_chunk}")
 }
 
-/// Set the maximal wall element count per chunk. This should be used after the value was set.
-///
-/// # Unsafe
-///
-/// It gets the static value of [`MAX_ELEMENTS_PER_CHUNK`] without checking if the value is written at the same time.
-/// Since the value should only be written at the start of the program, no problem should occur.
+/// Get the maximal wall element count per chunk.
 #[inline]
 pub fn get_max_element_per_chunk() -> usize {
-    unsafe { MAX_ELEMENTS_PER_CHUNK }
+    MAX_ELEMENTS_PER_CHUNK.load(Ordering::Relaxed)
+}
+
+/// The default workgroup size of the GPU methods. This value is replaced at the start of the program.
+static WORKGROUP_SIZE: AtomicU32 = AtomicU32::new(256);
+
+/// Set the workgroup size of the GPU methods.
+/// Already built shaders keep the workgroup size they were built with, so the value should be set before [`HeatTransfer1D::setup`] is called.
+pub fn set_workgroup_size(workgroup_size: u32) {
+    WORKGROUP_SIZE.store(workgroup_size, Ordering::Relaxed);
+    println!("Set workgroup size to {workgroup_size}")
+}
+
+/// Get the workgroup size of the GPU methods.
+#[inline]
+pub fn get_workgroup_size() -> u32 {
+    WORKGROUP_SIZE.load(Ordering::Relaxed)
+}
+
+/// The floating point precision of the CPU method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Precision {
+    #[default]
+    F32,
+    F64,
+}
+
+/// Whether the CPU method uses double precision. This value is replaced at the start of the program.
+static CPU_F64_PRECISION: AtomicBool = AtomicBool::new(false);
+
+/// Set the floating point precision of the CPU method.
+pub fn set_cpu_precision(precision: Precision) {
+    CPU_F64_PRECISION.store(precision == Precision::F64, Ordering::Relaxed);
+    println!("Set CPU precision to {precision:?}")
+}
+
+/// Get the floating point precision of the CPU method.
+#[inline]
+pub fn get_cpu_precision() -> Precision {
+    if CPU_F64_PRECISION.load(Ordering::Relaxed) {
+        Precision::F64
+    } else {
+        Precision::F32
+    }
+}
+
+/// The constants of the time step control and the radiation used by the CPU and GPU methods.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolverConfig {
+    /// Stefan Boltzmann constant.
+    pub sigma: f32,
+    /// The maximum temperature difference that may be between neighboring cells before the time step is reduced.
+    pub max_delta_temperature: f32,
+    /// The maximum number of times the time step may be reduced.
+    pub max_time_subdivisions: usize,
+    /// Whether the emission of the wall surfaces is calculated. If disabled, the emission terms are zero independent of the emissivity of the materials, while the convective heat transfer is kept.
+    pub radiation: bool,
+}
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            sigma: cpu::SIGMA,
+            max_delta_temperature: cpu::MAX_DELTA_TEMPERATURE,
+            max_time_subdivisions: cpu::MAX_TIME_SUBDIVISIONS,
+            radiation: true,
+        }
+    }
+}
+
+/// The bits of the default Stefan Boltzmann constant. This value is replaced at the start of the program.
+static SOLVER_SIGMA: AtomicU32 = AtomicU32::new(cpu::SIGMA.to_bits());
+/// The bits of the default maximum temperature difference between neighboring cells. This value is replaced at the start of the program.
+static SOLVER_MAX_DELTA_TEMPERATURE: AtomicU32 =
+    AtomicU32::new(cpu::MAX_DELTA_TEMPERATURE.to_bits());
+/// The default maximum number of time step reductions. This value is replaced at the start of the program.
+static SOLVER_MAX_TIME_SUBDIVISIONS: AtomicUsize = AtomicUsize::new(cpu::MAX_TIME_SUBDIVISIONS);
+/// The default state of the surface emission. This value is replaced at the start of the program.
+static SOLVER_RADIATION: AtomicBool = AtomicBool::new(true);
+
+/// Set the [`SolverConfig`] of the CPU and GPU methods.
+/// Already set up methods keep the configuration they were set up with, so the value should be set before [`HeatTransfer1D::setup`] is called.
+pub fn set_solver_config(solver_config: SolverConfig) {
+    SOLVER_SIGMA.store(solver_config.sigma.to_bits(), Ordering::Relaxed);
+    SOLVER_MAX_DELTA_TEMPERATURE.store(
+        solver_config.max_delta_temperature.to_bits(),
+        Ordering::Relaxed,
+    );
+    SOLVER_MAX_TIME_SUBDIVISIONS.store(solver_config.max_time_subdivisions, Ordering::Relaxed);
+    SOLVER_RADIATION.store(solver_config.radiation, Ordering::Relaxed);
+    println!("Set solver config to {solver_config:?}")
 }
 
-/// The data of a single [`WallCell`]
+/// Get the [`SolverConfig`] of the CPU and GPU methods.
+#[inline]
+pub fn get_solver_config() -> SolverConfig {
+    SolverConfig {
+        sigma: f32::from_bits(SOLVER_SIGMA.load(Ordering::Relaxed)),
+        max_delta_temperature: f32::from_bits(SOLVER_MAX_DELTA_TEMPERATURE.load(Ordering::Relaxed)),
+        max_time_subdivisions: SOLVER_MAX_TIME_SUBDIVISIONS.load(Ordering::Relaxed),
+        radiation: SOLVER_RADIATION.load(Ordering::Relaxed),
+    }
+}
+
+/// The data of a single [`WallCell`]. The CPU solver can calculate with double precision, see [`Precision`].
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
-pub struct WallCell {
-    pub size: f32,
+pub struct WallCell<F = f32> {
+    pub size: F,
     pub material: u32,
-    pub temperature: f32,
+    pub temperature: F,
 }
 unsafe impl Zeroable for WallCell {}
 unsafe impl Pod for WallCell {}
 
 /// The data of a single one dimensional [`WallElement`].
 #[derive(Debug, Clone, PartialEq, Default)]
-pub struct WallElement(pub Vec<WallCell>);
+pub struct WallElement<F = f32> {
+    cells: Vec<WallCell<F>>,
+    /// The thermal contact resistance in m²K/W between a cell and the following cell. Empty if all cells are in perfect contact.
+    contact_resistances: Vec<F>,
+    /// Whether the front and the back side exchange heat with the gas only by convection, so the emission of the side is ignored.
+    convective: [bool; 2],
+}
 impl WallElement {
     pub fn new(inner: Vec<WallCell>) -> Self {
-        Self(inner)
+        Self {
+            cells: inner,
+            contact_resistances: vec![],
+            convective: [false, false],
+        }
+    }
+
+    /// Creates a [`WallElement`] with a thermal contact resistance for every interface between two neighboring cells.
+    pub fn with_contact_resistances(inner: Vec<WallCell>, contact_resistances: Vec<f32>) -> Self {
+        Self {
+            cells: inner,
+            contact_resistances,
+            convective: [false, false],
+        }
+    }
+
+    /// Marks the front and / or the back side as purely convective. The side exchanges heat only by `h * (T_gas - T_surface)` and its emission is ignored.
+    /// Note `q_f` and / or `q_b` has to be set to `h * T_gas`.
+    pub fn with_convective(mut self, convective: [bool; 2]) -> Self {
+        self.convective = convective;
+        self
+    }
+
+    /// Creates a [`WallElement`] from layers of a material index inside the [`MaterialList`] and a thickness in m, like a `SURF` with `MATL_ID` and `THICKNESS`.
+    /// The layers are divided with [`Discretization::Geometric`] and all cells start at [`DEFAULT_INITIAL_TEMPERATURE`], see [`cells_from_materials_and_thickness`].
+    /// The material of a [`WallCell`] is the index of the layer material, so the solver has to be set up with the materials of [`MaterialList::into_materials`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if no layer is passed or a material index does not exist inside the [`MaterialList`].
+    pub fn from_layers(material_list: &MaterialList, layers: &[(usize, f32)]) -> Self {
+        Self::from_layers_with_discretization(material_list, layers, Discretization::Geometric)
+    }
+
+    /// Creates a [`WallElement`] like [`WallElement::from_layers`], but each layer is divided into `cell_count` cells of equal size, like `CELL_COUNT` of a `SURF`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no layer is passed.
+    pub fn from_uniform_layers(
+        material_list: &MaterialList,
+        layers: &[(usize, f32)],
+        cell_count: usize,
+    ) -> Self {
+        Self::from_layers_with_discretization(
+            material_list,
+            layers,
+            Discretization::Uniform { n: cell_count },
+        )
+    }
+
+    /// Creates a [`WallElement`] from the layers with the passed [`Discretization`], including the boundary cells.
+    fn from_layers_with_discretization(
+        material_list: &MaterialList,
+        layers: &[(usize, f32)],
+        discretization: Discretization,
+    ) -> Self {
+        let (material_ids, thicknesses): (Vec<_>, Vec<_>) = layers.iter().copied().unzip();
+        let cells = cells_from_materials_and_thickness(
+            material_list,
+            &material_ids,
+            &thicknesses,
+            DEFAULT_INITIAL_TEMPERATURE,
+            discretization,
+        )
+        .into_iter()
+        .map(|SurfaceCell { material_id, size }| WallCell {
+            size,
+            material: material_id,
+            temperature: DEFAULT_INITIAL_TEMPERATURE,
+        })
+        .collect();
+        Self::new(cells)
+    }
+}
+
+impl<F: Float> WallElement<F> {
+    /// Returns the thermal contact resistance between the cell at `index` and the following cell.
+    #[inline]
+    pub fn contact_resistance(&self, index: usize) -> F {
+        self.contact_resistances
+            .get(index)
+            .copied()
+            .unwrap_or_else(F::zero)
+    }
+
+    /// Returns the thermal contact resistances between each cell and the following cell. Empty if all cells are in perfect contact.
+    #[inline]
+    pub fn contact_resistances(&self) -> &[F] {
+        &self.contact_resistances
+    }
+
+    /// Returns whether the front and the back side are purely convective, see [`WallElement::with_convective`].
+    #[inline]
+    pub fn convective(&self) -> [bool; 2] {
+        self.convective
+    }
+
+    /// Returns whether any interface between two cells has a thermal contact resistance.
+    #[inline]
+    pub fn has_contact_resistance(&self) -> bool {
+        self.contact_resistances.iter().any(|r| !r.is_zero())
+    }
+
+    /// Converts the [`WallElement`] to another precision.
+    pub fn cast<T: Float>(self) -> WallElement<T> {
+        let cast = |value: F| T::from(value).unwrap_or_else(T::nan);
+        WallElement {
+            cells: self
+                .cells
+                .into_iter()
+                .map(|cell| WallCell {
+                    size: cast(cell.size),
+                    material: cell.material,
+                    temperature: cast(cell.temperature),
+                })
+                .collect(),
+            contact_resistances: self.contact_resistances.into_iter().map(cast).collect(),
+            convective: self.convective,
+        }
     }
 
     #[allow(clippy::should_implement_trait)]
-    pub fn into_iter(self) -> std::vec::IntoIter<WallCell> {
-        self.0.into_iter()
+    pub fn into_iter(self) -> std::vec::IntoIter<WallCell<F>> {
+        self.cells.into_iter()
     }
 }
 
-impl Deref for WallElement {
-    type Target = Vec<WallCell>;
+impl<F> Deref for WallElement<F> {
+    type Target = Vec<WallCell<F>>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.cells
     }
 }
-impl DerefMut for WallElement {
+impl<F> DerefMut for WallElement<F> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.cells
+    }
+}
+
+/// The minimal number of cells of a [`WallElement`]. The solver needs a boundary cell on both sides and at least one inner cell.
+pub const MIN_CELL_COUNT: usize = 3;
+
+/// Checks that the buffers passed to [`HeatTransfer1D::update`] contain a value for each of the `wall_element_count` [`WallElement`]s the method was set up with.
+///
+/// # Errors
+///
+/// This function will return an error if the length of a buffer differs from the number of [`WallElement`]s.
+pub fn check_buffer_lengths(
+    wall_element_count: usize,
+    wall_heat_transfer_coefficients: &[[f32; 2]],
+    wall_q_in: &[[f32; 2]],
+    wall_temperature: &[[f32; 2]],
+) -> Result<()> {
+    for (name, len) in [
+        (
+            "wall heat transfer coefficients",
+            wall_heat_transfer_coefficients.len(),
+        ),
+        ("wall q in", wall_q_in.len()),
+        ("wall temperature", wall_temperature.len()),
+    ] {
+        if len != wall_element_count {
+            bail!("The {name} buffer should have a value for every wall element, expected {wall_element_count}, got {len}.");
+        }
+    }
+    Ok(())
+}
+
+/// Checks that all [`WallElement`]s have at least [`MIN_CELL_COUNT`] cells.
+///
+/// # Errors
+///
+/// This function will return an error if a [`WallElement`] has less than [`MIN_CELL_COUNT`] cells.
+pub fn check_cell_counts(wall_elements: &[WallElement]) -> Result<()> {
+    if let Some((i, wall_element)) = wall_elements
+        .iter()
+        .enumerate()
+        .find(|(_, w)| w.len() < MIN_CELL_COUNT)
+    {
+        bail!(
+            "The wall element {i} has {} cells, but at least {MIN_CELL_COUNT} are needed.",
+            wall_element.len()
+        );
+    }
+    Ok(())
+}
+
+/// Checks that no [`WallElement`] has a thermal contact resistance. The GPU methods do not support contact resistances.
+///
+/// # Errors
+///
+/// This function will return an error if a [`WallElement`] has a contact resistance other than zero.
+pub fn check_no_contact_resistances(wall_elements: &[WallElement]) -> Result<()> {
+    if let Some(i) = wall_elements
+        .iter()
+        .position(|w| w.has_contact_resistance())
+    {
+        bail!("The wall element {i} has a thermal contact resistance, which is not supported by this method.");
+    }
+    Ok(())
+}
+
+/// Returns the purely convective sides shared by all [`WallElement`]s, see [`WallElement::with_convective`].
+/// The GPU methods compile the sides into their shaders, so all [`WallElement`]s have to share them.
+///
+/// # Errors
+///
+/// This function will return an error if the [`WallElement`]s have different convective sides.
+pub fn shared_convective_sides(wall_elements: &[WallElement]) -> Result<[bool; 2]> {
+    let convective = wall_elements
+        .first()
+        .map(WallElement::convective)
+        .unwrap_or_default();
+    if let Some(i) = wall_elements
+        .iter()
+        .position(|w| w.convective() != convective)
+    {
+        bail!("The wall element {i} has other convective sides than the first wall element, which is not supported by this method.");
+    }
+    Ok(convective)
+}
+
+/// Whether the GPU methods record the submit and the map-back time of each update. This value is replaced at the start of the program.
+static DETAILED_TIMING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// The [`GpuTiming`] accumulated by the GPU updates of the current thread since the last call of [`take_gpu_timing`].
+    static GPU_TIMING: Cell<GpuTiming> = Cell::new(GpuTiming::default());
+}
+
+/// Set whether the GPU methods record the submit and the map-back time of each update.
+pub fn set_detailed_timing(detailed_timing: bool) {
+    DETAILED_TIMING.store(detailed_timing, Ordering::Relaxed);
+    println!("Set detailed timing to {detailed_timing}")
+}
+
+/// Get whether the GPU methods record the submit and the map-back time of each update.
+#[inline]
+pub fn get_detailed_timing() -> bool {
+    DETAILED_TIMING.load(Ordering::Relaxed)
+}
+
+/// The time spent in the parts of the GPU updates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GpuTiming {
+    /// The time to write the input buffers and to submit the dispatches.
+    pub submit: Duration,
+    /// The time to wait for the dispatches and to copy the results back from the mapped buffers.
+    pub map_back: Duration,
+}
+
+/// Adds the passed times to the [`GpuTiming`] of the current thread, if detailed timing is enabled.
+#[inline]
+fn record_gpu_timing(submit: Duration, map_back: Duration) {
+    if get_detailed_timing() {
+        GPU_TIMING.with(|timing| {
+            let GpuTiming {
+                submit: total_submit,
+                map_back: total_map_back,
+            } = timing.get();
+            timing.set(GpuTiming {
+                submit: total_submit + submit,
+                map_back: total_map_back + map_back,
+            });
+        });
+    }
+}
+
+/// Returns the [`GpuTiming`] accumulated by the GPU updates of the current thread and resets it.
+pub fn take_gpu_timing() -> GpuTiming {
+    GPU_TIMING.with(Cell::take)
+}
+
+/// Whether the CPU methods count the time steps that needed more subdivisions than [`SolverConfig::max_time_subdivisions`] allows. This value is replaced at the start of the program.
+static DIAGNOSTICS: AtomicBool = AtomicBool::new(false);
+
+/// Set whether the CPU methods count the time steps that needed more subdivisions than [`SolverConfig::max_time_subdivisions`] allows.
+/// Already set up methods keep the state they were set up with, so the value should be set before [`HeatTransfer1D::setup`] is called.
+pub fn set_diagnostics(diagnostics: bool) {
+    DIAGNOSTICS.store(diagnostics, Ordering::Relaxed);
+    println!("Set diagnostics to {diagnostics}")
+}
+
+/// Get whether the CPU methods count the time steps that needed more subdivisions than [`SolverConfig::max_time_subdivisions`] allows.
+#[inline]
+pub fn get_diagnostics() -> bool {
+    DIAGNOSTICS.load(Ordering::Relaxed)
+}
+
+/// How often the time step of a single [`WallElement`] was subdivided fewer times than its temperature differences required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SubdivisionDiagnostics {
+    /// The number of time steps of all wall elements.
+    pub steps: usize,
+    /// The number of time steps of all wall elements that were capped at [`SolverConfig::max_time_subdivisions`] and are therefore under-resolved.
+    pub capped_steps: usize,
+}
+
+/// Counts the time steps of the wall elements of a simulation for the [`SubdivisionDiagnostics`]. The wall elements may be updated in parallel.
+#[derive(Debug, Default)]
+pub struct SubdivisionCounter {
+    steps: AtomicUsize,
+    capped_steps: AtomicUsize,
+}
+impl SubdivisionCounter {
+    /// Returns a new counter, if diagnostics are enabled with [`set_diagnostics`].
+    pub fn new_if_enabled() -> Option<Self> {
+        get_diagnostics().then(Self::default)
+    }
+
+    /// Counts a single time step of a wall element.
+    #[inline]
+    pub fn record(&self, capped: bool) {
+        self.steps.fetch_add(1, Ordering::Relaxed);
+        if capped {
+            self.capped_steps.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the counted [`SubdivisionDiagnostics`].
+    pub fn diagnostics(&self) -> SubdivisionDiagnostics {
+        SubdivisionDiagnostics {
+            steps: self.steps.load(Ordering::Relaxed),
+            capped_steps: self.capped_steps.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets the counter for a new simulation.
+    pub fn reset(&self) {
+        self.steps.store(0, Ordering::Relaxed);
+        self.capped_steps.store(0, Ordering::Relaxed);
     }
 }
 
@@ -105,6 +525,14 @@ pub trait HeatTransfer1D {
     where
         Self: Sized;
 
+    /// Replaces the [`WallElement`]s of an already initialized simulation while keeping the device, the pipelines and the materials passed to [`HeatTransfer1D::setup`].
+    /// The passed [`WallElement`]s must have the same cell layout as the ones passed to [`HeatTransfer1D::setup`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the reinitialization fails.
+    fn reset(&mut self, wall_elements: Vec<WallElement>) -> Result<()>;
+
     /// Updates the heat transfer with the next time step.
     ///
     /// # Errors
@@ -117,9 +545,130 @@ pub trait HeatTransfer1D {
         wall_q_in: &[[f32; 2]],
         wall_temperature: &mut [[f32; 2]],
     ) -> Result<()>;
+
+    /// Updates the heat transfer with the next time step and an additional volumetric heat generation in W/m³.
+    /// `heat_generation` contains a value for every cell of every [`WallElement`], one [`WallElement`] after another.
+    /// The default implementation only supports a heat generation of zero and is equal to [`HeatTransfer1D::update`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the update fails.
+    /// - the method does not support a heat generation other than zero.
+    fn update_with_heat_generation(
+        &mut self,
+        delta_time: f32,
+        wall_heat_transfer_coefficients: &[[f32; 2]],
+        wall_q_in: &[[f32; 2]],
+        heat_generation: &[f32],
+        wall_temperature: &mut [[f32; 2]],
+    ) -> Result<()> {
+        if heat_generation.iter().any(|q| *q != 0.0) {
+            bail!("Volumetric heat generation is not supported by this method.");
+        }
+        self.update(
+            delta_time,
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )
+    }
+
+    /// Returns the [`SubdivisionDiagnostics`] since the last [`HeatTransfer1D::setup`] or [`HeatTransfer1D::reset`], if the method counts them.
+    /// Only the CPU methods count them and only if diagnostics are enabled with [`set_diagnostics`].
+    fn subdivision_diagnostics(&self) -> Option<SubdivisionDiagnostics> {
+        None
+    }
+
+    /// Returns the temperatures of all cells of every [`WallElement`], including the boundary cells at the front and the back, in the order the [`WallElement`]s were passed to [`HeatTransfer1D::setup`] or [`HeatTransfer1D::reset`].
+    /// The GPU methods read the temperatures back from the device.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the temperatures can not be read back.
+    fn profile(&self) -> Result<Vec<Vec<f32>>>;
+}
+
+/// The size in bytes of a WGSL `vec2<f32>`, the element type of the `wall_q_in` array of the GPU shaders.
+const WALL_SLOT_SIZE: usize = 8;
+
+// The GPU methods write the front and back temperature of a wall element into its slot of the `wall_q_in` buffer, which is then read back as the wall temperatures.
+// The energy insertions and the temperatures therefore both have to match the layout of a slot.
+const _: () = assert!(
+    std::mem::size_of::<[f32; 2]>() == WALL_SLOT_SIZE,
+    "The energy insertions and temperatures of a wall element have to match a WGSL vec2<f32>."
+);
+
+/// Copies the temperatures that were read back from the mapped `wall_q_in` buffer into the wall temperatures.
+/// The buffer is reused for the read back, see [`update_bind_group`].
+///
+/// # Panics
+///
+/// Panics if the mapped data does not contain exactly one slot for each wall temperature.
+#[inline]
+fn read_wall_temperatures(data: &[u8], wall_temperature: &mut [[f32; 2]]) {
+    assert_eq!(
+        data.len(),
+        wall_temperature.len() * WALL_SLOT_SIZE,
+        "The read back buffer does not contain one slot for each of the {} wall temperatures.",
+        wall_temperature.len()
+    );
+    wall_temperature.copy_from_slice(bytemuck::cast_slice(data));
+}
+
+/// Copies a storage [`Buffer`] of the GPU methods into a new mappable buffer and reads it back.
+/// The storage buffer must be created with [`BufferUsages::COPY_SRC`].
+///
+/// # Errors
+///
+/// This function will return an error if the buffer can not be mapped.
+fn read_storage_buffer(device: &Device, queue: &Queue, buffer: &Buffer) -> Result<Vec<u8>> {
+    let read_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("Profile Read Buffer"),
+        size: buffer.size(),
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut command_encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("Profile Command Encoder"),
+    });
+    command_encoder.copy_buffer_to_buffer(buffer, 0, &read_buffer, 0, buffer.size());
+    queue.submit(Some(command_encoder.finish()));
+
+    let (sender, receiver) = channel();
+    read_buffer.slice(..).map_async(MapMode::Read, |result| {
+        let _ = sender.send(result);
+    });
+    let (result, _) = block_on(join(receiver, DeviceFuture(device)));
+    result
+        .with_context(|| "The profile read buffer was dropped before it was mapped.")?
+        .with_context(|| "Failed to map the profile read buffer.")?;
+    let data = read_buffer.slice(..).get_mapped_range().to_vec();
+    read_buffer.unmap();
+    Ok(data)
+}
+
+/// Returns the cell temperatures of the wall elements that were read back from a `wall_elements` buffer of the GPU methods 3 and 4.
+/// Each wall element is stored as its cell count followed by `max_cell_count` [`WallCell`]s, of which only the first cell count are used.
+#[inline]
+fn read_padded_wall_elements(data: &[u8], max_cell_count: usize) -> Vec<Vec<f32>> {
+    let wall_element_size =
+        std::mem::size_of::<u32>() + std::mem::size_of::<WallCell>() * max_cell_count;
+    data.chunks_exact(wall_element_size)
+        .map(|wall_element| {
+            let (cell_count, wall_cells) = wall_element.split_at(std::mem::size_of::<u32>());
+            let cell_count = bytemuck::pod_read_unaligned::<u32>(cell_count) as usize;
+            wall_cells
+                .chunks_exact(std::mem::size_of::<WallCell>())
+                .take(cell_count)
+                .map(|wall_cell| bytemuck::pod_read_unaligned::<WallCell>(wall_cell).temperature)
+                .collect()
+        })
+        .collect()
 }
 
 /// Create the update [`BindGroup`] with all the [`Buffer`]s.
+/// The `wall_q_in` buffer is also used to read back the temperatures: the shaders overwrite the energy insertions of each wall element with its front and back temperature, so it can be mapped for reading and copied with [`read_wall_temperatures`].
 #[inline]
 fn update_bind_group(
     device: &Device,
@@ -135,7 +684,7 @@ fn update_bind_group(
     });
     let wall_q_in_buffer = device.create_buffer(&BufferDescriptor {
         label: Some("Wall Energy Insertions"),
-        size: (std::mem::size_of::<[f32; 2]>() * wall_element_count) as u64,
+        size: (WALL_SLOT_SIZE * wall_element_count) as u64,
         usage: BufferUsages::STORAGE
             | BufferUsages::MAP_READ
             | BufferUsages::MAP_WRITE
@@ -175,3 +724,132 @@ fn update_bind_group(
         delta_time_buffer,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fds::Ramp, heat_transfer::gpu::get_shared_gpu_device_and_queue};
+
+    fn steel() -> Material {
+        Material {
+            specific_heat: Ramp::from(460.0),
+            conductivity: Ramp::from(45.8),
+            density: 7850.0,
+            emissivity: 0.9,
+        }
+    }
+
+    /// Sets up the method `H` with a single wall element and returns the error of an update with a `wall_q_in` buffer for two wall elements.
+    fn wrong_buffer_length_error<H: HeatTransfer1D>() -> String {
+        let wall_element = WallElement::new(vec![
+            WallCell {
+                size: 0.001,
+                material: 0,
+                temperature: 20.0,
+            };
+            6
+        ]);
+        let mut heat_transfer = H::setup(vec![steel()], vec![wall_element]).unwrap();
+        heat_transfer
+            .update(1.0, &[[10.0; 2]], &[[0.0; 2]; 2], &mut [[0.0; 2]])
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn wrong_buffer_length_gives_the_same_error_for_all_methods() {
+        let expected =
+            "The wall q in buffer should have a value for every wall element, expected 1, got 2.";
+        assert_eq!(wrong_buffer_length_error::<cpu::CPUSetupData>(), expected);
+        assert_eq!(
+            wrong_buffer_length_error::<cpu::CPUF64SetupData>(),
+            expected
+        );
+        if get_shared_gpu_device_and_queue().is_err() {
+            println!("Skipped the GPU methods, since no GPU is available.");
+            return;
+        }
+        assert_eq!(
+            wrong_buffer_length_error::<gpu_m1::GPUSetupData>(),
+            expected
+        );
+        assert_eq!(
+            wrong_buffer_length_error::<gpu_m2::GPUSetupData>(),
+            expected
+        );
+        assert_eq!(
+            wrong_buffer_length_error::<gpu_m3::GPUSetupData>(),
+            expected
+        );
+        assert_eq!(
+            wrong_buffer_length_error::<gpu_m4::GPUSetupData>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn gpu_methods_reject_contact_resistances() {
+        let materials = vec![steel()];
+        let cell = WallCell {
+            size: 0.001,
+            material: 0,
+            temperature: 20.0,
+        };
+        let wall_elements = vec![WallElement::with_contact_resistances(
+            vec![cell; 6],
+            vec![0.0, 0.0, 1e-3, 0.0, 0.0],
+        )];
+
+        let errors = [
+            gpu_m1::GPUSetupData::setup(materials.clone(), wall_elements.clone()).err(),
+            gpu_m2::GPUSetupData::setup(materials.clone(), wall_elements.clone()).err(),
+            gpu_m3::GPUSetupData::setup(materials.clone(), wall_elements.clone()).err(),
+            gpu_m4::GPUSetupData::setup(materials, wall_elements).err(),
+        ];
+        for error in errors {
+            let error = error.expect("setup has to fail").to_string();
+            assert!(error.contains("contact resistance"), "{error}");
+        }
+    }
+
+    #[test]
+    fn shared_convective_sides_of_wall_elements() {
+        let wall_element = WallElement::new(vec![WallCell::default(); 3]);
+        assert_eq!(shared_convective_sides(&[]).unwrap(), [false, false]);
+        assert_eq!(
+            shared_convective_sides(&[
+                wall_element.clone().with_convective([true, false]),
+                wall_element.clone().with_convective([true, false]),
+            ])
+            .unwrap(),
+            [true, false]
+        );
+        assert!(shared_convective_sides(&[
+            wall_element.clone().with_convective([true, true]),
+            wall_element,
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn read_wall_temperatures_round_trips_slots() {
+        let expected = [[1.5, 2.5], [300.0, 400.0], [-20.0, 1200.25]];
+        // A WGSL `vec2<f32>` slot holds the front and the back temperature as little endian floats.
+        let data = expected
+            .iter()
+            .flatten()
+            .flat_map(|t: &f32| t.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(data.len(), expected.len() * WALL_SLOT_SIZE);
+
+        let mut wall_temperature = [[0.0; 2]; 3];
+        read_wall_temperatures(&data, &mut wall_temperature);
+        assert_eq!(wall_temperature, expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "one slot for each of the 2 wall temperatures")]
+    fn read_wall_temperatures_rejects_missing_slots() {
+        read_wall_temperatures(&[0; WALL_SLOT_SIZE], &mut [[0.0; 2]; 2]);
+    }
+}
@@ -1,4 +1,11 @@
-use crate::fds::{Material, Ramp};
+use crate::{
+    fds::{Material, Ramp},
+    heat_transfer::one_dimensional::{
+        get_solver_config, get_workgroup_size, gpu_m1, gpu_m2, gpu_m3, gpu_m4, SolverConfig,
+    },
+};
+use anyhow::*;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
 use std::fmt::Write;
 
 /// Transforms a [`Ramp`] to the inner of a ramp function for a shader.
@@ -115,3 +122,189 @@ pub fn insert_gpu_m3_data(shader: &str, max_cell_count: usize) -> String {
     );
     shader.replace("//! max_cell_count\n", &max_cell_count)
 }
+
+/// Insert the workgroup size to a shader.
+/// Shaders that need the size inside the code can additionally use the `WORKGROUP_SIZE` constant.
+pub fn insert_workgroup_size(shader: &str, workgroup_size: u32) -> String {
+    let workgroup_size_const = format!("const WORKGROUP_SIZE: u32 = {workgroup_size}u; //");
+    let workgroup_size = format!("@workgroup_size({workgroup_size}) //");
+    shader
+        .replace("//! workgroup_size\n", &workgroup_size)
+        .replace("//! workgroup_size_const\n", &workgroup_size_const)
+}
+
+/// Insert the constants of the [`SolverConfig`] to a shader, so the GPU methods use the same values as the CPU methods.
+pub fn insert_solver_config(shader: &str, solver_config: &SolverConfig) -> String {
+    let max_delta_temperature = format!(
+        "const MAX_DELTA_TEMPERATURE: f32 = {:.32}; //",
+        solver_config.max_delta_temperature
+    );
+    let max_time_subdivisions = format!(
+        "const MAX_TIME_SUBDIVISIONS: u32 = {}u; //",
+        solver_config.max_time_subdivisions
+    );
+    let sigma = format!("const SIGMA: f32 = {:.32}; //", solver_config.sigma);
+    let radiation = format!("const RADIATION: bool = {}; //", solver_config.radiation);
+    shader
+        .replace("//! max_delta_temperature\n", &max_delta_temperature)
+        .replace("//! max_time_subdivisions\n", &max_time_subdivisions)
+        .replace("//! sigma\n", &sigma)
+        .replace("//! radiation\n", &radiation)
+}
+
+/// Insert the purely convective sides of the wall elements to a shader, see [`WallElement::with_convective`](super::one_dimensional::WallElement::with_convective).
+pub fn insert_convective_sides(shader: &str, convective: [bool; 2]) -> String {
+    let convective_front = format!("const CONVECTIVE_FRONT: bool = {}; //", convective[0]);
+    let convective_back = format!("const CONVECTIVE_BACK: bool = {}; //", convective[1]);
+    shader
+        .replace("//! convective_front\n", &convective_front)
+        .replace("//! convective_back\n", &convective_back)
+}
+
+/// Returns a representative material set for [`validate_shaders`].
+/// It contains materials with constant values, short ramps and long ramps, so every branch of the generated ramp functions and the `default` and `case` arms of the material switches are generated.
+fn validation_materials() -> Vec<Material> {
+    vec![
+        Material {
+            specific_heat: Ramp::from(900.0),
+            conductivity: Ramp::from(1.2),
+            density: 2300.0,
+            emissivity: 0.0,
+        },
+        Material {
+            specific_heat: Ramp::from(vec![(20.0, 900.0), (200.0, 1000.0), (1200.0, 1100.0)]),
+            conductivity: Ramp::from(vec![(0.0, 1.4), (1200.0, 0.5)]),
+            density: 2300.0,
+            emissivity: 0.7,
+        },
+        Material {
+            specific_heat: Ramp::from(vec![
+                (20.0, 439.8),
+                (400.0, 605.9),
+                (630.0, 786.4),
+                (690.0, 936.9),
+                (720.0, 1388.3),
+                (735.0, 5000.0),
+                (750.0, 1482.9),
+                (780.0, 908.7),
+                (830.0, 725.0),
+                (900.0, 650.0),
+                (1200.0, 650.0),
+            ]),
+            conductivity: Ramp::from(vec![(20.0, 53.3), (800.0, 27.3), (1200.0, 27.3)]),
+            density: 7850.0,
+            emissivity: 0.79,
+        },
+    ]
+}
+
+/// Returns the name and the generated source of the shader of every GPU method, built like in the setup of the methods with the passed materials.
+/// The shader of the GPU M2 method is built for a single wall element with a cell of each material.
+fn generated_shaders(materials: &[Material]) -> Vec<(&'static str, String)> {
+    let solver_config = get_solver_config();
+    let workgroup_size = get_workgroup_size();
+    let cell_materials = (0..materials.len() as u32)
+        .flat_map(|m| [m, m])
+        .collect::<Vec<_>>();
+    let cell_sizes = vec![0.01; cell_materials.len()];
+
+    let build = |shader: &str, insert: &dyn Fn(&str) -> String| {
+        let shader = insert_material_data(shader, materials);
+        let shader = insert_solver_config(&shader, &solver_config);
+        let shader = insert_convective_sides(&shader, [true, false]);
+        let shader = insert(&shader);
+        insert_workgroup_size(&shader, workgroup_size)
+    };
+    vec![
+        ("gpu_m1.wgsl", build(gpu_m1::SHADER, &|s| s.to_string())),
+        (
+            "gpu_m2.wgsl",
+            build(gpu_m2::SHADER, &|s| {
+                insert_gpu_m2_data(s, &cell_sizes, &cell_materials)
+            }),
+        ),
+        (
+            "gpu_m3.wgsl",
+            build(gpu_m3::SHADER, &|s| {
+                insert_gpu_m3_data(s, cell_materials.len())
+            }),
+        ),
+        (
+            "gpu_m4.wgsl",
+            build(gpu_m4::SHADER, &|s| {
+                insert_gpu_m3_data(s, cell_materials.len())
+            }),
+        ),
+    ]
+}
+
+/// Returns the passed source with the line number in front of every line.
+fn numbered_source(source: &str) -> String {
+    source
+        .lines()
+        .enumerate()
+        .fold(String::new(), |mut output, (i, line)| {
+            let _ = writeln!(output, "{:>5} | {line}", i + 1);
+            output
+        })
+}
+
+/// Parses and validates a generated WGSL shader with `naga`.
+///
+/// # Errors
+///
+/// This function will return an error with the location of the error and the whole numbered source if the shader could not be parsed or is invalid.
+fn validate_shader(name: &str, source: &str) -> Result<()> {
+    let report = match naga::front::wgsl::parse_str(source) {
+        std::result::Result::Ok(module) => {
+            match Validator::new(ValidationFlags::all(), Capabilities::default()).validate(&module)
+            {
+                std::result::Result::Ok(_) => return Ok(()),
+                Err(err) => (
+                    err.location(source),
+                    err.emit_to_string_with_path(source, name),
+                ),
+            }
+        }
+        Err(err) => (
+            err.location(source),
+            err.emit_to_string_with_path(source, name),
+        ),
+    };
+    let location = match report.0 {
+        Some(location) => format!(
+            " at line {} column {}",
+            location.line_number, location.line_position
+        ),
+        None => String::new(),
+    };
+    bail!(
+        "The generated shader {name} is invalid{location}.\n{}\nGenerated source:\n{}",
+        report.1,
+        numbered_source(source)
+    )
+}
+
+/// Generates the shaders of all GPU methods with a representative material set and the set solver config and workgroup size and validates them with `naga`.
+/// No GPU is needed, so errors of the generated code are found before a simulation fails inside the driver.
+///
+/// # Errors
+///
+/// This function will return an error for every shader that could not be parsed or is invalid.
+pub fn validate_shaders() -> Result<(), Vec<Error>> {
+    let errors = generated_shaders(&validation_materials())
+        .into_iter()
+        .filter_map(|(name, source)| match validate_shader(name, &source) {
+            std::result::Result::Ok(()) => {
+                println!("  {name} is valid");
+                None
+            }
+            Err(err) => Some(err),
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        std::result::Result::Ok(())
+    } else {
+        Err(errors)
+    }
+}
@@ -1,32 +1,137 @@
 use std::{
     fs::File,
     io::{BufRead, BufReader, LineWriter, Lines, Write},
+    iter::Peekable,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
     time::Instant,
 };
 
 use anyhow::*;
+use clap::ValueEnum;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     fds::Devices,
     heat_transfer::{
+        gpu::get_shared_gpu_adapter_info,
         one_dimensional::{
             cpu::{CPUSetupData, ADIABATIC_H, CONST_TEMP_H},
-            gpu_m1, gpu_m2, gpu_m3, HeatTransfer1D,
+            get_detailed_timing, gpu_m1, gpu_m2, gpu_m3, gpu_m4, take_gpu_timing, GpuTiming,
+            HeatTransfer1D,
         },
         simulations::duplication,
+        two_dimensional::{self, HeatTransfer2D, WallSection},
     },
+    output::benchmark_root,
+    provenance::provenance,
+    summary::{record_benchmark, BenchmarkRecord},
 };
 
 use super::{
-    load_fds_simulation_one_dimensional, SimulationKind, SimulationMethod, SimulationType1D,
-    DELTA_TIME_SOLID_FACTOR,
+    load_delta_time_solid_factor, load_fds_simulation_one_dimensional,
+    load_fds_simulation_two_dimensional, SimulationKind, SimulationMethod, SimulationType1D,
+    SimulationType2D, DEVICE_FILE,
 };
 
-/// The amount a simulation is rerun in order to determent the median simulation time.
-pub const SIMULATION_RERUNS: usize = 100;
-/// The amount of simulation steps that should be done.
-pub const SIMULATION_STEPS: usize = 100;
+/// The amount a simulation is rerun in order to determent the median simulation time. This value is replaced at the start of the program.
+static SIMULATION_RERUNS: AtomicUsize = AtomicUsize::new(100);
+/// The amount of simulation steps that should be done. This value is replaced at the start of the program.
+static SIMULATION_STEPS: AtomicUsize = AtomicUsize::new(100);
+
+/// Set the amount a simulation is rerun in order to determent the median simulation time.
+pub fn set_simulation_reruns(simulation_reruns: usize) {
+    SIMULATION_RERUNS.store(simulation_reruns, Ordering::Relaxed);
+    println!("Set simulation reruns to {simulation_reruns}")
+}
+
+/// Get the amount a simulation is rerun in order to determent the median simulation time.
+#[inline]
+pub fn get_simulation_reruns() -> usize {
+    SIMULATION_RERUNS.load(Ordering::Relaxed)
+}
+
+/// Set the amount of simulation steps that should be done.
+pub fn set_simulation_steps(simulation_steps: usize) {
+    SIMULATION_STEPS.store(simulation_steps, Ordering::Relaxed);
+    println!("Set simulation steps to {simulation_steps}")
+}
+
+/// Get the amount of simulation steps that should be done.
+#[inline]
+pub fn get_simulation_steps() -> usize {
+    SIMULATION_STEPS.load(Ordering::Relaxed)
+}
+
+/// The amount of untimed updates that are done before the GPU benchmark starts. This value is replaced at the start of the program.
+static WARMUP_STEPS: AtomicUsize = AtomicUsize::new(3);
+
+/// Set the amount of untimed updates that are done before the GPU benchmark starts.
+pub fn set_warmup_steps(warmup_steps: usize) {
+    WARMUP_STEPS.store(warmup_steps, Ordering::Relaxed);
+    println!("Set warmup steps to {warmup_steps}")
+}
+
+/// Get the amount of untimed updates that are done before the GPU benchmark starts.
+#[inline]
+pub fn get_warmup_steps() -> usize {
+    WARMUP_STEPS.load(Ordering::Relaxed)
+}
+
+/// Whether benchmarks that already contain all reruns are skipped. This value is replaced at the start of the program.
+static BENCHMARK_RESUME: AtomicBool = AtomicBool::new(false);
+
+/// Set whether benchmarks that already contain all reruns are skipped.
+pub fn set_benchmark_resume(benchmark_resume: bool) {
+    BENCHMARK_RESUME.store(benchmark_resume, Ordering::Relaxed);
+    println!("Set benchmark resume to {benchmark_resume}")
+}
+
+/// Get whether benchmarks that already contain all reruns are skipped.
+#[inline]
+pub fn get_benchmark_resume() -> bool {
+    BENCHMARK_RESUME.load(Ordering::Relaxed)
+}
+
+/// Whether missing radiative heat flux columns of the diabatic benchmark device files are read as 0 instead of failing. This value is replaced at the start of the program.
+static BEST_EFFORT_DEVICES: AtomicBool = AtomicBool::new(false);
+
+/// Set whether missing radiative heat flux columns of the diabatic benchmark device files are read as 0 instead of failing.
+pub fn set_best_effort_devices(best_effort_devices: bool) {
+    BEST_EFFORT_DEVICES.store(best_effort_devices, Ordering::Relaxed);
+    println!("Set best effort devices to {best_effort_devices}")
+}
+
+/// Get whether missing radiative heat flux columns of the diabatic benchmark device files are read as 0 instead of failing.
+#[inline]
+pub fn get_best_effort_devices() -> bool {
+    BEST_EFFORT_DEVICES.load(Ordering::Relaxed)
+}
+
+/// The prefix of the radiative heat flux devices that may be missing with [`get_best_effort_devices`].
+const RADIATIVE_HEAT_FLUX_DEVICE: &str = "DEVC_WALL_RADIATIVE_HEAT_FLUX";
+
+/// Opens the benchmark devices of the device file at the path.
+/// With [`get_best_effort_devices`] missing radiative heat flux columns are read as 0 and a warning is printed.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the device file cannot be read.
+/// - a device is missing inside the device file that is not a radiative heat flux with best effort devices.
+fn open_benchmark_devices<S: AsRef<str>>(device_path: &Path, devices: &[S]) -> Result<Devices> {
+    let best_effort_devices = get_best_effort_devices();
+    let (devices, missing) = Devices::try_new_with_optional(device_path, devices, |device| {
+        best_effort_devices && device.starts_with(RADIATIVE_HEAT_FLUX_DEVICE)
+    })?;
+    if missing > 0 {
+        println!(
+            "Warning: {missing} radiative heat flux columns are missing in the file at {device_path:?} and are read as 0."
+        );
+    }
+    Ok(devices)
+}
 
 /// An helper struct for reading the simulation data for a benchmark test line by line witch means simulation step by simulation step.
 pub struct SimulationBenchmarkDevice {
@@ -34,17 +139,19 @@ pub struct SimulationBenchmarkDevice {
     buffer_wall_heat_transfer_coefficient: Vec<[f32; 2]>,
     buffer_wall_q_in: Vec<[f32; 2]>,
     simulation_kind: SimulationKind,
+    delta_time_solid_factor: u8,
     device: Devices,
 }
 impl SimulationBenchmarkDevice {
-    /// Attempts to create a [`SimulationBenchmarkDevice`].
+    /// Attempts to create a [`SimulationBenchmarkDevice`] that reads the [`DEVICE_FILE`] inside the passed simulation directory.
     ///
     /// # Errors
     ///
     /// This function will return an error if
     /// - the transmitted device file cannot be read.
-    /// - the transmitted device file does not match the requested devices.
+    /// - the transmitted device file does not match the requested devices. With [`get_best_effort_devices`] only the radiative heat flux devices may be missing.
     /// - the number of wall elements is 0.
+    /// - the factor of the solid delta time can not be read, see [`load_delta_time_solid_factor`].
     pub fn try_new<P: AsRef<Path>>(
         simulation_kind: SimulationKind,
         path: P,
@@ -68,7 +175,15 @@ impl SimulationBenchmarkDevice {
 
         const ADIABATIC: [&str; 0] = [];
 
-        let device_path = path.as_ref().join("result/heat_transfer_devc.csv");
+        const CONVECTIVE: [&str; 4] = [
+            "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_WEST",
+            "DEVC_GAS_TEMPERATURE_WEST",
+            "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_EAST",
+            "DEVC_GAS_TEMPERATURE_EAST",
+        ];
+
+        let device_path = path.as_ref().join(DEVICE_FILE);
+        let delta_time_solid_factor = load_delta_time_solid_factor(&path)?;
 
         if wall_element_count == 0 {
             bail!("Count should be at least 1.")
@@ -79,17 +194,19 @@ impl SimulationBenchmarkDevice {
                         SimulationKind::Diabatic => DIABATIC.iter(),
                         SimulationKind::DiabaticOneSide => DIABATIC_NO_RADIATION.iter(),
                         SimulationKind::Adiabatic => ADIABATIC.iter(),
+                        SimulationKind::Convective => CONVECTIVE.iter(),
                     }
                     .map(move |s| format!("{s}_{}", i + 1))
                 })
                 .collect::<Vec<String>>();
             devices.insert(0, "Time".to_string());
-            let device = Devices::try_new(device_path, &devices)?;
+            let device = open_benchmark_devices(&device_path, &devices)?;
             std::result::Result::Ok(Self {
                 last_time: 0.0,
                 buffer_wall_heat_transfer_coefficient: vec![[0.0, 0.0]; wall_element_count],
                 buffer_wall_q_in: vec![[0.0, 0.0]; wall_element_count],
                 simulation_kind,
+                delta_time_solid_factor,
                 device,
             })
         } else {
@@ -97,14 +214,16 @@ impl SimulationBenchmarkDevice {
                 SimulationKind::Diabatic => DIABATIC.to_vec(),
                 SimulationKind::DiabaticOneSide => DIABATIC_NO_RADIATION.to_vec(),
                 SimulationKind::Adiabatic => ADIABATIC.to_vec(),
+                SimulationKind::Convective => CONVECTIVE.to_vec(),
             };
             devices.insert(0, "Time");
-            let device = Devices::try_new(device_path, &devices)?;
+            let device = open_benchmark_devices(&device_path, &devices)?;
             std::result::Result::Ok(Self {
                 last_time: 0.0,
                 buffer_wall_heat_transfer_coefficient: vec![[0.0, 0.0]],
                 buffer_wall_q_in: vec![[0.0, 0.0]],
                 simulation_kind,
+                delta_time_solid_factor,
                 device,
             })
         }
@@ -127,7 +246,7 @@ impl Iterator for SimulationBenchmarkDevice {
     type Item = Result<f32>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        for _ in 0..(DELTA_TIME_SOLID_FACTOR - 1) {
+        for _ in 0..(self.delta_time_solid_factor - 1) {
             if let Err(err) = self.device.next()? {
                 return Some(Err(err));
             }
@@ -153,7 +272,7 @@ impl Iterator for SimulationBenchmarkDevice {
                 }
                 SimulationKind::DiabaticOneSide => {
                     let n = i * 4;
-                    self.buffer_wall_q_in[i] = [data[n + 1], data[n + 3]];
+                    self.buffer_wall_heat_transfer_coefficient[i] = [data[n + 1], data[n + 3]];
                     self.buffer_wall_q_in[i] =
                         [data[n + 1] * data[n + 2], data[n + 3] * data[n + 4]];
                 }
@@ -161,6 +280,12 @@ impl Iterator for SimulationBenchmarkDevice {
                     self.buffer_wall_heat_transfer_coefficient[i] = [CONST_TEMP_H, ADIABATIC_H];
                     self.buffer_wall_q_in[i] = [200.0, 0.0];
                 }
+                SimulationKind::Convective => {
+                    let n = i * 4;
+                    self.buffer_wall_heat_transfer_coefficient[i] = [data[n + 1], data[n + 3]];
+                    self.buffer_wall_q_in[i] =
+                        [data[n + 1] * data[n + 2], data[n + 3] * data[n + 4]];
+                }
             }
         }
 
@@ -168,13 +293,164 @@ impl Iterator for SimulationBenchmarkDevice {
     }
 }
 
+/// All file formats a benchmark can be stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum BenchmarkFormat {
+    /// Plaintext file with a header and one simulation time per line.
+    /// With detailed timing the submit and the map-back time of the GPU methods follow the simulation time on the same line, separated by a space.
+    #[default]
+    Bin,
+    /// JSON file with the header values and all simulation times.
+    Json,
+}
+impl BenchmarkFormat {
+    /// Returns the file extension of this [`BenchmarkFormat`].
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BenchmarkFormat::Bin => "bin",
+            BenchmarkFormat::Json => "json",
+        }
+    }
+
+    /// Returns the file name of a benchmark with the passed size for this [`BenchmarkFormat`].
+    pub fn file_name(&self, size: usize) -> String {
+        format!("{size}.{}", self.extension())
+    }
+
+    /// Returns the size of a benchmark file name of this [`BenchmarkFormat`], the inverse of [`BenchmarkFormat::file_name`].
+    pub fn size(&self, file_name: &str) -> Option<usize> {
+        file_name
+            .strip_suffix(self.extension())?
+            .strip_suffix('.')?
+            .parse()
+            .ok()
+    }
+}
+
+/// Returns the sizes of all benchmarks inside the directory in the format set with [`set_benchmark_format`], sorted ascending.
+/// A directory that does not exist or can not be read contains no benchmarks.
+pub fn benchmark_sizes<P: AsRef<Path>>(directory: P) -> Vec<usize> {
+    let benchmark_format = get_benchmark_format();
+    let Result::Ok(entries) = std::fs::read_dir(directory) else {
+        return vec![];
+    };
+    let mut sizes = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| benchmark_format.size(&entry.file_name().to_string_lossy()))
+        .collect::<Vec<_>>();
+    sizes.sort_unstable();
+    sizes
+}
+
+/// Whether the benchmarks are written and read as JSON. This value is replaced at the start of the program.
+static BENCHMARK_FORMAT_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Set the file format of the benchmarks.
+pub fn set_benchmark_format(benchmark_format: BenchmarkFormat) {
+    BENCHMARK_FORMAT_JSON.store(benchmark_format == BenchmarkFormat::Json, Ordering::Relaxed);
+    println!("Set benchmark format to {benchmark_format:?}")
+}
+
+/// Get the file format of the benchmarks.
+#[inline]
+pub fn get_benchmark_format() -> BenchmarkFormat {
+    if BENCHMARK_FORMAT_JSON.load(Ordering::Relaxed) {
+        BenchmarkFormat::Json
+    } else {
+        BenchmarkFormat::Bin
+    }
+}
+
+/// The hardware a benchmark was executed on, so benchmarks collected from several machines can be told apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BenchmarkHardware {
+    /// The name of the GPU adapter or the CPU.
+    pub device: Option<String>,
+    /// The backend of the GPU adapter or `CPU`.
+    pub backend: Option<String>,
+}
+impl BenchmarkHardware {
+    /// Returns the [`BenchmarkHardware`] the passed [`SimulationMethod`] is executed on.
+    /// GPU methods use the adapter of the shared device, so the hardware is empty if the shared device was not requested yet.
+    pub fn of_simulation_method(simulation_method: SimulationMethod) -> Self {
+        match simulation_method {
+            SimulationMethod::OneDimensionalGpuM1
+            | SimulationMethod::OneDimensionalGpuM2
+            | SimulationMethod::OneDimensionalGpuM3
+            | SimulationMethod::OneDimensionalGpuM4 => match get_shared_gpu_adapter_info() {
+                Some(adapter_info) => Self {
+                    device: Some(adapter_info.name),
+                    backend: Some(format!("{:?}", adapter_info.backend)),
+                },
+                None => Self::default(),
+            },
+            _ => {
+                let threads = std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1);
+                Self {
+                    device: Some(format!("{} ({threads} threads)", cpu_name())),
+                    backend: Some("CPU".to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// Returns the model name of the CPU or the architecture if it can not be determined.
+fn cpu_name() -> String {
+    std::fs::read_to_string("/proc/cpuinfo")
+        .ok()
+        .and_then(|cpuinfo| {
+            cpuinfo
+                .lines()
+                .find(|l| l.starts_with("model name"))
+                .and_then(|l| l.split(':').next_back())
+                .map(|name| name.trim().to_string())
+        })
+        .unwrap_or_else(|| std::env::consts::ARCH.to_string())
+}
+
+/// The content of a benchmark stored as JSON.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BenchmarkJson {
+    size: usize,
+    steps: usize,
+    reruns: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    device: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backend: Option<String>,
+    /// The build of the program that wrote the benchmark, see [`provenance`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    build: Option<String>,
+    times: Vec<f64>,
+    /// The submit times of the GPU methods, only written with detailed timing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    submit_times: Vec<f64>,
+    /// The map-back times of the GPU methods, only written with detailed timing.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    map_back_times: Vec<f64>,
+}
+
+/// The destination of a [`BenchmarkWriter`].
+enum BenchmarkWriterTarget {
+    Bin(LineWriter<File>),
+    Json(BenchmarkJson),
+}
+
 /// Helper to write the benchmarks to disk.
+/// The file is added to the [`summary`](crate::summary) when the writer is dropped, so benchmarks that fail midway are listed as incomplete.
 pub struct BenchmarkWriter {
     pub size: usize,
-    line_writer: LineWriter<File>,
+    path: PathBuf,
+    reruns: usize,
+    target: BenchmarkWriterTarget,
 }
 impl BenchmarkWriter {
-    /// Trys to create a [`BenchmarkWriter`].
+    /// Trys to create a [`BenchmarkWriter`] with the format set by [`set_benchmark_format`].
+    /// The header contains the [`BenchmarkHardware`] of the simulation method of the `benchmark_path_part` and the [`provenance`] of the build.
     ///
     /// # Errors
     ///
@@ -187,21 +463,92 @@ impl BenchmarkWriter {
         benchmark_path_part: &BenchmarkPathPart,
         size: usize,
     ) -> Result<Self> {
-        let dir_path = path.as_ref().join(benchmark_path_part.path_str()?);
-        std::fs::create_dir_all(&dir_path)
-            .with_context(|| format!("Failed to create directories at path {dir_path:?}"))?;
-        let write_path = dir_path.join(format!("{size}.bin"));
-        let file = std::fs::File::create(write_path)
-            .with_context(|| "Failed to create file at {write_path:?}.")?;
+        match get_benchmark_format() {
+            BenchmarkFormat::Bin => Self::try_new_bin(path, benchmark_path_part, size),
+            BenchmarkFormat::Json => Self::try_new_json(path, benchmark_path_part, size),
+        }
+    }
+
+    /// Trys to create a [`BenchmarkWriter`] that writes a plaintext file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the path can not be determent.
+    /// - the destination directory can not be created.
+    /// - the data can not be written to the file.
+    pub fn try_new_bin<P: AsRef<Path>>(
+        path: P,
+        benchmark_path_part: &BenchmarkPathPart,
+        size: usize,
+    ) -> Result<Self> {
+        let write_path =
+            create_benchmark_path(path, benchmark_path_part, size, BenchmarkFormat::Bin)?;
+        let file = std::fs::File::create(&write_path)
+            .with_context(|| format!("Failed to create file at {write_path:?}."))?;
         let mut line_writer = LineWriter::new(file);
         writeln!(line_writer, "Size: {size}")
             .with_context(|| "Failed to write size inside buffer")?;
-        writeln!(line_writer, "Steps: {SIMULATION_STEPS}")
+        writeln!(line_writer, "Steps: {}", get_simulation_steps())
             .with_context(|| "Failed to write size inside buffer")?;
-        writeln!(line_writer, "Reruns: {SIMULATION_RERUNS}")
+        writeln!(line_writer, "Reruns: {}", get_simulation_reruns())
             .with_context(|| "Failed to write size inside buffer")?;
+        let BenchmarkHardware { device, backend } =
+            BenchmarkHardware::of_simulation_method(benchmark_path_part.simulation_method);
+        if let Some(device) = device {
+            writeln!(line_writer, "Device: {device}")
+                .with_context(|| "Failed to write device inside buffer")?;
+        }
+        if let Some(backend) = backend {
+            writeln!(line_writer, "Backend: {backend}")
+                .with_context(|| "Failed to write backend inside buffer")?;
+        }
+        writeln!(line_writer, "Build: {}", provenance())
+            .with_context(|| "Failed to write build inside buffer")?;
+
+        std::result::Result::Ok(Self {
+            size,
+            path: write_path,
+            reruns: 0,
+            target: BenchmarkWriterTarget::Bin(line_writer),
+        })
+    }
+
+    /// Trys to create a [`BenchmarkWriter`] that writes a JSON file.
+    /// The whole file is rewritten after each simulation time, so an interrupted benchmark is still a valid JSON file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the path can not be determent.
+    /// - the destination directory can not be created.
+    /// - the data can not be written to the file.
+    pub fn try_new_json<P: AsRef<Path>>(
+        path: P,
+        benchmark_path_part: &BenchmarkPathPart,
+        size: usize,
+    ) -> Result<Self> {
+        let path = create_benchmark_path(path, benchmark_path_part, size, BenchmarkFormat::Json)?;
+        let BenchmarkHardware { device, backend } =
+            BenchmarkHardware::of_simulation_method(benchmark_path_part.simulation_method);
+        let benchmark = BenchmarkJson {
+            size,
+            steps: get_simulation_steps(),
+            reruns: get_simulation_reruns(),
+            device,
+            backend,
+            build: Some(provenance()),
+            times: Vec::with_capacity(get_simulation_reruns()),
+            ..Default::default()
+        };
+        write_json(&path, &benchmark)?;
 
-        std::result::Result::Ok(Self { size, line_writer })
+        std::result::Result::Ok(Self {
+            size,
+            path,
+            reruns: 0,
+            target: BenchmarkWriterTarget::Json(benchmark),
+        })
     }
 
     /// Writes the simulation time to the buffer.
@@ -209,20 +556,149 @@ impl BenchmarkWriter {
     /// # Errors
     ///
     /// This function will return an error if the write fails.
-    pub fn write(&mut self, time: f64) -> std::result::Result<(), std::io::Error> {
-        writeln!(self.line_writer, "{}", time)
+    pub fn write(&mut self, time: f64) -> Result<()> {
+        match &mut self.target {
+            BenchmarkWriterTarget::Bin(line_writer) => {
+                writeln!(line_writer, "{}", time)?;
+            }
+            BenchmarkWriterTarget::Json(benchmark) => {
+                benchmark.times.push(time);
+                write_json(&self.path, benchmark)?;
+            }
+        }
+        self.reruns += 1;
+        Ok(())
+    }
+
+    /// Writes the simulation time together with the submit and the map-back time of a GPU method to the buffer.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the write fails.
+    pub fn write_detailed(&mut self, time: f64, gpu_timing: GpuTiming) -> Result<()> {
+        let submit = gpu_timing.submit.as_secs_f64();
+        let map_back = gpu_timing.map_back.as_secs_f64();
+        match &mut self.target {
+            BenchmarkWriterTarget::Bin(line_writer) => {
+                writeln!(line_writer, "{time} {submit} {map_back}")?;
+            }
+            BenchmarkWriterTarget::Json(benchmark) => {
+                benchmark.times.push(time);
+                benchmark.submit_times.push(submit);
+                benchmark.map_back_times.push(map_back);
+                write_json(&self.path, benchmark)?;
+            }
+        }
+        self.reruns += 1;
+        Ok(())
+    }
+}
+impl Drop for BenchmarkWriter {
+    fn drop(&mut self) {
+        record_benchmark(BenchmarkRecord {
+            path: self.path.clone(),
+            size: self.size,
+            reruns: self.reruns,
+            complete: self.reruns >= get_simulation_reruns(),
+            skipped: false,
+        });
+    }
+}
+
+/// Creates the directories of a benchmark and returns the path of the benchmark file.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the path can not be determent.
+/// - the destination directory can not be created.
+fn create_benchmark_path<P: AsRef<Path>>(
+    path: P,
+    benchmark_path_part: &BenchmarkPathPart,
+    size: usize,
+    benchmark_format: BenchmarkFormat,
+) -> Result<PathBuf> {
+    let dir_path = path.as_ref().join(benchmark_path_part.path_str()?);
+    std::fs::create_dir_all(&dir_path)
+        .with_context(|| format!("Failed to create directories at path {dir_path:?}"))?;
+    Ok(dir_path.join(benchmark_format.file_name(size)))
+}
+
+/// Checks whether the benchmark should be skipped, because resuming is enabled with [`set_benchmark_resume`] and the existing file already contains [`get_simulation_reruns`] simulation times.
+/// Benchmarks that can not be read are treated as incomplete and are overwritten. Skipped benchmarks are logged.
+///
+/// # Errors
+///
+/// This function will return an error if the path can not be determent.
+pub fn skip_complete_benchmark<P: AsRef<Path>>(
+    path: P,
+    benchmark_path_part: &BenchmarkPathPart,
+    size: usize,
+    multi_progress: &MultiProgress,
+    prefix: &str,
+) -> Result<bool> {
+    if !get_benchmark_resume() {
+        return Ok(false);
     }
+    let file_path = path
+        .as_ref()
+        .join(benchmark_path_part.path_str()?)
+        .join(get_benchmark_format().file_name(size));
+    let Result::Ok(reader) = BenchmarkReader::try_new(&file_path) else {
+        return Ok(false);
+    };
+    let samples = reader.map_while(Result::ok).count();
+    if samples < get_simulation_reruns() {
+        return Ok(false);
+    }
+    multi_progress.suspend(|| {
+        println!("  {prefix} skipped, {file_path:?} already contains {samples} reruns.")
+    });
+    record_benchmark(BenchmarkRecord {
+        path: file_path,
+        size,
+        reruns: samples,
+        complete: true,
+        skipped: true,
+    });
+    Ok(true)
+}
+
+/// Writes a benchmark as JSON to the passed path.
+/// The benchmark is first written to a temporary file next to it, which then replaces the file, so the file is never left partially written.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the temporary file can not be written.
+/// - the temporary file can not be renamed to the passed path.
+fn write_json(path: &Path, benchmark: &BenchmarkJson) -> Result<()> {
+    let tmp_path = path.with_extension("json.tmp");
+    let file = File::create(&tmp_path)
+        .with_context(|| format!("Failed to create file at {tmp_path:?}."))?;
+    serde_json::to_writer(file, benchmark)
+        .with_context(|| format!("Failed to write benchmark to {tmp_path:?}."))?;
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to move {tmp_path:?} to {path:?}."))
+}
+
+/// The source of a [`BenchmarkReader`].
+enum BenchmarkReaderSource {
+    Bin(Peekable<Lines<BufReader<File>>>),
+    Json(std::vec::IntoIter<f64>),
 }
 
 /// Helper to read the benchmark from disk.
 pub struct BenchmarkReader {
-    lines: Lines<BufReader<File>>,
+    source: BenchmarkReaderSource,
     _size: usize,
     _steps: usize,
-    _reruns: usize,
+    reruns: usize,
+    hardware: BenchmarkHardware,
+    build: Option<String>,
 }
 impl BenchmarkReader {
-    /// Tries to create a [`BenchmarkReader`].
+    /// Tries to create a [`BenchmarkReader`]. Files with the extension `json` are read as JSON, all other files as plaintext.
     ///
     /// # Errors
     ///
@@ -231,14 +707,29 @@ impl BenchmarkReader {
     /// - the file can not be read.
     pub fn try_new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            Self::try_new_json(path)
+        } else {
+            Self::try_new_bin(path)
+        }
+    }
+
+    /// Tries to create a [`BenchmarkReader`] for a plaintext file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the passed file does not exist.
+    /// - the file can not be read.
+    fn try_new_bin(path: &Path) -> Result<Self> {
         let file =
             File::open(path).with_context(|| format!("Failed to open file at {:?}.", path))?;
-        let mut lines = BufReader::new(file).lines();
+        let mut lines = BufReader::new(file).lines().peekable();
         let size = lines
             .next()
             .ok_or(anyhow!("Line is missing."))??
             .split(':')
-            .last()
+            .next_back()
             .ok_or(anyhow!("Failed to split line."))?
             .trim()
             .parse::<usize>()?;
@@ -246,7 +737,7 @@ impl BenchmarkReader {
             .next()
             .ok_or(anyhow!("Line is missing."))??
             .split(':')
-            .last()
+            .next_back()
             .ok_or(anyhow!("Failed to split line."))?
             .trim()
             .parse::<usize>()?;
@@ -254,35 +745,209 @@ impl BenchmarkReader {
             .next()
             .ok_or(anyhow!("Line is missing."))??
             .split(':')
-            .last()
+            .next_back()
             .ok_or(anyhow!("Failed to split line."))?
             .trim()
             .parse::<usize>()?;
+        // The hardware and build lines are optional, since older benchmarks do not contain them.
+        let mut hardware = BenchmarkHardware::default();
+        let mut build = None;
+        while let Some(std::result::Result::Ok(line)) = lines.peek() {
+            if let Some(device) = line.strip_prefix("Device:") {
+                hardware.device = Some(device.trim().to_string());
+            } else if let Some(backend) = line.strip_prefix("Backend:") {
+                hardware.backend = Some(backend.trim().to_string());
+            } else if let Some(line) = line.strip_prefix("Build:") {
+                build = Some(line.trim().to_string());
+            } else {
+                break;
+            }
+            lines.next();
+        }
 
         Ok(Self {
-            lines,
+            source: BenchmarkReaderSource::Bin(lines),
             _size: size,
             _steps: steps,
-            _reruns: reruns,
+            reruns,
+            hardware,
+            build,
         })
     }
+
+    /// Tries to create a [`BenchmarkReader`] for a JSON file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - the passed file does not exist.
+    /// - the file can not be parsed.
+    fn try_new_json(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open file at {:?}.", path))?;
+        let BenchmarkJson {
+            size,
+            steps,
+            reruns,
+            device,
+            backend,
+            build,
+            times,
+            ..
+        } = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to parse benchmark at {:?}.", path))?;
+
+        Ok(Self {
+            source: BenchmarkReaderSource::Json(times.into_iter()),
+            _size: size,
+            _steps: steps,
+            reruns,
+            hardware: BenchmarkHardware { device, backend },
+            build,
+        })
+    }
+
+    /// Returns the number of reruns stored in the header of the benchmark.
+    pub fn reruns(&self) -> usize {
+        self.reruns
+    }
+
+    /// Returns the [`BenchmarkHardware`] stored in the header of the benchmark.
+    pub fn hardware(&self) -> &BenchmarkHardware {
+        &self.hardware
+    }
+
+    /// Returns the build of the program that wrote the benchmark, if it is stored in the header.
+    pub fn build(&self) -> Option<&str> {
+        self.build.as_deref()
+    }
+
+    /// Reads all simulation times and calculates the [`BenchmarkStats`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - a simulation time can not be read.
+    /// - the benchmark contains no simulation times.
+    pub fn stats(self) -> Result<BenchmarkStats> {
+        let reruns = self.reruns;
+        let times = self.collect::<Result<Vec<f64>>>()?;
+        BenchmarkStats::try_from_times(times, reruns)
+    }
 }
 impl Iterator for BenchmarkReader {
     type Item = Result<f64>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let line = self.lines.next()?;
-
-        match line {
-            std::result::Result::Ok(s) => match s.parse::<f64>() {
-                std::result::Result::Ok(ok) => Some(Ok(ok)),
+        match &mut self.source {
+            // Only the simulation time in the first column is read, the detailed timing columns are ignored.
+            BenchmarkReaderSource::Bin(lines) => match lines.next()? {
+                std::result::Result::Ok(s) => {
+                    match s.split(' ').next().unwrap_or_default().parse::<f64>() {
+                        std::result::Result::Ok(ok) => Some(Ok(ok)),
+                        Err(err) => Some(Err(anyhow::Error::from(err))),
+                    }
+                }
                 Err(err) => Some(Err(anyhow::Error::from(err))),
             },
-            Err(err) => Some(Err(anyhow::Error::from(err))),
+            BenchmarkReaderSource::Json(times) => times.next().map(Ok),
         }
     }
 }
 
+/// Statistics of the simulation times of a benchmark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchmarkStats {
+    /// The number of collected simulation times.
+    pub count: usize,
+    /// The number of reruns stored in the header of the benchmark.
+    pub reruns: usize,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    /// The 5th percentile.
+    pub p5: f64,
+    /// The 25th percentile, the lower quartile.
+    pub p25: f64,
+    /// The 75th percentile, the upper quartile.
+    pub p75: f64,
+    /// The 95th percentile.
+    pub p95: f64,
+    pub min: f64,
+    pub max: f64,
+}
+impl BenchmarkStats {
+    /// Calculates the [`BenchmarkStats`] of the passed simulation times.
+    /// The percentiles are linearly interpolated between the closest ranks.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no simulation times are passed.
+    pub fn try_from_times(mut times: Vec<f64>, reruns: usize) -> Result<Self> {
+        if times.is_empty() {
+            bail!("No simulation times to calculate statistics from.");
+        }
+        times.sort_by(|a, b| a.total_cmp(b));
+        let count = times.len();
+        let mean = times.iter().sum::<f64>() / count as f64;
+        let variance = times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / count as f64;
+
+        Ok(Self {
+            count,
+            reruns,
+            mean,
+            median: percentile(&times, 0.5),
+            std_dev: variance.sqrt(),
+            p5: percentile(&times, 0.05),
+            p25: percentile(&times, 0.25),
+            p75: percentile(&times, 0.75),
+            p95: percentile(&times, 0.95),
+            min: times[0],
+            max: times[count - 1],
+        })
+    }
+}
+
+/// Calculates the percentile of already sorted values.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Creates the progress bar for the reruns of a single benchmark size.
+///
+/// # Errors
+///
+/// This function will return an error if the template of the progress bar is invalid.
+pub fn rerun_progress_bar(
+    multi_progress: &MultiProgress,
+    prefix: String,
+    reruns: usize,
+) -> Result<ProgressBar> {
+    let progress_bar = multi_progress.add(ProgressBar::new(reruns as u64));
+    progress_bar.set_style(
+        ProgressStyle::with_template("  {prefix} [{bar:40}] {pos}/{len} ({elapsed})")
+            .with_context(|| "Failed to create the progress bar style.")?
+            .progress_chars("=> "),
+    );
+    progress_bar.set_prefix(prefix);
+    Ok(progress_bar)
+}
+
+/// Finishes the progress bar. If the progress bar is hidden, because the output is no terminal, the result is printed instead.
+pub fn finish_progress_bar(progress_bar: &ProgressBar) {
+    progress_bar.finish();
+    if progress_bar.is_hidden() {
+        println!(
+            "  {} finished {} reruns.",
+            progress_bar.prefix(),
+            progress_bar.position()
+        );
+    }
+}
+
 /// Helper to create the path to the benchmark.
 #[derive(Debug, Clone)]
 pub struct BenchmarkPathPart {
@@ -327,7 +992,7 @@ impl BenchmarkPathPart {
 /// - the fds simulation file can not be loaded.
 /// - the [`WallElement`]s can not be duplicated correctly.
 /// - a [`BenchmarkWriter`] can not be created.
-/// - it failed to flush the stdout for status information.
+/// - the progress bar can not be created.
 /// - it failed to initialize the simulation.
 /// - it failed to update the simulation.
 /// - the [`BenchmarkWriter`] failed to read the next time step.
@@ -337,55 +1002,105 @@ fn one_dimensional<P: AsRef<Path>, S: HeatTransfer1D>(
     elements: &[usize],
     simulation_kind: SimulationKind,
     simulation_type: SimulationType1D,
+    multi_progress: &MultiProgress,
+    prefix: &str,
 ) -> Result<()> {
     let path = path.as_ref();
     let (materials, wall_elements) = load_fds_simulation_one_dimensional(path)
         .with_context(|| format!("Failed to build simulation for file at {:?}", path))?;
+    let wall_elements = simulation_kind.mark_convective_sides(wall_elements);
 
     let benchmark_path_part = BenchmarkPathPart::new(None, label, simulation_type.into());
+    let reruns = get_simulation_reruns();
+    let steps = get_simulation_steps();
+    // The CPU method compiles no shader and allocates no device buffers, so it is benchmarked without warmup.
+    let warmup_steps = match simulation_type {
+        SimulationType1D::Cpu => 0,
+        _ => get_warmup_steps(),
+    };
+    // The CPU method has no submit and map-back time, so it is always written without the detailed timing.
+    let detailed_timing = get_detailed_timing() && simulation_type != SimulationType1D::Cpu;
     for &e in elements {
         let duplication = duplication(e, wall_elements.len())?;
-
-        println!("  Start benchmark with size {}.", e);
-        let mut benchmark_writer = BenchmarkWriter::try_new(
-            PathBuf::from("benchmark").join(path),
+        if skip_complete_benchmark(
+            benchmark_root().join(path),
             &benchmark_path_part,
             e,
-        )
-        .with_context(|| {
-            format!(
-                "Failed to create benchmark writer for simulation at {:?}",
-                path
-            )
-        })?;
-        let mut time = 10.0;
-        print!("  Simulation 0/{SIMULATION_RERUNS}");
-        std::io::stdout()
-            .flush()
-            .with_context(|| "Failed to flush stdout.")?;
-        for i in 0..SIMULATION_RERUNS {
-            if time >= 0.25 {
-                print!("\r   Simulation {}/{SIMULATION_RERUNS}", i + 1);
-                std::io::stdout().flush().unwrap();
-                time = 0.0;
-            }
+            multi_progress,
+            &format!("{prefix} {e}"),
+        )? {
+            continue;
+        }
+
+        let progress_bar = rerun_progress_bar(multi_progress, format!("{prefix} {e}"), reruns)?;
+        // Wall elements are mapped to the new length. If there are more then 1 type, the types are cloned with an equal amount one after another.
+        let duplicated_wall_elements = wall_elements
+            .iter()
+            .flat_map(|w| vec![w.clone(); duplication])
+            .collect::<Vec<_>>();
+
+        // The device and the pipelines are only created once per size. Each rerun only reinitializes the buffers.
+        let mut gpu_setup_data = S::setup(materials.clone(), duplicated_wall_elements.clone())
+            .with_context(|| "Failed to setup shader.")?;
+        // The writer is created after the setup, so the adapter of the GPU methods is known for the header.
+        let mut benchmark_writer =
+            BenchmarkWriter::try_new(benchmark_root().join(path), &benchmark_path_part, e)
+                .with_context(|| {
+                    format!(
+                        "Failed to create benchmark writer for simulation at {:?}",
+                        path
+                    )
+                })?;
+
+        // The first updates include the shader compilation and buffer allocation latency, so they are done without timing.
+        if warmup_steps > 0 {
             let mut device =
                 SimulationBenchmarkDevice::try_new(simulation_kind, path, wall_elements.len())?;
-            // Wall elements are mapped to the new length. If there are more then 1 type, the types are cloned with an equal amount one after another.
-            let wall_elements = wall_elements
-                .iter()
-                .flat_map(|w| vec![w.clone(); duplication])
-                .collect::<Vec<_>>();
+            let mut wall_temperature_buffer = vec![[0.0; 2]; e];
+            for _ in 0..warmup_steps {
+                let Some(delta_time) = device.next() else {
+                    break;
+                };
+                let delta_time = delta_time?;
+
+                let (wall_heat_transfer_coefficients, wall_q_in) = device.buffers();
+                let wall_heat_transfer_coefficients = wall_heat_transfer_coefficients
+                    .iter()
+                    .flat_map(|&v| vec![v; duplication])
+                    .collect::<Vec<_>>();
+                let wall_q_in = wall_q_in
+                    .iter()
+                    .flat_map(|&v| vec![v; duplication])
+                    .collect::<Vec<_>>();
 
-            let mut gpu_setup_data = S::setup(materials.clone(), wall_elements)
-                .with_context(|| "Failed to setup shader.")?;
+                gpu_setup_data
+                    .update(
+                        delta_time,
+                        &wall_heat_transfer_coefficients,
+                        &wall_q_in,
+                        &mut wall_temperature_buffer,
+                    )
+                    .with_context(|| "Failed warmup update")?;
+            }
+        }
+
+        for i in 0..reruns {
+            let mut device =
+                SimulationBenchmarkDevice::try_new(simulation_kind, path, wall_elements.len())?;
+            if i > 0 || warmup_steps > 0 {
+                gpu_setup_data
+                    .reset(duplicated_wall_elements.clone())
+                    .with_context(|| "Failed to reset shader.")?;
+            }
+            // Discards the timing of the warmup and the previous rerun.
+            take_gpu_timing();
 
             let mut wall_temperature_buffer = vec![[0.0; 2]; e];
             let mut elapsed = 0.0;
             let mut i = 0;
             while let Some(delta_time) = device.next() {
                 let delta_time = delta_time?;
-                if i > SIMULATION_STEPS {
+                if i > steps {
                     break;
                 }
                 i += 1;
@@ -411,16 +1126,21 @@ fn one_dimensional<P: AsRef<Path>, S: HeatTransfer1D>(
                     )
                     .with_context(|| "Failed update")?;
                 elapsed += start.elapsed().as_secs_f64();
-                time += elapsed;
             }
-            benchmark_writer.write(elapsed).with_context(|| {
+            if detailed_timing {
+                benchmark_writer.write_detailed(elapsed, take_gpu_timing())
+            } else {
+                benchmark_writer.write(elapsed)
+            }
+            .with_context(|| {
                 format!(
                     "Failed to write to the benchmark writer for simulation at {:?}",
                     path
                 )
             })?;
+            progress_bar.inc(1);
         }
-        println!("\r   Simulation {SIMULATION_RERUNS}/{SIMULATION_RERUNS}");
+        finish_progress_bar(&progress_bar);
     }
     Ok(())
 }
@@ -435,6 +1155,8 @@ pub fn one_dimensional_cpu<P: AsRef<Path>>(
     label: String,
     elements: &[usize],
     simulation_kind: SimulationKind,
+    multi_progress: &MultiProgress,
+    prefix: &str,
 ) -> Result<()> {
     one_dimensional::<P, CPUSetupData>(
         path,
@@ -442,6 +1164,8 @@ pub fn one_dimensional_cpu<P: AsRef<Path>>(
         elements,
         simulation_kind,
         SimulationType1D::Cpu,
+        multi_progress,
+        prefix,
     )
 }
 
@@ -455,6 +1179,8 @@ pub fn one_dimensional_gpu_m1<P: AsRef<Path>>(
     label: String,
     elements: &[usize],
     simulation_kind: SimulationKind,
+    multi_progress: &MultiProgress,
+    prefix: &str,
 ) -> Result<()> {
     one_dimensional::<P, gpu_m1::GPUSetupData>(
         path,
@@ -462,6 +1188,8 @@ pub fn one_dimensional_gpu_m1<P: AsRef<Path>>(
         elements,
         simulation_kind,
         SimulationType1D::GpuM1,
+        multi_progress,
+        prefix,
     )
 }
 
@@ -475,6 +1203,8 @@ pub fn one_dimensional_gpu_m2<P: AsRef<Path>>(
     label: String,
     elements: &[usize],
     simulation_kind: SimulationKind,
+    multi_progress: &MultiProgress,
+    prefix: &str,
 ) -> Result<()> {
     one_dimensional::<P, gpu_m2::GPUSetupData>(
         path,
@@ -482,6 +1212,8 @@ pub fn one_dimensional_gpu_m2<P: AsRef<Path>>(
         elements,
         simulation_kind,
         SimulationType1D::GpuM2,
+        multi_progress,
+        prefix,
     )
 }
 
@@ -495,6 +1227,8 @@ pub fn one_dimensional_gpu_m3<P: AsRef<Path>>(
     label: String,
     elements: &[usize],
     simulation_kind: SimulationKind,
+    multi_progress: &MultiProgress,
+    prefix: &str,
 ) -> Result<()> {
     one_dimensional::<P, gpu_m3::GPUSetupData>(
         path,
@@ -502,6 +1236,32 @@ pub fn one_dimensional_gpu_m3<P: AsRef<Path>>(
         elements,
         simulation_kind,
         SimulationType1D::GpuM3,
+        multi_progress,
+        prefix,
+    )
+}
+
+/// Start the GPU M4 benchmark simulation.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be started.
+pub fn one_dimensional_gpu_m4<P: AsRef<Path>>(
+    path: P,
+    label: String,
+    elements: &[usize],
+    simulation_kind: SimulationKind,
+    multi_progress: &MultiProgress,
+    prefix: &str,
+) -> Result<()> {
+    one_dimensional::<P, gpu_m4::GPUSetupData>(
+        path,
+        label,
+        elements,
+        simulation_kind,
+        SimulationType1D::GpuM4,
+        multi_progress,
+        prefix,
     )
 }
 
@@ -516,11 +1276,350 @@ pub fn one_dimensional_by_simulation_type<P: AsRef<Path>>(
     elements: &[usize],
     simulation_kind: SimulationKind,
     simulation_type: SimulationType1D,
+    multi_progress: &MultiProgress,
+    prefix: &str,
 ) -> Result<()> {
     match simulation_type {
-        SimulationType1D::Cpu => one_dimensional_cpu(path, label, elements, simulation_kind),
-        SimulationType1D::GpuM1 => one_dimensional_gpu_m1(path, label, elements, simulation_kind),
-        SimulationType1D::GpuM2 => one_dimensional_gpu_m2(path, label, elements, simulation_kind),
-        SimulationType1D::GpuM3 => one_dimensional_gpu_m3(path, label, elements, simulation_kind),
+        SimulationType1D::Cpu => one_dimensional_cpu(
+            path,
+            label,
+            elements,
+            simulation_kind,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM1 => one_dimensional_gpu_m1(
+            path,
+            label,
+            elements,
+            simulation_kind,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM2 => one_dimensional_gpu_m2(
+            path,
+            label,
+            elements,
+            simulation_kind,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM3 => one_dimensional_gpu_m3(
+            path,
+            label,
+            elements,
+            simulation_kind,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM4 => one_dimensional_gpu_m4(
+            path,
+            label,
+            elements,
+            simulation_kind,
+            multi_progress,
+            prefix,
+        ),
+    }
+}
+
+/// The prefix of the [`BenchmarkPathPart`] of the setup benchmarks, see [`one_dimensional_setup_by_simulation_type`].
+pub const SETUP_PREFIX: &str = "setup";
+
+/// Executes a benchmark that only times [`HeatTransfer1D::setup`], which creates the pipelines, compiles the shader with the injected material data and allocates the buffers. No time step is simulated.
+/// The first setup of each size is not timed, since it also creates the shared device of the GPU methods and the adapter has to be known for the header of the [`BenchmarkWriter`].
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the fds simulation file can not be loaded.
+/// - the [`WallElement`]s can not be duplicated correctly.
+/// - a [`BenchmarkWriter`] can not be created.
+/// - the progress bar can not be created.
+/// - it failed to initialize the simulation.
+fn one_dimensional_setup<P: AsRef<Path>, S: HeatTransfer1D>(
+    path: P,
+    label: String,
+    elements: &[usize],
+    simulation_type: SimulationType1D,
+    multi_progress: &MultiProgress,
+    prefix: &str,
+) -> Result<()> {
+    let path = path.as_ref();
+    let (materials, wall_elements) = load_fds_simulation_one_dimensional(path)
+        .with_context(|| format!("Failed to build simulation for file at {:?}", path))?;
+
+    let benchmark_path_part = BenchmarkPathPart::new(
+        Some(SETUP_PREFIX.to_string()),
+        label,
+        simulation_type.into(),
+    );
+    let reruns = get_simulation_reruns();
+    for &e in elements {
+        let duplication = duplication(e, wall_elements.len())?;
+        if skip_complete_benchmark(
+            benchmark_root().join(path),
+            &benchmark_path_part,
+            e,
+            multi_progress,
+            &format!("{prefix} {e}"),
+        )? {
+            continue;
+        }
+
+        let progress_bar = rerun_progress_bar(multi_progress, format!("{prefix} {e}"), reruns)?;
+        let duplicated_wall_elements = wall_elements
+            .iter()
+            .flat_map(|w| vec![w.clone(); duplication])
+            .collect::<Vec<_>>();
+
+        // The first setup also creates the shared device of the GPU methods, so it is not timed.
+        S::setup(materials.clone(), duplicated_wall_elements.clone())
+            .with_context(|| "Failed to setup shader.")?;
+        // The writer is created after the setup, so the adapter of the GPU methods is known for the header.
+        let mut benchmark_writer =
+            BenchmarkWriter::try_new(benchmark_root().join(path), &benchmark_path_part, e)
+                .with_context(|| {
+                    format!(
+                        "Failed to create benchmark writer for simulation at {:?}",
+                        path
+                    )
+                })?;
+
+        for _ in 0..reruns {
+            // The data is cloned before the timing starts, so only the setup itself is measured.
+            let materials = materials.clone();
+            let wall_elements = duplicated_wall_elements.clone();
+            let start = Instant::now();
+            let setup_data =
+                S::setup(materials, wall_elements).with_context(|| "Failed to setup shader.")?;
+            let elapsed = start.elapsed().as_secs_f64();
+            drop(setup_data);
+
+            benchmark_writer.write(elapsed).with_context(|| {
+                format!(
+                    "Failed to write to the benchmark writer for simulation at {:?}",
+                    path
+                )
+            })?;
+            progress_bar.inc(1);
+        }
+        finish_progress_bar(&progress_bar);
+    }
+    Ok(())
+}
+
+/// Start the benchmark of the setup for a given simulation method. The times are written to the [`BenchmarkPathPart`] with the prefix [`SETUP_PREFIX`].
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be started.
+pub fn one_dimensional_setup_by_simulation_type<P: AsRef<Path>>(
+    path: P,
+    label: String,
+    elements: &[usize],
+    simulation_type: SimulationType1D,
+    multi_progress: &MultiProgress,
+    prefix: &str,
+) -> Result<()> {
+    match simulation_type {
+        SimulationType1D::Cpu => one_dimensional_setup::<P, CPUSetupData>(
+            path,
+            label,
+            elements,
+            simulation_type,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM1 => one_dimensional_setup::<P, gpu_m1::GPUSetupData>(
+            path,
+            label,
+            elements,
+            simulation_type,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM2 => one_dimensional_setup::<P, gpu_m2::GPUSetupData>(
+            path,
+            label,
+            elements,
+            simulation_type,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM3 => one_dimensional_setup::<P, gpu_m3::GPUSetupData>(
+            path,
+            label,
+            elements,
+            simulation_type,
+            multi_progress,
+            prefix,
+        ),
+        SimulationType1D::GpuM4 => one_dimensional_setup::<P, gpu_m4::GPUSetupData>(
+            path,
+            label,
+            elements,
+            simulation_type,
+            multi_progress,
+            prefix,
+        ),
+    }
+}
+
+/// Execute a two dimensional benchmark simulation. The columns of the [`WallSection`] are duplicated to match the element count and all columns get the same boundary conditions.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the fds simulation file can not be loaded.
+/// - the columns can not be duplicated correctly.
+/// - a [`BenchmarkWriter`] can not be created.
+/// - the progress bar can not be created.
+/// - it failed to initialize the simulation.
+/// - it failed to update the simulation.
+/// - the [`BenchmarkWriter`] failed to read the next time step.
+fn two_dimensional<P: AsRef<Path>, S: HeatTransfer2D>(
+    path: P,
+    label: String,
+    elements: &[usize],
+    simulation_kind: SimulationKind,
+    simulation_type: SimulationType2D,
+    multi_progress: &MultiProgress,
+    prefix: &str,
+) -> Result<()> {
+    let path = path.as_ref();
+    let (materials, wall_section) = load_fds_simulation_two_dimensional(path)
+        .with_context(|| format!("Failed to build simulation for file at {:?}", path))?;
+
+    let benchmark_path_part = BenchmarkPathPart::new(None, label, simulation_type.into());
+    let reruns = get_simulation_reruns();
+    let steps = get_simulation_steps();
+    for &e in elements {
+        let duplication = duplication(e, wall_section.columns.len())?;
+        if skip_complete_benchmark(
+            benchmark_root().join(path),
+            &benchmark_path_part,
+            e,
+            multi_progress,
+            &format!("{prefix} {e}"),
+        )? {
+            continue;
+        }
+
+        let progress_bar = rerun_progress_bar(multi_progress, format!("{prefix} {e}"), reruns)?;
+        let mut benchmark_writer =
+            BenchmarkWriter::try_new(benchmark_root().join(path), &benchmark_path_part, e)
+                .with_context(|| {
+                    format!(
+                        "Failed to create benchmark writer for simulation at {:?}",
+                        path
+                    )
+                })?;
+        let duplicated_wall_section = WallSection::new(
+            wall_section
+                .columns
+                .iter()
+                .flat_map(|w| vec![w.clone(); duplication])
+                .collect::<Vec<_>>(),
+            wall_section.column_width,
+        );
+
+        let mut setup_data = S::setup(materials.clone(), duplicated_wall_section.clone())
+            .with_context(|| "Failed to setup simulation.")?;
+
+        for i in 0..reruns {
+            let mut device = SimulationBenchmarkDevice::try_new(simulation_kind, path, 1)?;
+            if i > 0 {
+                setup_data
+                    .reset(duplicated_wall_section.clone())
+                    .with_context(|| "Failed to reset simulation.")?;
+            }
+
+            let mut wall_temperature_buffer = vec![[0.0; 2]; e];
+            let mut elapsed = 0.0;
+            let mut i = 0;
+            while let Some(delta_time) = device.next() {
+                let delta_time = delta_time?;
+                if i > steps {
+                    break;
+                }
+                i += 1;
+
+                let (wall_heat_transfer_coefficients, wall_q_in) = device.buffers();
+                let wall_heat_transfer_coefficients = vec![wall_heat_transfer_coefficients[0]; e];
+                let wall_q_in = vec![wall_q_in[0]; e];
+
+                let start = Instant::now();
+
+                setup_data
+                    .update(
+                        delta_time,
+                        &wall_heat_transfer_coefficients,
+                        &wall_q_in,
+                        &mut wall_temperature_buffer,
+                    )
+                    .with_context(|| "Failed update")?;
+                elapsed += start.elapsed().as_secs_f64();
+            }
+            benchmark_writer.write(elapsed).with_context(|| {
+                format!(
+                    "Failed to write to the benchmark writer for simulation at {:?}",
+                    path
+                )
+            })?;
+            progress_bar.inc(1);
+        }
+        finish_progress_bar(&progress_bar);
+    }
+    Ok(())
+}
+
+/// Start the two dimensional CPU benchmark simulation.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be started.
+pub fn two_dimensional_cpu<P: AsRef<Path>>(
+    path: P,
+    label: String,
+    elements: &[usize],
+    simulation_kind: SimulationKind,
+    multi_progress: &MultiProgress,
+    prefix: &str,
+) -> Result<()> {
+    two_dimensional::<P, two_dimensional::cpu::CPUSetupData>(
+        path,
+        label,
+        elements,
+        simulation_kind,
+        SimulationType2D::Cpu,
+        multi_progress,
+        prefix,
+    )
+}
+
+/// Start the two dimensional benchmark simulation for a given simulation method.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be started.
+pub fn two_dimensional_by_simulation_type<P: AsRef<Path>>(
+    path: P,
+    label: String,
+    elements: &[usize],
+    simulation_kind: SimulationKind,
+    simulation_type: SimulationType2D,
+    multi_progress: &MultiProgress,
+    prefix: &str,
+) -> Result<()> {
+    match simulation_type {
+        SimulationType2D::Cpu => two_dimensional_cpu(
+            path,
+            label,
+            elements,
+            simulation_kind,
+            multi_progress,
+            prefix,
+        ),
     }
 }
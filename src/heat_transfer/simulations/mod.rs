@@ -1,11 +1,18 @@
 use crate::{
-    fds::{parse_script_from_file, Material, Meta, SurfaceCell},
-    heat_transfer::one_dimensional::WallElement,
+    fds::{parse_script_from_file, parse_wall_increment_from_file, Material, Meta, SurfaceCell},
+    heat_transfer::{one_dimensional::WallElement, two_dimensional::WallSection},
 };
 use anyhow::*;
 use clap::ValueEnum;
-use std::fmt::Display;
-use std::{path::Path, vec};
+use serde::Deserialize;
+use std::{fmt::Display, str::FromStr};
+use std::{
+    fs::{canonicalize, metadata},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+    time::SystemTime,
+    vec,
+};
 
 mod benchmark;
 pub mod temperature;
@@ -13,9 +20,32 @@ pub use benchmark::*;
 
 use super::one_dimensional;
 
-/// The factor the gas delta time is multiplied to get the solid delta time.
+/// The FDS input file inside the directory of a simulation.
+/// A simulation directory has the layout
+/// ```text
+/// <simulation>/heat_transfer.fds
+/// <simulation>/result/heat_transfer_devc.csv
+/// ```
+/// and can be placed anywhere, e.g. inside a temporary directory.
+pub const SIMULATION_FILE: &str = "heat_transfer.fds";
+
+/// The device output of FDS relative to the directory of a simulation, see [`SIMULATION_FILE`].
+pub const DEVICE_FILE: &str = "result/heat_transfer_devc.csv";
+
+/// The default factor the gas delta time is multiplied to get the solid delta time. It is used if a simulation does not set `WALL_INCREMENT`, like FDS does.
 pub const DELTA_TIME_SOLID_FACTOR: u8 = 2;
 
+/// Returns the factor the gas delta time is multiplied to get the solid delta time for the simulation inside the passed directory.
+/// It is read from `WALL_INCREMENT` of the `&TIME` namespace of the [`SIMULATION_FILE`] and defaults to [`DELTA_TIME_SOLID_FACTOR`].
+///
+/// # Errors
+///
+/// This function will return an error if the simulation file can not be parsed or `WALL_INCREMENT` is invalid.
+pub fn load_delta_time_solid_factor<P: AsRef<Path>>(path: P) -> Result<u8> {
+    let wall_increment = parse_wall_increment_from_file(path.as_ref().join(SIMULATION_FILE))?;
+    Ok(wall_increment.unwrap_or(DELTA_TIME_SOLID_FACTOR))
+}
+
 /// All supported simulation methods.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum SimulationMethod {
@@ -27,8 +57,12 @@ pub enum SimulationMethod {
     OneDimensionalGpuM2 = 0b00000100,
     #[clap(name = "1d_gpu_m3")]
     OneDimensionalGpuM3 = 0b00001000,
+    #[clap(name = "1d_gpu_m4")]
+    OneDimensionalGpuM4 = 0b00010000,
     #[clap(name = "1d")]
-    OneDimensional = 0b00001111,
+    OneDimensional = 0b00011111,
+    #[clap(name = "2d_cpu")]
+    TwoDimensionalCpu = 0b00100000,
     #[clap(name = "fds")]
     SpeedTestFDS = 0b10000000,
 }
@@ -53,6 +87,8 @@ impl SimulationMethod {
             SimulationMethod::OneDimensionalGpuM1 => Ok(SimulationType1D::GpuM1.path_str()),
             SimulationMethod::OneDimensionalGpuM2 => Ok(SimulationType1D::GpuM2.path_str()),
             SimulationMethod::OneDimensionalGpuM3 => Ok(SimulationType1D::GpuM3.path_str()),
+            SimulationMethod::OneDimensionalGpuM4 => Ok(SimulationType1D::GpuM4.path_str()),
+            SimulationMethod::TwoDimensionalCpu => Ok(SimulationType2D::Cpu.path_str()),
             SimulationMethod::SpeedTestFDS => Ok("fds"),
             SimulationMethod::OneDimensional => {
                 bail!("{self:?} is a collection, therefore has no distinct path.")
@@ -67,24 +103,41 @@ impl From<SimulationType1D> for SimulationMethod {
             SimulationType1D::GpuM1 => Self::OneDimensionalGpuM1,
             SimulationType1D::GpuM2 => Self::OneDimensionalGpuM2,
             SimulationType1D::GpuM3 => Self::OneDimensionalGpuM3,
+            SimulationType1D::GpuM4 => Self::OneDimensionalGpuM4,
+        }
+    }
+}
+
+impl From<SimulationType2D> for SimulationMethod {
+    fn from(value: SimulationType2D) -> Self {
+        match value {
+            SimulationType2D::Cpu => Self::TwoDimensionalCpu,
         }
     }
 }
 
 /// All simulation methods for the one dimensional heat transfer.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+/// The command line names and [`FromStr`] use the [`path_str`](SimulationType1D::path_str) of the method.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum SimulationType1D {
+    #[clap(name = "cpu")]
     Cpu,
+    #[clap(name = "gpu_m1")]
     GpuM1,
+    #[clap(name = "gpu_m2")]
     GpuM2,
+    #[clap(name = "gpu_m3")]
     GpuM3,
+    #[clap(name = "gpu_m4")]
+    GpuM4,
 }
 impl SimulationType1D {
-    pub const ALL_1D: [SimulationType1D; 4] = [
+    pub const ALL_1D: [SimulationType1D; 5] = [
         SimulationType1D::Cpu,
         SimulationType1D::GpuM1,
         SimulationType1D::GpuM2,
         SimulationType1D::GpuM3,
+        SimulationType1D::GpuM4,
     ];
     pub fn path_str(&self) -> &'static str {
         match self {
@@ -92,6 +145,7 @@ impl SimulationType1D {
             SimulationType1D::GpuM1 => "gpu_m1",
             SimulationType1D::GpuM2 => "gpu_m2",
             SimulationType1D::GpuM3 => "gpu_m3",
+            SimulationType1D::GpuM4 => "gpu_m4",
         }
     }
     pub fn is_simulation_type(&self, simulation_types: Option<&[SimulationMethod]>) -> bool {
@@ -113,6 +167,56 @@ impl Display for SimulationType1D {
             SimulationType1D::GpuM1 => "GPU M1",
             SimulationType1D::GpuM2 => "GPU M2",
             SimulationType1D::GpuM3 => "GPU M3",
+            SimulationType1D::GpuM4 => "GPU M4",
+        };
+        write!(f, "{s}")
+    }
+}
+impl FromStr for SimulationType1D {
+    type Err = Error;
+
+    /// Parses the [`path_str`](SimulationType1D::path_str) of a [`SimulationType1D`], e.g. `gpu_m3`.
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL_1D
+            .into_iter()
+            .find(|simulation_type| simulation_type.path_str() == s)
+            .ok_or_else(|| {
+                anyhow!(
+                    "Unknown one dimensional simulation method {s:?}. Available: {:?}.",
+                    Self::ALL_1D.map(|simulation_type| simulation_type.path_str())
+                )
+            })
+    }
+}
+
+/// All simulation methods for the two dimensional heat transfer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SimulationType2D {
+    Cpu,
+}
+impl SimulationType2D {
+    pub const ALL_2D: [SimulationType2D; 1] = [SimulationType2D::Cpu];
+    pub fn path_str(&self) -> &'static str {
+        match self {
+            SimulationType2D::Cpu => "2d_cpu",
+        }
+    }
+    pub fn is_simulation_type(&self, simulation_types: Option<&[SimulationMethod]>) -> bool {
+        match simulation_types {
+            Some(simulation_types) => {
+                let left: SimulationMethod = (*self).into();
+                simulation_types
+                    .iter()
+                    .any(|s| left as u16 & (*s) as u16 != 0)
+            }
+            None => true,
+        }
+    }
+}
+impl Display for SimulationType2D {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            SimulationType2D::Cpu => "2D CPU",
         };
         write!(f, "{s}")
     }
@@ -123,8 +227,8 @@ impl Display for SimulationType1D {
 /// # Errors
 ///
 /// This function will return an error if in the simulation are multiple surfaces defined and the amount of surfaces, witch are equal to the amount of measured wall elements inside a fds simulation, and is not completely divisible the desired element count.
-fn duplication(elements: usize, wall_elements: usize) -> Result<usize> {
-    if elements % wall_elements > 0 {
+pub(crate) fn duplication(elements: usize, wall_elements: usize) -> Result<usize> {
+    if !elements.is_multiple_of(wall_elements) {
         bail!(
             "Element count ({elements}) must be divisible without residue by wall element count {}.",
             wall_elements
@@ -133,18 +237,91 @@ fn duplication(elements: usize, wall_elements: usize) -> Result<usize> {
     Ok(elements / wall_elements)
 }
 
-/// Loads the FDS simulation for a one dimensional simulation.
+/// The [`Material`]s together with their `ID` and the [`WallElement`]s of a one dimensional simulation.
+pub type NamedSimulation1D = (Vec<(String, Material)>, Vec<WallElement>);
+
+/// A loaded one dimensional simulation together with the canonicalized path and the modification time of its simulation file.
+type CachedSimulation1D = (
+    PathBuf,
+    SystemTime,
+    Vec<(String, Material)>,
+    Vec<WallElement>,
+);
+
+/// The one dimensional simulations that were already loaded during this process.
+static SIMULATION_1D_CACHE: Mutex<Vec<CachedSimulation1D>> = Mutex::new(Vec::new());
+
+/// Loads the FDS simulation for a one dimensional simulation from the [`SIMULATION_FILE`] inside the passed directory.
+/// The loaded simulation is cached by the canonicalized path and the modification time of its simulation file, so a simulation is only parsed again after the file changed.
+/// Every call returns its own clone of the cached [`WallElement`]s.
 ///
 /// # Errors
 ///
 /// This function will return an error if
 /// - the passed file can not be parsed.
-/// - the file is defined as 3D inside the meta data
+/// - the file is defined as 2D or 3D inside the meta data
 pub fn load_fds_simulation_one_dimensional<P: AsRef<Path>>(
     path: P,
 ) -> Result<(Vec<Material>, Vec<WallElement>)> {
+    let (materials, wall_elements) = load_fds_simulation_one_dimensional_named(path)?;
+    Ok((
+        materials
+            .into_iter()
+            .map(|(_, material)| material)
+            .collect(),
+        wall_elements,
+    ))
+}
+
+/// Loads the FDS simulation for a one dimensional simulation like [`load_fds_simulation_one_dimensional`], but keeps the `ID` of each [`Material`].
+/// The `material` of a [`WallCell`](one_dimensional::WallCell) is the index of its material inside the returned list.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the passed file can not be parsed.
+/// - the file is defined as 2D or 3D inside the meta data
+pub fn load_fds_simulation_one_dimensional_named<P: AsRef<Path>>(
+    path: P,
+) -> Result<NamedSimulation1D> {
     let path = path.as_ref();
-    let simulation_file_path = path.join("heat_transfer.fds");
+    let key = canonicalize(path.join(SIMULATION_FILE))
+        .and_then(|file| std::result::Result::Ok((metadata(&file)?.modified()?, file)));
+    let std::result::Result::Ok((modified, file)) = key else {
+        return parse_fds_simulation_one_dimensional(path);
+    };
+
+    if let Some((_, _, materials, wall_elements)) = SIMULATION_1D_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(cached_file, cached_modified, ..)| {
+            *cached_file == file && *cached_modified == modified
+        })
+    {
+        return Ok((materials.clone(), wall_elements.clone()));
+    }
+
+    // The cache is not locked while parsing, so different simulations can be parsed in parallel.
+    let (materials, wall_elements) = parse_fds_simulation_one_dimensional(path)?;
+    let mut cache = SIMULATION_1D_CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    cache.retain(|(cached_file, ..)| *cached_file != file);
+    cache.push((file, modified, materials.clone(), wall_elements.clone()));
+    Ok((materials, wall_elements))
+}
+
+/// Parses the FDS simulation for a one dimensional simulation without the cache of [`load_fds_simulation_one_dimensional_named`].
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the passed file can not be parsed.
+/// - the file is defined as 2D or 3D inside the meta data
+/// - a [`WallElement`] has less than [`MIN_CELL_COUNT`](one_dimensional::MIN_CELL_COUNT) cells.
+fn parse_fds_simulation_one_dimensional(path: &Path) -> Result<NamedSimulation1D> {
+    let simulation_file_path = path.join(SIMULATION_FILE);
 
     let (meta, material_list, surface_list) = parse_script_from_file(simulation_file_path)
         .with_context(|| format!("Failed to parse script at {path:?}."))?;
@@ -152,24 +329,109 @@ pub fn load_fds_simulation_one_dimensional<P: AsRef<Path>>(
         Meta::OneDimensional { surface_ids } => {
             let mut wall_elements = vec![];
             for surface_id in surface_ids {
-                let wall_cells = surface_list[surface_id]
-                    .1
+                let surface = &surface_list[surface_id].1;
+                let wall_cells = surface
                     .iter()
                     .map(
                         |SurfaceCell { material_id, size }| one_dimensional::WallCell {
                             material: *material_id,
                             size: *size,
-                            temperature: 20.0,
+                            temperature: surface.initial_temperature,
                         },
                     )
                     .collect::<Vec<_>>();
-                wall_elements.push(WallElement::new(wall_cells))
+                wall_elements.push(WallElement::with_contact_resistances(
+                    wall_cells,
+                    surface.contact_resistances.clone(),
+                ))
             }
 
-            let materials = material_list.into_materials();
+            one_dimensional::check_cell_counts(&wall_elements)
+                .with_context(|| format!("The simulation at {path:?} has too few cells."))?;
+            let materials = material_list.into_named_materials();
 
             Ok((materials, wall_elements))
         }
+        Meta::TwoDimensional { .. } => {
+            bail!("{path:?} is a 2D simulation.")
+        }
+        Meta::ThreeDimensional { .. } => {
+            bail!("{path:?} is a 3D simulation.")
+        }
+    }
+}
+
+/// The [`Material`]s together with their `ID` and the [`WallSection`] of a two dimensional simulation.
+pub type NamedSimulation2D = (Vec<(String, Material)>, WallSection);
+
+/// Loads the FDS simulation for a two dimensional simulation from the [`SIMULATION_FILE`] inside the passed directory.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the passed file can not be parsed.
+/// - the file is not defined as 2D inside the meta data
+pub fn load_fds_simulation_two_dimensional<P: AsRef<Path>>(
+    path: P,
+) -> Result<(Vec<Material>, WallSection)> {
+    let (materials, wall_section) = load_fds_simulation_two_dimensional_named(path)?;
+    Ok((
+        materials
+            .into_iter()
+            .map(|(_, material)| material)
+            .collect(),
+        wall_section,
+    ))
+}
+
+/// Loads the FDS simulation for a two dimensional simulation like [`load_fds_simulation_two_dimensional`], but keeps the `ID` of each [`Material`].
+/// The `material` of a [`WallCell`](one_dimensional::WallCell) is the index of its material inside the returned list.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the passed file can not be parsed.
+/// - the file is not defined as 2D inside the meta data
+pub fn load_fds_simulation_two_dimensional_named<P: AsRef<Path>>(
+    path: P,
+) -> Result<NamedSimulation2D> {
+    let path = path.as_ref();
+    let simulation_file_path = path.join(SIMULATION_FILE);
+
+    let (meta, material_list, surface_list) = parse_script_from_file(simulation_file_path)
+        .with_context(|| format!("Failed to parse script at {path:?}."))?;
+    match meta {
+        Meta::TwoDimensional {
+            x,
+            column_width,
+            surface_id,
+        } => {
+            let surface = &surface_list[surface_id].1;
+            let wall_cells = surface
+                .iter()
+                .map(
+                    |SurfaceCell { material_id, size }| one_dimensional::WallCell {
+                        material: *material_id,
+                        size: *size,
+                        temperature: surface.initial_temperature,
+                    },
+                )
+                .collect::<Vec<_>>();
+            let columns = vec![
+                WallElement::with_contact_resistances(
+                    wall_cells,
+                    surface.contact_resistances.clone()
+                );
+                x
+            ];
+
+            let materials = material_list.into_named_materials();
+
+            Ok((materials, WallSection::new(columns, column_width)))
+        }
+        Meta::OneDimensional { .. } => {
+            bail!("{path:?} is a 1D simulation.")
+        }
         Meta::ThreeDimensional { .. } => {
             bail!("{path:?} is a 3D simulation.")
         }
@@ -177,13 +439,25 @@ pub fn load_fds_simulation_one_dimensional<P: AsRef<Path>>(
 }
 
 /// All kinds of simulations that can be run.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SimulationKind {
     Diabatic = 0b001,
     DiabaticOneSide = 0b010,
     Adiabatic = 0b100,
+    /// Both wall sides exchange heat with the gas only by convection, the radiation is ignored.
+    Convective = 0b1000,
 }
 impl SimulationKind {
+    /// Marks the sides of the [`WallElement`]s that are purely convective with this [`SimulationKind`], see [`WallElement::with_convective`].
+    pub fn mark_convective_sides(&self, wall_elements: Vec<WallElement>) -> Vec<WallElement> {
+        let convective = *self == SimulationKind::Convective;
+        wall_elements
+            .into_iter()
+            .map(|w| w.with_convective([convective, convective]))
+            .collect()
+    }
+
     /// Checks if this [`SimulationKind`] is defined in the parsed argument.
     pub fn is_simulation_kind(&self, simulation_kind: Option<&[SimulationKind]>) -> bool {
         match simulation_kind {
@@ -194,3 +468,41 @@ impl SimulationKind {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulation_type_1d_path_str_round_trip() {
+        for simulation_type in SimulationType1D::ALL_1D {
+            let path_str = simulation_type.path_str();
+            assert_eq!(
+                path_str.parse::<SimulationType1D>().unwrap(),
+                simulation_type
+            );
+            // The command line names must match the parsed names.
+            assert_eq!(
+                simulation_type.to_possible_value().unwrap().get_name(),
+                path_str
+            );
+        }
+        let err = "gpu_m5".parse::<SimulationType1D>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown one dimensional simulation method \"gpu_m5\". Available: [\"cpu\", \"gpu_m1\", \"gpu_m2\", \"gpu_m3\", \"gpu_m4\"]."
+        );
+    }
+
+    #[test]
+    fn load_two_dimensional_deck() {
+        let (materials, wall_section) =
+            load_fds_simulation_two_dimensional_named("fds/2D/Diabatic/steel_k_c").unwrap();
+        assert_eq!(wall_section.columns.len(), 16);
+        assert_eq!(wall_section.column_width, 0.01);
+        assert!(!materials.is_empty());
+        assert!(wall_section.columns[0]
+            .iter()
+            .all(|c| (c.material as usize) < materials.len()));
+    }
+}
@@ -1,76 +1,98 @@
 use crate::{
-    fds::Devices,
+    fds::{count_device_rows, Devices},
     heat_transfer::one_dimensional::{
-        cpu::{CPUSetupData, ADIABATIC_H, CONST_TEMP_H},
-        gpu_m1, gpu_m2, gpu_m3, HeatTransfer1D,
+        cpu::{CPUF64SetupData, CPUSetupData, EnergyBalance, ADIABATIC_H, CONST_TEMP_H},
+        get_cpu_precision, get_solver_config, gpu_m1, gpu_m2, gpu_m3, gpu_m4, HeatTransfer1D,
+        Precision, SubdivisionDiagnostics, WallElement, MIN_CELL_COUNT,
     },
 };
 use anyhow::*;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use super::{
-    load_fds_simulation_one_dimensional, SimulationKind, SimulationType1D, DELTA_TIME_SOLID_FACTOR,
+    load_delta_time_solid_factor, load_fds_simulation_one_dimensional,
+    load_fds_simulation_one_dimensional_named, SimulationKind, SimulationType1D, DEVICE_FILE,
 };
 
 /// An helper struct for reading the simulation data for a temperature plot line by line witch means simulation step by simulation step.
 pub struct SimulationTemperatureDevice1D {
     simulation_kind: SimulationKind,
     last_time: f32,
+    delta_time_solid_factor: u8,
     device: Devices,
 }
 impl SimulationTemperatureDevice1D {
-    /// Attempts to create a [`SimulationBenchmarkDevice`].
+    /// Attempts to create a [`SimulationTemperatureDevice1D`] that reads the [`DEVICE_FILE`] inside the passed simulation directory.
+    /// If an `element` index is passed, the devices of this wall element are read, which are suffixed with the 1-based index, e.g. `DEVC_WALL_TEMPERATURE_WEST_1`.
     ///
     /// # Errors
     ///
     /// This function will return an error if
     /// - the transmitted device file cannot be read.
     /// - the transmitted device file does not match the requested devices.
-    pub fn try_new<P: AsRef<Path>>(simulation_kind: SimulationKind, path: P) -> Result<Self> {
+    /// - the factor of the solid delta time can not be read, see [`load_delta_time_solid_factor`].
+    pub fn try_new<P: AsRef<Path>>(
+        simulation_kind: SimulationKind,
+        path: P,
+        element: Option<usize>,
+    ) -> Result<Self> {
         let path = path.as_ref();
-        let device_path = path.join("result/heat_transfer_devc.csv");
-        let device = match simulation_kind {
-            SimulationKind::Diabatic => Devices::try_new(
-                device_path,
-                &[
-                    "Time",
-                    "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_WEST",
-                    "DEVC_GAS_TEMPERATURE_WEST",
-                    "DEVC_WALL_RADIATIVE_HEAT_FLUX_WEST",
-                    "DEVC_WALL_TEMPERATURE_WEST",
-                    "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_EAST",
-                    "DEVC_GAS_TEMPERATURE_EAST",
-                    "DEVC_WALL_RADIATIVE_HEAT_FLUX_EAST",
-                    "DEVC_WALL_TEMPERATURE_EAST",
-                ],
-            )?,
-            SimulationKind::DiabaticOneSide => Devices::try_new(
-                device_path,
-                &[
-                    "Time",
-                    "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_WEST",
-                    "DEVC_GAS_TEMPERATURE_WEST",
-                    "DEVC_WALL_RADIATIVE_HEAT_FLUX_WEST",
-                    "DEVC_WALL_TEMPERATURE_WEST",
-                    "DEVC_WALL_TEMPERATURE_EAST",
-                ],
-            )?,
-            SimulationKind::Adiabatic => {
-                Devices::try_new(device_path, &["Time", "DEVC_WALL_TEMPERATURE_WEST"])?
-            }
+        let delta_time_solid_factor = load_delta_time_solid_factor(path)?;
+        let device_path = path.join(DEVICE_FILE);
+        let devices: &[&str] = match simulation_kind {
+            SimulationKind::Diabatic => &[
+                "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_WEST",
+                "DEVC_GAS_TEMPERATURE_WEST",
+                "DEVC_WALL_RADIATIVE_HEAT_FLUX_WEST",
+                "DEVC_WALL_TEMPERATURE_WEST",
+                "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_EAST",
+                "DEVC_GAS_TEMPERATURE_EAST",
+                "DEVC_WALL_RADIATIVE_HEAT_FLUX_EAST",
+                "DEVC_WALL_TEMPERATURE_EAST",
+            ],
+            SimulationKind::DiabaticOneSide => &[
+                "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_WEST",
+                "DEVC_GAS_TEMPERATURE_WEST",
+                "DEVC_WALL_RADIATIVE_HEAT_FLUX_WEST",
+                "DEVC_WALL_TEMPERATURE_WEST",
+                "DEVC_WALL_TEMPERATURE_EAST",
+            ],
+            SimulationKind::Adiabatic => &["DEVC_WALL_TEMPERATURE_WEST"],
+            SimulationKind::Convective => &[
+                "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_WEST",
+                "DEVC_GAS_TEMPERATURE_WEST",
+                "DEVC_WALL_TEMPERATURE_WEST",
+                "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_EAST",
+                "DEVC_GAS_TEMPERATURE_EAST",
+                "DEVC_WALL_TEMPERATURE_EAST",
+            ],
         };
+        let devices = ["Time".to_string()]
+            .into_iter()
+            .chain(devices.iter().map(|d| match element {
+                Some(i) => format!("{d}_{}", i + 1),
+                None => d.to_string(),
+            }))
+            .collect::<Vec<_>>();
+        let device = Devices::try_new(device_path, &devices)?;
         std::result::Result::Ok(Self {
             simulation_kind,
             last_time: 0.0,
+            delta_time_solid_factor,
             device,
         })
     }
+
+    /// Returns the number of device rows that are read per solid time step.
+    pub fn delta_time_solid_factor(&self) -> u8 {
+        self.delta_time_solid_factor
+    }
 }
 impl Iterator for SimulationTemperatureDevice1D {
     type Item = Result<(f32, [f32; 2], [f32; 2], [f32; 2])>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        for _ in 0..(DELTA_TIME_SOLID_FACTOR - 1) {
+        for _ in 0..(self.delta_time_solid_factor - 1) {
             if let Err(err) = self.device.next()? {
                 return Some(Err(err));
             }
@@ -120,6 +142,17 @@ impl Iterator for SimulationTemperatureDevice1D {
                     fds,
                 )))
             }
+            SimulationKind::Convective => {
+                let wall_heat_transfer_coefficient = [data[1], data[4]];
+                let wall_q_in = [data[1] * data[2], data[4] * data[5]];
+                let fds = [data[3], data[6]];
+                Some(std::result::Result::Ok((
+                    delta_time,
+                    wall_heat_transfer_coefficient,
+                    wall_q_in,
+                    fds,
+                )))
+            }
         }
     }
 }
@@ -158,35 +191,78 @@ impl Temperatures {
     }
 }
 
-/// Execute a simulation to validate with FDS
+/// Prints how many time steps of a simulation were capped at the maximum number of time subdivisions and are therefore under-resolved.
+fn print_subdivision_diagnostics(path: &Path, diagnostics: SubdivisionDiagnostics) {
+    let SubdivisionDiagnostics {
+        steps,
+        capped_steps,
+    } = diagnostics;
+    let share = match steps {
+        0 => 0.0,
+        steps => capped_steps as f64 / steps as f64 * 100.0,
+    };
+    println!(
+        "Diagnostics for {path:?}: {capped_steps} of {steps} time steps ({share:.2} %) needed more than {} time subdivisions.",
+        get_solver_config().max_time_subdivisions
+    );
+}
+
+/// Execute a simulation to validate with FDS.
+/// If an `element` index is passed, only this wall element of the simulation is calculated with its own devices.
+/// If a `progress` callback is passed, it is called after each simulation step with the current step and the total number of steps estimated from the length of the device file.
 ///
 /// # Errors
 ///
 /// This function will return an error if
 /// - the fds simulation file can not be loaded.
-/// - the fds simulation defines multiple materials inside the meta data.
+/// - the fds simulation defines multiple materials inside the meta data and no `element` is passed.
+/// - the passed `element` does not exist.
 /// - it failed to initialize the simulation.
 /// - it failed to update the simulation.
 fn one_dimensional<P: AsRef<Path>, H: HeatTransfer1D>(
     path: P,
     simulation_kind: SimulationKind,
+    element: Option<usize>,
+    mut progress: Option<&mut dyn FnMut(usize, usize)>,
 ) -> Result<Temperatures> {
     let device: SimulationTemperatureDevice1D =
-        SimulationTemperatureDevice1D::try_new(simulation_kind, &path).with_context(|| {
-            format!(
-                "Failed to build SimulationTemperatureDevice for file at {:?}",
-                path.as_ref()
-            )
-        })?;
+        SimulationTemperatureDevice1D::try_new(simulation_kind, &path, element).with_context(
+            || {
+                format!(
+                    "Failed to build SimulationTemperatureDevice for file at {:?}",
+                    path.as_ref()
+                )
+            },
+        )?;
     let (materials, wall_elements) = load_fds_simulation_one_dimensional(&path)
         .with_context(|| format!("Failed to build simulation for file at {:?}", path.as_ref()))?;
-    if wall_elements.len() > 1 {
-        bail!("Multiple wall elements in meta defined, wich is not supported in simulate_collect_temperature.");
-    }
+    let wall_elements = simulation_kind.mark_convective_sides(wall_elements);
+    let wall_elements = match element {
+        Some(i) => match wall_elements.get(i) {
+            Some(wall_element) => vec![wall_element.clone()],
+            None => bail!(
+                "The wall element {i} does not exist, the simulation has {} wall elements.",
+                wall_elements.len()
+            ),
+        },
+        None if wall_elements.len() > 1 => {
+            bail!("Multiple wall elements in meta defined, wich is not supported in simulate_collect_temperature.");
+        }
+        None => wall_elements,
+    };
 
     let mut heat_transfer =
         H::setup(materials, wall_elements).with_context(|| "Failed to setup heat transfer.")?;
 
+    let total_steps = match progress {
+        Some(_) => {
+            let device_path = path.as_ref().join(DEVICE_FILE);
+            (count_device_rows(&device_path)? / device.delta_time_solid_factor() as usize)
+                .saturating_sub(1)
+        }
+        None => 0,
+    };
+
     let mut time = vec![];
     let mut fds_front = vec![];
     let mut fds_back = vec![];
@@ -195,7 +271,7 @@ fn one_dimensional<P: AsRef<Path>, H: HeatTransfer1D>(
 
     let mut wall_temperature_buffer = [[0.0f32; 2]];
     let mut elapsed_time = 0.0;
-    for data in device.skip(1) {
+    for (step, data) in device.skip(1).enumerate() {
         let (delta_time, wall_heat_transfer_coefficient, wall_q_in, fds) = data?;
         fds_front.push(fds[0]);
         fds_back.push(fds[1]);
@@ -213,6 +289,12 @@ fn one_dimensional<P: AsRef<Path>, H: HeatTransfer1D>(
         time.push(elapsed_time);
         sim_front.push(wall_temperature_buffer[0][0]);
         sim_back.push(wall_temperature_buffer[0][1]);
+        if let Some(progress) = progress.as_mut() {
+            progress(step + 1, total_steps);
+        }
+    }
+    if let Some(diagnostics) = heat_transfer.subdivision_diagnostics() {
+        print_subdivision_diagnostics(path.as_ref(), diagnostics);
     }
 
     Ok(Temperatures {
@@ -224,7 +306,8 @@ fn one_dimensional<P: AsRef<Path>, H: HeatTransfer1D>(
     })
 }
 
-/// Start the CPU simulation.
+/// Start the CPU simulation with the precision set by [`set_cpu_precision`](crate::heat_transfer::one_dimensional::set_cpu_precision).
+/// The optional `progress` callback is called after each simulation step with the current step and the estimated total number of steps.
 ///
 /// # Errors
 ///
@@ -232,11 +315,21 @@ fn one_dimensional<P: AsRef<Path>, H: HeatTransfer1D>(
 pub fn one_dimensional_cpu<P: AsRef<Path>>(
     path: P,
     simulation_kind: SimulationKind,
+    element: Option<usize>,
+    progress: Option<&mut dyn FnMut(usize, usize)>,
 ) -> Result<Temperatures> {
-    one_dimensional::<P, CPUSetupData>(path, simulation_kind)
+    match get_cpu_precision() {
+        Precision::F32 => {
+            one_dimensional::<P, CPUSetupData>(path, simulation_kind, element, progress)
+        }
+        Precision::F64 => {
+            one_dimensional::<P, CPUF64SetupData>(path, simulation_kind, element, progress)
+        }
+    }
 }
 
 /// Start the GPU M1 simulation.
+/// The optional `progress` callback is called after each simulation step with the current step and the estimated total number of steps.
 ///
 /// # Errors
 ///
@@ -244,11 +337,14 @@ pub fn one_dimensional_cpu<P: AsRef<Path>>(
 pub fn one_dimensional_gpu_m1<P: AsRef<Path>>(
     path: P,
     simulation_kind: SimulationKind,
+    element: Option<usize>,
+    progress: Option<&mut dyn FnMut(usize, usize)>,
 ) -> Result<Temperatures> {
-    one_dimensional::<P, gpu_m1::GPUSetupData>(path, simulation_kind)
+    one_dimensional::<P, gpu_m1::GPUSetupData>(path, simulation_kind, element, progress)
 }
 
 /// Start the GPU M2 simulation.
+/// The optional `progress` callback is called after each simulation step with the current step and the estimated total number of steps.
 ///
 /// # Errors
 ///
@@ -256,11 +352,14 @@ pub fn one_dimensional_gpu_m1<P: AsRef<Path>>(
 pub fn one_dimensional_gpu_m2<P: AsRef<Path>>(
     path: P,
     simulation_kind: SimulationKind,
+    element: Option<usize>,
+    progress: Option<&mut dyn FnMut(usize, usize)>,
 ) -> Result<Temperatures> {
-    one_dimensional::<P, gpu_m2::GPUSetupData>(path, simulation_kind)
+    one_dimensional::<P, gpu_m2::GPUSetupData>(path, simulation_kind, element, progress)
 }
 
 /// Start the GPU M2 simulation.
+/// The optional `progress` callback is called after each simulation step with the current step and the estimated total number of steps.
 ///
 /// # Errors
 ///
@@ -268,8 +367,25 @@ pub fn one_dimensional_gpu_m2<P: AsRef<Path>>(
 pub fn one_dimensional_gpu_m3<P: AsRef<Path>>(
     path: P,
     simulation_kind: SimulationKind,
+    element: Option<usize>,
+    progress: Option<&mut dyn FnMut(usize, usize)>,
 ) -> Result<Temperatures> {
-    one_dimensional::<P, gpu_m3::GPUSetupData>(path, simulation_kind)
+    one_dimensional::<P, gpu_m3::GPUSetupData>(path, simulation_kind, element, progress)
+}
+
+/// Start the GPU M4 simulation.
+/// The optional `progress` callback is called after each simulation step with the current step and the estimated total number of steps.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be started.
+pub fn one_dimensional_gpu_m4<P: AsRef<Path>>(
+    path: P,
+    simulation_kind: SimulationKind,
+    element: Option<usize>,
+    progress: Option<&mut dyn FnMut(usize, usize)>,
+) -> Result<Temperatures> {
+    one_dimensional::<P, gpu_m4::GPUSetupData>(path, simulation_kind, element, progress)
 }
 
 /// Start the simulation for a given simulation method.
@@ -281,11 +397,445 @@ pub fn one_dimensional_by_type<P: AsRef<Path>>(
     path: P,
     simulation_kind: SimulationKind,
     simulation_type: SimulationType1D,
+) -> Result<Temperatures> {
+    one_dimensional_element_by_type(path, simulation_kind, simulation_type, None)
+}
+
+/// Start the simulation of a single wall element for a given simulation method. If no `element` is passed, the simulation must contain a single wall element.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be started.
+pub fn one_dimensional_element_by_type<P: AsRef<Path>>(
+    path: P,
+    simulation_kind: SimulationKind,
+    simulation_type: SimulationType1D,
+    element: Option<usize>,
 ) -> Result<Temperatures> {
     match simulation_type {
-        SimulationType1D::Cpu => one_dimensional_cpu(path, simulation_kind),
-        SimulationType1D::GpuM1 => one_dimensional_gpu_m1(path, simulation_kind),
-        SimulationType1D::GpuM2 => one_dimensional_gpu_m2(path, simulation_kind),
-        SimulationType1D::GpuM3 => one_dimensional_gpu_m3(path, simulation_kind),
+        SimulationType1D::Cpu => one_dimensional_cpu(path, simulation_kind, element, None),
+        SimulationType1D::GpuM1 => one_dimensional_gpu_m1(path, simulation_kind, element, None),
+        SimulationType1D::GpuM2 => one_dimensional_gpu_m2(path, simulation_kind, element, None),
+        SimulationType1D::GpuM3 => one_dimensional_gpu_m3(path, simulation_kind, element, None),
+        SimulationType1D::GpuM4 => one_dimensional_gpu_m4(path, simulation_kind, element, None),
+    }
+}
+
+/// Returns the wall elements of the simulation at the passed path that are compared with FDS.
+/// A simulation with a single wall element returns `[None]`. Otherwise the index of the first occurrence of each distinct wall element is returned, so a simulation with identical wall elements is only compared once.
+///
+/// # Errors
+///
+/// This function will return an error if the fds simulation file can not be loaded.
+pub fn compared_elements<P: AsRef<Path>>(path: P) -> Result<Vec<Option<usize>>> {
+    let path = path.as_ref();
+    let (_, wall_elements) = load_fds_simulation_one_dimensional(path)
+        .with_context(|| format!("Failed to build simulation for file at {path:?}"))?;
+    if wall_elements.len() == 1 {
+        return Ok(vec![None]);
+    }
+    Ok((0..wall_elements.len())
+        .filter(|&i| !wall_elements[..i].contains(&wall_elements[i]))
+        .map(Some)
+        .collect())
+}
+
+/// Start the simulation for a given simulation method with one [`Temperatures`] for each wall element returned by [`compared_elements`].
+///
+/// # Errors
+///
+/// This function will return an error if a simulation can not be started.
+pub fn one_dimensional_elements_by_type<P: AsRef<Path>>(
+    path: P,
+    simulation_kind: SimulationKind,
+    simulation_type: SimulationType1D,
+) -> Result<Vec<(Option<usize>, Temperatures)>> {
+    let path = path.as_ref();
+    compared_elements(path)?
+        .into_iter()
+        .map(|element| {
+            Ok((
+                element,
+                one_dimensional_element_by_type(path, simulation_kind, simulation_type, element)?,
+            ))
+        })
+        .collect()
+}
+
+/// The maximum absolute temperature difference of a simulation method to the CPU reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MethodDeviation {
+    pub simulation_type: SimulationType1D,
+    /// The maximum difference in K at the front of the wall. Infinite if the method produced a non-finite temperature.
+    pub max_front: f32,
+    /// The maximum difference in K at the back of the wall. Infinite if the method produced a non-finite temperature.
+    pub max_back: f32,
+}
+impl MethodDeviation {
+    /// The maximum difference of both wall sides.
+    pub fn max(&self) -> f32 {
+        self.max_front.max(self.max_back)
+    }
+}
+
+/// The result of the comparison of the GPU methods with the CPU reference.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerificationReport {
+    /// The maximum allowed difference in K.
+    pub tolerance: f32,
+    pub deviations: Vec<MethodDeviation>,
+}
+impl VerificationReport {
+    /// Checks if all methods stay within the tolerance.
+    pub fn passed(&self) -> bool {
+        self.deviations.iter().all(|d| d.max() <= self.tolerance)
+    }
+
+    /// Prints the deviation of each method as a table.
+    pub fn print(&self) {
+        println!(
+            "  {:>8} {:>16} {:>16} {:>8}",
+            "Method", "Front max [K]", "Back max [K]", "Status"
+        );
+        for deviation in &self.deviations {
+            println!(
+                "  {:>8} {:>16.6} {:>16.6} {:>8}",
+                deviation.simulation_type.to_string(),
+                deviation.max_front,
+                deviation.max_back,
+                if deviation.max() <= self.tolerance {
+                    "passed"
+                } else {
+                    "failed"
+                }
+            );
+        }
+    }
+}
+
+/// Returns the maximum absolute difference of two temperature series. A non-finite difference results in an infinite maximum.
+fn max_abs_difference(reference: &[f32], values: &[f32]) -> f32 {
+    reference
+        .iter()
+        .zip(values)
+        .map(|(r, v)| (r - v).abs())
+        .fold(0.0, |max, d| {
+            if d.is_finite() {
+                max.max(d)
+            } else {
+                f32::INFINITY
+            }
+        })
+}
+
+/// Determines the [`SimulationKind`] of a simulation from the directory names in its path, e.g. `fds/1D/Diabatic/steel_k_c`.
+///
+/// # Errors
+///
+/// This function will return an error if no directory of the path is the name of a [`SimulationKind`].
+pub fn simulation_kind_from_path<P: AsRef<Path>>(path: P) -> Result<SimulationKind> {
+    let path = path.as_ref();
+    path.iter()
+        .rev()
+        .find_map(|component| match component.to_str()? {
+            "Diabatic" => Some(SimulationKind::Diabatic),
+            "DiabaticOneSide" => Some(SimulationKind::DiabaticOneSide),
+            "Adiabatic" => Some(SimulationKind::Adiabatic),
+            "Convective" => Some(SimulationKind::Convective),
+            _ => None,
+        })
+        .with_context(|| {
+            format!("Failed to determine the simulation kind of {path:?}. Set it with --kind.")
+        })
+}
+
+/// Runs the simulation at the passed path with the CPU method and all GPU methods over the same device steps and compares the temperatures of each GPU method with the CPU reference.
+///
+/// # Errors
+///
+/// This function will return an error if a simulation can not be started.
+pub fn verify_gpu_methods<P: AsRef<Path>>(
+    path: P,
+    simulation_kind: SimulationKind,
+    tolerance: f32,
+) -> Result<VerificationReport> {
+    let path = path.as_ref();
+    let reference = one_dimensional_by_type(path, simulation_kind, SimulationType1D::Cpu)
+        .with_context(|| format!("Failed to run the CPU reference for {path:?}."))?;
+    let deviations = SimulationType1D::ALL_1D
+        .into_iter()
+        .filter(|s| *s != SimulationType1D::Cpu)
+        .map(|simulation_type| {
+            let temperatures = one_dimensional_by_type(path, simulation_kind, simulation_type)
+                .with_context(|| format!("Failed to run {simulation_type} for {path:?}."))?;
+            Ok(MethodDeviation {
+                simulation_type,
+                max_front: max_abs_difference(&reference.sim_front, &temperatures.sim_front),
+                max_back: max_abs_difference(&reference.sim_back, &temperatures.sim_back),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(VerificationReport {
+        tolerance,
+        deviations,
+    })
+}
+
+/// Runs the single wall element simulation at the passed path with the CPU method and prints the [`EnergyBalance`] of every step as a table.
+/// The imbalance is the change of the stored energy that is not explained by the energy entering through the surfaces. It should stay close to 0.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the fds simulation file or the device file can not be loaded.
+/// - the fds simulation defines multiple wall elements inside the meta data.
+/// - it failed to initialize the simulation.
+pub fn print_energy_balance<P: AsRef<Path>>(
+    path: P,
+    simulation_kind: SimulationKind,
+) -> Result<()> {
+    let path = path.as_ref();
+    let device =
+        SimulationTemperatureDevice1D::try_new(simulation_kind, path, None).with_context(|| {
+            format!("Failed to build SimulationTemperatureDevice for file at {path:?}")
+        })?;
+    let (named_materials, wall_elements) = load_fds_simulation_one_dimensional_named(path)
+        .with_context(|| format!("Failed to build simulation for file at {path:?}"))?;
+    let wall_elements = simulation_kind.mark_convective_sides(wall_elements);
+    if wall_elements.len() > 1 {
+        bail!("Multiple wall elements in meta defined, wich is not supported in print_energy_balance.");
+    }
+    let (names, materials): (Vec<_>, Vec<_>) = named_materials.into_iter().unzip();
+    println!("  Materials: {}", names.join(", "));
+
+    let mut heat_transfer = CPUSetupData::setup(materials, wall_elements)
+        .with_context(|| "Failed to setup heat transfer.")?;
+
+    println!(
+        "  {:>6} {:>10} {:>16} {:>16} {:>16} {:>16}",
+        "Step", "Time [s]", "Stored [J/m²]", "Delta [J/m²]", "Boundary [J/m²]", "Imbalance [J/m²]"
+    );
+    let mut wall_temperature_buffer = [[0.0f32; 2]];
+    let mut elapsed_time = 0.0;
+    let mut max_imbalance = 0.0f64;
+    for (step, data) in device.skip(1).enumerate() {
+        let (delta_time, wall_heat_transfer_coefficient, wall_q_in, _) = data?;
+        let energy_balance = heat_transfer.update_with_energy_balance(
+            delta_time,
+            &[wall_heat_transfer_coefficient],
+            &[wall_q_in],
+            &mut wall_temperature_buffer,
+        )?;
+        elapsed_time += delta_time;
+        let EnergyBalance {
+            stored_energy,
+            delta_stored_energy,
+            boundary_energy,
+        } = energy_balance[0];
+        let imbalance = energy_balance[0].imbalance();
+        max_imbalance = max_imbalance.max(imbalance.abs());
+        println!(
+            "  {step:>6} {elapsed_time:>10.2} {stored_energy:>16.3} {delta_stored_energy:>16.6} {boundary_energy:>16.6} {imbalance:>16.6}"
+        );
+    }
+    println!("  Maximum absolute imbalance {max_imbalance} J/m²");
+    if let Some(diagnostics) = heat_transfer.subdivision_diagnostics() {
+        print_subdivision_diagnostics(path, diagnostics);
+    }
+    Ok(())
+}
+
+/// Runs the single wall element simulation at the passed path over all device steps and returns the [`WallElement`] it was set up with and the temperatures of all of its cells after the last step, see [`HeatTransfer1D::profile`].
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the fds simulation file or the device file can not be loaded.
+/// - the fds simulation defines multiple wall elements inside the meta data.
+/// - it failed to initialize or update the simulation.
+/// - it failed to read back the temperatures.
+fn final_profile<P: AsRef<Path>, H: HeatTransfer1D>(
+    path: P,
+    simulation_kind: SimulationKind,
+) -> Result<(WallElement, Vec<f32>)> {
+    let path = path.as_ref();
+    let device =
+        SimulationTemperatureDevice1D::try_new(simulation_kind, path, None).with_context(|| {
+            format!("Failed to build SimulationTemperatureDevice for file at {path:?}")
+        })?;
+    let (materials, wall_elements) = load_fds_simulation_one_dimensional(path)
+        .with_context(|| format!("Failed to build simulation for file at {path:?}"))?;
+    let mut wall_elements = simulation_kind.mark_convective_sides(wall_elements);
+    if wall_elements.len() > 1 {
+        bail!("Multiple wall elements in meta defined, wich is not supported in dump_profile.");
+    }
+
+    let mut heat_transfer = H::setup(materials, wall_elements.clone())
+        .with_context(|| "Failed to setup heat transfer.")?;
+    let mut wall_temperature_buffer = [[0.0f32; 2]];
+    for data in device.skip(1) {
+        let (delta_time, wall_heat_transfer_coefficient, wall_q_in, _) = data?;
+        heat_transfer
+            .update(
+                delta_time,
+                &[wall_heat_transfer_coefficient],
+                &[wall_q_in],
+                &mut wall_temperature_buffer,
+            )
+            .with_context(|| "Failed to update heat transfer.")?;
+    }
+    let temperatures = heat_transfer
+        .profile()
+        .with_context(|| "Failed to read back the temperature profile.")?
+        .pop()
+        .with_context(|| "The temperature profile contains no wall element.")?;
+    Ok((wall_elements.remove(0), temperatures))
+}
+
+/// Returns the depth in m and the temperature of the front surface, the center of every inner cell and the back surface of a [`WallElement`].
+/// The surface temperatures are the mean of the boundary cell and the first inner cell, like the surface temperatures of [`HeatTransfer1D::update`].
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the temperatures do not contain a value for every cell of the wall element.
+/// - the wall element has less than [`MIN_CELL_COUNT`] cells.
+fn depth_profile(wall_element: &WallElement, temperatures: &[f32]) -> Result<Vec<(f32, f32)>> {
+    let len = wall_element.len();
+    if temperatures.len() != len {
+        bail!(
+            "The temperature profile has {} values, but the wall element has {len} cells.",
+            temperatures.len()
+        );
+    }
+    if len < MIN_CELL_COUNT {
+        bail!("The wall element has {len} cells, but at least {MIN_CELL_COUNT} are needed.");
+    }
+
+    let mut profile = vec![(0.0, (temperatures[0] + temperatures[1]) / 2.0)];
+    let mut depth = 0.0;
+    for (cell, temperature) in wall_element[1..(len - 1)]
+        .iter()
+        .zip(&temperatures[1..(len - 1)])
+    {
+        profile.push((depth + cell.size / 2.0, *temperature));
+        depth += cell.size;
+    }
+    profile.push((depth, (temperatures[len - 2] + temperatures[len - 1]) / 2.0));
+    Ok(profile)
+}
+
+/// Runs the single wall element simulation at the passed path with the passed method over all device steps and writes the temperature profile through the wall after the last step as CSV with the columns `depth` in m and `temperature`.
+/// The profile contains the front surface, the center of every inner cell and the back surface. It is written to `profile_<method>.csv` next to the [`DEVICE_FILE`] and the path of the file is returned.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the simulation fails, see [`one_dimensional`].
+/// - the temperatures can not be read back from the method.
+/// - the CSV file can not be written.
+pub fn dump_profile<P: AsRef<Path>>(
+    path: P,
+    simulation_kind: SimulationKind,
+    simulation_type: SimulationType1D,
+) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let (wall_element, temperatures) = match simulation_type {
+        SimulationType1D::Cpu => match get_cpu_precision() {
+            Precision::F32 => final_profile::<_, CPUSetupData>(path, simulation_kind),
+            Precision::F64 => final_profile::<_, CPUF64SetupData>(path, simulation_kind),
+        },
+        SimulationType1D::GpuM1 => final_profile::<_, gpu_m1::GPUSetupData>(path, simulation_kind),
+        SimulationType1D::GpuM2 => final_profile::<_, gpu_m2::GPUSetupData>(path, simulation_kind),
+        SimulationType1D::GpuM3 => final_profile::<_, gpu_m3::GPUSetupData>(path, simulation_kind),
+        SimulationType1D::GpuM4 => final_profile::<_, gpu_m4::GPUSetupData>(path, simulation_kind),
+    }?;
+    let profile = depth_profile(&wall_element, &temperatures)?;
+
+    let profile_path = path
+        .join(DEVICE_FILE)
+        .with_file_name(format!("profile_{}.csv", simulation_type.path_str()));
+    let mut csv = String::from("depth,temperature\n");
+    for (depth, temperature) in profile {
+        csv.push_str(&format!("{depth},{temperature}\n"));
+    }
+    std::fs::write(&profile_path, csv)
+        .with_context(|| format!("Failed to write file at {profile_path:?}."))?;
+    Ok(profile_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::heat_transfer::simulations::SIMULATION_FILE;
+    use std::fmt::Write;
+
+    /// The devices of a diabatic simulation, in the order of the columns written by [`write_minimal_simulation`].
+    const DEVICES: [&str; 8] = [
+        "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_WEST",
+        "DEVC_GAS_TEMPERATURE_WEST",
+        "DEVC_WALL_RADIATIVE_HEAT_FLUX_WEST",
+        "DEVC_WALL_TEMPERATURE_WEST",
+        "DEVC_WALL_HEAT_TRANSFER_COEFFICIENT_EAST",
+        "DEVC_GAS_TEMPERATURE_EAST",
+        "DEVC_WALL_RADIATIVE_HEAT_FLUX_EAST",
+        "DEVC_WALL_TEMPERATURE_EAST",
+    ];
+
+    /// Writes a simulation directory with a steel wall and a device file with `rows` time steps of 1 s, where the west gas heats up.
+    fn write_minimal_simulation(name: &str, rows: usize) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+        std::fs::create_dir_all(path.join("result")).unwrap();
+        std::fs::write(
+            path.join(SIMULATION_FILE),
+            "//META SURF_WALL;\n\
+            &TIME T_END=10.0, WALL_INCREMENT=1 /\n\
+            &MATL ID='STEEL', SPECIFIC_HEAT=0.46, CONDUCTIVITY=45.8, DENSITY=7850.0, EMISSIVITY=0.9 /\n\
+            &SURF ID='SURF_WALL', MATL_ID='STEEL', THICKNESS=0.01 /\n",
+        )
+        .unwrap();
+
+        let mut content = format!("s{}\nTime", ",-".repeat(DEVICES.len()));
+        for device in DEVICES {
+            write!(content, ",\"{device}\"").unwrap();
+        }
+        for row in 0..rows {
+            let gas_west = 20.0 + 50.0 * row as f32;
+            write!(
+                content,
+                "\n{row}.0,10.0,{gas_west},5.0,20.0,10.0,20.0,0.0,20.0"
+            )
+            .unwrap();
+        }
+        std::fs::write(path.join(DEVICE_FILE), content).unwrap();
+        path
+    }
+
+    #[test]
+    fn one_dimensional_cpu_smoke_test() {
+        let path = write_minimal_simulation("heat_transfer_smoke", 11);
+        let result = one_dimensional_cpu(&path, SimulationKind::Diabatic, None, None);
+        std::fs::remove_dir_all(&path).unwrap();
+        let temperatures = result.unwrap();
+
+        // The first row only sets the start time.
+        let steps = 10;
+        assert_eq!(temperatures.time.len(), steps);
+        assert_eq!(temperatures.fds_front.len(), steps);
+        assert_eq!(temperatures.fds_back.len(), steps);
+        assert_eq!(temperatures.sim_front.len(), steps);
+        assert_eq!(temperatures.sim_back.len(), steps);
+        assert_eq!(temperatures.time[steps - 1], 10.0);
+        for values in [
+            &temperatures.time,
+            &temperatures.fds_front,
+            &temperatures.fds_back,
+            &temperatures.sim_front,
+            &temperatures.sim_back,
+        ] {
+            assert!(values.iter().all(|v| v.is_finite()), "{values:?}");
+        }
+        // The hot gas on the west side heats the wall.
+        assert!(temperatures.sim_front[steps - 1] > 20.0);
     }
 }
@@ -0,0 +1,277 @@
+use rayon::prelude::*;
+
+use super::{check_wall_section, HeatTransfer2D, WallSection};
+use crate::{
+    fds::Material,
+    heat_transfer::one_dimensional::{
+        check_buffer_lengths, cpu::heat_transfer, get_solver_config, SolverConfig, WallElement,
+    },
+};
+
+/// All relevant data for the two dimensional heat transfer algorithm on the CPU.
+pub struct CPUSetupData {
+    materials: Vec<Material>,
+    wall_section: WallSection,
+    solver_config: SolverConfig,
+}
+
+impl HeatTransfer2D for CPUSetupData {
+    fn setup(materials: Vec<Material>, wall_section: WallSection) -> anyhow::Result<Self> {
+        check_wall_section(&wall_section)?;
+        Ok(Self {
+            materials,
+            wall_section,
+            solver_config: get_solver_config(),
+        })
+    }
+
+    fn reset(&mut self, wall_section: WallSection) -> anyhow::Result<()> {
+        check_wall_section(&wall_section)?;
+        self.wall_section = wall_section;
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        delta_time: f32,
+        wall_heat_transfer_coefficients: &[[f32; 2]],
+        wall_q_in: &[[f32; 2]],
+        wall_temperature: &mut [[f32; 2]],
+    ) -> anyhow::Result<()> {
+        check_buffer_lengths(
+            self.wall_section.columns.len(),
+            wall_heat_transfer_coefficients,
+            wall_q_in,
+            wall_temperature,
+        )?;
+        let materials = &self.materials;
+        let solver_config = &self.solver_config;
+
+        // The time step is split by direction. First it is solved implicitly across the thickness, equal to the one dimensional solver for every column.
+        self.wall_section
+            .columns
+            .par_iter_mut()
+            .zip_eq(wall_heat_transfer_coefficients.par_iter())
+            .zip_eq(wall_q_in.par_iter())
+            .for_each(
+                |((wall_element, wall_heat_transfer_coefficient), wall_q_in)| {
+                    heat_transfer(
+                        wall_element,
+                        materials,
+                        *wall_heat_transfer_coefficient,
+                        *wall_q_in,
+                        &[],
+                        solver_config,
+                        delta_time,
+                    );
+                },
+            );
+
+        // Afterwards it is solved implicitly along the width.
+        lateral_heat_transfer(&mut self.wall_section, materials, delta_time);
+
+        self.wall_section
+            .columns
+            .par_iter()
+            .zip_eq(wall_temperature.par_iter_mut())
+            .for_each(|(wall_element, wall_temperature)| {
+                let len = wall_element.len();
+                *wall_temperature = [
+                    (wall_element[0].temperature + wall_element[1].temperature) / 2.0,
+                    (wall_element[len - 1].temperature + wall_element[len - 2].temperature) / 2.0,
+                ];
+            });
+        Ok(())
+    }
+}
+
+/// Solves the heat transfer along the width of the [`WallSection`] for every inner row of cells.
+/// The first and the last column are insulated. All columns must have the same cell count, see [`check_wall_section`].
+#[inline]
+pub fn lateral_heat_transfer(
+    wall_section: &mut WallSection,
+    materials: &[Material],
+    delta_time: f32,
+) {
+    let columns = &wall_section.columns;
+    if columns.len() < 2 {
+        return;
+    }
+    let len = columns[0].len();
+    let dx = wall_section.column_width;
+
+    let rows = (1..(len - 1))
+        .into_par_iter()
+        .map(|row| {
+            let mut matrix = populate_lateral_solve_matrix(columns, row, materials, dx, delta_time);
+            solve_tridiagonal(&mut matrix);
+            matrix.into_iter().map(|m| m[3]).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    for (row, temperatures) in (1..(len - 1)).zip(rows) {
+        for (column, temperature) in wall_section.columns.iter_mut().zip(temperatures) {
+            column[row].temperature = temperature;
+        }
+    }
+}
+
+/// Filling the solution matrix for a single row of cells along the width.
+#[inline]
+pub fn populate_lateral_solve_matrix(
+    columns: &[WallElement],
+    row: usize,
+    materials: &[Material],
+    dx: f32,
+    delta_time: f32,
+) -> Vec<[f32; 4]> {
+    let n = columns.len();
+    let conductivities = columns
+        .iter()
+        .map(|c| {
+            materials[c[row].material as usize]
+                .conductivity
+                .calc(c[row].temperature)
+        })
+        .collect::<Vec<_>>();
+
+    (0..n)
+        .map(|i| {
+            let cell = &columns[i][row];
+            let material = &materials[cell.material as usize];
+            let f1 = material.density * material.specific_heat.calc(cell.temperature) * dx * dx;
+
+            // 0: b, 1: d, 2: a, 3: c
+            let b = if i > 0 {
+                -delta_time * (conductivities[i] + conductivities[i - 1]) / 2.0 / f1
+            } else {
+                0.0
+            };
+            let a = if i < n - 1 {
+                -delta_time * (conductivities[i] + conductivities[i + 1]) / 2.0 / f1
+            } else {
+                0.0
+            };
+            [b, 1.0 - a - b, a, cell.temperature]
+        })
+        .collect()
+}
+
+/// Solving a tridiagonal matrix with the Thomas algorithm. The solution is written into the last entry of every row.
+#[inline]
+pub fn solve_tridiagonal(matrix: &mut [[f32; 4]]) {
+    let n = matrix.len();
+    // 0: b, 1: d, 2: a, 3: c
+    for i in 1..n {
+        let r = matrix[i][0] / matrix[i - 1][1];
+        matrix[i][1] -= r * matrix[i - 1][2];
+        matrix[i][3] -= r * matrix[i - 1][3];
+    }
+
+    matrix[n - 1][3] /= matrix[n - 1][1];
+    for i in (0..(n - 1)).rev() {
+        matrix[i][3] = (matrix[i][3] - matrix[i][2] * matrix[i + 1][3]) / matrix[i][1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fds::Ramp,
+        heat_transfer::one_dimensional::{
+            cpu::{ADIABATIC_H, CONST_TEMP_H},
+            WallCell,
+        },
+    };
+
+    /// Returns steel with constant properties.
+    fn steel() -> Material {
+        Material {
+            specific_heat: Ramp::from(460.0),
+            conductivity: Ramp::from(45.8),
+            density: 7850.0,
+            emissivity: 0.9,
+        }
+    }
+
+    /// Returns a column of material 0 with `cell_count` cells of 1 mm at 20 °C.
+    fn column(cell_count: usize) -> WallElement {
+        WallElement::new(vec![
+            WallCell {
+                size: 0.001,
+                material: 0,
+                temperature: 20.0,
+            };
+            cell_count
+        ])
+    }
+
+    #[test]
+    fn uniform_section_equals_one_dimensional() {
+        let materials = vec![steel()];
+        let mut wall_element = column(12);
+        let mut setup_data = CPUSetupData::setup(
+            materials.clone(),
+            WallSection::new(vec![column(12); 4], 0.01),
+        )
+        .unwrap();
+        let solver_config = get_solver_config();
+        let wall_heat_transfer_coefficients = [[CONST_TEMP_H, ADIABATIC_H]; 4];
+        let wall_q_in = [[200.0, 0.0]; 4];
+        let mut wall_temperature = [[0.0; 2]; 4];
+        for _ in 0..20 {
+            setup_data
+                .update(
+                    0.5,
+                    &wall_heat_transfer_coefficients,
+                    &wall_q_in,
+                    &mut wall_temperature,
+                )
+                .unwrap();
+            heat_transfer(
+                &mut wall_element,
+                &materials,
+                wall_heat_transfer_coefficients[0],
+                wall_q_in[0],
+                &[],
+                &solver_config,
+                0.5,
+            );
+        }
+        for column in setup_data.wall_section.columns.iter() {
+            for (cell, expected) in column.iter().zip(wall_element.iter()) {
+                assert!(
+                    (cell.temperature - expected.temperature).abs() < 1e-3,
+                    "{} != {}",
+                    cell.temperature,
+                    expected.temperature
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn setup_rejects_uneven_section() {
+        let wall_section = WallSection::new(vec![column(12), column(8)], 0.01);
+        assert!(CPUSetupData::setup(vec![steel()], wall_section).is_err());
+        let wall_section = WallSection::new(vec![column(2), column(2)], 0.01);
+        assert!(CPUSetupData::setup(vec![steel()], wall_section).is_err());
+    }
+
+    #[test]
+    fn update_rejects_wrong_buffer_length() {
+        let mut setup_data =
+            CPUSetupData::setup(vec![steel()], WallSection::new(vec![column(12); 4], 0.01))
+                .unwrap();
+        let err = setup_data
+            .update(
+                0.5,
+                &[[CONST_TEMP_H, ADIABATIC_H]; 4],
+                &[[200.0, 0.0]; 4],
+                &mut [[0.0; 2]; 3],
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("expected 4, got 3"), "{err}");
+    }
+}
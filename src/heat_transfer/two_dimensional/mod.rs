@@ -0,0 +1,97 @@
+use crate::{
+    fds::Material,
+    heat_transfer::one_dimensional::{check_cell_counts, WallElement},
+};
+use anyhow::*;
+
+pub mod cpu;
+
+/// The data of a two dimensional cross-section through a wall.
+/// Each column is a [`WallElement`] across the thickness of the wall. All columns must have the same cell layout.
+/// The cross-section is insulated at the first and the last column.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WallSection {
+    pub columns: Vec<WallElement>,
+    /// The width of a single column in meters.
+    pub column_width: f32,
+}
+impl WallSection {
+    pub fn new(columns: Vec<WallElement>, column_width: f32) -> Self {
+        Self {
+            columns,
+            column_width,
+        }
+    }
+}
+
+/// Checks that all columns of the [`WallSection`] have at least [`MIN_CELL_COUNT`](crate::heat_transfer::one_dimensional::MIN_CELL_COUNT) cells and the same cell layout as the first column, and that the column width is finite and positive.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - a column has too few cells.
+/// - the cell count or the cell sizes of a column differ from the first column.
+/// - the column width is zero, negative or not finite.
+pub fn check_wall_section(wall_section: &WallSection) -> Result<()> {
+    check_cell_counts(&wall_section.columns)?;
+    if let Some((first, columns)) = wall_section.columns.split_first() {
+        for (i, column) in columns.iter().enumerate() {
+            if column.len() != first.len() {
+                bail!(
+                    "The column {} has {} cells, but the first column has {}.",
+                    i + 1,
+                    column.len(),
+                    first.len()
+                );
+            }
+            if column
+                .iter()
+                .zip(first.iter())
+                .any(|(a, b)| a.size != b.size)
+            {
+                bail!(
+                    "The cell sizes of column {} differ from the first column.",
+                    i + 1
+                );
+            }
+        }
+    }
+    let column_width = wall_section.column_width;
+    if !column_width.is_finite() || column_width <= 0.0 {
+        bail!("The column width should be finite and positive, but is {column_width}.");
+    }
+    Ok(())
+}
+
+/// Trait for initializing and updating a two dimensional heat transfer simulation.
+pub trait HeatTransfer2D {
+    /// Initialize the data for a heat transfer simulation for a fixed [`WallSection`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the initialization fails.
+    fn setup(materials: Vec<Material>, wall_section: WallSection) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Replaces the [`WallSection`] of an already initialized simulation while keeping the materials passed to [`HeatTransfer2D::setup`].
+    /// The passed [`WallSection`] must have the same layout as the one passed to [`HeatTransfer2D::setup`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the reinitialization fails.
+    fn reset(&mut self, wall_section: WallSection) -> Result<()>;
+
+    /// Updates the heat transfer with the next time step. The boundary conditions and the resulting surface temperatures are passed per column.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the update fails.
+    fn update(
+        &mut self,
+        delta_time: f32,
+        wall_heat_transfer_coefficients: &[[f32; 2]],
+        wall_q_in: &[[f32; 2]],
+        wall_temperature: &mut [[f32; 2]],
+    ) -> Result<()>;
+}
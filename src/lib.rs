@@ -0,0 +1,45 @@
+//! This program checks whether and how one dimensional heat transport can be calculated on a graphics card. For this purpose, a CPU algorithm and 3 GPU algorithms were created. The heat transport itself was implemented according to the FDS Technical Reference Guide. This programme was created as part of the master thesis "Ausführung eines Wärmetransportalgorithmus auf einer GPU".
+//!
+//! The library contains the solvers and the FDS parser, so they can be used without the command line interface of the binary.
+
+pub mod benchmark;
+pub mod clean;
+pub mod config;
+pub mod dry_run;
+pub mod fds;
+pub mod heat_transfer;
+pub mod modification;
+pub mod output;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod provenance;
+pub mod summary;
+
+use std::path::Path;
+
+pub use fds::Material;
+pub use heat_transfer::{
+    one_dimensional::{HeatTransfer1D, WallCell, WallElement},
+    simulations::{
+        load_fds_simulation_one_dimensional, load_fds_simulation_one_dimensional_named,
+        temperature::Temperatures, SimulationKind, SimulationType1D, DEVICE_FILE, SIMULATION_FILE,
+    },
+};
+
+/// Runs the one dimensional simulation inside the passed directory with the passed method and returns the temperatures of FDS and this program for the front and the back of the wall.
+/// The simulation must contain a single wall element and the FDS results in `result/heat_transfer_devc.csv`, see [`SIMULATION_FILE`] for the layout of the directory.
+///
+/// # Errors
+///
+/// This function will return an error if the simulation can not be loaded or started.
+pub fn simulate_temperatures<P: AsRef<Path>>(
+    path: P,
+    simulation_kind: SimulationKind,
+    simulation_type: SimulationType1D,
+) -> anyhow::Result<Temperatures> {
+    heat_transfer::simulations::temperature::one_dimensional_by_type(
+        path,
+        simulation_kind,
+        simulation_type,
+    )
+}
@@ -1,23 +1,46 @@
-//! This program checks whether and how one dimensional heat transport can be calculated on a graphics card. For this purpose, a CPU algorithm and 3 GPU algorithms were created. The heat transport itself was implemented according to the FDS Technical Reference Guide. This programme was created as part of the master thesis "Ausführung eines Wärmetransportalgorithmus auf einer GPU".
+//! The command line interface to create and run the FDS simulations, benchmark the CPU and GPU methods and plot the results.
 
-pub mod benchmark;
-pub mod fds;
-pub mod heat_transfer;
-pub mod modification;
 #[cfg(feature = "plot")]
-pub mod plot;
-
-use anyhow::{self, Context};
-use benchmark::{run_benchmark, BenchmarkName};
-use clap::Parser;
-use fds::{create_simulations, run_simulations};
-use heat_transfer::simulations::{SimulationKind, SimulationMethod};
-#[cfg(feature = "plot")]
-use plot::{plot_simulations, PlotType};
+use ::heat_transfer::plot::{
+    labels::{self, Language},
+    plot_simulations, PlotFormat, PlotType,
+};
+use ::heat_transfer::{
+    benchmark::{
+        self, export_csv, print_methods_compare, run_benchmark_with_chunk_sizes, BenchmarkName,
+    },
+    clean::{clean, CleanTarget},
+    config::Config,
+    dry_run,
+    fds::{self, create_simulations, print_cells, print_stability, run_simulations, KeepResults},
+    heat_transfer::{
+        self,
+        gpu::{GpuBackend, GpuPower},
+        one_dimensional::Precision,
+        simulations::{
+            temperature, BenchmarkFormat, SimulationKind, SimulationMethod, SimulationType1D,
+        },
+    },
+    modification::{self, CacheMode},
+    output,
+    provenance::provenance,
+    summary,
+};
+use clap::{Parser, ValueEnum};
+use std::{
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 /// Run and evaluate heat transfer simulations on cpu and gpu.
 #[derive(Parser)]
 struct Cli {
+    /// Read the simulations, benchmark, benchmark names, methods, kinds, plots and chunk sizes from a JSON config file, e.g. `{ "benchmark": "desktop_l", "method": ["1d_cpu"], "chunk_sizes": { "desktop_l": 18432 } }`.
+    /// The command line arguments override the values of the file.
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
     /// Set this flag to start the fds simulations.
     #[arg(short, long)]
     simulations: bool,
@@ -41,19 +64,298 @@ struct Cli {
     #[cfg(not(feature = "plot"))]
     plots: (),
 
+    /// Set the file format of the plots.
+    #[cfg_attr(feature = "plot", arg(long, value_name = "FORMAT", value_enum, default_value_t = PlotFormat::Svg))]
+    #[cfg(feature = "plot")]
+    plot_format: PlotFormat,
+
+    /// Set this flag, to additionally write the temperature plots as interactive HTML files.
+    #[cfg_attr(feature = "plot", arg(long))]
+    #[cfg(feature = "plot")]
+    plot_html: bool,
+
+    /// Set this flag, to plot the front and back side of the temperature plots as two rows of a single figure.
+    #[cfg_attr(feature = "plot", arg(long))]
+    #[cfg(feature = "plot")]
+    plot_combined: bool,
+
+    /// Set the language of the axis titles and legends of the plots. The English plots are written to the subdirectory `en` of the plot directory.
+    #[cfg_attr(feature = "plot", arg(long, value_name = "LANG", value_enum, default_value_t = Language::De))]
+    #[cfg(feature = "plot")]
+    lang: Language,
+
     /// Set the simulation kind wich should be used. If empty all kinds will be used.
     #[arg(short, long, value_name = "[KIND]", num_args = 1.., value_delimiter = ',')]
     kind: Option<Vec<SimulationKind>>,
 
+    /// Set the workgroup size of the GPU methods.
+    #[arg(long, value_name = "SIZE", default_value_t = 256, value_parser = clap::value_parser!(u32).range(1..))]
+    workgroup_size: u32,
+
+    /// Set the GPU backend wich should be used. If not set Vulkan and DX12 will be used.
+    #[arg(long, value_name = "BACKEND")]
+    backend: Option<GpuBackend>,
+
+    /// Set the power preference used to find a GPU. Low power often selects an integrated GPU.
+    #[arg(long, value_name = "POWER", value_enum, default_value_t = GpuPower::High)]
+    gpu_power: GpuPower,
+
+    /// Set the floating point precision of the CPU method.
+    #[arg(long, value_name = "PRECISION", value_enum, default_value_t = Precision::F32)]
+    precision: Precision,
+
+    /// Set the file format the benchmarks are written and read in.
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = BenchmarkFormat::Bin)]
+    benchmark_format: BenchmarkFormat,
+
+    /// Set the directory the benchmark and plot directories are placed in. Defaults to the current working directory.
+    #[arg(long, value_name = "PATH")]
+    output_dir: Option<PathBuf>,
+
+    /// Print the cells of all wall elements of the one dimensional simulation inside the passed directory as a table.
+    #[arg(long, value_name = "PATH")]
+    inspect_cells: Option<PathBuf>,
+
+    /// Print the largest time step an explicit scheme is stable with for the cells of the one dimensional simulation inside the passed directory.
+    /// The solver is implicit, so the limit is only advisory, but it shows which cells are too thin to resolve a time step accurately.
+    #[arg(long, value_name = "PATH")]
+    stability: Option<PathBuf>,
+
+    /// Run the one dimensional simulation inside the passed directory with the CPU and all GPU methods and fail if a GPU method differs from the CPU reference by more than the tolerance.
+    /// The simulation kind is taken from --kind if exactly one is set, otherwise from the path.
+    #[arg(long, value_name = "PATH")]
+    verify: Option<PathBuf>,
+
+    /// Set the maximum temperature difference between a GPU method and the CPU reference for --verify.
+    #[arg(long, value_name = "KELVIN", default_value_t = 0.1, value_parser = parse_positive_f32)]
+    tolerance: f32,
+
+    /// Run the single wall element simulation inside the passed directory with the CPU method and print the energy balance of every step.
+    /// Also counts the time steps of all CPU simulations that needed more subdivisions than --max-time-subdivisions allows.
+    /// The simulation kind is taken from --kind if exactly one is set, otherwise from the path.
+    #[arg(long, value_name = "PATH")]
+    diagnostics: Option<PathBuf>,
+
+    /// Run the single wall element simulation inside the passed directory and write the temperature profile through the wall after the last step as CSV with the depth and the temperature next to the device file.
+    /// A profile is written for every one dimensional method of --method and only for the CPU method if no method is set.
+    /// The simulation kind is taken from --kind if exactly one is set, otherwise from the path.
+    #[arg(long, value_name = "PATH")]
+    dump_profile: Option<PathBuf>,
+
+    /// Set this flag, to generate the shaders of all GPU methods with a representative material set and validate them without a GPU.
+    /// The shaders use the set workgroup size and solver settings. On failure the error location and the generated source are printed.
+    #[arg(long)]
+    validate_shaders: bool,
+
+    /// Delete the generated plots, benchmarks or FDS results before all other actions. The templates and simulation files are kept.
+    #[arg(long, value_name = "TARGET", value_enum)]
+    clean: Option<CleanTarget>,
+
+    /// Set this flag, to allow --clean to delete the benchmark directory, which also contains the recorded benchmarks.
+    #[arg(long, requires = "clean")]
+    force_clean: bool,
+
+    /// Export the statistics of all benchmarks inside the benchmark directory to a CSV file at the passed path.
+    #[arg(long, value_name = "PATH")]
+    export_csv: Option<PathBuf>,
+
+    /// Print a table of the median simulation times of the CPU, GPU M1, GPU M2 and GPU M3 method and the best GPU speedup for the benchmarks of the passed label.
+    /// The benchmarks are limited by --benchmark-name.
+    #[arg(long, value_name = "LABEL")]
+    methods_compare: Option<String>,
+
+    /// Set the amount a benchmark simulation is rerun.
+    #[arg(long, value_name = "RERUNS", default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    reruns: u64,
+
+    /// Set the amount of simulation steps of a benchmark simulation.
+    #[arg(long, value_name = "STEPS", default_value_t = 100, value_parser = clap::value_parser!(u64).range(1..))]
+    steps: u64,
+
+    /// Set the amount of untimed updates before a GPU benchmark simulation starts.
+    #[arg(long, value_name = "STEPS", default_value_t = 3)]
+    warmup: usize,
+
+    /// Set the maximum temperature difference between neighboring cells before the time step is reduced.
+    #[arg(long, value_name = "KELVIN", default_value_t = heat_transfer::one_dimensional::cpu::MAX_DELTA_TEMPERATURE, value_parser = parse_positive_f32)]
+    max_delta_temperature: f32,
+
+    /// Set the maximum number of times the time step may be reduced.
+    #[arg(long, value_name = "COUNT", default_value_t = heat_transfer::one_dimensional::cpu::MAX_TIME_SUBDIVISIONS as u64, value_parser = clap::value_parser!(u64).range(1..))]
+    max_time_subdivisions: u64,
+
+    /// Set this flag, to disable the surface emission in the solver while keeping the convective heat transfer and the emissivity of the materials.
+    #[arg(long)]
+    no_radiation: bool,
+
+    /// Set this flag, to fail on materials without an emissivity instead of using the FDS default of 0.9.
+    #[arg(long)]
+    strict_emissivity: bool,
+
+    /// Set this flag, to warn about surfaces whose smallest cell is below the stability limit of an explicit scheme. The solver is implicit, so this only hints at possible oscillations of thin layers.
+    #[arg(long)]
+    grid_stability_warning: bool,
+
+    /// Set this flag, to match device names exactly instead of ignoring surrounding whitespace and case.
+    #[arg(long)]
+    strict_device_names: bool,
+
+    /// Set how changed sources of simulations and plots are detected.
+    #[arg(long, value_name = "MODE", value_enum, default_value_t = CacheMode::Mtime)]
+    cache_mode: CacheMode,
+
+    /// Set the tolerance in milliseconds a result has to be newer than all of its sources, to handle filesystems with coarse modification times.
+    #[arg(long, value_name = "MS", default_value_t = 0)]
+    modification_tolerance: u64,
+
+    /// Set the amount of CPU benchmarks that run in parallel on a thread pool. The GPU benchmarks always run serially after the CPU benchmarks.
+    #[arg(long, value_name = "THREADS", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    benchmark_threads: u64,
+
+    /// Set the amount of FDS simulations that run in parallel. Defaults to the available parallelism of the machine.
+    #[arg(long, value_name = "JOBS", value_parser = clap::value_parser!(u64).range(1..))]
+    fds_jobs: Option<u64>,
+
+    /// Set the FDS executable, either a name that is searched in PATH or a path.
+    #[arg(long, value_name = "PATH", env = "FDS_BIN", default_value = fds::DEFAULT_FDS_BIN)]
+    fds_bin: PathBuf,
+
+    /// Set the MPI launcher that starts FDS, e.g. mpiexec or srun. It is called with `-n <CORES>` before the FDS executable.
+    #[arg(long, value_name = "PATH", env = "MPI_LAUNCHER", default_value = fds::DEFAULT_MPI_LAUNCHER)]
+    mpi_launcher: PathBuf,
+
+    /// Set the time in seconds after which a FDS simulation is killed and counted as failed. Without it, the simulations can run forever.
+    #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    fds_timeout: Option<u64>,
+
+    /// Set the core counts the FDS speed test is run with, e.g. `1,2,4,8` for a strong scaling test. Defaults to the available parallelism of the machine.
+    #[arg(long, value_name = "CORES", value_delimiter = ',', value_parser = clap::value_parser!(u64).range(1..))]
+    speed_test_cores: Option<Vec<u64>>,
+
+    /// Set which FDS results of the speed test reruns are kept. The results can fill the disk, since every rerun writes them again.
+    #[arg(long, value_name = "KEEP", value_enum, default_value_t = KeepResults::All)]
+    keep_results: KeepResults,
+
+    /// Limit the thickness benchmarks and plots to the passed wall thicknesses in cm, e.g. `50,500`. The thicknesses 5, 10, 50, 100 and 500 are available.
+    #[arg(long, value_name = "CM", value_delimiter = ',', value_parser = parse_thickness)]
+    thickness: Option<Vec<u32>>,
+
+    /// Set the wall element counts of the benchmarks and the benchmark plots, e.g. `10000`, instead of 256 to 32768. The additional chunk benchmark sizes are kept.
+    /// The counts have to be divisible by the wall element count of each benchmarked simulation and for the FDS speed test by 4.
+    #[arg(long, value_name = "COUNTS", value_delimiter = ',', value_parser = clap::value_parser!(u64).range(1..))]
+    elements: Option<Vec<u64>>,
+
+    /// Set a JSON file with a list of overrides of the built-in material properties of the created simulations, e.g. `[{ "material": "steel", "kind": "diabatic", "conductivity": 45.0 }]`.
+    /// The overrides are keyed by `material` and the optional `kind` and may set `density`, `emissivity`, `conductivity`, `conductivity_ramp`, `specific_heat` and `specific_heat_ramp`.
+    #[arg(long, value_name = "PATH")]
+    material_override: Option<PathBuf>,
+
+    /// Set this flag, to write the submit and the map-back time of the GPU methods next to the simulation time of each benchmark rerun.
+    #[arg(long)]
+    detailed_timing: bool,
+
+    /// Set this flag, to skip benchmarks whose file already contains all reruns instead of overwriting them.
+    #[arg(long)]
+    resume: bool,
+
+    /// Set this flag, to read missing radiative heat flux columns of the diabatic benchmarks as 0 instead of failing. The heat transfer coefficient and gas temperature columns are still required.
+    #[arg(long)]
+    best_effort_devices: bool,
+
+    /// Write a JSON summary of the run to the passed path when the program ends, also if it aborts.
+    /// It lists the created and run simulations with their status, the written benchmark files, the plots with their status and all errors.
+    #[arg(long, value_name = "PATH")]
+    summary_json: Option<PathBuf>,
+
+    /// Set this flag, to only print the simulations, benchmarks and plots that would run without executing them.
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Set this flag, to only print the version, git commit, profile, enabled features and GPU backends of this build.
+    /// The same line is printed at the start of every run and written into the header of each benchmark.
+    #[arg(long)]
+    about: bool,
+
     /// Set this flag, to continue even when an error occurs.
     #[arg(short, long)]
     force: bool,
 }
 
+fn parse_positive_f32(value: &str) -> anyhow::Result<f32> {
+    let number = value.parse::<f32>()?;
+    if !(number > 0.0 && number.is_finite()) {
+        anyhow::bail!("The value has to be a positive number.");
+    }
+    Ok(number)
+}
+
+fn parse_thickness(value: &str) -> anyhow::Result<u32> {
+    let thickness = value.trim().parse::<u32>()?;
+    let thicknesses = BenchmarkName::THICKNESS
+        .iter()
+        .filter_map(BenchmarkName::thickness)
+        .collect::<Vec<_>>();
+    if !thicknesses.contains(&thickness) {
+        anyhow::bail!(
+            "There is no thickness benchmark with {thickness} cm. Available: {thicknesses:?}."
+        );
+    }
+    Ok(thickness)
+}
+
+fn verify(path: &Path, kind: Option<&[SimulationKind]>, tolerance: f32) -> anyhow::Result<()> {
+    let simulation_kind = match kind {
+        Some([simulation_kind]) => *simulation_kind,
+        _ => temperature::simulation_kind_from_path(path)?,
+    };
+    let report = temperature::verify_gpu_methods(path, simulation_kind, tolerance)?;
+    report.print();
+    if !report.passed() {
+        anyhow::bail!("Some GPU methods differ from the CPU reference by more than {tolerance} K.");
+    }
+    Ok(())
+}
+
+fn diagnostics(path: &Path, kind: Option<&[SimulationKind]>) -> anyhow::Result<()> {
+    let simulation_kind = match kind {
+        Some([simulation_kind]) => *simulation_kind,
+        _ => temperature::simulation_kind_from_path(path)?,
+    };
+    temperature::print_energy_balance(path, simulation_kind)
+}
+
+fn dump_profile(
+    path: &Path,
+    kind: Option<&[SimulationKind]>,
+    method: Option<&[SimulationMethod]>,
+) -> anyhow::Result<()> {
+    let simulation_kind = match kind {
+        Some([simulation_kind]) => *simulation_kind,
+        _ => temperature::simulation_kind_from_path(path)?,
+    };
+    let simulation_types = match method {
+        Some(_) => SimulationType1D::value_variants()
+            .iter()
+            .copied()
+            .filter(|s| s.is_simulation_type(method))
+            .collect::<Vec<_>>(),
+        None => vec![SimulationType1D::Cpu],
+    };
+    if simulation_types.is_empty() {
+        anyhow::bail!("No one dimensional method is set with --method.");
+    }
+    for simulation_type in simulation_types {
+        let profile_path = temperature::dump_profile(path, simulation_kind, simulation_type)?;
+        println!("  Wrote the {simulation_type} profile to {profile_path:?}.");
+    }
+    Ok(())
+}
+
 fn evaluate_errors(errors: Result<(), Vec<anyhow::Error>>, cli: &Cli) -> bool {
     if let Err(err) = errors {
         println!("\n\n");
         for e in err {
+            summary::record_error(&e);
             println!("{e:?}\n");
         }
         if !cli.force {
@@ -65,35 +367,132 @@ fn evaluate_errors(errors: Result<(), Vec<anyhow::Error>>, cli: &Cli) -> bool {
     false
 }
 
-fn set_max_element_per_chunk(label: &str) -> anyhow::Result<()> {
-    for line in std::fs::read_to_string("benchmarks.txt")
-        .with_context(|| "Failed to read file at \"benchmarks.txt\"")?
-        .lines()
-    {
-        let line = line.trim();
-        if line.starts_with('#') {
-            continue;
-        }
-        let mut splits = line.split('=');
-        if let (Some(name), Some(number)) = (splits.next(), splits.next()) {
-            if name.trim() == label {
-                let number = number
-                    .trim()
-                    .parse::<usize>()
-                    .with_context(|| format!("Failed to parse {number}"))?;
-                heat_transfer::one_dimensional::set_max_element_per_chunk(number);
-                return Ok(());
-            }
+/// Writes the summary of the run to the path when it is dropped, so runs that return early are summarized as well.
+struct SummaryWriter(PathBuf);
+impl Drop for SummaryWriter {
+    fn drop(&mut self) {
+        match summary::write_summary(&self.0) {
+            Ok(()) => println!("Wrote the summary to {:?}", self.0),
+            Err(err) => println!("Warning: Failed to write the summary: {err:#}"),
         }
     }
+}
 
-    Err(anyhow::anyhow!("No chunk size assigned to the current benchmark label \"{label}\". Insert \"{label} = [size]\" inside \"benchmark.txt\" as a new line."))
+fn apply_config(cli: &mut Cli, config: &Config) {
+    cli.simulations |= config.simulations;
+    cli.benchmark = cli.benchmark.take().or_else(|| config.benchmark.clone());
+    cli.benchmark_name = cli
+        .benchmark_name
+        .take()
+        .or_else(|| config.benchmark_name.clone());
+    cli.method = cli.method.take().or_else(|| config.method.clone());
+    cli.kind = cli.kind.take().or_else(|| config.kind.clone());
+    #[cfg(feature = "plot")]
+    {
+        cli.plots = cli.plots.or(config.plots);
+    }
+    #[cfg(not(feature = "plot"))]
+    if config.plots.is_some() {
+        println!("Warning: The plots of the config file are ignored, since the plot feature is not enabled.");
+    }
 }
 
 fn main() {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
+    let _summary_writer = cli.summary_json.clone().map(SummaryWriter);
+    let config = match &cli.config {
+        Some(path) => match Config::load(path) {
+            Ok(config) => config,
+            Err(err) => {
+                summary::record_error(&err);
+                println!("{err:?}");
+                return;
+            }
+        },
+        None => Config::default(),
+    };
+    apply_config(&mut cli, &config);
+
+    heat_transfer::one_dimensional::set_workgroup_size(cli.workgroup_size);
+    heat_transfer::one_dimensional::set_cpu_precision(cli.precision);
+    if let Some(output_dir) = &cli.output_dir {
+        output::set_output_dir(output_dir.clone());
+    }
+    #[cfg(feature = "plot")]
+    labels::set_language(cli.lang);
+    heat_transfer::simulations::set_benchmark_format(cli.benchmark_format);
+    heat_transfer::simulations::set_simulation_reruns(cli.reruns as usize);
+    heat_transfer::simulations::set_simulation_steps(cli.steps as usize);
+    heat_transfer::simulations::set_warmup_steps(cli.warmup);
+    heat_transfer::simulations::set_benchmark_resume(cli.resume);
+    heat_transfer::simulations::set_best_effort_devices(cli.best_effort_devices);
+    heat_transfer::one_dimensional::set_detailed_timing(cli.detailed_timing);
+    heat_transfer::one_dimensional::set_solver_config(
+        heat_transfer::one_dimensional::SolverConfig {
+            max_delta_temperature: cli.max_delta_temperature,
+            max_time_subdivisions: cli.max_time_subdivisions as usize,
+            radiation: !cli.no_radiation,
+            ..Default::default()
+        },
+    );
+    heat_transfer::one_dimensional::set_diagnostics(cli.diagnostics.is_some());
+    fds::set_strict_emissivity(cli.strict_emissivity);
+    fds::set_strict_device_names(cli.strict_device_names);
+    fds::set_grid_stability_warning(cli.grid_stability_warning);
+    fds::set_fds_bin(cli.fds_bin.clone());
+    fds::set_mpi_launcher(cli.mpi_launcher.clone());
+    fds::set_fds_timeout(cli.fds_timeout.map(Duration::from_secs));
+    fds::set_keep_results(cli.keep_results);
+    if let Some(speed_test_cores) = &cli.speed_test_cores {
+        fds::set_speed_test_cores(speed_test_cores.iter().map(|c| *c as usize).collect());
+    }
+    modification::set_cache_mode(cli.cache_mode);
+    modification::set_modification_tolerance(cli.modification_tolerance);
+    if let Some(backend) = cli.backend {
+        heat_transfer::gpu::set_gpu_backends(backend.backends());
+    }
+    heat_transfer::gpu::set_gpu_power(cli.gpu_power);
+    dry_run::set_dry_run(cli.dry_run);
+    benchmark::set_benchmark_threads(cli.benchmark_threads as usize);
+    if let Some(thicknesses) = &cli.thickness {
+        benchmark::set_thicknesses(thicknesses.clone());
+    }
+    if let Some(elements) = &cli.elements {
+        benchmark::set_benchmark_elements(elements.iter().map(|&e| e as usize).collect());
+    }
+
+    println!("\n{}", provenance());
+    if cli.about {
+        return;
+    }
+
+    if let Some(clean_target) = cli.clean {
+        println!("\nStart cleaning of generated files");
+        if evaluate_errors(
+            clean(clean_target, cli.force_clean).map_err(|err| vec![err]),
+            &cli,
+        ) {
+            return;
+        }
+    }
+
+    if cli.validate_shaders {
+        println!("\nStart validation of the GPU shaders");
+        if evaluate_errors(heat_transfer::shader::validate_shaders(), &cli) {
+            return;
+        }
+    }
 
     if cli.simulations {
+        if let Some(path) = &cli.material_override {
+            if evaluate_errors(
+                fds::load_material_overrides(path).map_err(|err| vec![err]),
+                &cli,
+            ) {
+                return;
+            }
+        }
+
         println!("\nStart creation of fds simulations from templates");
         if evaluate_errors(
             create_simulations(cli.method.as_deref(), cli.kind.as_deref()),
@@ -103,8 +502,13 @@ fn main() {
         }
 
         println!("\nStart running of fds simulations");
+        let fds_jobs = cli.fds_jobs.map(|j| j as usize).unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(NonZeroUsize::get)
+                .unwrap_or(1)
+        });
         if evaluate_errors(
-            run_simulations(cli.method.as_deref(), cli.kind.as_deref()),
+            run_simulations(cli.method.as_deref(), cli.kind.as_deref(), fds_jobs),
             &cli,
         ) {
             return;
@@ -120,25 +524,92 @@ fn main() {
         release_mode = false;
     }
     if let Some(name) = &cli.benchmark {
-        if let Err(err) = set_max_element_per_chunk(name) {
-            println!("{}", err);
-            if !&cli.force {
-                return;
+        match config.chunk_sizes(name) {
+            Err(err) => {
+                summary::record_error(&err);
+                println!("{}", err);
+                if !&cli.force {
+                    return;
+                }
             }
-        } else if release_mode {
-            let result = run_benchmark(
-                name,
-                cli.method.as_deref(),
-                cli.kind.as_deref(),
-                cli.benchmark_name.as_deref(),
-            )
-            .map_err(|err| vec![err]);
+            Ok(chunk_sizes) if release_mode || cli.dry_run => {
+                let result = run_benchmark_with_chunk_sizes(
+                    name,
+                    &chunk_sizes,
+                    cli.method.as_deref(),
+                    cli.kind.as_deref(),
+                    cli.benchmark_name.as_deref(),
+                )
+                .map_err(|err| vec![err]);
 
-            if evaluate_errors(result, &cli) {
+                if evaluate_errors(result, &cli) {
+                    return;
+                }
+            }
+            Ok(_) => {
+                println!("Compile the program in release mode first to run benchmarks.");
                 return;
             }
-        } else {
-            println!("Compile the program in release mode first to run benchmarks.");
+        }
+    }
+
+    if let Some(path) = &cli.inspect_cells {
+        println!("\nStart inspection of cells");
+        if evaluate_errors(print_cells(path).map_err(|err| vec![err]), &cli) {
+            return;
+        }
+    }
+
+    if let Some(path) = &cli.stability {
+        println!("\nStart calculation of the explicit stability limit");
+        if evaluate_errors(print_stability(path).map_err(|err| vec![err]), &cli) {
+            return;
+        }
+    }
+
+    if let Some(path) = &cli.verify {
+        println!("\nStart verification of the GPU methods");
+        if evaluate_errors(
+            verify(path, cli.kind.as_deref(), cli.tolerance).map_err(|err| vec![err]),
+            &cli,
+        ) {
+            return;
+        }
+    }
+
+    if let Some(path) = &cli.diagnostics {
+        println!("\nStart energy diagnostics");
+        if evaluate_errors(
+            diagnostics(path, cli.kind.as_deref()).map_err(|err| vec![err]),
+            &cli,
+        ) {
+            return;
+        }
+    }
+
+    if let Some(path) = &cli.dump_profile {
+        println!("\nStart dump of the temperature profile");
+        if evaluate_errors(
+            dump_profile(path, cli.kind.as_deref(), cli.method.as_deref()).map_err(|err| vec![err]),
+            &cli,
+        ) {
+            return;
+        }
+    }
+
+    if let Some(path) = &cli.export_csv {
+        println!("\nStart export of benchmarks");
+        if evaluate_errors(export_csv(path).map_err(|err| vec![err]), &cli) {
+            return;
+        }
+    }
+
+    if let Some(label) = &cli.methods_compare {
+        println!("\nStart comparison of methods");
+        if evaluate_errors(
+            print_methods_compare(label, cli.benchmark_name.as_deref()).map_err(|err| vec![err]),
+            &cli,
+        ) {
             return;
         }
     }
@@ -153,6 +624,9 @@ fn main() {
         if evaluate_errors(
             plot_simulations(
                 plot_type,
+                cli.plot_format,
+                cli.plot_html,
+                cli.plot_combined,
                 cli.method.as_deref(),
                 cli.kind.as_deref(),
                 cli.benchmark_name.as_deref(),
@@ -163,5 +637,6 @@ fn main() {
         }
     }
 
+    summary::set_finished();
     println!("\nFinished without errors");
 }
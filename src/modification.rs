@@ -1,10 +1,184 @@
 //! This module contains help functions to determine when a file has been changed.
 
-use std::{path::Path, time::UNIX_EPOCH};
+use std::{
+    fs::File,
+    hash::Hasher,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::UNIX_EPOCH,
+};
 
 use anyhow::*;
+use clap::ValueEnum;
+use fnv::FnvHasher;
+
+/// The ways to detect whether the sources of a result have been changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum CacheMode {
+    /// Compare the modification times of the sources and results.
+    #[default]
+    Mtime,
+    /// Compare the content hashes of the sources with the ones stored next to the results.
+    Hash,
+}
+
+/// Whether the content hashes are used to detect changed sources. This value is replaced at the start of the program.
+static CACHE_MODE_HASH: AtomicBool = AtomicBool::new(false);
+
+/// Set the way changed sources are detected.
+pub fn set_cache_mode(cache_mode: CacheMode) {
+    CACHE_MODE_HASH.store(cache_mode == CacheMode::Hash, Ordering::Relaxed);
+    println!("Set cache mode to {cache_mode:?}")
+}
+
+/// Get the way changed sources are detected.
+#[inline]
+pub fn get_cache_mode() -> CacheMode {
+    if CACHE_MODE_HASH.load(Ordering::Relaxed) {
+        CacheMode::Hash
+    } else {
+        CacheMode::Mtime
+    }
+}
+
+/// The tolerance in milliseconds a result has to be newer than all sources. This value is replaced at the start of the program.
+static MODIFICATION_TOLERANCE: AtomicU64 = AtomicU64::new(0);
+
+/// Set the tolerance in milliseconds a result has to be newer than all sources.
+/// Filesystems like NFS or FAT store modification times with a resolution of up to two seconds, so a source changed shortly after a result can get the same or an earlier timestamp. A tolerance above this resolution treats such results as outdated.
+pub fn set_modification_tolerance(modification_tolerance: u64) {
+    MODIFICATION_TOLERANCE.store(modification_tolerance, Ordering::Relaxed);
+    println!("Set modification tolerance to {modification_tolerance} ms")
+}
+
+/// Get the tolerance in milliseconds a result has to be newer than all sources.
+#[inline]
+pub fn get_modification_tolerance() -> u64 {
+    MODIFICATION_TOLERANCE.load(Ordering::Relaxed)
+}
+
+/// Checks whether at least one source has been changed since the results were created, using the selected [`CacheMode`].
+/// See [`was_modified`] and [`was_modified_hash`].
+///
+/// # Errors
+/// This function will return an error if a source does not exist or can not be read.
+pub fn is_modified<P1: AsRef<Path>, P2: AsRef<Path>>(
+    sources: &[P1],
+    results: &[P2],
+) -> Result<bool> {
+    match get_cache_mode() {
+        CacheMode::Mtime => was_modified(sources, results),
+        CacheMode::Hash => was_modified_hash(sources, results),
+    }
+}
+
+/// Stores the content hashes of the sources next to the results, if the [`CacheMode::Hash`] is selected.
+/// This function should be called after the results were created successfully.
+///
+/// # Errors
+/// This function will return an error if a source can not be read or the hash file can not be written.
+pub fn store_source_hashes<P1: AsRef<Path>, P2: AsRef<Path>>(
+    sources: &[P1],
+    results: &[P2],
+) -> Result<()> {
+    if get_cache_mode() == CacheMode::Mtime {
+        return Ok(());
+    }
+    let Some(hash_path) = hash_path(results) else {
+        return Ok(());
+    };
+    std::fs::write(&hash_path, source_hashes(sources)?)
+        .with_context(|| format!("Failed to write the source hashes to {hash_path:?}."))
+}
+
+/// This function checks whether the content of at least one file in a list of files (`sources`) differs from the content hashes stored next to the results (`results`).
+/// If no hashes are stored, the modification times are compared with [`was_modified`] instead. If they show no change, the current hashes are stored.
+///
+/// # Errors
+/// This function will return an error if a source does not exist or can not be read.
+pub fn was_modified_hash<P1: AsRef<Path>, P2: AsRef<Path>>(
+    sources: &[P1],
+    results: &[P2],
+) -> Result<bool> {
+    if results.iter().any(|r| !r.as_ref().exists()) {
+        return Ok(true);
+    }
+    let Some(hash_path) = hash_path(results) else {
+        return was_modified(sources, results);
+    };
+    let hashes = source_hashes(sources)?;
+    if !hash_path.exists() {
+        let modified = was_modified(sources, results)?;
+        if !modified {
+            std::fs::write(&hash_path, hashes)
+                .with_context(|| format!("Failed to write the source hashes to {hash_path:?}."))?;
+        }
+        return Ok(modified);
+    }
+    let stored_hashes = std::fs::read_to_string(&hash_path)
+        .with_context(|| format!("Failed to read the source hashes from {hash_path:?}."))?;
+    Ok(stored_hashes != hashes)
+}
+
+/// Returns the path of the file the source hashes are stored in, next to the first result.
+fn hash_path<P: AsRef<Path>>(results: &[P]) -> Option<PathBuf> {
+    let result = results.first()?.as_ref();
+    let mut hash_path = result.as_os_str().to_owned();
+    hash_path.push(".hash");
+    Some(PathBuf::from(hash_path))
+}
+
+/// Returns one line with the content hash and the path for every source.
+///
+/// # Errors
+/// This function will return an error if a source does not exist or can not be read.
+fn source_hashes<P: AsRef<Path>>(sources: &[P]) -> Result<String> {
+    let mut hashes = String::new();
+    for source in sources {
+        let source = source.as_ref();
+        hashes += &format!("{:016x} {}\n", content_hash(source)?, source.display());
+    }
+    Ok(hashes)
+}
+
+/// Calculates the FNV-1a hash of the content of a file. The hash of a directory covers the names and contents of all its entries.
+///
+/// # Errors
+/// This function will return an error if the file does not exist or can not be read.
+fn content_hash(path: &Path) -> Result<u64> {
+    let mut hasher = FnvHasher::default();
+    if path.is_dir() {
+        let mut entries = std::fs::read_dir(path)
+            .with_context(|| format!("Failed to read directory at {path:?} for hashing."))?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()
+            .with_context(|| format!("Failed to read directory at {path:?} for hashing."))?;
+        entries.sort();
+        for entry in entries {
+            hasher.write(entry.as_os_str().as_encoded_bytes());
+            hasher.write_u64(content_hash(&entry)?);
+        }
+        return Ok(hasher.finish());
+    }
+
+    let mut file = File::open(path)
+        .with_context(|| format!("Failed to open file at {path:?} for hashing."))?;
+    let mut buffer = [0u8; 65536];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read file at {path:?} for hashing."))?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+    }
+    Ok(hasher.finish())
+}
 
 /// This function checks whether at least one entry in a list of files (`sources`) is newer than all entries in the other list of files (`results`). If this is the case, at least one source file has been changed
+/// A result only counts as newer if it exceeds the newest source by more than the tolerance set with [`set_modification_tolerance`].
 ///
 /// # Errors
 /// This function will return an error if a given path does not exist or the metadata cannot be read from the file.
@@ -14,8 +188,9 @@ pub fn was_modified<P1: AsRef<Path>, P2: AsRef<Path>>(
 ) -> Result<bool> {
     let min_duration = min_duration(results)?;
     let max_duration = max_duration(sources)?;
+    let tolerance = get_modification_tolerance() as u128 * 1_000_000;
 
-    Ok(min_duration <= max_duration)
+    Ok(min_duration <= max_duration.saturating_add(tolerance))
 }
 
 /// This function determines the time of the earliest modification from all given paths.
@@ -0,0 +1,48 @@
+//! This module contains the directory the benchmarks and plots are written to.
+
+use std::{
+    path::PathBuf,
+    sync::{PoisonError, RwLock},
+};
+
+/// The directory the `benchmark` and `plot` directories are placed in. If it is not set, the current working directory is used. This value is replaced at the start of the program.
+static OUTPUT_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Set the directory the `benchmark` and `plot` directories are placed in.
+pub fn set_output_dir(output_dir: PathBuf) {
+    println!("Set output directory to {output_dir:?}");
+    *OUTPUT_DIR.write().unwrap_or_else(PoisonError::into_inner) = Some(output_dir);
+}
+
+/// Get the directory the `benchmark` and `plot` directories are placed in, if one is set.
+#[inline]
+pub fn get_output_dir() -> Option<PathBuf> {
+    OUTPUT_DIR
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Returns the root directory of all benchmarks.
+pub fn benchmark_root() -> PathBuf {
+    output_root("benchmark")
+}
+
+/// Returns the root directory of all plots.
+/// The plots labeled in English are placed in the subdirectory `en`, so they do not replace the German plots.
+pub fn plot_root() -> PathBuf {
+    let plot_root = output_root("plot");
+    #[cfg(feature = "plot")]
+    if crate::plot::labels::get_language() == crate::plot::labels::Language::En {
+        return plot_root.join("en");
+    }
+    plot_root
+}
+
+/// Returns the passed directory inside the output directory.
+fn output_root(directory: &str) -> PathBuf {
+    match get_output_dir() {
+        Some(output_dir) => output_dir.join(directory),
+        None => PathBuf::from(directory),
+    }
+}
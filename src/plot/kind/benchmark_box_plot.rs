@@ -1,19 +1,22 @@
 //! Create a boxplot that displays the simulation time for different methods.
 
-use super::COLORS;
+use super::{plot_elements, plot_source, COLORS};
 use crate::{
+    dry_run::get_dry_run,
+    fds::max_cores_prefix,
     heat_transfer::simulations::{
-        BenchmarkPathPart, BenchmarkReader, SimulationType1D, SIMULATION_RERUNS,
+        get_benchmark_format, BenchmarkPathPart, BenchmarkReader, SimulationType1D, SETUP_PREFIX,
     },
-    modification::was_modified,
-    plot::Status,
+    modification::{is_modified, store_source_hashes},
+    output::{benchmark_root, plot_root},
+    plot::{labels::Label, PlotFormat, Status},
 };
 use anyhow::*;
 use plotly::{
     box_plot::BoxPoints,
     common::{Line, Title},
     layout::{self, Axis, Legend, Margin},
-    BoxPlot, ImageFormat, Layout, Plot,
+    BoxPlot, Layout, Plot,
 };
 use std::{
     fmt::Display,
@@ -42,8 +45,6 @@ pub struct BenchmarkBoxPlotSources {
     plot_directory: PathBuf,
     /// Data for all graph elements inside a plot.
     benchmark_sources: Vec<BenchmarkBoxPlotSource>,
-    /// The number of wall elements that should be displayed inside the plot.
-    elements: Vec<usize>,
     /// Suffix of the plot file.
     suffix: String,
     /// Wether the Legend should be floating or on the right.
@@ -51,13 +52,9 @@ pub struct BenchmarkBoxPlotSources {
 }
 impl BenchmarkBoxPlotSources {
     /// Create a [`BenchmarkBoxPlotSources`] for a plot that compares the different simulation methods.
-    pub fn compare_mode<P: AsRef<Path>, S: Display, E: AsRef<[usize]>>(
-        simulation_directory: P,
-        label: S,
-        elements: E,
-    ) -> Self {
+    pub fn compare_mode<P: AsRef<Path>, S: Display>(simulation_directory: P, label: S) -> Self {
         let simulation_directory = simulation_directory.as_ref();
-        let plot_directory = PathBuf::from("plot").join(simulation_directory);
+        let plot_directory = plot_root().join(simulation_directory);
         let benchmark_sources = [
             SimulationType1D::Cpu,
             SimulationType1D::GpuM1,
@@ -74,26 +71,55 @@ impl BenchmarkBoxPlotSources {
         })
         .collect::<Vec<_>>();
         let suffix = format!("{label}_compare_mode");
-        let elements = elements.as_ref().to_vec();
         Self {
             plot_directory,
             benchmark_sources,
-            elements,
+            suffix,
+            floating_legend: true,
+        }
+    }
+
+    /// Create a [`BenchmarkBoxPlotSources`] for a plot that compares the setup time of all one dimensional simulation methods.
+    pub fn setup_mode<P: AsRef<Path>, S: Display>(simulation_directory: P, label: S) -> Self {
+        let simulation_directory = simulation_directory.as_ref();
+        let plot_directory = plot_root().join(simulation_directory);
+        let benchmark_sources = SimulationType1D::ALL_1D
+            .into_iter()
+            .map(|s| BenchmarkBoxPlotSource {
+                simulation_directory: simulation_directory.to_path_buf(),
+                benchmark_path_part: BenchmarkPathPart::new(
+                    Some(SETUP_PREFIX.to_string()),
+                    label.to_string(),
+                    s.into(),
+                ),
+                color: match s {
+                    SimulationType1D::Cpu => COLORS[0].to_string(),
+                    SimulationType1D::GpuM1 => COLORS[1].to_string(),
+                    SimulationType1D::GpuM2 => COLORS[2].to_string(),
+                    SimulationType1D::GpuM3 => COLORS[3].to_string(),
+                    SimulationType1D::GpuM4 => COLORS[5].to_string(),
+                },
+                legend: s.to_string(),
+            })
+            .collect::<Vec<_>>();
+        let suffix = format!("{label}_setup");
+        Self {
+            plot_directory,
+            benchmark_sources,
             suffix,
             floating_legend: true,
         }
     }
 
     /// Create a [`BenchmarkBoxPlotSources`] for a plot that compares the influence of different materials.
-    pub fn compare_multiple<P: AsRef<Path>, S: Display, E: AsRef<[usize]>, N: AsRef<[u8]>>(
+    pub fn compare_multiple<P: AsRef<Path>, S: Display, N: AsRef<[u8]>>(
         simulation_directory: P,
         label: S,
-        elements: E,
         numbers: N,
         simulation_type: SimulationType1D,
     ) -> Self {
         let simulation_directory = simulation_directory.as_ref();
-        let plot_directory = PathBuf::from("plot").join(simulation_directory);
+        let plot_directory = plot_root().join(simulation_directory);
         let benchmark_sources = numbers
             .as_ref()
             .iter()
@@ -110,27 +136,24 @@ impl BenchmarkBoxPlotSources {
             })
             .collect::<Vec<_>>();
         let suffix = format!("{label}_compare_multiple_{}", simulation_type.path_str());
-        let elements = elements.as_ref().to_vec();
         Self {
             plot_directory,
             benchmark_sources,
-            elements,
             suffix,
             floating_legend: false,
         }
     }
 
     /// Create a [`BenchmarkBoxPlotSources`] for a plot that compares a cpu and gpu m3 simulation with FDS.
-    pub fn compare_fds<P1: AsRef<Path>, P2: AsRef<Path>, S: Display, E: AsRef<[usize]>>(
+    pub fn compare_fds<P1: AsRef<Path>, P2: AsRef<Path>, S: Display>(
         fds_directory: P1,
         simulation_directory: P2,
         label: S,
-        elements: E,
         simulation_types: &[SimulationType1D],
     ) -> Self {
         let simulation_directory = simulation_directory.as_ref();
         let fds_directory = fds_directory.as_ref();
-        let plot_directory = PathBuf::from("plot").join(fds_directory);
+        let plot_directory = plot_root().join(fds_directory);
         let benchmark_sources = simulation_types
             .iter()
             .map(|s| BenchmarkBoxPlotSource {
@@ -141,27 +164,26 @@ impl BenchmarkBoxPlotSources {
                     SimulationType1D::GpuM1 => COLORS[1].to_string(),
                     SimulationType1D::GpuM2 => COLORS[2].to_string(),
                     SimulationType1D::GpuM3 => COLORS[3].to_string(),
+                    SimulationType1D::GpuM4 => COLORS[5].to_string(),
                 },
                 legend: s.to_string(),
             })
             .chain([BenchmarkBoxPlotSource {
                 benchmark_path_part: BenchmarkPathPart {
-                    prefix: Some("multi_core".to_string()),
+                    prefix: Some(max_cores_prefix(fds_directory, &label.to_string())),
                     label: label.to_string(),
                     simulation_method:
                         crate::heat_transfer::simulations::SimulationMethod::SpeedTestFDS,
                 },
                 color: COLORS[4].to_string(),
-                legend: "FDS max Kerne".to_string(),
+                legend: Label::FdsMaxCores.text().to_string(),
                 simulation_directory: fds_directory.to_path_buf(),
             }])
             .collect::<Vec<_>>();
         let suffix = format!("{label}_compare_fds");
-        let elements = elements.as_ref().to_vec();
         Self {
             plot_directory,
             benchmark_sources,
-            elements,
             suffix,
             floating_legend: true,
         }
@@ -169,28 +191,42 @@ impl BenchmarkBoxPlotSources {
 }
 
 /// Create a boxplot that displays the simulation time for different methods
-pub fn plot(benchmark_source: BenchmarkBoxPlotSources) -> Result<Status> {
+pub fn plot(benchmark_source: BenchmarkBoxPlotSources, plot_format: PlotFormat) -> Result<Status> {
     let BenchmarkBoxPlotSources {
         plot_directory,
         benchmark_sources,
-        elements,
         suffix,
         floating_legend,
     } = benchmark_source;
 
-    create_dir_all(&plot_directory)
-        .with_context(|| format!("Failed to create directories at {plot_directory:?}."))?;
-    let save_path = plot_directory.join(format!("benchmark_{}.svg", suffix));
+    let save_path =
+        plot_directory.join(format!("benchmark_{}.{}", suffix, plot_format.extension()));
+    let benchmark_directories = benchmark_sources
+        .iter()
+        .map(|b| {
+            Ok(benchmark_root()
+                .join(&b.simulation_directory)
+                .join(b.benchmark_path_part.path_str()?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let elements = plot_elements(&benchmark_directories);
+    if elements.is_empty() {
+        return Ok(Status::Ignored {
+            simulation: SIMULATION_NAME,
+            reason: format!(
+                "\n     no size has a benchmark inside each of {:?}",
+                benchmark_directories
+            ),
+            path: save_path,
+        });
+    }
     let paths = elements
         .iter()
         .flat_map(|e| {
-            benchmark_sources
+            benchmark_directories
                 .iter()
                 .map(|b| {
-                    let path = PathBuf::from("benchmark")
-                        .join(&b.simulation_directory)
-                        .join(b.benchmark_path_part.path_str().unwrap())
-                        .join(format!("{}.bin", e));
+                    let path = b.join(get_benchmark_format().file_name(*e));
                     if path.exists() {
                         std::result::Result::Ok(path)
                     } else {
@@ -203,15 +239,21 @@ pub fn plot(benchmark_source: BenchmarkBoxPlotSources) -> Result<Status> {
                 })
                 .collect::<Vec<_>>()
         })
-        .chain([std::result::Result::Ok(PathBuf::from(
-            "src/plot/kind/benchmark_box_plot.rs",
-        ))])
+        .chain(plot_source("src/plot/kind/benchmark_box_plot.rs").map(std::result::Result::Ok))
         .collect::<Result<Vec<PathBuf>, Status>>();
     let paths = match paths {
         std::result::Result::Ok(ok) => ok,
         Err(err) => return Ok(err),
     };
-    if !was_modified(&paths, &[&save_path])? {
+    if get_dry_run() {
+        return Ok(Status::DryRun {
+            simulation: SIMULATION_NAME,
+            path: save_path,
+        });
+    }
+    create_dir_all(&plot_directory)
+        .with_context(|| format!("Failed to create directories at {plot_directory:?}."))?;
+    if !is_modified(&paths, &[&save_path])? {
         return Ok(Status::Passed {
             simulation: SIMULATION_NAME,
             path: save_path,
@@ -222,7 +264,7 @@ pub fn plot(benchmark_source: BenchmarkBoxPlotSources) -> Result<Status> {
         &save_path
     );
 
-    let mut legend = Legend::new().title(Title::new("Legende"));
+    let mut legend = Legend::new().title(Title::new(Label::Legend.text()));
     if floating_legend {
         legend = legend
             .border_color("#000000")
@@ -237,14 +279,14 @@ pub fn plot(benchmark_source: BenchmarkBoxPlotSources) -> Result<Status> {
         .y_axis(
             Axis::new()
                 .show_line(true)
-                .title(Title::new("Zeit [s]"))
+                .title(Title::new(Label::Time.text()))
                 .type_(plotly::layout::AxisType::Log),
         )
         .x_axis(
             Axis::new()
                 .show_line(true)
                 .type_(layout::AxisType::Category)
-                .title(Title::new("Wandelemente"))
+                .title(Title::new(Label::WallElements.text()))
                 .show_grid(true)
                 .ticks_on(layout::TicksPosition::Boundaries),
         )
@@ -254,18 +296,14 @@ pub fn plot(benchmark_source: BenchmarkBoxPlotSources) -> Result<Status> {
     let mut plot = Plot::new();
     plot.set_layout(layout);
 
-    let mut box_plots_x =
-        vec![Vec::with_capacity(SIMULATION_RERUNS * elements.len()); benchmark_sources.len()];
-    let mut box_plots_y =
-        vec![Vec::with_capacity(SIMULATION_RERUNS * elements.len()); benchmark_sources.len()];
+    let mut box_plots_x = vec![Vec::new(); benchmark_sources.len()];
+    let mut box_plots_y = vec![Vec::new(); benchmark_sources.len()];
     for e in elements {
-        for (i, benchmark_source) in benchmark_sources.iter().enumerate() {
-            let benchmark_path = benchmark_source
-                .simulation_directory
-                .join(benchmark_source.benchmark_path_part.path_str()?)
-                .join(format!("{}.bin", e));
-            let benchmark_reader =
-                BenchmarkReader::try_new(PathBuf::from("benchmark").join(benchmark_path))?;
+        for (i, benchmark_directory) in benchmark_directories.iter().enumerate() {
+            let benchmark_path = benchmark_directory.join(get_benchmark_format().file_name(e));
+            let benchmark_reader = BenchmarkReader::try_new(benchmark_path)?;
+            box_plots_x[i].reserve(benchmark_reader.reruns());
+            box_plots_y[i].reserve(benchmark_reader.reruns());
             for time in benchmark_reader {
                 let time = time?;
                 box_plots_y[i].push(time);
@@ -287,8 +325,9 @@ pub fn plot(benchmark_source: BenchmarkBoxPlotSources) -> Result<Status> {
             )
         });
 
-    plot.write_image(&save_path, ImageFormat::SVG, 600, 350, 1.0);
+    plot_format.write(&plot, &save_path, 600, 350);
     if save_path.exists() {
+        store_source_hashes(&paths, &[&save_path])?;
         Ok(Status::Succeeded {
             simulation: SIMULATION_NAME,
             path: save_path,
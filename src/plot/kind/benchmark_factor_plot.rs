@@ -1,17 +1,22 @@
 //! Create a bar chart witch displays the ratio beten two simulations.
 
-use super::COLORS;
+use super::{common_benchmark_sizes, plot_elements, plot_source, COLORS};
 use crate::{
-    benchmark::{BENCHMARK_CHUNK, BENCHMARK_ELEMENTS},
-    heat_transfer::simulations::{BenchmarkReader, SIMULATION_RERUNS},
-    modification::was_modified,
-    plot::Status,
+    benchmark::{get_set_benchmark_elements, get_thicknesses, BenchmarkName, BENCHMARK_CHUNK},
+    dry_run::get_dry_run,
+    heat_transfer::simulations::{get_benchmark_format, BenchmarkReader, BenchmarkStats},
+    modification::{is_modified, store_source_hashes},
+    output::{benchmark_root, plot_root},
+    plot::{
+        labels::{thickness_legend, Label},
+        PlotFormat, Status,
+    },
 };
 use anyhow::*;
 use plotly::{
-    common::{Anchor, DashType, Marker, Title},
+    common::{Anchor, DashType, ErrorData, ErrorType, Marker, Title},
     layout::{self, Axis, BarMode, Legend, Margin, Shape, ShapeLine, ShapeType},
-    Bar, ImageFormat, Layout, Plot,
+    Bar, Layout, Plot,
 };
 use std::{
     fmt::Display,
@@ -32,6 +37,45 @@ struct BenchmarkFactorPlotSource {
     color: String,
 }
 
+/// The thickness benchmarks with the directory, the thickness in m and the number of cells of their graph elements.
+const THICKNESS_SOURCES: [(BenchmarkName, &str, f32, u32); 5] = [
+    (BenchmarkName::DiabaticThickness005, "005cm", 0.05, 6),
+    (BenchmarkName::DiabaticThickness010, "010cm", 0.10, 8),
+    (BenchmarkName::DiabaticThickness050, "050cm", 0.50, 13),
+    (BenchmarkName::DiabaticThickness100, "100cm", 1.00, 14),
+    (BenchmarkName::DiabaticThickness500, "500cm", 5.00, 19),
+];
+
+/// Returns the suffix of the thickness plots for the thicknesses selected with [`set_thicknesses`](crate::benchmark::set_thicknesses), e.g. `_050_500`.
+/// Without a selection the suffix is empty, so the plot of all thicknesses keeps its name.
+fn thickness_suffix() -> String {
+    get_thicknesses()
+        .map(|thicknesses| {
+            thicknesses
+                .iter()
+                .map(|thickness| format!("_{thickness:03}"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Returns the largest three benchmark elements followed by [`BENCHMARK_CHUNK`], the sizes that show the impact of the chunk adjustment.
+/// Without elements set with [`set_benchmark_elements`](crate::benchmark::set_benchmark_elements), the sizes with a benchmark inside each of the passed directories are used and only the present chunk sizes are added.
+/// Fewer elements are used if less than three are available.
+fn chunk_elements(directories: &[PathBuf]) -> Vec<usize> {
+    let (elements, chunk_elements) = match get_set_benchmark_elements() {
+        Some(elements) => (elements, BENCHMARK_CHUNK.to_vec()),
+        None => common_benchmark_sizes(directories)
+            .into_iter()
+            .partition(|e| !BENCHMARK_CHUNK.contains(e)),
+    };
+    elements[elements.len().saturating_sub(3)..]
+        .iter()
+        .chain(chunk_elements.iter())
+        .copied()
+        .collect()
+}
+
 /// Enum to determent the position of the legend.
 enum LegendPos {
     TopLeft,
@@ -46,8 +90,8 @@ pub struct BenchmarkFactorPlotSources {
     benchmark_sources: Vec<BenchmarkFactorPlotSource>,
     /// suffix for the `benchmark_sources` for the full path
     benchmark_compare_path: [PathBuf; 2],
-    /// The number of wall elements that should be displayed inside the plot.
-    elements: Vec<usize>,
+    /// Whether only the sizes that show the impact of the chunk adjustment are displayed inside the plot, see [`chunk_elements`].
+    chunk_elements: bool,
     /// Suffix of the plot file.
     suffix: String,
     /// Lable of the y-Axis.
@@ -58,32 +102,29 @@ pub struct BenchmarkFactorPlotSources {
 impl BenchmarkFactorPlotSources {
     /// Create a [`BenchmarkFactorPlotSources`] for a plot that compares the impact of different thicknesses on the simulation time between cpu and gpu m3.
     pub fn thickness_mode<P: AsRef<Path>, S: Display>(simulation_directory: P, label: S) -> Self {
-        let elements = &BENCHMARK_ELEMENTS;
-        let benchmark_directory = PathBuf::from("benchmark").join(simulation_directory.as_ref());
-        let plot_directory = PathBuf::from("plot").join(simulation_directory.as_ref());
-        let benchmark_sources = [
-            ("005cm", "0,05 m (6 Zellen)"),
-            ("010cm", "0,10 m (8 Zellen)"),
-            ("050cm", "0,50 m (13 Zellen)"),
-            ("100cm", "1,00 m (14 Zellen)"),
-            ("500cm", "5,00 m (19 Zellen)"),
-        ]
-        .into_iter()
-        .enumerate()
-        .map(|(i, (path_part, legend))| BenchmarkFactorPlotSource {
-            benchmark_directory: benchmark_directory.join(path_part).join(label.to_string()),
-            color: COLORS[i].to_string(),
-            legend: legend.to_string(),
-        })
-        .collect::<Vec<_>>();
-        let suffix = format!("{label}_thickness_mode");
-        let elements = elements.as_ref().to_vec();
+        let benchmark_directory = benchmark_root().join(simulation_directory.as_ref());
+        let plot_directory = plot_root().join(simulation_directory.as_ref());
+        let benchmark_sources = THICKNESS_SOURCES
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (benchmark_name, ..))| benchmark_name.is_selected_thickness())
+            .map(
+                |(i, (_, path_part, thickness, cells))| BenchmarkFactorPlotSource {
+                    benchmark_directory: benchmark_directory
+                        .join(path_part)
+                        .join(label.to_string()),
+                    color: COLORS[i].to_string(),
+                    legend: thickness_legend(thickness, cells),
+                },
+            )
+            .collect::<Vec<_>>();
+        let suffix = format!("{label}_thickness_mode{}", thickness_suffix());
         Self {
             plot_directory,
             benchmark_sources,
-            elements,
+            chunk_elements: false,
             suffix,
-            y_axis: "Verhältnis CPU / GPU M3".to_string(),
+            y_axis: Label::RatioCpuGpuM3.text().to_string(),
             benchmark_compare_path: [PathBuf::from("cpu"), PathBuf::from("gpu_m3")],
             legend_pos: LegendPos::TopLeft,
         }
@@ -95,35 +136,30 @@ impl BenchmarkFactorPlotSources {
         computer_1: &str,
         computer_2: &str,
     ) -> Self {
-        let elements = BENCHMARK_ELEMENTS[(BENCHMARK_ELEMENTS.len() - 3)..]
-            .iter()
-            .chain(BENCHMARK_CHUNK.iter())
-            .copied()
+        let benchmark_directory = benchmark_root().join(simulation_directory.as_ref());
+        let plot_directory = plot_root().join(simulation_directory.as_ref());
+        let benchmark_sources = THICKNESS_SOURCES
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (benchmark_name, ..))| benchmark_name.is_selected_thickness())
+            .map(
+                |(i, (_, path_part, thickness, cells))| BenchmarkFactorPlotSource {
+                    benchmark_directory: benchmark_directory.join(path_part),
+                    color: COLORS[i].to_string(),
+                    legend: thickness_legend(thickness, cells),
+                },
+            )
             .collect::<Vec<_>>();
-        let benchmark_directory = PathBuf::from("benchmark").join(simulation_directory.as_ref());
-        let plot_directory = PathBuf::from("plot").join(simulation_directory.as_ref());
-        let benchmark_sources = [
-            ("005cm", "0,05 m (6 Zellen)"),
-            ("010cm", "0,10 m (8 Zellen)"),
-            ("050cm", "0,50 m (13 Zellen)"),
-            ("100cm", "1,00 m (14 Zellen)"),
-            ("500cm", "5,00 m (19 Zellen)"),
-        ]
-        .into_iter()
-        .enumerate()
-        .map(|(i, (path_part, legend))| BenchmarkFactorPlotSource {
-            benchmark_directory: benchmark_directory.join(path_part),
-            color: COLORS[i].to_string(),
-            legend: legend.to_string(),
-        })
-        .collect::<Vec<_>>();
-        let suffix = format!("compare_{computer_1}_to_{computer_2}_thickness_mode");
+        let suffix = format!(
+            "compare_{computer_1}_to_{computer_2}_thickness_mode{}",
+            thickness_suffix()
+        );
         Self {
             plot_directory,
             benchmark_sources,
-            elements,
+            chunk_elements: true,
             suffix,
-            y_axis: "Verhältnis ohne/mit".to_string(),
+            y_axis: Label::RatioWithoutWith.text().to_string(),
             benchmark_compare_path: [
                 PathBuf::from(computer_1).join("gpu_m3"),
                 PathBuf::from(computer_2).join("gpu_m3"),
@@ -138,28 +174,23 @@ impl BenchmarkFactorPlotSources {
         computer_1: &str,
         computer_2: &str,
     ) -> Self {
-        let elements = BENCHMARK_ELEMENTS[(BENCHMARK_ELEMENTS.len() - 3)..]
-            .iter()
-            .chain(BENCHMARK_CHUNK.iter())
-            .copied()
-            .collect::<Vec<_>>();
-        let benchmark_directory = PathBuf::from("benchmark").join(simulation_directory.as_ref());
-        let plot_directory = PathBuf::from("plot").join(simulation_directory.as_ref());
+        let benchmark_directory = benchmark_root().join(simulation_directory.as_ref());
+        let plot_directory = plot_root().join(simulation_directory.as_ref());
         let benchmark_sources = vec![BenchmarkFactorPlotSource {
             benchmark_directory,
-            legend: "Verhältnis".to_string(),
+            legend: Label::Ratio.text().to_string(),
             color: COLORS[3].to_string(),
         }];
         let suffix = format!("compare_{computer_1}_to_{computer_2}_mode");
         Self {
-            y_axis: "Verhältnis ohne/mit".to_string(),
+            y_axis: Label::RatioWithoutWith.text().to_string(),
             plot_directory,
             benchmark_compare_path: [
                 PathBuf::from(computer_1).join("gpu_m3"),
                 PathBuf::from(computer_2).join("gpu_m3"),
             ],
             benchmark_sources,
-            elements,
+            chunk_elements: true,
             suffix,
             legend_pos: LegendPos::BottomRight,
         }
@@ -167,20 +198,46 @@ impl BenchmarkFactorPlotSources {
 }
 
 /// Create a bar chart witch displays the ratio beten two simulations.
-pub fn plot(benchmark_source: BenchmarkFactorPlotSources) -> Result<Status> {
+/// The bars show the ratio of the medians. The error bars show the band between the ratio of the lower quartile to the upper quartile and the ratio of the upper quartile to the lower quartile.
+pub fn plot(
+    benchmark_source: BenchmarkFactorPlotSources,
+    plot_format: PlotFormat,
+) -> Result<Status> {
     let BenchmarkFactorPlotSources {
         plot_directory,
         benchmark_sources,
-        elements,
+        chunk_elements: chunk,
         suffix,
         benchmark_compare_path,
         y_axis,
         legend_pos,
     } = benchmark_source;
 
-    create_dir_all(&plot_directory)
-        .with_context(|| format!("Failed to create directories at {plot_directory:?}."))?;
-    let save_path = plot_directory.join(format!("benchmark_{}.svg", suffix));
+    let save_path =
+        plot_directory.join(format!("benchmark_{}.{}", suffix, plot_format.extension()));
+    let benchmark_directories = benchmark_sources
+        .iter()
+        .flat_map(|b| {
+            benchmark_compare_path
+                .iter()
+                .map(|p| b.benchmark_directory.join(p))
+        })
+        .collect::<Vec<_>>();
+    let elements = if chunk {
+        chunk_elements(&benchmark_directories)
+    } else {
+        plot_elements(&benchmark_directories)
+    };
+    if elements.is_empty() {
+        return Ok(Status::Ignored {
+            simulation: SIMULATION_NAME,
+            reason: format!(
+                "\n     no size has a benchmark inside each of {:?}",
+                benchmark_directories
+            ),
+            path: save_path,
+        });
+    }
     let paths = elements
         .iter()
         .flat_map(|e| {
@@ -190,11 +247,11 @@ pub fn plot(benchmark_source: BenchmarkFactorPlotSources) -> Result<Status> {
                     let path_benchmark_compare_1 = &b
                         .benchmark_directory
                         .join(&benchmark_compare_path[0])
-                        .join(format!("{}.bin", e));
+                        .join(get_benchmark_format().file_name(*e));
                     let path_benchmark_compare_2 = &b
                         .benchmark_directory
                         .join(&benchmark_compare_path[1])
-                        .join(format!("{}.bin", e));
+                        .join(get_benchmark_format().file_name(*e));
 
                     if path_benchmark_compare_1.exists() && path_benchmark_compare_2.exists() {
                         std::result::Result::Ok(b.benchmark_directory.clone())
@@ -211,15 +268,21 @@ pub fn plot(benchmark_source: BenchmarkFactorPlotSources) -> Result<Status> {
                 })
                 .collect::<Vec<_>>()
         })
-        .chain([std::result::Result::Ok(PathBuf::from(
-            "src/plot/kind/benchmark_factor_plot.rs",
-        ))])
+        .chain(plot_source("src/plot/kind/benchmark_factor_plot.rs").map(std::result::Result::Ok))
         .collect::<Result<Vec<PathBuf>, Status>>();
     let paths = match paths {
         std::result::Result::Ok(ok) => ok,
         Err(err) => return Ok(err),
     };
-    if !was_modified(&paths, &[&save_path])? {
+    if get_dry_run() {
+        return Ok(Status::DryRun {
+            simulation: SIMULATION_NAME,
+            path: save_path,
+        });
+    }
+    create_dir_all(&plot_directory)
+        .with_context(|| format!("Failed to create directories at {plot_directory:?}."))?;
+    if !is_modified(&paths, &[&save_path])? {
         return Ok(Status::Passed {
             simulation: SIMULATION_NAME,
             path: save_path,
@@ -231,7 +294,7 @@ pub fn plot(benchmark_source: BenchmarkFactorPlotSources) -> Result<Status> {
     );
 
     let mut legend = Legend::new()
-        .title(Title::new("Legende"))
+        .title(Title::new(Label::Legend.text()))
         .border_color("#000000")
         .border_width(1);
     match legend_pos {
@@ -257,7 +320,7 @@ pub fn plot(benchmark_source: BenchmarkFactorPlotSources) -> Result<Status> {
             Axis::new()
                 .show_line(true)
                 .type_(layout::AxisType::Category)
-                .title(Title::new("Wandelemente"))
+                .title(Title::new(Label::WallElements.text()))
                 .show_grid(true)
                 .ticks_on(layout::TicksPosition::Boundaries),
         )
@@ -287,39 +350,43 @@ pub fn plot(benchmark_source: BenchmarkFactorPlotSources) -> Result<Status> {
     for benchmark_source in benchmark_sources.into_iter() {
         let mut x = vec![];
         let mut y = vec![];
+        let mut error_plus = vec![];
+        let mut error_minus = vec![];
         for e in elements.iter() {
             let path_benchmark_cpu = benchmark_source
                 .benchmark_directory
                 .join(&benchmark_compare_path[0])
-                .join(format!("{}.bin", *e));
-            let mut compare_1_times = BenchmarkReader::try_new(path_benchmark_cpu)?
-                .collect::<Result<Vec<f64>, Error>>()?;
-            compare_1_times.sort_by(|a, b| a.total_cmp(b));
-            let compare_1_time = (compare_1_times[SIMULATION_RERUNS / 2]
-                + compare_1_times[SIMULATION_RERUNS / 2 + 1])
-                / 2.0;
+                .join(get_benchmark_format().file_name(*e));
+            let compare_1_stats = complete_stats(path_benchmark_cpu)?;
             let compare_1_path_benchmark = benchmark_source
                 .benchmark_directory
                 .join(&benchmark_compare_path[1])
-                .join(format!("{}.bin", *e));
-            let mut compare_2_times = BenchmarkReader::try_new(compare_1_path_benchmark)?
-                .collect::<Result<Vec<f64>, Error>>()?;
-            compare_2_times.sort_by(|a, b| a.total_cmp(b));
-            let compare_2_time = (compare_2_times[SIMULATION_RERUNS / 2]
-                + compare_2_times[SIMULATION_RERUNS / 2 + 1])
-                / 2.0;
+                .join(get_benchmark_format().file_name(*e));
+            let compare_2_stats = complete_stats(compare_1_path_benchmark)?;
+            let ratio = compare_1_stats.median / compare_2_stats.median;
             x.push(*e);
-            y.push(compare_1_time / compare_2_time)
+            y.push(ratio);
+            error_plus.push(compare_1_stats.p75 / compare_2_stats.p25 - ratio);
+            error_minus.push(ratio - compare_1_stats.p25 / compare_2_stats.p75);
         }
         plot.add_trace(
             Bar::new(x, y)
                 .name(benchmark_source.legend)
-                .marker(Marker::new().color(benchmark_source.color)),
+                .marker(Marker::new().color(benchmark_source.color))
+                .error_y(
+                    ErrorData::new(ErrorType::Data)
+                        .symmetric(false)
+                        .array(error_plus)
+                        .array_minus(error_minus)
+                        .color("000000AA")
+                        .thickness(1.0),
+                ),
         )
     }
 
-    plot.write_image(&save_path, ImageFormat::SVG, 600, 350, 1.0);
+    plot_format.write(&plot, &save_path, 600, 350);
     if save_path.exists() {
+        store_source_hashes(&paths, &[&save_path])?;
         Ok(Status::Succeeded {
             simulation: SIMULATION_NAME,
             path: save_path,
@@ -331,3 +398,25 @@ pub fn plot(benchmark_source: BenchmarkFactorPlotSources) -> Result<Status> {
         })
     }
 }
+
+/// Reads the benchmark at the passed path and returns the [`BenchmarkStats`] of the simulation times.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - the benchmark can not be read.
+/// - the benchmark contains less simulation times than reruns stored in its header.
+fn complete_stats<P: AsRef<Path>>(path: P) -> Result<BenchmarkStats> {
+    let path = path.as_ref();
+    let stats = BenchmarkReader::try_new(path)?
+        .stats()
+        .with_context(|| format!("Failed to read benchmark at {path:?}."))?;
+    if stats.count < stats.reruns {
+        bail!(
+            "The benchmark at {path:?} contains {} of {} simulation times.",
+            stats.count,
+            stats.reruns
+        );
+    }
+    Ok(stats)
+}
@@ -1,18 +1,31 @@
 use std::fs::create_dir_all;
 
 use crate::{
-    fds::{cells_from_materials_and_thickness, parse_script_from_file},
-    plot::{kind::COLORS, Status},
+    dry_run::get_dry_run,
+    fds::{
+        cells_from_materials_and_thickness, parse_script_from_file, Discretization,
+        DEFAULT_INITIAL_TEMPERATURE,
+    },
+    output::plot_root,
+    plot::{kind::COLORS, labels::Label, PlotFormat, Status},
 };
 use anyhow::*;
 use plotly::{
     common::{Marker, MarkerSymbol, Mode, Title},
     layout::{Axis, AxisType, Legend, Margin},
-    ImageFormat, Layout, Plot, Scatter,
+    Layout, Plot, Scatter,
 };
 
-pub fn plot() -> Result<Status> {
+pub fn plot(plot_format: PlotFormat) -> Result<Status> {
     const THICKNESSES: [f32; 10] = [0.01, 0.02, 0.05, 0.10, 0.20, 0.50, 1.0, 2.0, 5.0, 10.0];
+    let dir = plot_root().join("helper");
+    let path = dir.join(format!("cell_count.{}", plot_format.extension()));
+    if get_dry_run() {
+        return Ok(Status::DryRun {
+            simulation: "helper",
+            path,
+        });
+    }
 
     let (_, materials_c, _) =
         parse_script_from_file("fds/1D/Diabatic/concrete_k_c/heat_transfer.fds").with_context(
@@ -29,17 +42,34 @@ pub fn plot() -> Result<Status> {
     ) {
         let cells_c = THICKNESSES
             .iter()
-            .map(|n| cells_from_materials_and_thickness(&materials_c, &[material_c], &[*n]).len())
+            .map(|n| {
+                cells_from_materials_and_thickness(
+                    &materials_c,
+                    &[material_c],
+                    &[*n],
+                    DEFAULT_INITIAL_TEMPERATURE,
+                    Discretization::Geometric,
+                )
+                .len()
+            })
             .collect::<Vec<_>>();
         let cells_s = THICKNESSES
             .iter()
             .map(|n| {
-                cells_from_materials_and_thickness(&materials_s, &[material_s], &[*n]).len() - 2
+                cells_from_materials_and_thickness(
+                    &materials_s,
+                    &[material_s],
+                    &[*n],
+                    DEFAULT_INITIAL_TEMPERATURE,
+                    Discretization::Geometric,
+                )
+                .len()
+                    - 2
             })
             .collect::<Vec<_>>();
 
         let legend = Legend::new()
-            .title(Title::new("Legende"))
+            .title(Title::new(Label::Legend.text()))
             .border_color("#000000")
             .border_width(1)
             .x(0.02)
@@ -48,10 +78,10 @@ pub fn plot() -> Result<Status> {
         let layout = Layout::new()
             .legend(legend)
             .show_legend(true)
-            .y_axis(Axis::new().title(Title::new("Anzahl Zellen")))
+            .y_axis(Axis::new().title(Title::new(Label::CellCount.text())))
             .x_axis(
                 Axis::new()
-                    .title(Title::new("Wanddicke [m]"))
+                    .title(Title::new(Label::WallThickness.text()))
                     .show_line(true)
                     .type_(AxisType::Log)
                     .tick_values(THICKNESSES.iter().map(|t| *t as f64).collect()),
@@ -62,31 +92,25 @@ pub fn plot() -> Result<Status> {
 
         plot.add_trace(
             Scatter::new(THICKNESSES.to_vec(), cells_c)
-                .name("Beton")
+                .name(Label::Concrete.text())
                 .mode(Mode::Markers)
                 .marker(Marker::new().symbol(MarkerSymbol::Diamond).color(COLORS[0])),
         );
         plot.add_trace(
             Scatter::new(THICKNESSES.to_vec(), cells_s)
-                .name("Stahl")
+                .name(Label::Steel.text())
                 .mode(Mode::Markers)
                 .marker(Marker::new().symbol(MarkerSymbol::Circle).color(COLORS[1])),
         );
-        create_dir_all("plot/helper")
-            .with_context(|| "Failed to create directories at \"plot/helper/\".")?;
-        plot.write_image(
-            "plot/helper/cell_count.svg",
-            ImageFormat::SVG,
-            600,
-            300,
-            1.0,
-        );
+        create_dir_all(&dir)
+            .with_context(|| format!("Failed to create directories at {dir:?}."))?;
+        plot_format.write(&plot, &path, 600, 300);
     } else {
         bail!("Failed to find material in FDS simulation.");
     }
 
     Ok(Status::Succeeded {
         simulation: "helper",
-        path: "plot/helper/cell_count.svg".into(),
+        path,
     })
 }
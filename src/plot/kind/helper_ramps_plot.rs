@@ -1,12 +1,16 @@
-use std::{fs::create_dir_all, path::PathBuf};
+use std::fs::create_dir_all;
 
-use crate::plot::{kind::COLORS, Status};
+use crate::{
+    dry_run::get_dry_run,
+    output::plot_root,
+    plot::{kind::COLORS, labels::Label, PlotFormat, Status},
+};
 use anyhow::*;
 use plotly::{
     color::NamedColor,
     common::{Anchor, Line, Marker, MarkerSymbol, Mode, Title},
     layout::{Axis, Legend, Margin},
-    ImageFormat, Layout, Plot, Scatter,
+    Layout, Plot, Scatter,
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -67,24 +71,24 @@ fn concrete_k(values: impl Iterator<Item = f64>) -> impl Iterator<Item = f64> {
 }
 
 /// Create the plot for the concrete conductivity
-pub fn plot_concrete_k() -> Result<Status> {
-    plot_one(PlotType::Concrete, PlotMode::Conductivity)
+pub fn plot_concrete_k(plot_format: PlotFormat) -> Result<Status> {
+    plot_one(PlotType::Concrete, PlotMode::Conductivity, plot_format)
 }
 /// Create the plot for the concrete specific heat
-pub fn plot_concrete_c() -> Result<Status> {
-    plot_one(PlotType::Concrete, PlotMode::SpecificHeat)
+pub fn plot_concrete_c(plot_format: PlotFormat) -> Result<Status> {
+    plot_one(PlotType::Concrete, PlotMode::SpecificHeat, plot_format)
 }
 /// Create the plot for the steel conductivity
-pub fn plot_steel_k() -> Result<Status> {
-    plot_one(PlotType::Steel, PlotMode::Conductivity)
+pub fn plot_steel_k(plot_format: PlotFormat) -> Result<Status> {
+    plot_one(PlotType::Steel, PlotMode::Conductivity, plot_format)
 }
 /// Create the plot for the steel specific heat
-pub fn plot_steel_c() -> Result<Status> {
-    plot_one(PlotType::Steel, PlotMode::SpecificHeat)
+pub fn plot_steel_c(plot_format: PlotFormat) -> Result<Status> {
+    plot_one(PlotType::Steel, PlotMode::SpecificHeat, plot_format)
 }
 
 /// Create the plots for the different materials and
-fn plot_one(plot_type: PlotType, plot_mode: PlotMode) -> Result<Status> {
+fn plot_one(plot_type: PlotType, plot_mode: PlotMode, plot_format: PlotFormat) -> Result<Status> {
     let (path_type_name, color) = match plot_type {
         PlotType::Concrete => ("concrete", COLORS[0]),
         PlotType::Steel => ("steel", COLORS[1]),
@@ -130,20 +134,31 @@ fn plot_one(plot_type: PlotType, plot_mode: PlotMode) -> Result<Status> {
                 (plot, precise_x, precise_y, approximate_x, approximate_y)
             }
         };
-    let dir = PathBuf::from("plot/helper/ramp");
-    let path = dir.join(format!("{}_{}.svg", path_type_name, path_mode_name));
+    let dir = plot_root().join("helper/ramp");
+    let path = dir.join(format!(
+        "{}_{}.{}",
+        path_type_name,
+        path_mode_name,
+        plot_format.extension()
+    ));
+    if get_dry_run() {
+        return Ok(Status::DryRun {
+            simulation: "helper",
+            path,
+        });
+    }
 
     create_dir_all(&dir).with_context(|| format!("Failed to create directories at {dir:?}."))?;
 
     plot.add_trace(
         Scatter::new(precise_x, precise_y)
-            .name("Genau")
+            .name(Label::Exact.text())
             .mode(Mode::Lines)
             .line(Line::new().color(NamedColor::LightGray).width(4.0)),
     );
     plot.add_trace(
         Scatter::new(approximate_x, approximate_y)
-            .name("Approximiert")
+            .name(Label::Approximated.text())
             .mode(Mode::LinesMarkers)
             .line(
                 Line::new()
@@ -153,7 +168,7 @@ fn plot_one(plot_type: PlotType, plot_mode: PlotMode) -> Result<Status> {
             .marker(Marker::new().symbol(MarkerSymbol::X).size(8)),
     );
 
-    plot.write_image(&path, ImageFormat::SVG, 400, 300, 1.0);
+    plot_format.write(&plot, &path, 400, 300);
 
     Ok(Status::Succeeded {
         simulation: "helper",
@@ -164,7 +179,7 @@ fn plot_one(plot_type: PlotType, plot_mode: PlotMode) -> Result<Status> {
 /// Create the canvas of the plot
 fn plot_canvas(plot_mode: PlotMode, range_x: Vec<f64>, range_y: Vec<f64>) -> Plot {
     let legend = Legend::new()
-        .title(Title::new("Legende"))
+        .title(Title::new(Label::Legend.text()))
         .border_color("#000000")
         .border_width(1)
         .x_anchor(Anchor::Right)
@@ -176,15 +191,15 @@ fn plot_canvas(plot_mode: PlotMode, range_x: Vec<f64>, range_y: Vec<f64>) -> Plo
         .show_legend(true)
         .x_axis(
             Axis::new()
-                .title(Title::new("Temperatur [°C]"))
+                .title(Title::new(Label::Temperature.text()))
                 .show_line(true)
                 .range(range_x),
         )
         .y_axis(
             Axis::new()
                 .title(Title::new(match plot_mode {
-                    PlotMode::SpecificHeat => "Spezifische Wärme [J/(kg⋅K)]",
-                    PlotMode::Conductivity => "Wärmeleitfähigkeit [W/(m⋅K)]",
+                    PlotMode::SpecificHeat => Label::SpecificHeat.text(),
+                    PlotMode::Conductivity => Label::Conductivity.text(),
                 }))
                 .range(range_y),
         )
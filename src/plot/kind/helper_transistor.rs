@@ -1,20 +1,31 @@
-use std::{fs::create_dir_all, path::PathBuf};
+use std::fs::create_dir_all;
 
 use anyhow::*;
 use plotly::{
     box_plot::BoxPoints,
     common::{Anchor, Line, Marker, Title},
     layout::{Axis, AxisType, Legend, Margin},
-    BoxPlot, ImageFormat, Layout, Plot,
+    BoxPlot, Layout, Plot,
 };
 
-use crate::plot::Status;
+use crate::{
+    dry_run::get_dry_run,
+    output::plot_root,
+    plot::{labels::Label, PlotFormat, Status},
+};
 
 use super::COLORS;
 
 /// Create a plot for the transistor count for the different years.
-pub fn plot() -> Result<Status> {
-    let path = PathBuf::from("plot/helper/transistors.svg");
+pub fn plot(plot_format: PlotFormat) -> Result<Status> {
+    let dir = plot_root().join("helper");
+    let path = dir.join(format!("transistors.{}", plot_format.extension()));
+    if get_dry_run() {
+        return Ok(Status::DryRun {
+            simulation: "helper",
+            path,
+        });
+    }
 
     let amd_cpu = vec![
         (4300000u64, 1996u16),
@@ -179,7 +190,7 @@ pub fn plot() -> Result<Status> {
     ];
 
     let legend = Legend::new()
-        .title(Title::new("Legende"))
+        .title(Title::new(Label::Legend.text()))
         .border_color("#000000")
         .border_width(1)
         .x_anchor(Anchor::Left)
@@ -191,21 +202,20 @@ pub fn plot() -> Result<Status> {
         .show_legend(true)
         .x_axis(
             Axis::new()
-                .title(Title::new("Jahr"))
+                .title(Title::new(Label::Year.text()))
                 .show_line(true)
                 .type_(AxisType::Date),
         )
         .y_axis(
             Axis::new()
-                .title(Title::new("Transistoren"))
+                .title(Title::new(Label::Transistors.text()))
                 .type_(AxisType::Log),
         )
         .margin(Margin::new().top(10).left(60).right(20).bottom(60));
     let mut plot = Plot::new();
     plot.set_layout(layout);
 
-    create_dir_all("plot/helper")
-        .with_context(|| format!("Failed to create directories at {path:?}."))?;
+    create_dir_all(&dir).with_context(|| format!("Failed to create directories at {path:?}."))?;
 
     let (amd_cpu_t, amd_cpu_j): (Vec<u64>, Vec<u16>) = amd_cpu.into_iter().unzip();
     plot.add_trace(
@@ -248,7 +258,7 @@ pub fn plot() -> Result<Status> {
             .marker(Marker::new().color(COLORS[3])),
     );
 
-    plot.write_image(&path, ImageFormat::SVG, 600, 300, 1.0);
+    plot_format.write(&plot, &path, 600, 300);
 
     Ok(Status::Succeeded {
         simulation: "helper",
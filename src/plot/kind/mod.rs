@@ -3,7 +3,37 @@ pub mod benchmark_factor_plot;
 pub mod helper_cell_count;
 pub mod helper_ramps_plot;
 pub mod helper_transistor;
+pub mod scaling_line_plot;
 pub mod temperature_diff;
 
+use std::path::PathBuf;
+
+use crate::{benchmark::get_set_benchmark_elements, heat_transfer::simulations::benchmark_sizes};
+
 /// Red, Blue, Green, Orange, Purple, Yellow
 const COLORS: [&str; 6] = ["ff3f33", "0075da", "2acd3e", "ff8614", "b205ca", "ffdd00"];
+
+/// Returns the sizes that have a benchmark inside each of the passed directories, sorted ascending.
+fn common_benchmark_sizes(directories: &[PathBuf]) -> Vec<usize> {
+    let Some((first, others)) = directories.split_first() else {
+        return vec![];
+    };
+    let others = others.iter().map(benchmark_sizes).collect::<Vec<_>>();
+    benchmark_sizes(first)
+        .into_iter()
+        .filter(|size| others.iter().all(|sizes| sizes.contains(size)))
+        .collect()
+}
+
+/// Returns the wall element counts of a benchmark plot whose graph elements read the benchmarks inside the passed directories.
+/// These are the counts set with [`set_benchmark_elements`](crate::benchmark::set_benchmark_elements) or otherwise the sizes with a benchmark inside each directory, so the plots only depend on the benchmark files present.
+fn plot_elements(directories: &[PathBuf]) -> Vec<usize> {
+    get_set_benchmark_elements().unwrap_or_else(|| common_benchmark_sizes(directories))
+}
+
+/// Returns the path of the source file of a plot kind, if the program runs inside the repository.
+/// Outside of it, e.g. with a copied benchmark directory, the plots only depend on their benchmarks.
+fn plot_source(path: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(path);
+    path.exists().then_some(path)
+}
@@ -0,0 +1,210 @@
+//! Create a line plot that displays the median simulation time over the number of wall elements for different methods.
+
+use super::{plot_elements, plot_source, COLORS};
+use crate::{
+    dry_run::get_dry_run,
+    heat_transfer::simulations::{
+        get_benchmark_format, BenchmarkPathPart, BenchmarkReader, SimulationType1D,
+    },
+    modification::{is_modified, store_source_hashes},
+    output::{benchmark_root, plot_root},
+    plot::{labels::Label, PlotFormat, Status},
+};
+use anyhow::*;
+use plotly::{
+    common::{Line, Marker, Mode, Title},
+    layout::{Axis, AxisType, Legend, Margin},
+    Layout, Plot, Scatter,
+};
+use std::{
+    fmt::Display,
+    fs::create_dir_all,
+    path::{Path, PathBuf},
+};
+
+const SIMULATION_NAME: &str = "scaling_line_plot.rs";
+
+/// Data for a line inside a plot.
+struct ScalingLinePlotSource {
+    /// direction fo the simulation.
+    simulation_directory: PathBuf,
+    /// suffix for `simulation_directory` for the full path
+    benchmark_path_part: BenchmarkPathPart,
+    /// Legend name.
+    legend: String,
+    /// Line color.
+    color: String,
+}
+
+/// All data to create a line plot that displays the median simulation time over the number of wall elements for different methods.
+pub struct ScalingLinePlotSources {
+    /// The directory the plot should be saved.
+    plot_directory: PathBuf,
+    /// Data for all lines inside a plot.
+    benchmark_sources: Vec<ScalingLinePlotSource>,
+    /// Suffix of the plot file.
+    suffix: String,
+}
+impl ScalingLinePlotSources {
+    /// Create a [`ScalingLinePlotSources`] for a plot that compares the scaling of the different simulation methods.
+    pub fn compare_mode<P: AsRef<Path>, S: Display>(simulation_directory: P, label: S) -> Self {
+        let simulation_directory = simulation_directory.as_ref();
+        let plot_directory = plot_root().join(simulation_directory);
+        let benchmark_sources = [
+            SimulationType1D::Cpu,
+            SimulationType1D::GpuM1,
+            SimulationType1D::GpuM2,
+            SimulationType1D::GpuM3,
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| ScalingLinePlotSource {
+            simulation_directory: simulation_directory.to_path_buf(),
+            benchmark_path_part: BenchmarkPathPart::new(None, label.to_string(), s.into()),
+            color: COLORS[i].to_string(),
+            legend: s.to_string(),
+        })
+        .collect::<Vec<_>>();
+        let suffix = format!("{label}_scaling_mode");
+        Self {
+            plot_directory,
+            benchmark_sources,
+            suffix,
+        }
+    }
+}
+
+/// Create a line plot that displays the median simulation time over the number of wall elements for different methods.
+pub fn plot(benchmark_source: ScalingLinePlotSources, plot_format: PlotFormat) -> Result<Status> {
+    let ScalingLinePlotSources {
+        plot_directory,
+        benchmark_sources,
+        suffix,
+    } = benchmark_source;
+
+    let save_path =
+        plot_directory.join(format!("benchmark_{}.{}", suffix, plot_format.extension()));
+    let benchmark_directories = benchmark_sources
+        .iter()
+        .map(|b| {
+            Ok(benchmark_root()
+                .join(&b.simulation_directory)
+                .join(b.benchmark_path_part.path_str()?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let elements = plot_elements(&benchmark_directories);
+    if elements.is_empty() {
+        return Ok(Status::Ignored {
+            simulation: SIMULATION_NAME,
+            reason: format!(
+                "\n     no size has a benchmark inside each of {:?}",
+                benchmark_directories
+            ),
+            path: save_path,
+        });
+    }
+    let paths = elements
+        .iter()
+        .flat_map(|e| {
+            benchmark_directories
+                .iter()
+                .map(|b| {
+                    let path = b.join(get_benchmark_format().file_name(*e));
+                    if path.exists() {
+                        std::result::Result::Ok(path)
+                    } else {
+                        Err(Status::Ignored {
+                            simulation: SIMULATION_NAME,
+                            reason: format!("\n     {:?} does not exist", path),
+                            path: save_path.clone(),
+                        })
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .chain(plot_source("src/plot/kind/scaling_line_plot.rs").map(std::result::Result::Ok))
+        .collect::<Result<Vec<PathBuf>, Status>>();
+    let paths = match paths {
+        std::result::Result::Ok(ok) => ok,
+        Err(err) => return Ok(err),
+    };
+    if get_dry_run() {
+        return Ok(Status::DryRun {
+            simulation: SIMULATION_NAME,
+            path: save_path,
+        });
+    }
+    create_dir_all(&plot_directory)
+        .with_context(|| format!("Failed to create directories at {plot_directory:?}."))?;
+    if !is_modified(&paths, &[&save_path])? {
+        return Ok(Status::Passed {
+            simulation: SIMULATION_NAME,
+            path: save_path,
+        });
+    };
+    println!(
+        "  Start \"{SIMULATION_NAME}\" plot for fds simulation at {:?}.",
+        &save_path
+    );
+
+    let layout = Layout::new()
+        .legend(
+            Legend::new()
+                .title(Title::new(Label::Legend.text()))
+                .border_color("#000000")
+                .border_width(1)
+                .x(0.01)
+                .y(0.99),
+        )
+        .show_legend(true)
+        .y_axis(
+            Axis::new()
+                .show_line(true)
+                .title(Title::new(Label::MedianTime.text()))
+                .type_(AxisType::Log),
+        )
+        .x_axis(
+            Axis::new()
+                .show_line(true)
+                .title(Title::new(Label::WallElements.text()))
+                .show_grid(true)
+                .type_(AxisType::Log),
+        )
+        .margin(Margin::new().top(10).left(60).right(60).bottom(60));
+
+    let mut plot = Plot::new();
+    plot.set_layout(layout);
+
+    for (benchmark_source, benchmark_directory) in
+        benchmark_sources.into_iter().zip(benchmark_directories)
+    {
+        let medians = elements
+            .iter()
+            .map(|&e| {
+                let benchmark_path = benchmark_directory.join(get_benchmark_format().file_name(e));
+                Ok(BenchmarkReader::try_new(benchmark_path)?.stats()?.median)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        plot.add_trace(
+            Scatter::new(elements.clone(), medians)
+                .mode(Mode::LinesMarkers)
+                .line(Line::new().color(benchmark_source.color.clone()))
+                .marker(Marker::new().color(benchmark_source.color))
+                .name(&benchmark_source.legend),
+        );
+    }
+
+    plot_format.write(&plot, &save_path, 600, 350);
+    if save_path.exists() {
+        store_source_hashes(&paths, &[&save_path])?;
+        Ok(Status::Succeeded {
+            simulation: SIMULATION_NAME,
+            path: save_path,
+        })
+    } else {
+        Ok(Status::Failed {
+            simulation: SIMULATION_NAME,
+            path: save_path,
+        })
+    }
+}
@@ -1,35 +1,51 @@
 use super::COLORS;
 use crate::{
-    heat_transfer::simulations::{
-        temperature::{one_dimensional_by_type, Diff, Temperatures},
-        SimulationKind, SimulationType1D,
+    dry_run::get_dry_run,
+    heat_transfer::{
+        one_dimensional::{get_cpu_precision, Precision},
+        simulations::{
+            temperature::{
+                compared_elements, one_dimensional_elements_by_type, Diff, Temperatures,
+            },
+            SimulationKind, SimulationType1D,
+        },
     },
-    modification::was_modified,
-    plot::Status,
+    modification::{is_modified, store_source_hashes},
+    output::plot_root,
+    plot::{labels::Label, PlotFormat, Status},
 };
 use anyhow::*;
 use plotly::{
     color::{NamedColor, Rgba},
     common::{AxisSide, DashType, Font, Line, Mode, Title},
     layout::{Axis, Legend, Margin},
-    ImageFormat, Layout, Plot, Scatter,
+    Layout, Plot, Scatter,
 };
 use std::path::{Path, PathBuf};
 
 /// Start and plot the temperature of a 1D simulation with a comparison between FDS and this program for the different simulation methods.
+/// A simulation with multiple distinct wall elements is plotted once per wall element, see [`compared_elements`].
+/// If `combined` is set, the front and back side are plotted as two rows of a single figure instead of two separate files.
 pub fn plot_one_dimensional_by_type<P: AsRef<Path>>(
     path: P,
     simulation_kind: SimulationKind,
     simulation_type: SimulationType1D,
+    plot_format: PlotFormat,
+    html: bool,
+    combined: bool,
 ) -> Result<Status> {
     match simulation_type {
         SimulationType1D::Cpu => plot_one_dimensional(
             path,
             simulation_kind,
             simulation_type,
+            plot_format,
+            html,
+            combined,
             &[
                 "src/plot/kind/temperature_diff.rs",
                 "src/heat_transfer/one_dimensional/cpu.rs",
+                "src/fds/ramp.rs",
                 "src/heat_transfer/simulations/temperature.rs",
             ],
         ),
@@ -37,6 +53,9 @@ pub fn plot_one_dimensional_by_type<P: AsRef<Path>>(
             path,
             simulation_kind,
             simulation_type,
+            plot_format,
+            html,
+            combined,
             &[
                 "src/plot/kind/temperature_diff.rs",
                 "src/heat_transfer/one_dimensional/gpu_m1.rs",
@@ -48,6 +67,9 @@ pub fn plot_one_dimensional_by_type<P: AsRef<Path>>(
             path,
             simulation_kind,
             simulation_type,
+            plot_format,
+            html,
+            combined,
             &[
                 "src/plot/kind/temperature_diff.rs",
                 "src/heat_transfer/one_dimensional/gpu_m2.rs",
@@ -59,6 +81,9 @@ pub fn plot_one_dimensional_by_type<P: AsRef<Path>>(
             path,
             simulation_kind,
             simulation_type,
+            plot_format,
+            html,
+            combined,
             &[
                 "src/plot/kind/temperature_diff.rs",
                 "src/heat_transfer/one_dimensional/gpu_m3.rs",
@@ -66,6 +91,37 @@ pub fn plot_one_dimensional_by_type<P: AsRef<Path>>(
                 "src/heat_transfer/simulations/temperature.rs",
             ],
         ),
+        SimulationType1D::GpuM4 => plot_one_dimensional(
+            path,
+            simulation_kind,
+            simulation_type,
+            plot_format,
+            html,
+            combined,
+            &[
+                "src/plot/kind/temperature_diff.rs",
+                "src/heat_transfer/one_dimensional/gpu_m4.rs",
+                "src/heat_transfer/one_dimensional/gpu_m4.wgsl",
+                "src/heat_transfer/simulations/temperature.rs",
+            ],
+        ),
+    }
+}
+
+/// The image files the temperatures of the front and back side are plotted to.
+enum PlotPaths {
+    /// A separate file for the front and the back side.
+    Separate { front: PathBuf, back: PathBuf },
+    /// A single file with a row for the front and a row for the back side.
+    Combined(PathBuf),
+}
+impl PlotPaths {
+    /// Returns the paths of all image files.
+    fn images(&self) -> Vec<&Path> {
+        match self {
+            PlotPaths::Separate { front, back } => vec![front, back],
+            PlotPaths::Combined(path) => vec![path],
+        }
     }
 }
 
@@ -73,9 +129,10 @@ pub fn plot_one_dimensional_by_type<P: AsRef<Path>>(
 fn plot(
     temperatures: Temperatures,
     plot_path: PathBuf,
-    plot_path_f: PathBuf,
-    plot_path_b: PathBuf,
+    plot_paths: &PlotPaths,
     simulation_type_str: &'static str,
+    plot_format: PlotFormat,
+    html: bool,
 ) -> Result<Status> {
     let Diff {
         front: diff_front,
@@ -91,10 +148,55 @@ fn plot(
 
     std::fs::create_dir_all(&plot_path)
         .with_context(|| format!("Failed to create directories {:?}.", plot_path))?;
-    plot_temperature_time_diff(&plot_path_f, time.clone(), fds_front, sim_front, diff_front);
-    plot_temperature_time_diff(&plot_path_b, time, fds_back, sim_back, diff_back);
+    match plot_paths {
+        PlotPaths::Separate { front, back } => {
+            plot_temperature_time_diff(
+                front,
+                plot_format,
+                html,
+                time.clone(),
+                fds_front,
+                sim_front,
+                diff_front,
+            );
+            plot_temperature_time_diff(
+                back,
+                plot_format,
+                html,
+                time,
+                fds_back,
+                sim_back,
+                diff_back,
+            );
+        }
+        PlotPaths::Combined(path) => {
+            let mut plot = plot_canvas_combined(diff_range(&diff_front), diff_range(&diff_back));
+            add_temperature_time_diff_traces(
+                &mut plot,
+                time.clone(),
+                fds_front,
+                sim_front,
+                diff_front,
+                ("y", "y2"),
+                true,
+            );
+            add_temperature_time_diff_traces(
+                &mut plot,
+                time,
+                fds_back,
+                sim_back,
+                diff_back,
+                ("y3", "y4"),
+                false,
+            );
+            plot_format.write(&plot, path, 600, 600);
+            if html {
+                plot.write_html(path.with_extension("html"));
+            }
+        }
+    }
 
-    if plot_path_f.exists() && plot_path_b.exists() {
+    if output_paths(plot_paths, html).iter().all(|p| p.exists()) {
         Ok(Status::Succeeded {
             simulation: simulation_type_str,
             path: plot_path,
@@ -108,26 +210,63 @@ fn plot(
 }
 
 /// Start and plot the temperature of a 1D simulation with a comparison between FDS and this program.
+/// If multiple wall elements are compared, the index of the wall element is added to the file names, e.g. `cpu_1_f.png`.
+/// A CPU simulation with [`Precision::F64`] is plotted to its own files, e.g. `cpu_f64_f.png`, so it is not mistaken for the cached single precision plot.
 fn plot_one_dimensional<P: AsRef<Path>>(
     path: P,
     simulation_kind: SimulationKind,
     simulation_type: SimulationType1D,
+    plot_format: PlotFormat,
+    html: bool,
+    combined: bool,
     modification_paths: &'static [&'static str],
 ) -> Result<Status> {
     let simulation_type_str = simulation_type.path_str();
     let path = path.as_ref();
-    let plot_path = PathBuf::from("plot").join(path);
-    let plot_path_f = plot_path.join(format!("{simulation_type_str}_f.svg"));
-    let plot_path_b = plot_path.join(format!("{simulation_type_str}_b.svg"));
-
-    if !was_modified(
-        &modification_paths
-            .iter()
-            .map(PathBuf::from)
-            .chain([path.join("heat_transfer.fds")])
-            .collect::<Vec<_>>(),
-        &[&plot_path_f, &plot_path_b],
-    )? {
+    let plot_path = plot_root().join(path);
+    if get_dry_run() {
+        return Ok(Status::DryRun {
+            simulation: simulation_type_str,
+            path: plot_path,
+        });
+    }
+    // The simulation is loaded after the dry run check, since it may not be created yet.
+    let elements = compared_elements(path)
+        .with_context(|| format!("Failed to load fds simulation at {:?}.", path))?;
+    let file_prefix = match (simulation_type, get_cpu_precision()) {
+        (SimulationType1D::Cpu, Precision::F64) => format!("{simulation_type_str}_f64"),
+        _ => simulation_type_str.to_string(),
+    };
+    let element_plot_paths = elements
+        .iter()
+        .map(|element| {
+            let name = match (elements.len(), element) {
+                (2.., Some(i)) => format!("{file_prefix}_{}", i + 1),
+                _ => file_prefix.clone(),
+            };
+            if combined {
+                PlotPaths::Combined(
+                    plot_path.join(format!("{name}_fb.{}", plot_format.extension())),
+                )
+            } else {
+                PlotPaths::Separate {
+                    front: plot_path.join(format!("{name}_f.{}", plot_format.extension())),
+                    back: plot_path.join(format!("{name}_b.{}", plot_format.extension())),
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let sources = modification_paths
+        .iter()
+        .map(PathBuf::from)
+        .chain([path.join("heat_transfer.fds")])
+        .collect::<Vec<_>>();
+    let results = element_plot_paths
+        .iter()
+        .flat_map(|plot_paths| output_paths(plot_paths, html))
+        .collect::<Vec<_>>();
+    if !is_modified(&sources, &results)? {
         return Ok(Status::Passed {
             simulation: simulation_type_str,
             path: plot_path,
@@ -138,79 +277,190 @@ fn plot_one_dimensional<P: AsRef<Path>>(
         simulation_type_str, path
     );
 
-    let temperatures = one_dimensional_by_type(path, simulation_kind, simulation_type)
-        .with_context(|| format!("Failed fds simulation at {:?}.", path))?;
-    plot(
-        temperatures,
-        plot_path,
-        plot_path_f,
-        plot_path_b,
-        simulation_type_str,
-    )
+    let element_temperatures =
+        one_dimensional_elements_by_type(path, simulation_kind, simulation_type)
+            .with_context(|| format!("Failed fds simulation at {:?}.", path))?;
+    for ((_, temperatures), plot_paths) in element_temperatures.into_iter().zip(&element_plot_paths)
+    {
+        let status = plot(
+            temperatures,
+            plot_path.clone(),
+            plot_paths,
+            simulation_type_str,
+            plot_format,
+            html,
+        )?;
+        if !matches!(status, Status::Succeeded { .. }) {
+            return Ok(status);
+        }
+    }
+    store_source_hashes(&sources, &results)?;
+    Ok(Status::Succeeded {
+        simulation: simulation_type_str,
+        path: plot_path,
+    })
+}
+
+/// Returns the paths of all files that are created for the plots of the front and back side.
+/// If `html` is set, the interactive HTML files next to the images are included.
+fn output_paths(plot_paths: &PlotPaths, html: bool) -> Vec<PathBuf> {
+    let images = plot_paths.images();
+    let mut paths = images.iter().map(|p| p.to_path_buf()).collect::<Vec<_>>();
+    if html {
+        paths.extend(images.iter().map(|p| p.with_extension("html")));
+    }
+    paths
 }
 
 /// Plot the temperature of a 1D simulation with a comparison between FDS and this program.
+/// If `html` is set, an interactive HTML file is written next to the image.
 fn plot_temperature_time_diff(
     path: &Path,
+    plot_format: PlotFormat,
+    html: bool,
     time: Vec<f32>,
     fds: Vec<f32>,
     sim: Vec<f32>,
     diff: Vec<f32>,
+) {
+    let mut plot = plot_canvas(diff_range(&diff));
+    add_temperature_time_diff_traces(&mut plot, time, fds, sim, diff, ("y", "y2"), true);
+    plot_format.write(&plot, path, 600, 350);
+    if html {
+        plot.write_html(path.with_extension("html"));
+    }
+}
+
+/// Adds the traces of the FDS temperature, the temperature of this program and their difference to the plot.
+/// The temperatures are drawn on the first and the difference on the second of the passed y-axes.
+fn add_temperature_time_diff_traces(
+    plot: &mut Plot,
+    time: Vec<f32>,
+    fds: Vec<f32>,
+    sim: Vec<f32>,
+    diff: Vec<f32>,
+    (y_axis, y_axis_diff): (&str, &str),
+    show_legend: bool,
 ) {
     let diff_plot = Scatter::new(time.clone(), diff)
         .mode(Mode::Lines)
         .line(Line::new().dash(DashType::Dot).color(COLORS[2]))
-        .name("Differenz")
-        .y_axis("y2");
+        .name(Label::Difference.text())
+        .y_axis(y_axis_diff)
+        .show_legend(show_legend);
     let fds_plot = Scatter::new(time.clone(), fds)
         .mode(Mode::Lines)
         .line(Line::new().color(COLORS[1]))
-        .name("FDS");
+        .name(Label::Fds.text())
+        .y_axis(y_axis)
+        .show_legend(show_legend);
     let sim_plot = Scatter::new(time, sim)
         .mode(Mode::Lines)
         .line(Line::new().dash(DashType::Dash).color(COLORS[0]))
-        .name("Programm");
+        .name(Label::Program.text())
+        .y_axis(y_axis)
+        .show_legend(show_legend);
 
-    let mut plot = plot_canvas();
     plot.add_trace(diff_plot);
     plot.add_trace(fds_plot);
     plot.add_trace(sim_plot);
-    plot.write_image(path, ImageFormat::SVG, 600, 350, 1.0);
 }
 
-/// create the canvas of the plot.
-fn plot_canvas() -> Plot {
+/// create the canvas of the plot with the passed range of the difference axis.
+fn plot_canvas(diff_range: [f32; 2]) -> Plot {
     let legend = Legend::new()
-        .title(Title::new("Legende"))
+        .title(Title::new(Label::Legend.text()))
         .border_color("#000000")
         .border_width(1)
         .x(0.77)
         .y(0.02);
 
-    let light_red = Rgba::new(42, 205, 62, 0.5);
-
     let layout = Layout::new()
         .legend(legend)
         .show_legend(true)
-        .y_axis(Axis::new().title(Title::new("Temperatur [°C]")))
-        .x_axis(Axis::new().title(Title::new("Zeit [s]")).show_line(true))
+        .y_axis(Axis::new().title(Title::new(Label::Temperature.text())))
+        .x_axis(
+            Axis::new()
+                .title(Title::new(Label::Time.text()))
+                .show_line(true),
+        )
         .margin(Margin::new().top(10).left(60).right(60).bottom(60))
-        .y_axis2(
+        .y_axis2(diff_axis("y", diff_range));
+
+    let mut plot = Plot::new();
+    plot.set_layout(layout);
+    plot
+}
+
+/// create the canvas of the plot with a row for the front side on top and a row for the back side below, both sharing the time axis.
+/// Each row has its own range of the difference axis.
+fn plot_canvas_combined(diff_range_front: [f32; 2], diff_range_back: [f32; 2]) -> Plot {
+    let legend = Legend::new()
+        .title(Title::new(Label::Legend.text()))
+        .border_color("#000000")
+        .border_width(1)
+        .x(0.77)
+        .y(0.01);
+
+    let layout = Layout::new()
+        .legend(legend)
+        .show_legend(true)
+        .y_axis(
+            Axis::new()
+                .title(Title::new(Label::TemperatureFront.text()))
+                .domain(&[0.55, 1.0]),
+        )
+        .y_axis3(
+            Axis::new()
+                .title(Title::new(Label::TemperatureBack.text()))
+                .domain(&[0.0, 0.45])
+                .anchor("x"),
+        )
+        .x_axis(
             Axis::new()
-                .title(Title::new("Differenz [K]").font(Font::new().color(NamedColor::Black)))
-                .overlaying("y")
-                .side(AxisSide::Right)
-                .zero_line(true)
-                .zero_line_color(light_red)
-                .auto_range(false)
-                .range(vec![-2.0, 2.0])
+                .title(Title::new(Label::Time.text()))
                 .show_line(true)
-                .show_grid(false)
-                .tick_font(Font::new().color(NamedColor::Black))
-                .color(COLORS[2]),
-        );
+                .anchor("y3"),
+        )
+        .margin(Margin::new().top(10).left(60).right(60).bottom(60))
+        .y_axis2(diff_axis("y", diff_range_front))
+        .y_axis4(diff_axis("y3", diff_range_back));
 
     let mut plot = Plot::new();
     plot.set_layout(layout);
     plot
 }
+
+/// The range of the difference axis, as long as the difference stays inside of it.
+const MIN_DIFF_RANGE: [f32; 2] = [-2.0, 2.0];
+
+/// Returns the range of the difference axis that contains all finite values of the difference with a margin of 10 %.
+/// The range is never smaller than [`MIN_DIFF_RANGE`], so small differences keep the same scale in all plots.
+fn diff_range(diff: &[f32]) -> [f32; 2] {
+    let (min, max) = diff
+        .iter()
+        .filter(|d| d.is_finite())
+        .fold((0.0f32, 0.0f32), |(min, max), &d| (min.min(d), max.max(d)));
+    let margin = (max - min) * 0.1;
+    [
+        (min - margin).min(MIN_DIFF_RANGE[0]),
+        (max + margin).max(MIN_DIFF_RANGE[1]),
+    ]
+}
+
+/// Create the secondary y-axis of the temperature difference with the passed range that overlays the passed y-axis.
+fn diff_axis(overlaying: &str, range: [f32; 2]) -> Axis {
+    let light_red = Rgba::new(42, 205, 62, 0.5);
+    Axis::new()
+        .title(Title::new(Label::DifferenceAxis.text()).font(Font::new().color(NamedColor::Black)))
+        .overlaying(overlaying)
+        .side(AxisSide::Right)
+        .zero_line(true)
+        .zero_line_color(light_red)
+        .auto_range(false)
+        .range(range.to_vec())
+        .show_line(true)
+        .show_grid(false)
+        .tick_font(Font::new().color(NamedColor::Black))
+        .color(COLORS[2])
+}
@@ -0,0 +1,192 @@
+//! The texts of the axis titles and legends of the plots in all supported languages.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use clap::ValueEnum;
+
+/// Whether the plots are labeled in English instead of German. This value is replaced at the start of the program.
+static LANGUAGE_EN: AtomicBool = AtomicBool::new(false);
+
+/// All languages the plots can be labeled in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, ValueEnum)]
+pub enum Language {
+    #[default]
+    De,
+    En,
+}
+
+/// Set the language the plots are labeled in.
+pub fn set_language(language: Language) {
+    LANGUAGE_EN.store(language == Language::En, Ordering::Relaxed);
+    println!("Set plot language to {language:?}")
+}
+
+/// Get the language the plots are labeled in.
+#[inline]
+pub fn get_language() -> Language {
+    if LANGUAGE_EN.load(Ordering::Relaxed) {
+        Language::En
+    } else {
+        Language::De
+    }
+}
+
+/// All texts that are used as axis titles, legend titles or legend entries of the plots.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Label {
+    Legend,
+    Time,
+    MedianTime,
+    WallElements,
+    Temperature,
+    TemperatureFront,
+    TemperatureBack,
+    Difference,
+    DifferenceAxis,
+    Fds,
+    FdsMaxCores,
+    Program,
+    Ratio,
+    RatioCpuGpuM3,
+    RatioWithoutWith,
+    SpecificHeat,
+    Conductivity,
+    Exact,
+    Approximated,
+    CellCount,
+    WallThickness,
+    Concrete,
+    Steel,
+    Year,
+    Transistors,
+}
+impl Label {
+    /// Returns the text of this [`Label`] in the language set with [`set_language`].
+    pub fn text(self) -> &'static str {
+        match get_language() {
+            Language::De => self.german(),
+            Language::En => self.english(),
+        }
+    }
+
+    /// Returns the German text of this [`Label`].
+    fn german(self) -> &'static str {
+        match self {
+            Label::Legend => "Legende",
+            Label::Time => "Zeit [s]",
+            Label::MedianTime => "Median Zeit [s]",
+            Label::WallElements => "Wandelemente",
+            Label::Temperature => "Temperatur [°C]",
+            Label::TemperatureFront => "Temperatur vorne [°C]",
+            Label::TemperatureBack => "Temperatur hinten [°C]",
+            Label::Difference => "Differenz",
+            Label::DifferenceAxis => "Differenz [K]",
+            Label::Fds => "FDS",
+            Label::FdsMaxCores => "FDS max Kerne",
+            Label::Program => "Programm",
+            Label::Ratio => "Verhältnis",
+            Label::RatioCpuGpuM3 => "Verhältnis CPU / GPU M3",
+            Label::RatioWithoutWith => "Verhältnis ohne/mit",
+            Label::SpecificHeat => "Spezifische Wärme [J/(kg⋅K)]",
+            Label::Conductivity => "Wärmeleitfähigkeit [W/(m⋅K)]",
+            Label::Exact => "Genau",
+            Label::Approximated => "Approximiert",
+            Label::CellCount => "Anzahl Zellen",
+            Label::WallThickness => "Wanddicke [m]",
+            Label::Concrete => "Beton",
+            Label::Steel => "Stahl",
+            Label::Year => "Jahr",
+            Label::Transistors => "Transistoren",
+        }
+    }
+
+    /// Returns the English text of this [`Label`].
+    fn english(self) -> &'static str {
+        match self {
+            Label::Legend => "Legend",
+            Label::Time => "Time [s]",
+            Label::MedianTime => "Median time [s]",
+            Label::WallElements => "Wall elements",
+            Label::Temperature => "Temperature [°C]",
+            Label::TemperatureFront => "Front temperature [°C]",
+            Label::TemperatureBack => "Back temperature [°C]",
+            Label::Difference => "Difference",
+            Label::DifferenceAxis => "Difference [K]",
+            Label::Fds => "FDS",
+            Label::FdsMaxCores => "FDS max cores",
+            Label::Program => "Program",
+            Label::Ratio => "Ratio",
+            Label::RatioCpuGpuM3 => "Ratio CPU / GPU M3",
+            Label::RatioWithoutWith => "Ratio without/with",
+            Label::SpecificHeat => "Specific heat [J/(kg⋅K)]",
+            Label::Conductivity => "Thermal conductivity [W/(m⋅K)]",
+            Label::Exact => "Exact",
+            Label::Approximated => "Approximated",
+            Label::CellCount => "Number of cells",
+            Label::WallThickness => "Wall thickness [m]",
+            Label::Concrete => "Concrete",
+            Label::Steel => "Steel",
+            Label::Year => "Year",
+            Label::Transistors => "Transistors",
+        }
+    }
+}
+
+/// Returns the legend entry of a wall with the passed thickness in m and number of cells, e.g. `0,05 m (6 Zellen)`.
+/// German uses a decimal comma.
+pub fn thickness_legend(thickness: f32, cells: u32) -> String {
+    match get_language() {
+        Language::De => format!("{thickness:.2} m ({cells} Zellen)").replace('.', ","),
+        Language::En => format!("{thickness:.2} m ({cells} cells)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABELS: [Label; 25] = [
+        Label::Legend,
+        Label::Time,
+        Label::MedianTime,
+        Label::WallElements,
+        Label::Temperature,
+        Label::TemperatureFront,
+        Label::TemperatureBack,
+        Label::Difference,
+        Label::DifferenceAxis,
+        Label::Fds,
+        Label::FdsMaxCores,
+        Label::Program,
+        Label::Ratio,
+        Label::RatioCpuGpuM3,
+        Label::RatioWithoutWith,
+        Label::SpecificHeat,
+        Label::Conductivity,
+        Label::Exact,
+        Label::Approximated,
+        Label::CellCount,
+        Label::WallThickness,
+        Label::Concrete,
+        Label::Steel,
+        Label::Year,
+        Label::Transistors,
+    ];
+
+    /// Sequences that appear if UTF-8 text is decoded with a wrong encoding, e.g. `°` as Latin-1 or `ä` as EUC-KR.
+    const MOJIBAKE: [&str; 5] = ["\u{FFFD}", "Â", "Ã", "챈", "채"];
+
+    #[test]
+    fn labels_contain_no_mojibake() {
+        for label in LABELS {
+            for text in [label.german(), label.english()] {
+                assert!(
+                    MOJIBAKE.iter().all(|m| !text.contains(m)),
+                    "{label:?}: {text}"
+                );
+            }
+        }
+        assert_eq!(Label::Ratio.german(), "Verhältnis");
+        assert_eq!(Label::Temperature.english(), "Temperature [°C]");
+    }
+}
@@ -1,20 +1,34 @@
 mod kind;
+pub mod labels;
 
-use std::{path::PathBuf, thread};
+use std::{
+    collections::BTreeSet,
+    panic,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    thread,
+};
 
 use anyhow::*;
 use clap::ValueEnum;
+use plotly::{ImageFormat, Plot};
 use rayon::prelude::*;
 
 use self::kind::{benchmark_box_plot, helper_cell_count, helper_ramps_plot, helper_transistor};
 use crate::{
-    benchmark::{BenchmarkName, BENCHMARK_ELEMENTS},
-    heat_transfer::simulations::{SimulationKind, SimulationMethod, SimulationType1D},
+    benchmark::BenchmarkName,
+    dry_run::get_dry_run,
+    heat_transfer::simulations::{
+        benchmark_sizes, SimulationKind, SimulationMethod, SimulationType1D,
+    },
+    output::benchmark_root,
     plot::kind::{
         benchmark_box_plot::BenchmarkBoxPlotSources,
         benchmark_factor_plot::{self, BenchmarkFactorPlotSources},
+        scaling_line_plot::{self, ScalingLinePlotSources},
         temperature_diff,
     },
+    summary::{record_plot, PlotRecord, RecordStatus},
 };
 
 /// All possible plot types.
@@ -31,6 +45,84 @@ impl PlotType {
     }
 }
 
+/// All supported file formats of the plots.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, ValueEnum)]
+pub enum PlotFormat {
+    #[default]
+    Svg,
+    Png,
+    Pdf,
+    /// An interactive HTML file, which does not need the image backend kaleido.
+    Html,
+}
+impl PlotFormat {
+    /// Returns the file extension of this [`PlotFormat`].
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlotFormat::Svg => "svg",
+            PlotFormat::Png => "png",
+            PlotFormat::Pdf => "pdf",
+            PlotFormat::Html => "html",
+        }
+    }
+
+    /// Returns the [`ImageFormat`] of this [`PlotFormat`] or [`None`] for [`PlotFormat::Html`].
+    fn image_format(&self) -> Option<ImageFormat> {
+        match self {
+            PlotFormat::Svg => Some(ImageFormat::SVG),
+            PlotFormat::Png => Some(ImageFormat::PNG),
+            PlotFormat::Pdf => Some(ImageFormat::PDF),
+            PlotFormat::Html => None,
+        }
+    }
+
+    /// Writes the plot with the passed size in pixels to the passed path in this [`PlotFormat`].
+    /// The size is ignored for [`PlotFormat::Html`], since the HTML plot fills the browser window.
+    pub fn write<P: AsRef<Path>>(&self, plot: &Plot, path: P, width: usize, height: usize) {
+        match self.image_format() {
+            Some(image_format) => plot.write_image(path, image_format, width, height, 1.0),
+            None => plot.write_html(path),
+        }
+    }
+}
+
+/// Whether the image backend kaleido could write the probe image. It is determined once by [`image_backend_available`].
+static IMAGE_BACKEND_AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+/// Checks once whether the image backend kaleido can write images by writing a small probe image to the temporary directory.
+/// plotly panics if the kaleido executable is missing, so the panic is caught and its message suppressed.
+fn image_backend_available() -> bool {
+    *IMAGE_BACKEND_AVAILABLE.get_or_init(|| {
+        let path = std::env::temp_dir().join(format!(
+            "heat_transfer_image_backend_probe_{}.svg",
+            std::process::id()
+        ));
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(|_| {}));
+        let written = panic::catch_unwind(|| {
+            Plot::new().write_image(&path, ImageFormat::SVG, 10, 10, 1.0);
+        })
+        .is_ok();
+        panic::set_hook(hook);
+        let available = written && path.exists();
+        _ = std::fs::remove_file(&path);
+        available
+    })
+}
+
+/// Returns the passed [`PlotFormat`] or [`PlotFormat::Html`], if the image backend kaleido is not available.
+/// In a dry run the image backend is not checked.
+fn available_plot_format(plot_format: PlotFormat) -> PlotFormat {
+    if plot_format == PlotFormat::Html || get_dry_run() || image_backend_available() {
+        return plot_format;
+    }
+    println!(
+        "Warning: The image backend kaleido is not available, the plots are written as HTML files instead of {:?}. Install kaleido for plotly to create images.",
+        plot_format
+    );
+    PlotFormat::Html
+}
+
 /// All possible return stati a simulation can return.
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Status {
@@ -51,6 +143,39 @@ pub enum Status {
         simulation: &'static str,
         path: PathBuf,
     },
+    DryRun {
+        simulation: &'static str,
+        path: PathBuf,
+    },
+}
+impl Status {
+    /// Returns the [`PlotRecord`] of this [`Status`] for the summary of the run.
+    fn record(&self) -> PlotRecord {
+        let (simulation, path, status, reason) = match self {
+            Status::Passed { simulation, path } => (simulation, path, RecordStatus::Passed, None),
+            Status::Ignored {
+                simulation,
+                reason,
+                path,
+            } => (
+                simulation,
+                path,
+                RecordStatus::Ignored,
+                Some(reason.clone()),
+            ),
+            Status::Succeeded { simulation, path } => {
+                (simulation, path, RecordStatus::Succeeded, None)
+            }
+            Status::Failed { simulation, path } => (simulation, path, RecordStatus::Failed, None),
+            Status::DryRun { simulation, path } => (simulation, path, RecordStatus::DryRun, None),
+        };
+        PlotRecord {
+            plot: simulation.to_string(),
+            path: path.clone(),
+            status,
+            reason,
+        }
+    }
 }
 
 /// Print the state of all simulations and return `true`, if an error occurred.
@@ -75,6 +200,7 @@ pub fn print_plot_state(
 
     let mut any_failed = false;
     for r in s {
+        record_plot(r.record());
         match r {
             Status::Ignored {
                 simulation,
@@ -103,30 +229,69 @@ pub fn print_plot_state(
                     path
                 );
             }
+            Status::DryRun { simulation, path } => {
+                println!(
+                    "  Would create \"{simulation}\" plot for fds simulation at {:?}.",
+                    path
+                );
+            }
         }
     }
 
     any_failed
 }
 
-/// Determine all names of all computers on which the benchmarks were performed.
-///
-/// # Errors
-///
-/// This function will return an error if the `benchmarks.txt` can not be read.
-fn plot_benchmark_files() -> Result<Vec<String>> {
-    let labels = std::fs::read_to_string("benchmarks.txt")
-        .with_context(|| "Failed to read file at \"benchmarks.txt\"")?
-        .lines()
-        .map(|s| s.trim())
-        .filter(|s| !s.starts_with('#'))
-        .filter_map(|s| s.split('=').next())
-        .map(|s| s.trim().to_string())
+/// The directory of the benchmarks with multiple materials. It contains a directory for each material count.
+const MULTIPLE_DIRECTORY: &str = "fds/1D/Diabatic/multiple";
+
+/// Determine the labels of all benchmarks inside the benchmark directory, e.g. `desktop_l`, sorted by name.
+/// The label is the name of the directory above a method directory with benchmark files, so the benchmarks can be plotted from the benchmark directory alone.
+fn benchmark_labels() -> Vec<String> {
+    /// Adds the labels of all benchmarks inside the directory and its subdirectories.
+    fn add_labels(directory: &Path, labels: &mut BTreeSet<String>) {
+        if !benchmark_sizes(directory).is_empty() {
+            if let Some(label) = directory.parent().and_then(Path::file_name) {
+                labels.insert(label.to_string_lossy().to_string());
+            }
+        }
+        let std::result::Result::Ok(entries) = std::fs::read_dir(directory) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                add_labels(&path, labels);
+            }
+        }
+    }
+
+    let benchmark_root = benchmark_root();
+    let mut labels = BTreeSet::new();
+    add_labels(&benchmark_root, &mut labels);
+    if labels.is_empty() {
+        println!("Warning: No benchmarks found inside {benchmark_root:?}.");
+    }
+    labels.into_iter().collect()
+}
+
+/// Returns the material counts of the benchmarks with multiple materials that contain benchmarks of the label, sorted ascending, e.g. `[1, 2, 4, 16]`.
+fn multiple_material_counts(label: &str) -> Vec<u8> {
+    let directory = benchmark_root().join(MULTIPLE_DIRECTORY);
+    let std::result::Result::Ok(entries) = std::fs::read_dir(directory) else {
+        return vec![];
+    };
+    let mut material_counts = entries
+        .flatten()
+        .filter(|entry| entry.path().join(label).is_dir())
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
         .collect::<Vec<_>>();
-    Ok(labels)
+    material_counts.sort_unstable();
+    material_counts
 }
 
 /// Starts the temperature plot of all simulations.
+/// If `html` is set, the temperature plots are additionally written as interactive HTML files.
+/// If `combined` is set, the front and back side of the temperature plots are written to a single figure.
 ///
 /// # Panics
 ///
@@ -137,12 +302,18 @@ fn plot_benchmark_files() -> Result<Vec<String>> {
 /// This function will return an error if any simulation failed to run.
 pub fn plot_simulations(
     plot_type: PlotType,
+    plot_format: PlotFormat,
+    html: bool,
+    combined: bool,
     method: Option<&[SimulationMethod]>,
     kind: Option<&[SimulationKind]>,
     benchmark_names: Option<&[BenchmarkName]>,
 ) -> Result<(), Vec<anyhow::Error>> {
     let mut any_failed = false;
     let mut errors = vec![];
+    let plot_format = available_plot_format(plot_format);
+    // The HTML plots are already the main output, so they are not written a second time.
+    let html = html && plot_format != PlotFormat::Html;
 
     if PlotType::Helper.is_plot_type(plot_type) {
         let results = [
@@ -154,7 +325,7 @@ pub fn plot_simulations(
             helper_ramps_plot::plot_steel_k,
         ]
         .par_iter()
-        .map(|v| v())
+        .map(|v| v(plot_format))
         .collect::<Vec<_>>();
         if print_plot_state(results, &mut errors) {
             any_failed = true;
@@ -164,91 +335,131 @@ pub fn plot_simulations(
     if PlotType::Benchmark.is_plot_type(plot_type) {
         let mut handles = vec![];
         println!("\n Plot Benchmarks");
-        plot_benchmark_files()
-            .map_err(|err| vec![err])?
-            .into_iter()
-            .for_each(|l| {
-                BenchmarkName::MATERIAL.iter().for_each(|&b| {
-                    if b.is_benchmark(benchmark_names) {
-                        let l = l.clone();
-                        let handle = thread::spawn(move || {
-                            benchmark_box_plot::plot(BenchmarkBoxPlotSources::compare_mode(
-                                b.path_str(),
-                                l,
-                                BENCHMARK_ELEMENTS,
-                            ))
-                        });
-                        handles.push(handle);
-                    }
-                });
-                SimulationType1D::ALL_1D.into_iter().for_each(|s| {
-                    if s.is_simulation_type(method) {
-                        let l = l.clone();
-                        let handle = thread::spawn(move || {
-                            benchmark_box_plot::plot(BenchmarkBoxPlotSources::compare_multiple(
-                                "fds/1D/Diabatic/multiple",
-                                l,
-                                BENCHMARK_ELEMENTS,
-                                [1, 2, 4, 8, 16],
-                                s,
-                            ))
-                        });
-                        handles.push(handle);
-                    }
-                });
-                if SimulationMethod::SpeedTestFDS.is_simulation_type(method) {
+        let labels = benchmark_labels();
+        labels.iter().for_each(|l| {
+            BenchmarkName::MATERIAL.iter().for_each(|&b| {
+                if b.is_benchmark(benchmark_names) {
+                    let label = l.clone();
+                    let handle = thread::spawn(move || {
+                        scaling_line_plot::plot(
+                            ScalingLinePlotSources::compare_mode(b.path_str(), label),
+                            plot_format,
+                        )
+                    });
+                    handles.push(handle);
                     let l = l.clone();
-                    let handle = thread::spawn(|| {
-                        benchmark_box_plot::plot(BenchmarkBoxPlotSources::compare_fds(
-                            "fds/1D/AdiabaticSpeedTest",
-                            "fds/1D/Adiabatic/concrete_k_c",
-                            l,
-                            BENCHMARK_ELEMENTS,
-                            &[SimulationType1D::Cpu, SimulationType1D::GpuM3],
-                        ))
+                    let handle = thread::spawn(move || {
+                        benchmark_box_plot::plot(
+                            BenchmarkBoxPlotSources::compare_mode(b.path_str(), l),
+                            plot_format,
+                        )
                     });
                     handles.push(handle);
                 }
-                if BenchmarkName::DiabaticThickness.is_benchmark(benchmark_names) {
+            });
+            let material_counts = multiple_material_counts(l);
+            SimulationType1D::ALL_1D.into_iter().for_each(|s| {
+                if s.is_simulation_type(method) && !material_counts.is_empty() {
                     let l = l.clone();
+                    let material_counts = material_counts.clone();
                     let handle = thread::spawn(move || {
-                        benchmark_factor_plot::plot(BenchmarkFactorPlotSources::thickness_mode(
-                            "fds/1D/Diabatic/thickness_steel_k_c",
-                            l,
-                        ))
+                        benchmark_box_plot::plot(
+                            BenchmarkBoxPlotSources::compare_multiple(
+                                MULTIPLE_DIRECTORY,
+                                l,
+                                material_counts,
+                                s,
+                            ),
+                            plot_format,
+                        )
                     });
                     handles.push(handle);
                 }
             });
-        for [c1, c2] in [["desktop_l_n", "desktop_l"], ["laptop_l_n", "laptop_l"]] {
-            if BenchmarkName::DiabaticConcreteKC.is_benchmark(benchmark_names) {
+            if BenchmarkName::SetupSteelKC.is_benchmark(benchmark_names) {
+                let l = l.clone();
                 let handle = thread::spawn(move || {
-                    benchmark_factor_plot::plot(BenchmarkFactorPlotSources::compare_chunk_mode(
-                        "fds/1D/Diabatic/concrete_k_c",
-                        c1,
-                        c2,
-                    ))
+                    benchmark_box_plot::plot(
+                        BenchmarkBoxPlotSources::setup_mode(
+                            BenchmarkName::SetupSteelKC.path_str(),
+                            l,
+                        ),
+                        plot_format,
+                    )
+                });
+                handles.push(handle);
+            }
+            if SimulationMethod::SpeedTestFDS.is_simulation_type(method) {
+                let l = l.clone();
+                let handle = thread::spawn(move || {
+                    benchmark_box_plot::plot(
+                        BenchmarkBoxPlotSources::compare_fds(
+                            "fds/1D/AdiabaticSpeedTest",
+                            "fds/1D/Adiabatic/concrete_k_c",
+                            l,
+                            &[SimulationType1D::Cpu, SimulationType1D::GpuM3],
+                        ),
+                        plot_format,
+                    )
                 });
                 handles.push(handle);
             }
-            if BenchmarkName::DiabaticSteelKC.is_benchmark(benchmark_names) {
+            if BenchmarkName::DiabaticThickness.is_benchmark(benchmark_names) {
+                let l = l.clone();
                 let handle = thread::spawn(move || {
-                    benchmark_factor_plot::plot(BenchmarkFactorPlotSources::compare_chunk_mode(
-                        "fds/1D/Diabatic/steel_k_c",
-                        c1,
-                        c2,
-                    ))
+                    benchmark_factor_plot::plot(
+                        BenchmarkFactorPlotSources::thickness_mode(
+                            "fds/1D/Diabatic/thickness_steel_k_c",
+                            l,
+                        ),
+                        plot_format,
+                    )
                 });
                 handles.push(handle);
             }
+        });
+        // The benchmarks without chunk adjustment are labeled like the label with the adjustment and the suffix `_n`, see `run_benchmark_with_chunk_sizes`.
+        let chunk_pairs = labels
+            .iter()
+            .filter(|l| labels.contains(&format!("{l}_n")))
+            .map(|l| [format!("{l}_n"), l.clone()])
+            .collect::<Vec<_>>();
+        for [c1, c2] in chunk_pairs {
+            for (benchmark_name, simulation_directory) in [
+                (
+                    BenchmarkName::DiabaticConcreteKC,
+                    "fds/1D/Diabatic/concrete_k_c",
+                ),
+                (BenchmarkName::DiabaticSteelKC, "fds/1D/Diabatic/steel_k_c"),
+                (
+                    BenchmarkName::DiabaticGypsumKC,
+                    "fds/1D/Diabatic/gypsum_k_c",
+                ),
+            ] {
+                if benchmark_name.is_benchmark(benchmark_names) {
+                    let (c1, c2) = (c1.clone(), c2.clone());
+                    let handle = thread::spawn(move || {
+                        benchmark_factor_plot::plot(
+                            BenchmarkFactorPlotSources::compare_chunk_mode(
+                                simulation_directory,
+                                &c1,
+                                &c2,
+                            ),
+                            plot_format,
+                        )
+                    });
+                    handles.push(handle);
+                }
+            }
             if BenchmarkName::DiabaticThickness.is_benchmark(benchmark_names) {
                 let handle = thread::spawn(move || {
                     benchmark_factor_plot::plot(
                         BenchmarkFactorPlotSources::compare_chunk_thickness_mode(
                             "fds/1D/Diabatic/thickness_steel_k_c",
-                            c1,
-                            c2,
+                            &c1,
+                            &c2,
                         ),
+                        plot_format,
                     )
                 });
                 handles.push(handle);
@@ -275,12 +486,17 @@ pub fn plot_simulations(
                             "fds/1D/Adiabatic/steel_k_c",
                             "fds/1D/Adiabatic/concrete_simple",
                             "fds/1D/Adiabatic/concrete_k_c",
+                            "fds/1D/Adiabatic/gypsum_simple",
+                            "fds/1D/Adiabatic/gypsum_k_c",
                         ] {
                             let handle = thread::spawn(move || {
                                 temperature_diff::plot_one_dimensional_by_type(
                                     path,
                                     SimulationKind::Adiabatic,
                                     simulation_type,
+                                    plot_format,
+                                    html,
+                                    combined,
                                 )
                             });
                             handles.push(handle)
@@ -292,12 +508,39 @@ pub fn plot_simulations(
                             "fds/1D/DiabaticOneSide/steel_k_c",
                             "fds/1D/DiabaticOneSide/concrete_simple",
                             "fds/1D/DiabaticOneSide/concrete_k_c",
+                            "fds/1D/DiabaticOneSide/gypsum_simple",
+                            "fds/1D/DiabaticOneSide/gypsum_k_c",
                         ] {
                             let handle = thread::spawn(move || {
                                 temperature_diff::plot_one_dimensional_by_type(
                                     path,
                                     SimulationKind::DiabaticOneSide,
                                     simulation_type,
+                                    plot_format,
+                                    html,
+                                    combined,
+                                )
+                            });
+                            handles.push(handle)
+                        }
+                    }
+                    if SimulationKind::Convective.is_simulation_kind(kind) {
+                        for path in [
+                            "fds/1D/Convective/steel_simple",
+                            "fds/1D/Convective/steel_k_c",
+                            "fds/1D/Convective/concrete_simple",
+                            "fds/1D/Convective/concrete_k_c",
+                            "fds/1D/Convective/gypsum_simple",
+                            "fds/1D/Convective/gypsum_k_c",
+                        ] {
+                            let handle = thread::spawn(move || {
+                                temperature_diff::plot_one_dimensional_by_type(
+                                    path,
+                                    SimulationKind::Convective,
+                                    simulation_type,
+                                    plot_format,
+                                    html,
+                                    combined,
                                 )
                             });
                             handles.push(handle)
@@ -309,18 +552,28 @@ pub fn plot_simulations(
                             "fds/1D/Diabatic/steel_k_c",
                             "fds/1D/Diabatic/concrete_simple",
                             "fds/1D/Diabatic/concrete_k_c",
+                            "fds/1D/Diabatic/gypsum_simple",
+                            "fds/1D/Diabatic/gypsum_k_c",
                             // Thickness
                             "fds/1D/Diabatic/thickness_steel_k_c/005cm",
                             "fds/1D/Diabatic/thickness_steel_k_c/010cm",
                             "fds/1D/Diabatic/thickness_steel_k_c/050cm",
                             "fds/1D/Diabatic/thickness_steel_k_c/100cm",
                             "fds/1D/Diabatic/thickness_steel_k_c/500cm",
+                            // Multiple materials, plotted once per wall element
+                            "fds/1D/Diabatic/multiple/2",
+                            "fds/1D/Diabatic/multiple/4",
+                            "fds/1D/Diabatic/multiple/8",
+                            "fds/1D/Diabatic/multiple/16",
                         ] {
                             let handle = thread::spawn(move || {
                                 temperature_diff::plot_one_dimensional_by_type(
                                     path,
                                     SimulationKind::Diabatic,
                                     simulation_type,
+                                    plot_format,
+                                    html,
+                                    combined,
                                 )
                             });
                             handles.push(handle)
@@ -0,0 +1,38 @@
+//! This module contains the build information that is printed at the start of the program and written into the benchmarks.
+
+use crate::heat_transfer::gpu::get_gpu_backends;
+
+/// The version of the crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The short hash of the git commit the program was built from. It ends with `-dirty` if tracked files were modified and is `unknown` outside of a git repository.
+pub const GIT_HASH: &str = env!("HEAT_TRANSFER_GIT_HASH");
+
+/// The cargo profile the program was built with, e.g. `debug` or `release`.
+pub const PROFILE: &str = env!("HEAT_TRANSFER_PROFILE");
+
+/// Returns the enabled cargo features of the crate.
+pub fn features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "plot") {
+        features.push("plot");
+    }
+    if cfg!(feature = "gzip") {
+        features.push("gzip");
+    }
+    features
+}
+
+/// Returns a single line with the version, the git commit, the profile, the enabled features and the GPU backends selected with [`set_gpu_backends`](crate::heat_transfer::gpu::set_gpu_backends).
+pub fn provenance() -> String {
+    let features = features();
+    let features = if features.is_empty() {
+        "none".to_string()
+    } else {
+        features.join(", ")
+    };
+    format!(
+        "heat_transfer {VERSION} (commit {GIT_HASH}, profile {PROFILE}, features: {features}, GPU backends: {:?})",
+        get_gpu_backends()
+    )
+}
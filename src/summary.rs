@@ -0,0 +1,172 @@
+//! This module collects what a run created, simulated, benchmarked and plotted, so it can be written as a machine-readable summary.
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use anyhow::*;
+use serde::Serialize;
+
+use crate::provenance::provenance;
+
+/// The collected summary of the run. It is filled by the actions while they run.
+static SUMMARY: Mutex<Summary> = Mutex::new(Summary {
+    build: String::new(),
+    finished: false,
+    errors: Vec::new(),
+    simulations: SimulationSummary {
+        created: Vec::new(),
+        run: Vec::new(),
+    },
+    benchmarks: Vec::new(),
+    plots: Vec::new(),
+});
+
+/// The status of a created simulation, a run simulation or a plot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordStatus {
+    /// The file was created or replaced.
+    Created,
+    /// The result is newer than its sources, so nothing was done.
+    Passed,
+    /// The simulation or plot finished successfully.
+    Succeeded,
+    /// The simulation or plot failed.
+    Failed,
+    /// The plot was not created, see the reason of the [`PlotRecord`].
+    Ignored,
+    /// The action was only printed, see [`crate::dry_run`].
+    DryRun,
+}
+
+/// A created or run simulation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationRecord {
+    pub path: PathBuf,
+    pub status: RecordStatus,
+}
+
+/// The simulations of the run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimulationSummary {
+    /// The simulations created from the templates.
+    pub created: Vec<SimulationRecord>,
+    /// The simulations run with FDS.
+    pub run: Vec<SimulationRecord>,
+}
+
+/// A benchmark file that was written or skipped.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchmarkRecord {
+    pub path: PathBuf,
+    pub size: usize,
+    /// The amount of simulation times the file contains.
+    pub reruns: usize,
+    /// Whether the file contains all reruns. An incomplete file is left behind by a failed benchmark.
+    pub complete: bool,
+    /// Whether the file already contained all reruns and was skipped because of `--resume`.
+    pub skipped: bool,
+}
+
+/// A plot of a simulation or benchmark.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlotRecord {
+    pub plot: String,
+    pub path: PathBuf,
+    pub status: RecordStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// The machine-readable summary of a run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    /// The [`provenance`] of the build.
+    pub build: String,
+    /// Whether the run reached its end. Errors that were ignored with `--force` are still listed.
+    pub finished: bool,
+    pub errors: Vec<String>,
+    pub simulations: SimulationSummary,
+    /// The benchmark files. Benchmarks of a dry run are not listed, since no file is written.
+    pub benchmarks: Vec<BenchmarkRecord>,
+    pub plots: Vec<PlotRecord>,
+}
+
+/// Runs the passed function with the collected [`Summary`].
+fn with_summary<T>(f: impl FnOnce(&mut Summary) -> T) -> T {
+    f(&mut SUMMARY.lock().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Adds a simulation created from a template to the summary.
+pub fn record_created_simulation(path: PathBuf, status: RecordStatus) {
+    with_summary(|s| {
+        s.simulations
+            .created
+            .push(SimulationRecord { path, status })
+    });
+}
+
+/// Adds a simulation run with FDS to the summary.
+pub fn record_run_simulation(path: PathBuf, status: RecordStatus) {
+    with_summary(|s| s.simulations.run.push(SimulationRecord { path, status }));
+}
+
+/// Adds a written or skipped benchmark file to the summary.
+pub fn record_benchmark(benchmark: BenchmarkRecord) {
+    with_summary(|s| s.benchmarks.push(benchmark));
+}
+
+/// Adds a plot to the summary.
+pub fn record_plot(plot: PlotRecord) {
+    with_summary(|s| s.plots.push(plot));
+}
+
+/// Adds an error to the summary. The error is written with its whole context in a single line.
+pub fn record_error(error: &Error) {
+    let error = format!("{error:#}");
+    with_summary(|s| s.errors.push(error));
+}
+
+/// Marks the run as finished in the summary.
+pub fn set_finished() {
+    with_summary(|s| s.finished = true);
+}
+
+/// Returns a copy of the collected [`Summary`].
+pub fn summary() -> Summary {
+    with_summary(|s| Summary {
+        build: provenance(),
+        ..s.clone()
+    })
+}
+
+/// Writes the collected [`Summary`] as JSON to the passed path. The records of each action are sorted by path.
+///
+/// # Errors
+///
+/// This function will return an error if the file can not be written.
+pub fn write_summary<P: AsRef<Path>>(path: P) -> Result<()> {
+    let path = path.as_ref();
+    let mut summary = summary();
+    summary
+        .simulations
+        .created
+        .sort_by(|a, b| a.path.cmp(&b.path));
+    summary.simulations.run.sort_by(|a, b| a.path.cmp(&b.path));
+    summary
+        .benchmarks
+        .sort_by(|a, b| (&a.path, a.size).cmp(&(&b.path, b.size)));
+    summary
+        .plots
+        .sort_by(|a, b| (&a.path, &a.plot).cmp(&(&b.path, &b.plot)));
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directories at path {parent:?}"))?;
+    }
+    let file = File::create(path).with_context(|| format!("Failed to create file at {path:?}."))?;
+    serde_json::to_writer_pretty(file, &summary)
+        .with_context(|| format!("Failed to write summary to {path:?}."))
+}